@@ -0,0 +1,11 @@
+-- Long-lived refresh tokens for renewing access tokens without credentials
+-- Only a SHA-256 hash of each token is stored; deleting a row revokes it
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    practitioner_id UUID NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_refresh_tokens_practitioner ON refresh_tokens(practitioner_id);
@@ -0,0 +1,11 @@
+-- Practitioner roles for community moderation
+ALTER TABLE practitioners
+    ADD COLUMN role VARCHAR(20) NOT NULL DEFAULT 'practitioner'
+    CHECK (role IN ('practitioner', 'moderator', 'admin'));
+
+-- Keep practitioners' session history when a moderated ritual is deleted
+ALTER TABLE ritual_sessions ALTER COLUMN ritual_id DROP NOT NULL;
+ALTER TABLE ritual_sessions DROP CONSTRAINT ritual_sessions_ritual_id_fkey;
+ALTER TABLE ritual_sessions
+    ADD CONSTRAINT ritual_sessions_ritual_id_fkey
+    FOREIGN KEY (ritual_id) REFERENCES sacred_rituals(id) ON DELETE SET NULL;
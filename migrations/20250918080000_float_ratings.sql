@@ -0,0 +1,4 @@
+-- The API models read these as f64; NUMERIC columns cannot be decoded into them
+ALTER TABLE sacred_rituals ALTER COLUMN effectiveness_rating TYPE DOUBLE PRECISION;
+ALTER TABLE ritual_sessions ALTER COLUMN transformation_intensity TYPE DOUBLE PRECISION;
+ALTER TABLE oracle_insights ALTER COLUMN confidence_score TYPE DOUBLE PRECISION;
@@ -0,0 +1,11 @@
+-- One effectiveness rating per practitioner per ritual
+CREATE TABLE ritual_ratings (
+    ritual_id UUID NOT NULL REFERENCES sacred_rituals(id) ON DELETE CASCADE,
+    practitioner_id UUID NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    rating INTEGER NOT NULL CHECK (rating >= 1 AND rating <= 5),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (ritual_id, practitioner_id)
+);
+
+CREATE INDEX idx_ritual_ratings_practitioner ON ritual_ratings(practitioner_id);
@@ -0,0 +1,12 @@
+-- Catalog the native frequency_tuning ritual alongside the other foundational rituals
+INSERT INTO sacred_rituals (name, description, intent, tradition, difficulty_level, required_archetypes, energy_requirements, is_public) VALUES
+(
+    'frequency_tuning',
+    'Harmonic ritual that tunes each elemental frequency toward its nearest Solfeggio tone',
+    'To bring every energetic frequency into harmonic pitch with the sacred scale',
+    'elemental',
+    'intermediate',
+    '["Sage"]',
+    '{"Fire": 0.3}',
+    true
+);
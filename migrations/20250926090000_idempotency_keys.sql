@@ -0,0 +1,11 @@
+-- Results of ritual executions sent with an Idempotency-Key header, so a
+-- retried request gets the first result back instead of running again
+CREATE TABLE idempotency_keys (
+    practitioner_id UUID NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    result JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (practitioner_id, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
@@ -0,0 +1,3 @@
+-- The parameters a ritual expects, as a map from parameter name to JSON type,
+-- checked against each execution request before the ritual runs
+ALTER TABLE sacred_rituals ADD COLUMN parameter_schema JSONB NOT NULL DEFAULT '{}';
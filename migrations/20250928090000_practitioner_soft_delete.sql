@@ -0,0 +1,3 @@
+-- Accounts are soft-deleted: they stop authenticating at `deleted_at` but can be
+-- restored within a grace window
+ALTER TABLE practitioners ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,4 @@
+-- Each practitioner's choice of oracle provider, model and temperature, and
+-- optionally their own API key, encrypted with the server's REFLECTION_KEY_SECRET
+ALTER TABLE practitioners ADD COLUMN reflection_preferences JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE practitioners ADD COLUMN reflection_api_key TEXT;
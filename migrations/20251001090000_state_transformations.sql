@@ -0,0 +1,15 @@
+-- Every state change a stored ritual session made, so an unexpected state can be
+-- traced back to the ritual that caused it
+CREATE TABLE state_transformations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    practitioner_id UUID NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    session_id UUID NOT NULL REFERENCES ritual_sessions(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL, -- order of the change within its session
+    change_type VARCHAR(50) NOT NULL,
+    description TEXT NOT NULL,
+    magnitude DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_state_transformations_practitioner ON state_transformations(practitioner_id, created_at);
+CREATE INDEX idx_state_transformations_session ON state_transformations(session_id);
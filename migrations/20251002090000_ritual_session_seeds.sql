@@ -0,0 +1,3 @@
+-- The seed each ritual session ran with; together with its recorded parameters
+-- it lets the session be replayed to check the state it produced
+ALTER TABLE ritual_sessions ADD COLUMN seed BIGINT;
@@ -0,0 +1,205 @@
+-- SQLite schema for single-practitioner / offline use of the Codex API
+-- Mirrors the Postgres migrations in ../migrations up to ritual ratings.
+--
+-- SQLite has no native UUID, JSONB or TIMESTAMPTZ types, so following sqlx's encodings:
+--   UUIDs are 16-byte BLOBs (randomblob(16) fills in ids the application does not supply),
+--   JSON is stored as TEXT, and timestamps as RFC 3339 TEXT in UTC.
+
+CREATE TABLE practitioners (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    email TEXT UNIQUE NOT NULL,
+    password_hash TEXT NOT NULL,
+    spiritual_name TEXT,
+    archetypal_preferences TEXT DEFAULT '{}',
+    energy_alignments TEXT DEFAULT '{}',
+    privacy_level TEXT DEFAULT 'private',
+    sacred_path TEXT,
+    role TEXT NOT NULL DEFAULT 'practitioner'
+        CHECK (role IN ('practitioner', 'moderator', 'admin')),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE archetypal_states (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    state_data TEXT NOT NULL,
+    archetypes TEXT NOT NULL,
+    energies TEXT NOT NULL,
+    integrations TEXT DEFAULT '[]',
+    symbols TEXT DEFAULT '[]',
+    transformations TEXT DEFAULT '[]',
+    state_hash TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE sacred_rituals (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT NOT NULL,
+    intent TEXT NOT NULL,
+    tradition TEXT DEFAULT 'universal',
+    difficulty_level TEXT DEFAULT 'beginner',
+    required_archetypes TEXT DEFAULT '[]',
+    energy_requirements TEXT DEFAULT '{}',
+    wasm_module_data BLOB,
+    wasm_module_hash TEXT,
+    module_language TEXT,
+    author_id BLOB REFERENCES practitioners(id),
+    usage_count INTEGER DEFAULT 0,
+    effectiveness_rating REAL DEFAULT 0.0,
+    rating_count INTEGER DEFAULT 0,
+    is_public BOOLEAN DEFAULT false,
+    tags TEXT DEFAULT '[]',
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE ritual_sessions (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    ritual_id BLOB REFERENCES sacred_rituals(id) ON DELETE SET NULL,
+    pre_state_id BLOB REFERENCES archetypal_states(id),
+    post_state_id BLOB REFERENCES archetypal_states(id),
+    execution_duration_ms INTEGER,
+    transformation_intensity REAL,
+    subjective_experience TEXT,
+    ai_interpretation TEXT,
+    integration_notes TEXT,
+    effectiveness_rating INTEGER CHECK (effectiveness_rating >= 1 AND effectiveness_rating <= 10),
+    ritual_parameters TEXT DEFAULT '{}',
+    intention TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE oracle_insights (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    session_id BLOB REFERENCES ritual_sessions(id) ON DELETE CASCADE,
+    practitioner_id BLOB REFERENCES practitioners(id) ON DELETE CASCADE,
+    insight_type TEXT DEFAULT 'interpretation',
+    archetypal_analysis TEXT,
+    integration_suggestions TEXT,
+    symbolic_emergence TEXT,
+    oracle_model TEXT,
+    confidence_score REAL DEFAULT 0.0,
+    full_response TEXT,
+    query_context TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE collective_spaces (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    name TEXT NOT NULL,
+    purpose TEXT NOT NULL,
+    tradition TEXT,
+    facilitator_id BLOB REFERENCES practitioners(id),
+    participant_limit INTEGER DEFAULT 12,
+    current_participants INTEGER DEFAULT 0,
+    shared_state TEXT DEFAULT '{}',
+    ritual_schedule TEXT,
+    access_level TEXT DEFAULT 'invite',
+    is_active BOOLEAN DEFAULT true,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE ritual_reviews (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    ritual_id BLOB NOT NULL REFERENCES sacred_rituals(id) ON DELETE CASCADE,
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    rating INTEGER NOT NULL CHECK (rating >= 1 AND rating <= 10),
+    review_text TEXT,
+    transformation_achieved BOOLEAN DEFAULT false,
+    would_recommend BOOLEAN DEFAULT true,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    UNIQUE(ritual_id, practitioner_id)
+);
+
+CREATE TABLE refresh_tokens (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    expires_at TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE ritual_ratings (
+    ritual_id BLOB NOT NULL REFERENCES sacred_rituals(id) ON DELETE CASCADE,
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    rating INTEGER NOT NULL CHECK (rating >= 1 AND rating <= 5),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (ritual_id, practitioner_id)
+);
+
+CREATE INDEX idx_practitioners_email ON practitioners(email);
+CREATE INDEX idx_archetypal_states_practitioner ON archetypal_states(practitioner_id);
+CREATE INDEX idx_archetypal_states_created ON archetypal_states(created_at);
+CREATE INDEX idx_ritual_sessions_practitioner ON ritual_sessions(practitioner_id);
+CREATE INDEX idx_ritual_sessions_ritual ON ritual_sessions(ritual_id);
+CREATE INDEX idx_ritual_sessions_created ON ritual_sessions(created_at);
+CREATE INDEX idx_sacred_rituals_public ON sacred_rituals(is_public) WHERE is_public = true;
+CREATE INDEX idx_sacred_rituals_author ON sacred_rituals(author_id);
+CREATE INDEX idx_sacred_rituals_tradition ON sacred_rituals(tradition);
+CREATE INDEX idx_oracle_insights_session ON oracle_insights(session_id);
+CREATE INDEX idx_oracle_insights_practitioner ON oracle_insights(practitioner_id);
+CREATE INDEX idx_refresh_tokens_practitioner ON refresh_tokens(practitioner_id);
+CREATE INDEX idx_ritual_ratings_practitioner ON ritual_ratings(practitioner_id);
+
+INSERT INTO sacred_rituals (name, description, intent, tradition, difficulty_level, required_archetypes, energy_requirements, is_public) VALUES
+(
+    'shadow_integration',
+    'A deep ritual for integrating shadow aspects of the psyche through archetypal activation',
+    'To bring unconscious shadow material into conscious awareness for integration and wholeness',
+    'jungian',
+    'intermediate',
+    '["Shadow"]',
+    '{"Shadow": 0.3, "Earth": 0.2}',
+    true
+),
+(
+    'archetype_invocation',
+    'Sacred invocation ritual to activate dormant archetypal forces within the psyche',
+    'To awaken and align with specific archetypal energies for personal transformation',
+    'universal',
+    'beginner',
+    '[]',
+    '{"Fire": 0.2, "Air": 0.1}',
+    true
+),
+(
+    'energy_attunement',
+    'Harmonic ritual for balancing and attuning elemental energies within the sacred self',
+    'To achieve energetic balance and harmonic resonance across all elemental frequencies',
+    'elemental',
+    'beginner',
+    '["Sage"]',
+    '{"Fire": 0.1, "Water": 0.1, "Earth": 0.1, "Air": 0.1}',
+    true
+),
+(
+    'void_contemplation',
+    'Deep contemplative ritual for entering the sacred void and connecting with infinite potential',
+    'To dissolve temporary identity and connect with the eternal, formless essence',
+    'mystical',
+    'advanced',
+    '["Sage", "Mystic"]',
+    '{"Void": 0.5}',
+    true
+);
+
+-- Keep updated_at current, as the Postgres trigger does
+CREATE TRIGGER update_practitioners_updated_at AFTER UPDATE ON practitioners
+BEGIN
+    UPDATE practitioners SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER update_sacred_rituals_updated_at AFTER UPDATE ON sacred_rituals
+BEGIN
+    UPDATE sacred_rituals SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER update_collective_spaces_updated_at AFTER UPDATE ON collective_spaces
+BEGIN
+    UPDATE collective_spaces SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+END;
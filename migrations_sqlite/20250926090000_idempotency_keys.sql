@@ -0,0 +1,11 @@
+-- Results of ritual executions sent with an Idempotency-Key header, so a
+-- retried request gets the first result back instead of running again
+CREATE TABLE idempotency_keys (
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    idempotency_key TEXT NOT NULL,
+    result TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (practitioner_id, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
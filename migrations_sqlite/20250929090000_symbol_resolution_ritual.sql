@@ -0,0 +1,12 @@
+-- Catalog the native symbol_resolution ritual alongside the other foundational rituals
+INSERT INTO sacred_rituals (name, description, intent, tradition, difficulty_level, required_archetypes, energy_requirements, is_public) VALUES
+(
+    'symbol_resolution',
+    'Contemplative ritual that resolves each symbol of known meaning into a lasting integration',
+    'To bring what the unresolved symbols carry into conscious understanding',
+    'jungian',
+    'beginner',
+    '["Sage"]',
+    '{}',
+    true
+);
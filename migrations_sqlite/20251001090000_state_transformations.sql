@@ -0,0 +1,15 @@
+-- Every state change a stored ritual session made, so an unexpected state can be
+-- traced back to the ritual that caused it
+CREATE TABLE state_transformations (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    session_id BLOB NOT NULL REFERENCES ritual_sessions(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    change_type TEXT NOT NULL,
+    description TEXT NOT NULL,
+    magnitude REAL NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_state_transformations_practitioner ON state_transformations(practitioner_id, created_at);
+CREATE INDEX idx_state_transformations_session ON state_transformations(session_id);
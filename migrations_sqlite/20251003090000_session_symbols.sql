@@ -0,0 +1,12 @@
+-- The symbols each stored ritual session gave rise to, one row per symbol, so
+-- symbol statistics can be aggregated with plain SQL
+CREATE TABLE session_symbols (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    practitioner_id BLOB NOT NULL REFERENCES practitioners(id) ON DELETE CASCADE,
+    session_id BLOB NOT NULL REFERENCES ritual_sessions(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    symbol TEXT NOT NULL
+);
+
+CREATE INDEX idx_session_symbols_practitioner ON session_symbols(practitioner_id);
+CREATE INDEX idx_session_symbols_symbol ON session_symbols(symbol);
@@ -28,11 +28,80 @@ extern "C" {
     fn random() -> f64;
 }
 
+// Host functions the engine links under its `codex` import module
+#[link(wasm_import_module = "codex")]
+extern "C" {
+    /// Writes the state as JSON into a buffer from `__codex_alloc` and returns its
+    /// pointer in the high 32 bits and length in the low 32, or 0 when nothing was written
+    fn get_state_json() -> i64;
+
+    /// How many evolution cycles the practitioner has completed
+    fn get_evolution_cycle() -> i32;
+}
+
+thread_local! {
+    /// The buffer `__codex_alloc` hands the host; reused across calls and never freed
+    static HOST_BUFFER: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(Vec::new());
+}
+
+/// Hands the host a `len`-byte buffer to write the state JSON into. The buffer stays
+/// owned by this module, so `read_state` only copies out of it.
+#[no_mangle]
+pub extern "C" fn __codex_alloc(len: i32) -> i32 {
+    HOST_BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.clear();
+        buffer.resize(len.max(0) as usize, 0);
+        buffer.as_mut_ptr() as i32
+    })
+}
+
 // Define a macro for easier logging
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+/// The parts of the engine's symbolic state a ritual can read in one call
+#[derive(Serialize, Deserialize, Default)]
+pub struct StateView {
+    pub archetypes: HashMap<String, ArchetypeView>,
+    pub energies: HashMap<String, EnergyView>,
+    pub unresolved_symbols: Vec<String>,
+    pub evolution_cycle: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchetypeView {
+    pub name: String,
+    pub activation_level: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EnergyView {
+    pub frequency: f64,
+    pub amplitude: f64,
+}
+
+/// Fetch the full symbolic state from the host; empty if the host returned nothing
+pub fn read_state() -> StateView {
+    let packed = unsafe { get_state_json() } as u64;
+    let len = (packed & 0xffff_ffff) as usize;
+    if packed >> 32 == 0 || len == 0 {
+        return StateView::default();
+    }
+    // The host wrote into the buffer `__codex_alloc` just sized for it
+    HOST_BUFFER.with(|buffer| {
+        let buffer = buffer.borrow();
+        let json = buffer.get(..len).unwrap_or_default();
+        serde_json::from_slice(json).unwrap_or_default()
+    })
+}
+
+/// How many evolution cycles the practitioner has completed; never negative
+pub fn evolution_cycle() -> u32 {
+    unsafe { get_evolution_cycle() }.max(0) as u32
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct RitualResult {
     pub success: bool,
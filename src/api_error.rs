@@ -0,0 +1,192 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::fmt::Display;
+
+use crate::CodexError;
+
+/// The JSON body of every failed API request
+#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ErrorResponse {
+    /// Machine-readable error code, such as `not_found`
+    pub code: String,
+    /// Human-readable description of what went wrong
+    pub error: String,
+}
+
+/// An error a web handler returns, rendered as its status code and an `ErrorResponse`
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("{0}")]
+    Unauthorized(String),
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Gone(String),
+
+    #[error("{0}")]
+    TooManyRequests(String),
+
+    #[error("{0}")]
+    ServiceUnavailable(String),
+
+    #[error(transparent)]
+    Internal(#[from] CodexError),
+}
+
+impl ApiError {
+    /// An internal error for an operation that failed, described as `"{context}: {error}"`
+    pub fn internal(context: &str, error: impl Display) -> Self {
+        Self::Internal(CodexError::Operation {
+            context: context.to_string(),
+            reason: error.to_string(),
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Gone(_) => StatusCode::GONE,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "bad_request",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::Conflict(_) => "conflict",
+            Self::Gone(_) => "gone",
+            Self::TooManyRequests(_) => "too_many_requests",
+            Self::ServiceUnavailable(_) => "service_unavailable",
+            Self::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn body(&self) -> ErrorResponse {
+        ErrorResponse {
+            code: self.code().to_string(),
+            error: self.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let Self::Internal(error) = &self {
+            tracing::error!("Request failed: {}", error);
+        }
+        (self.status(), Json(self.body())).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn response_parts(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_each_variant_maps_to_its_status_and_code() {
+        let cases = [
+            (
+                ApiError::BadRequest("bad".into()),
+                StatusCode::BAD_REQUEST,
+                "bad_request",
+            ),
+            (
+                ApiError::Unauthorized("who".into()),
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+            ),
+            (
+                ApiError::Forbidden("no".into()),
+                StatusCode::FORBIDDEN,
+                "forbidden",
+            ),
+            (
+                ApiError::NotFound("gone".into()),
+                StatusCode::NOT_FOUND,
+                "not_found",
+            ),
+            (
+                ApiError::Conflict("taken".into()),
+                StatusCode::CONFLICT,
+                "conflict",
+            ),
+            (ApiError::Gone("expired".into()), StatusCode::GONE, "gone"),
+            (
+                ApiError::TooManyRequests("slow".into()),
+                StatusCode::TOO_MANY_REQUESTS,
+                "too_many_requests",
+            ),
+            (
+                ApiError::ServiceUnavailable("later".into()),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service_unavailable",
+            ),
+            (
+                ApiError::internal("Failed", "boom"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+            ),
+        ];
+
+        for (error, status, code) in cases {
+            let message = error.to_string();
+            let (actual_status, body) = response_parts(error).await;
+            assert_eq!(actual_status, status);
+            assert_eq!(body, serde_json::json!({ "code": code, "error": message }));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_keep_their_message() {
+        let (_, body) = response_parts(ApiError::NotFound("Sacred ritual not found".into())).await;
+        assert_eq!(body["error"], "Sacred ritual not found");
+    }
+
+    #[tokio::test]
+    async fn test_internal_errors_describe_the_codex_error() {
+        let (_, body) = response_parts(ApiError::internal(
+            "Failed to fetch state",
+            "connection reset",
+        ))
+        .await;
+        assert_eq!(body["error"], "Failed to fetch state: connection reset");
+
+        let (_, body) = response_parts(ApiError::from(CodexError::RitualNotFound {
+            name: "dawn".into(),
+        }))
+        .await;
+        assert_eq!(body["code"], "internal_error");
+        assert_eq!(body["error"], "Ritual not found: dawn");
+    }
+}
@@ -0,0 +1,131 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::models::REFLECTION_KEY_SECRET_ENV;
+
+const FORMAT: &str = "codex-sealed-api-key-v1";
+const NONCE_LEN: usize = 24;
+/// Salt for deriving the sealing key. The secret belongs to the whole server, so a
+/// fixed salt lets one derivation at startup serve every practitioner's key.
+const KEY_SALT: &[u8] = b"codex-reflection-api-key";
+
+static API_KEY_SEALER: OnceLock<ApiKeySealer> = OnceLock::new();
+
+/// Why a practitioner's oracle API key could not be sealed or opened
+#[derive(thiserror::Error, Debug)]
+pub enum ApiKeyError {
+    #[error("{0} is not set")]
+    NotConfigured(&'static str),
+
+    #[error("Could not derive the API key sealing key: {0}")]
+    KeyDerivation(String),
+
+    #[error("Could not seal the API key")]
+    Seal,
+
+    #[error("Unreadable sealed API key: {0}")]
+    Unreadable(String),
+
+    #[error("Sealed API key failed authentication: {0} changed or the stored value was altered")]
+    Authentication(&'static str),
+}
+
+/// Stored form of a sealed API key; the key itself is XChaCha20-Poly1305 ciphertext
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedApiKey {
+    format: String,
+    #[serde(with = "crate::encoding::hex_bytes")]
+    nonce: Vec<u8>,
+    #[serde(with = "crate::encoding::hex_bytes")]
+    ciphertext: Vec<u8>,
+}
+
+/// Seals and opens practitioners' own oracle API keys under a key derived once, with
+/// Argon2id, from `REFLECTION_KEY_SECRET`
+pub struct ApiKeySealer {
+    cipher: XChaCha20Poly1305,
+}
+
+impl ApiKeySealer {
+    pub fn from_secret(secret: &str) -> Result<Self, ApiKeyError> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(secret.as_bytes(), KEY_SALT, &mut key)
+            .map_err(|e| ApiKeyError::KeyDerivation(e.to_string()))?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new(&key.into()),
+        })
+    }
+
+    /// Seals `api_key` under a fresh nonce for storage in `practitioners.reflection_api_key`
+    pub fn seal(&self, api_key: &str) -> Result<String, ApiKeyError> {
+        let mut nonce = vec![0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(XNonce::from_slice(&nonce), api_key.as_bytes())
+            .map_err(|_| ApiKeyError::Seal)?;
+
+        serde_json::to_string(&SealedApiKey {
+            format: FORMAT.to_string(),
+            nonce,
+            ciphertext,
+        })
+        .map_err(|_| ApiKeyError::Seal)
+    }
+
+    /// Opens a value written by `seal`, failing if the secret changed or it was altered
+    pub fn open(&self, sealed: &str) -> Result<String, ApiKeyError> {
+        let sealed: SealedApiKey =
+            serde_json::from_str(sealed).map_err(|e| ApiKeyError::Unreadable(e.to_string()))?;
+        if sealed.format != FORMAT || sealed.nonce.len() != NONCE_LEN {
+            return Err(ApiKeyError::Unreadable(format!(
+                "unsupported format '{}'",
+                sealed.format
+            )));
+        }
+
+        let api_key = self
+            .cipher
+            .decrypt(XNonce::from_slice(&sealed.nonce), sealed.ciphertext.as_slice())
+            .map_err(|_| ApiKeyError::Authentication(REFLECTION_KEY_SECRET_ENV))?;
+        String::from_utf8(api_key).map_err(|e| ApiKeyError::Unreadable(e.to_string()))
+    }
+}
+
+/// The process-wide sealer, derived from `REFLECTION_KEY_SECRET` on first use; the server
+/// calls this at startup so no request pays for the derivation
+pub fn api_key_sealer() -> Result<&'static ApiKeySealer, ApiKeyError> {
+    if let Some(sealer) = API_KEY_SEALER.get() {
+        return Ok(sealer);
+    }
+    let secret = std::env::var(REFLECTION_KEY_SECRET_ENV)
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .ok_or(ApiKeyError::NotConfigured(REFLECTION_KEY_SECRET_ENV))?;
+    let sealer = ApiKeySealer::from_secret(&secret)?;
+    Ok(API_KEY_SEALER.get_or_init(|| sealer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_key_opens_only_under_its_secret() {
+        let sealer = ApiKeySealer::from_secret("server secret").unwrap();
+        let sealed = sealer.seal("sk-practitioner").unwrap();
+
+        assert!(!sealed.contains("sk-practitioner"));
+        assert_eq!(sealer.open(&sealed).unwrap(), "sk-practitioner");
+        assert!(matches!(
+            ApiKeySealer::from_secret("another secret").unwrap().open(&sealed),
+            Err(ApiKeyError::Authentication(_))
+        ));
+        assert!(matches!(sealer.open("sk-plaintext"), Err(ApiKeyError::Unreadable(_))));
+    }
+}
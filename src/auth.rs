@@ -1,56 +1,338 @@
 use axum::{
     extract::{Request, State},
-    http::{header::AUTHORIZATION, StatusCode},
+    http::header::AUTHORIZATION,
     middleware::Next,
     response::Response,
 };
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use crate::api_error::ApiError;
+use crate::database::{with_pool, Database};
 use crate::models::{AuthToken, Practitioner, PractitionerProfile};
+use crate::CodexError;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // practitioner_id
     pub email: String,
     pub spiritual_name: Option<String>,
+    #[serde(default)]
+    pub role: Role,
     pub exp: usize, // expiration time
     pub iat: usize, // issued at
+    /// Who issued the token, when `CODEX_JWT_ISSUER` is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Which service the token is meant for, when `CODEX_JWT_AUDIENCE` is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
 }
 
-const JWT_SECRET: &[u8] = b"sacred_codex_jwt_secret_key_change_in_production";
+/// A token's `aud` claim, which the JWT spec allows as one string or a list of them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    /// Whether the token is meant for `audience`
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Audience::One(one) => one == audience,
+            Audience::Many(many) => many.iter().any(|name| name == audience),
+        }
+    }
+}
+
+/// What a practitioner is allowed to do; each role includes the powers of those below it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Practitioner,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    /// Parses the `practitioners.role` column, treating unknown values as the least privileged role
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "admin" => Role::Admin,
+            "moderator" => Role::Moderator,
+            _ => Role::Practitioner,
+        }
+    }
+}
+
+/// Succeeds when the practitioner holds `required` or a more privileged role.
+///
+/// The role comes from the practitioner's stored record rather than their token's
+/// claims, so a demotion takes effect before the token expires.
+pub fn require_role(practitioner: &Practitioner, required: Role) -> Result<(), ApiError> {
+    if Role::from_name(&practitioner.role) >= required {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(format!(
+            "This requires the {:?} role",
+            required
+        )))
+    }
+}
+
+/// Environment variable holding the secret used to sign access tokens
+pub const JWT_SECRET_ENV: &str = "CODEX_JWT_SECRET";
+
+/// Environment variable overriding how many seconds an access token stays valid
+pub const JWT_TTL_SECONDS_ENV: &str = "CODEX_JWT_TTL_SECONDS";
+/// Environment variable naming the issuer tokens carry and must carry to verify
+pub const JWT_ISSUER_ENV: &str = "CODEX_JWT_ISSUER";
+/// Environment variable naming the audience tokens carry and must carry to verify
+pub const JWT_AUDIENCE_ENV: &str = "CODEX_JWT_AUDIENCE";
+
+/// How long an access token stays valid unless `CODEX_JWT_TTL_SECONDS` says otherwise
+pub const DEFAULT_JWT_TTL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Only debug builds may run without a configured secret
+#[cfg(debug_assertions)]
+const DEV_JWT_SECRET: &[u8] = b"sacred_codex_jwt_secret_key_change_in_production";
+
+static JWT_KEYS: OnceLock<JwtKeys> = OnceLock::new();
+
+/// Signing and verification keys derived from a single HMAC secret, with the
+/// lifetime and optional issuer and audience of the tokens they sign
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    ttl_seconds: u64,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl JwtKeys {
+    pub fn from_secret(secret: &[u8]) -> Self {
+        Self {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            ttl_seconds: DEFAULT_JWT_TTL_SECONDS,
+            issuer: None,
+            audience: None,
+        }
+    }
+
+    pub fn with_ttl_seconds(mut self, ttl_seconds: u64) -> Self {
+        self.ttl_seconds = ttl_seconds;
+        self
+    }
+
+    /// Signs tokens with `iss` and only verifies tokens issued by `issuer`
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Signs tokens with `aud` and only verifies tokens meant for `audience`
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Reads `CODEX_JWT_SECRET`, falling back to a development secret in debug builds,
+    /// and the optional `CODEX_JWT_TTL_SECONDS`, `CODEX_JWT_ISSUER` and `CODEX_JWT_AUDIENCE`
+    pub fn from_env() -> Result<Self, CodexError> {
+        let keys = match std::env::var(JWT_SECRET_ENV) {
+            Ok(secret) if !secret.is_empty() => Self::from_secret(secret.as_bytes()),
+            _ => Self::fallback()?,
+        };
+        keys.with_claims_from_env_values(
+            std::env::var(JWT_TTL_SECONDS_ENV).ok(),
+            std::env::var(JWT_ISSUER_ENV).ok(),
+            std::env::var(JWT_AUDIENCE_ENV).ok(),
+        )
+    }
+
+    /// Applies the token settings as read from the environment; unset or empty
+    /// values keep the defaults
+    fn with_claims_from_env_values(
+        mut self,
+        ttl_seconds: Option<String>,
+        issuer: Option<String>,
+        audience: Option<String>,
+    ) -> Result<Self, CodexError> {
+        let set = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+        if let Some(ttl) = set(ttl_seconds) {
+            self.ttl_seconds = ttl
+                .trim()
+                .parse()
+                .ok()
+                .filter(|ttl| *ttl > 0)
+                .ok_or_else(|| CodexError::Configuration {
+                    reason: format!(
+                        "{} must be a positive number of seconds, got '{}'",
+                        JWT_TTL_SECONDS_ENV, ttl
+                    ),
+                })?;
+        }
+        if let Some(issuer) = set(issuer) {
+            self = self.with_issuer(issuer);
+        }
+        if let Some(audience) = set(audience) {
+            self = self.with_audience(audience);
+        }
+        Ok(self)
+    }
+
+    #[cfg(debug_assertions)]
+    fn fallback() -> Result<Self, CodexError> {
+        tracing::warn!(
+            "{} is not set; using the development JWT secret",
+            JWT_SECRET_ENV
+        );
+        Ok(Self::from_secret(DEV_JWT_SECRET))
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn fallback() -> Result<Self, CodexError> {
+        Err(CodexError::Configuration {
+            reason: format!("{} must be set to sign access tokens", JWT_SECRET_ENV),
+        })
+    }
+
+    pub fn sign(&self, practitioner: &Practitioner) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize;
+
+        let claims = Claims {
+            sub: practitioner.id.to_string(),
+            email: practitioner.email.clone(),
+            spiritual_name: practitioner.spiritual_name.clone(),
+            role: Role::from_name(&practitioner.role),
+            exp: now + self.ttl_seconds as usize,
+            iat: now,
+            iss: self.issuer.clone(),
+            aud: self.audience.clone().map(Audience::One),
+        };
+
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_exp = true;
+        // A configured issuer or audience must also be present in the token
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            validation.set_required_spec_claims(&["exp", "iss"]);
+        }
+        match &self.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                validation.required_spec_claims.insert("aud".to_string());
+            }
+            // Without a configured audience, tokens are accepted whatever they name
+            None => validation.validate_aud = false,
+        }
+
+        decode::<Claims>(token, &self.decoding, &validation).map(|data| data.claims)
+    }
+}
+
+/// Loads the process-wide JWT keys; the server calls this at startup to fail fast
+pub fn init_jwt_keys() -> Result<&'static JwtKeys, CodexError> {
+    if let Some(keys) = JWT_KEYS.get() {
+        return Ok(keys);
+    }
+    let keys = JwtKeys::from_env()?;
+    Ok(JWT_KEYS.get_or_init(|| keys))
+}
+
+fn jwt_keys() -> &'static JwtKeys {
+    init_jwt_keys().unwrap_or_else(|e| panic!("{}", e))
+}
 
 pub fn create_jwt_token(
     practitioner: &Practitioner,
 ) -> Result<String, jsonwebtoken::errors::Error> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize;
+    jwt_keys().sign(practitioner)
+}
 
-    let claims = Claims {
-        sub: practitioner.id.to_string(),
-        email: practitioner.email.clone(),
-        spiritual_name: practitioner.spiritual_name.clone(),
-        exp: now + 24 * 60 * 60, // 24 hours
-        iat: now,
-    };
+pub fn verify_jwt_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+    jwt_keys().verify(token)
+}
 
-    encode(
-        &Header::new(Algorithm::HS256),
-        &claims,
-        &EncodingKey::from_secret(JWT_SECRET),
-    )
+/// How long a refresh token can be exchanged for new access tokens
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Creates an opaque refresh token: 32 random bytes, hex encoded
+pub fn generate_refresh_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
-pub fn verify_jwt_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.validate_exp = true;
+/// Refresh tokens are only ever stored as their SHA-256 hash
+pub fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Issues a new refresh token for a practitioner and stores its hash
+pub async fn store_refresh_token(
+    db: &Database,
+    practitioner_id: Uuid,
+) -> Result<String, sqlx::Error> {
+    let token = generate_refresh_token();
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(REFRESH_TOKEN_TTL_DAYS);
+
+    with_pool!(db, |pool| {
+        sqlx::query(
+            "INSERT INTO refresh_tokens (id, practitioner_id, token_hash, expires_at)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(practitioner_id)
+        .bind(hash_refresh_token(&token))
+        .bind(expires_at)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    })?;
+
+    Ok(token)
+}
 
-    decode::<Claims>(token, &DecodingKey::from_secret(JWT_SECRET), &validation)
-        .map(|data| data.claims)
+/// Returns the practitioner a refresh token belongs to, if it exists and has not expired
+pub async fn redeem_refresh_token(db: &Database, token: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    with_pool!(db, |pool| {
+        sqlx::query_scalar::<_, Uuid>(
+            "SELECT practitioner_id FROM refresh_tokens WHERE token_hash = $1 AND expires_at > $2",
+        )
+        .bind(hash_refresh_token(token))
+        .bind(chrono::Utc::now())
+        .fetch_optional(pool)
+        .await
+    })
+}
+
+/// Deletes a refresh token so it can no longer be redeemed; false if it was unknown
+pub async fn revoke_refresh_token(db: &Database, token: &str) -> Result<bool, sqlx::Error> {
+    let rows_affected = with_pool!(db, |pool| {
+        sqlx::query("DELETE FROM refresh_tokens WHERE token_hash = $1")
+            .bind(hash_refresh_token(token))
+            .execute(pool)
+            .await
+            .map(|result| result.rows_affected())
+    })?;
+
+    Ok(rows_affected > 0)
 }
 
 pub fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
@@ -61,43 +343,68 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, bcrypt::Bcryp
     bcrypt::verify(password, hash)
 }
 
+/// Verifies an access token and loads the practitioner it was issued to,
+/// ensuring they still exist and have not deleted their account
+pub async fn authenticate_token(
+    db: &Database,
+    token: &str,
+) -> Result<(Practitioner, Claims), ApiError> {
+    let invalid = || ApiError::Unauthorized("Invalid or expired access token".to_string());
+    let claims = verify_jwt_token(token).map_err(|_| invalid())?;
+    let practitioner_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid())?;
+
+    let practitioner = find_active_practitioner(db, practitioner_id)
+        .await
+        .map_err(|e| ApiError::internal("Failed to load practitioner", e))?
+        .ok_or_else(|| ApiError::Unauthorized("Account not found or deleted".to_string()))?;
+
+    Ok((practitioner, claims))
+}
+
+/// Loads a practitioner unless their account has been soft-deleted
+pub async fn find_active_practitioner(
+    db: &Database,
+    practitioner_id: Uuid,
+) -> Result<Option<Practitioner>, sqlx::Error> {
+    with_pool!(db, |pool| {
+        sqlx::query_as::<_, Practitioner>(
+            "SELECT * FROM practitioners WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(practitioner_id)
+        .fetch_optional(pool)
+        .await
+    })
+}
+
 pub async fn auth_middleware(
     State(app_state): State<crate::handlers::AppState>,
     mut request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, ApiError> {
+    let missing = || ApiError::Unauthorized("Missing bearer token".to_string());
     let auth_header = request
         .headers()
         .get(AUTHORIZATION)
         .and_then(|header| header.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .ok_or_else(missing)?;
 
     if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
+        return Err(missing());
     }
 
     let token = &auth_header[7..]; // Remove "Bearer " prefix
+    let (practitioner, claims) = authenticate_token(&app_state.db, token).await?;
 
-    let claims = verify_jwt_token(token).map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    // Get practitioner from database to ensure they still exist
-    let practitioner_id = Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    let practitioner =
-        sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE id = $1")
-            .bind(practitioner_id)
-            .fetch_one(&app_state.db)
-            .await
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    // Add practitioner to request extensions for handlers to access
+    // Add practitioner and claims to request extensions for handlers to access
     request.extensions_mut().insert(practitioner);
+    request.extensions_mut().insert(claims);
 
     Ok(next.run(request).await)
 }
 
 pub fn create_auth_response(
     practitioner: &Practitioner,
+    refresh_token: String,
 ) -> Result<AuthToken, jsonwebtoken::errors::Error> {
     let token = create_jwt_token(practitioner)?;
 
@@ -109,11 +416,177 @@ pub fn create_auth_response(
         energy_alignments: practitioner.energy_alignments.clone(),
         privacy_level: practitioner.privacy_level.clone(),
         sacred_path: practitioner.sacred_path.clone(),
+        role: practitioner.role.clone(),
         member_since: practitioner.created_at,
     };
 
     Ok(AuthToken {
         token,
+        refresh_token,
         practitioner: profile,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn test_practitioner() -> Practitioner {
+        Practitioner {
+            id: Uuid::new_v4(),
+            email: "seeker@codex.test".to_string(),
+            password_hash: String::new(),
+            spiritual_name: Some("Seeker".to_string()),
+            archetypal_preferences: serde_json::json!({}),
+            energy_alignments: serde_json::json!({}),
+            privacy_level: "private".to_string(),
+            sacred_path: None,
+            role: "practitioner".to_string(),
+            created_at: Utc::now(),
+            deleted_at: None,
+            reflection_preferences: serde_json::json!({}),
+            reflection_api_key: None,
+        }
+    }
+
+    #[test]
+    fn test_refresh_tokens_are_random_and_hashed() {
+        let first = generate_refresh_token();
+        let second = generate_refresh_token();
+
+        assert_eq!(first.len(), 64);
+        assert_ne!(first, second);
+        assert_eq!(hash_refresh_token(&first), hash_refresh_token(&first));
+        assert_ne!(hash_refresh_token(&first), first);
+        assert_ne!(hash_refresh_token(&first), hash_refresh_token(&second));
+    }
+
+    #[test]
+    fn test_token_verifies_with_signing_secret() {
+        let keys = JwtKeys::from_secret(b"first-secret");
+        let practitioner = test_practitioner();
+
+        let token = keys.sign(&practitioner).unwrap();
+        let claims = keys.verify(&token).unwrap();
+
+        assert_eq!(claims.sub, practitioner.id.to_string());
+    }
+
+    #[test]
+    fn test_token_carries_practitioner_role() {
+        let keys = JwtKeys::from_secret(b"first-secret");
+        let mut practitioner = test_practitioner();
+        practitioner.role = "moderator".to_string();
+
+        let claims = keys.verify(&keys.sign(&practitioner).unwrap()).unwrap();
+
+        assert_eq!(claims.role, Role::Moderator);
+    }
+
+    #[test]
+    fn test_require_role_respects_hierarchy() {
+        let mut practitioner = test_practitioner();
+        assert_eq!(
+            require_role(&practitioner, Role::Moderator)
+                .unwrap_err()
+                .status(),
+            axum::http::StatusCode::FORBIDDEN
+        );
+
+        practitioner.role = "admin".to_string();
+        assert!(require_role(&practitioner, Role::Moderator).is_ok());
+        assert!(require_role(&practitioner, Role::Admin).is_ok());
+    }
+
+    #[test]
+    fn test_token_expiry_follows_configured_ttl() {
+        let default_claims = {
+            let keys = JwtKeys::from_secret(b"first-secret");
+            keys.verify(&keys.sign(&test_practitioner()).unwrap())
+                .unwrap()
+        };
+        assert_eq!(
+            default_claims.exp - default_claims.iat,
+            DEFAULT_JWT_TTL_SECONDS as usize
+        );
+        assert_eq!(default_claims.iss, None);
+        assert_eq!(default_claims.aud, None);
+
+        let keys = JwtKeys::from_secret(b"first-secret")
+            .with_claims_from_env_values(Some("900".to_string()), None, Some(String::new()))
+            .unwrap();
+        let claims = keys
+            .verify(&keys.sign(&test_practitioner()).unwrap())
+            .unwrap();
+        assert_eq!(claims.exp - claims.iat, 900);
+
+        // Long expired, well beyond the validation leeway
+        let expired = JwtKeys::from_secret(b"first-secret").with_ttl_seconds(1);
+        let mut stale = expired
+            .verify(&expired.sign(&test_practitioner()).unwrap())
+            .unwrap();
+        stale.exp -= 3600;
+        let token = encode(&Header::new(Algorithm::HS256), &stale, &expired.encoding).unwrap();
+        assert!(expired.verify(&token).is_err());
+
+        for invalid in ["0", "-5", "soon"] {
+            assert!(JwtKeys::from_secret(b"first-secret")
+                .with_claims_from_env_values(Some(invalid.to_string()), None, None)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_token_for_another_audience_or_issuer_is_rejected() {
+        let codex = JwtKeys::from_secret(b"shared-secret")
+            .with_issuer("codex")
+            .with_audience("codex-api");
+        let token = codex.sign(&test_practitioner()).unwrap();
+        let claims = codex.verify(&token).unwrap();
+        assert_eq!(claims.iss.as_deref(), Some("codex"));
+        assert_eq!(claims.aud, Some(Audience::One("codex-api".to_string())));
+
+        let other_audience = JwtKeys::from_secret(b"shared-secret").with_audience("other-api");
+        assert!(other_audience.verify(&token).is_err());
+        let other_issuer = JwtKeys::from_secret(b"shared-secret").with_issuer("elsewhere");
+        assert!(other_issuer.verify(&token).is_err());
+
+        // An audience list only has to name this service among others
+        let mut listed = claims.clone();
+        listed.aud = Some(Audience::Many(vec!["other-api".to_string(), "codex-api".to_string()]));
+        let listed = encode(&Header::new(Algorithm::HS256), &listed, &codex.encoding).unwrap();
+        let listed_claims = codex.verify(&listed).unwrap();
+        assert!(listed_claims.aud.unwrap().contains("codex-api"));
+        assert!(other_audience.verify(&listed).is_ok());
+        let unrelated = encode(
+            &Header::new(Algorithm::HS256),
+            &Claims { aud: Some(Audience::Many(vec!["elsewhere".to_string()])), ..claims.clone() },
+            &codex.encoding,
+        )
+        .unwrap();
+        assert!(codex.verify(&unrelated).is_err());
+
+        // A service that expects an audience refuses tokens that name none
+        let unscoped = JwtKeys::from_secret(b"shared-secret")
+            .sign(&test_practitioner())
+            .unwrap();
+        assert!(codex.verify(&unscoped).is_err());
+
+        // Without configured claims, verification is as before
+        assert!(JwtKeys::from_secret(b"shared-secret")
+            .verify(&token)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_token_fails_under_different_secret() {
+        let token = JwtKeys::from_secret(b"first-secret")
+            .sign(&test_practitioner())
+            .unwrap();
+
+        assert!(JwtKeys::from_secret(b"second-secret")
+            .verify(&token)
+            .is_err());
+    }
+}
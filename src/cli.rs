@@ -1,6 +1,11 @@
-use crate::{CodexEngine, CodexError};
-use clap::{Parser, Subcommand};
+use crate::doctor::{self, CheckStatus, DoctorReport};
+use crate::reflection::ReflectionConfig;
+use crate::{CodexEngine, CodexError, RitualResult, StateDiff};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
 #[derive(Parser)]
 #[command(
@@ -10,10 +15,109 @@ use colored::*;
                  evolving archetypal states, and reflecting on the mysteries of transformation."
 )]
 pub struct Cli {
+    /// Output format for command results
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Directory for state, history and custom rituals [default: $CODEX_DATA_DIR or ~/.codex]
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Decorated text for people, colored when writing to a terminal
+    Text,
+    /// Machine-readable JSON on stdout
+    Json,
+    /// Markdown reflections to keep in files or journals; other commands print uncolored text
+    Md,
+    /// Decorated text that keeps its ANSI colors even when redirected
+    Ansi,
+}
+
+/// Order `codex history` lists past rituals in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum HistorySort {
+    /// In the order they were performed, oldest first
+    Time,
+    /// Highest resonance first
+    Resonance,
+}
+
+/// Which past rituals `codex history` shows, in what order, and which page of them
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct HistoryQuery {
+    /// Only show executions of this ritual
+    #[arg(long)]
+    pub ritual: Option<String>,
+    /// Order to list them in
+    #[arg(long, value_enum, default_value_t = HistorySort::Time)]
+    pub sort: HistorySort,
+    /// Show at most this many rituals per page
+    #[arg(long)]
+    pub limit: Option<usize>,
+    /// Which page of `--limit` rituals to show, starting from 1
+    #[arg(long, default_value_t = 1, requires = "limit")]
+    pub page: usize,
+}
+
+impl Default for HistoryQuery {
+    fn default() -> Self {
+        Self {
+            ritual: None,
+            sort: HistorySort::Time,
+            limit: None,
+            page: 1,
+        }
+    }
+}
+
+impl HistoryQuery {
+    /// The matching entries of `history` for this page, each with its index in
+    /// `history` so `codex reflect --session` can still find it
+    pub fn select<'a>(&self, history: &'a [RitualResult]) -> Vec<(usize, &'a RitualResult)> {
+        let mut selected: Vec<(usize, &RitualResult)> = history
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| match &self.ritual {
+                Some(name) => &result.ritual_name == name,
+                None => true,
+            })
+            .collect();
+        if self.sort == HistorySort::Resonance {
+            // A stable sort, so equal resonances stay in the order they were performed
+            selected.sort_by(|a, b| b.1.resonance_level.total_cmp(&a.1.resonance_level));
+        }
+
+        match self.limit {
+            Some(limit) => selected
+                .into_iter()
+                .skip(limit.saturating_mul(self.page.max(1) - 1))
+                .take(limit)
+                .collect(),
+            None => selected,
+        }
+    }
+
+    /// How many pages the rituals matching this query fill
+    fn page_count(&self, history: &[RitualResult]) -> usize {
+        let matching = Self {
+            limit: None,
+            ..self.clone()
+        }
+        .select(history)
+        .len();
+        match self.limit {
+            Some(limit) if limit > 0 => matching.div_ceil(limit).max(1),
+            _ => 1,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Execute a symbolic ritual
@@ -30,10 +134,26 @@ pub enum Commands {
     },
     /// Seek AI reflection on the last ritual
     #[command(name = "reflect")]
-    Reflect,
+    Reflect {
+        /// Reflect on an earlier ritual, by its index in `codex history`
+        #[arg(long)]
+        session: Option<usize>,
+        /// Ask the oracle again instead of recalling the cached reflection
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// List past ritual executions
+    #[command(name = "history")]
+    History {
+        #[command(flatten)]
+        query: HistoryQuery,
+    },
     /// List available rituals
     #[command(name = "list")]
     List,
+    /// Check the data directory, state file and reflection setup
+    #[command(name = "doctor")]
+    Doctor,
     /// Initialize or reset the symbolic state
     #[command(name = "init")]
     Init {
@@ -50,6 +170,46 @@ pub enum RitualCommands {
     Run {
         /// Name of the ritual to execute
         name: String,
+        /// Seed the ritual's randomness so repeated runs from the same state match
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Preview the ritual's effect without saving the new state
+        #[arg(long)]
+        dry_run: bool,
+        /// First invoke any required archetype that is too faint for the ritual
+        #[arg(long, conflicts_with = "dry_run")]
+        prepare: bool,
+    },
+    /// Run several rituals in sequence, each on the state left by the last
+    #[command(name = "chain")]
+    Chain {
+        /// Names of the rituals to execute, in order
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Continue a WASM ritual that was interrupted after saving a checkpoint
+    #[command(name = "resume")]
+    Resume {
+        /// Execution id of the interrupted ritual, as shown in its outcome or `codex history`
+        execution_id: Uuid,
+    },
+    /// Run a WASM ritual module from disk without saving anything, to test it locally
+    #[command(name = "run-wasm")]
+    RunWasm {
+        /// The compiled ritual module (.wasm, or .wat text)
+        path: PathBuf,
+        /// Run against the state in this file (.json, .toml or .yaml) instead of the current one
+        #[arg(long)]
+        state: Option<PathBuf>,
+        /// Seed the module's `get_random` so repeated runs match
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Define a custom ritual from a TOML file and keep it for later sessions
+    #[command(name = "add")]
+    Add {
+        /// TOML file with the ritual's name, description, intent and requirements
+        path: PathBuf,
     },
 }
 
@@ -61,20 +221,101 @@ pub enum StateCommands {
     /// Show a summary of the current state
     #[command(name = "summary")]
     Summary,
+    /// Roll back to the state before the last ritual
+    #[command(name = "undo")]
+    Undo,
+    /// Write the current state to a file (.json, .toml or .yaml)
+    #[command(name = "export")]
+    Export {
+        /// Destination file; the extension selects the format
+        path: PathBuf,
+    },
+    /// Load the state from a file (.json, .toml or .yaml)
+    #[command(name = "import")]
+    Import {
+        /// File to read; the extension selects the format
+        path: PathBuf,
+        /// Keep the stronger of current and imported activations instead of replacing
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Return one archetype to dormancy, keeping its evolution
+    #[command(name = "reset-archetype")]
+    ResetArchetype {
+        /// Archetype to reset, e.g. Shadow
+        name: String,
+    },
+    /// Still one energy, keeping its frequency and polarity
+    #[command(name = "reset-energy")]
+    ResetEnergy {
+        /// Energy to reset
+        name: String,
+    },
 }
 
 pub async fn run_cli() -> Result<(), CodexError> {
     let cli = Cli::parse();
 
+    // The flag wins over `CODEX_DATA_DIR`, which wins over `~/.codex`
+    let data_dir = match cli.data_dir {
+        Some(data_dir) => data_dir,
+        None => CodexEngine::get_data_directory()?,
+    };
+
+    match cli.format {
+        OutputFormat::Ansi => colored::control::set_override(true),
+        OutputFormat::Md => colored::control::set_override(false),
+        OutputFormat::Text | OutputFormat::Json => {}
+    }
+
+    // Diagnose before loading the engine, which may be what is broken
+    if matches!(cli.command, Commands::Doctor) {
+        return run_doctor(&data_dir, cli.format);
+    }
+
+    if cli.format == OutputFormat::Json {
+        return run_json(data_dir, cli.command).await;
+    }
+    if let (OutputFormat::Md, Commands::Reflect { session, refresh }) = (cli.format, &cli.command) {
+        return reflect_markdown(data_dir, *session, *refresh).await;
+    }
+
     // Print the sacred banner
     print_banner();
 
-    let mut engine = CodexEngine::new()?;
+    let mut engine = CodexEngine::new_with_data_dir(data_dir)?;
 
     match cli.command {
         Commands::Ritual { action } => match action {
-            RitualCommands::Run { name } => {
-                execute_ritual(&mut engine, &name).await?;
+            RitualCommands::Run {
+                name,
+                seed,
+                dry_run,
+                prepare,
+            } => {
+                execute_ritual(&mut engine, &name, seed, dry_run, prepare).await?;
+            }
+            RitualCommands::Chain { names } => {
+                execute_ritual_chain(&mut engine, &names).await?;
+            }
+            RitualCommands::Resume { execution_id } => {
+                resume_ritual(&mut engine, execution_id).await?;
+            }
+            RitualCommands::RunWasm { path, state, seed } => {
+                let outcome = engine.run_wasm_file(&path, state.as_deref(), seed).await?;
+                show_state_diff(&outcome.diff);
+            }
+            RitualCommands::Add { path } => {
+                let ritual = engine.add_ritual_from_file(&path)?;
+                println!(
+                    "{}",
+                    format!("📜 Ritual '{}' added from {}", ritual.name, path.display())
+                        .bright_green()
+                );
+                println!(
+                    "{}",
+                    format!("Run it with: codex ritual run {}", ritual.name).white()
+                );
             }
         },
         Commands::State { action } => match action {
@@ -84,13 +325,50 @@ pub async fn run_cli() -> Result<(), CodexError> {
             StateCommands::Summary => {
                 show_state_summary(&engine);
             }
+            StateCommands::Undo => {
+                undo_last_ritual(&mut engine)?;
+            }
+            StateCommands::Export { path } => {
+                engine.export_state(&path)?;
+                println!(
+                    "{}",
+                    format!("📦 Symbolic state exported to {}", path.display()).bright_green()
+                );
+            }
+            StateCommands::Import { path, merge } => {
+                engine.import_state(&path, merge)?;
+                let verb = if merge { "merged" } else { "imported" };
+                println!(
+                    "{}",
+                    format!("📥 Symbolic state {} from {}", verb, path.display()).bright_green()
+                );
+                println!("{}", engine.get_state().get_activation_summary().white());
+            }
+            StateCommands::ResetArchetype { name } => {
+                reset_entity(
+                    &mut engine,
+                    "archetype",
+                    &name,
+                    CodexEngine::reset_archetype,
+                )?;
+            }
+            StateCommands::ResetEnergy { name } => {
+                reset_entity(&mut engine, "energy", &name, CodexEngine::reset_energy)?;
+            }
         },
-        Commands::Reflect => {
-            engine.reflect().await?;
+        Commands::Reflect { session, refresh } => {
+            match session {
+                Some(index) => engine.reflect_on(index, refresh).await?,
+                None => engine.reflect(refresh).await?,
+            };
+        }
+        Commands::History { query } => {
+            show_history(&engine, &query);
         }
         Commands::List => {
             engine.list_available_rituals();
         }
+        Commands::Doctor => unreachable!("doctor runs before the engine loads"),
         Commands::Init { force } => {
             initialize_system(&mut engine, force)?;
         }
@@ -99,6 +377,149 @@ pub async fn run_cli() -> Result<(), CodexError> {
     Ok(())
 }
 
+/// Runs a command with a quiet engine and writes only its result, as JSON, to stdout
+async fn run_json(data_dir: PathBuf, command: Commands) -> Result<(), CodexError> {
+    let mut engine = CodexEngine::new_quiet_with_data_dir(data_dir)?;
+
+    match command {
+        Commands::Ritual { action } => match action {
+            RitualCommands::Run {
+                name,
+                seed,
+                dry_run: true,
+                ..
+            } => print_json(&serde_json::json!({
+                "dry_run": true,
+                "result": engine.preview_ritual(&name, seed).await?,
+            })),
+            RitualCommands::Run {
+                name,
+                seed,
+                prepare: true,
+                ..
+            } => print_json(&engine.execute_prepared_ritual(&name, seed).await?),
+            RitualCommands::Run { name, seed, .. } => {
+                print_json(&engine.execute_ritual_with_seed(&name, seed).await?)
+            }
+            RitualCommands::Chain { names } => print_json(&engine.execute_chain(&names).await?),
+            RitualCommands::Resume { execution_id } => {
+                print_json(&engine.resume_ritual(execution_id).await?)
+            }
+            RitualCommands::RunWasm { path, state, seed } => {
+                print_json(&engine.run_wasm_file(&path, state.as_deref(), seed).await?)
+            }
+            RitualCommands::Add { path } => print_json(&engine.add_ritual_from_file(&path)?),
+        },
+        Commands::State { action } => match action {
+            StateCommands::View | StateCommands::Summary => print_json(engine.get_state()),
+            StateCommands::Undo => {
+                let undone = engine.undo()?;
+                print_json(&serde_json::json!({
+                    "undone": undone,
+                    "state": engine.get_state(),
+                }))
+            }
+            StateCommands::Export { path } => {
+                engine.export_state(&path)?;
+                print_json(&serde_json::json!({ "exported": path }))
+            }
+            StateCommands::Import { path, merge } => {
+                engine.import_state(&path, merge)?;
+                print_json(engine.get_state())
+            }
+            StateCommands::ResetArchetype { name } => {
+                let reset = engine.reset_archetype(&name);
+                if reset {
+                    engine.save_state()?;
+                }
+                print_json(&serde_json::json!({ "archetype": name, "reset": reset }))
+            }
+            StateCommands::ResetEnergy { name } => {
+                let reset = engine.reset_energy(&name);
+                if reset {
+                    engine.save_state()?;
+                }
+                print_json(&serde_json::json!({ "energy": name, "reset": reset }))
+            }
+        },
+        Commands::Reflect { session, refresh } => match session {
+            Some(index) => print_json(&engine.reflect_on(index, refresh).await?),
+            None => print_json(&engine.reflect(refresh).await?),
+        },
+        Commands::History { query } => {
+            let results: Vec<&RitualResult> = query
+                .select(engine.history())
+                .into_iter()
+                .map(|(_, result)| result)
+                .collect();
+            print_json(&results)
+        }
+        Commands::List => print_json(&engine.ritual_definitions()),
+        Commands::Doctor => unreachable!("doctor runs before the engine loads"),
+        Commands::Init { force } => {
+            let initialized = force || engine.get_state().archetypes.is_empty();
+            if initialized {
+                engine.save_state()?;
+            }
+            print_json(&serde_json::json!({ "initialized": initialized }))
+        }
+    }
+}
+
+/// Reflects with a quiet engine and writes only the Markdown reflection to stdout
+async fn reflect_markdown(
+    data_dir: PathBuf,
+    session: Option<usize>,
+    refresh: bool,
+) -> Result<(), CodexError> {
+    let engine = CodexEngine::new_quiet_with_data_dir(data_dir)?;
+    let reflection = match session {
+        Some(index) => engine.reflect_on(index, refresh).await?,
+        None => engine.reflect(refresh).await?,
+    };
+    print!(
+        "{}",
+        engine.reflector().format_reflection_markdown(&reflection)
+    );
+    Ok(())
+}
+
+/// Prints the diagnostic checklist and fails if any hard check failed
+fn run_doctor(data_dir: &Path, format: OutputFormat) -> Result<(), CodexError> {
+    let report = doctor::run_diagnostics(data_dir, &ReflectionConfig::default());
+
+    match format {
+        OutputFormat::Json => print_json(&report)?,
+        OutputFormat::Text | OutputFormat::Md | OutputFormat::Ansi => show_doctor_report(&report),
+    }
+
+    match report.failures() {
+        0 => Ok(()),
+        failures => Err(CodexError::Configuration {
+            reason: format!("{} diagnostic check(s) failed", failures),
+        }),
+    }
+}
+
+fn show_doctor_report(report: &DoctorReport) {
+    println!("\n{}", "🩺 CODEX DOCTOR".bright_cyan().bold());
+    println!("{}", "═".repeat(62).bright_purple());
+    for check in &report.checks {
+        let marker = match check.status {
+            CheckStatus::Pass => "✅ pass".bright_green(),
+            CheckStatus::Warn => "⚠️  warn".bright_yellow(),
+            CheckStatus::Fail => "❌ fail".bright_red(),
+        };
+        println!("{}  {:<20} {}", marker, check.name, check.detail);
+    }
+    println!("{}", "═".repeat(62).bright_purple());
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), CodexError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
 fn print_banner() {
     let banner = r#"
     ╔══════════════════════════════════════════════════════════╗
@@ -115,7 +536,13 @@ fn print_banner() {
     println!("{}", banner.bright_purple());
 }
 
-async fn execute_ritual(engine: &mut CodexEngine, ritual_name: &str) -> Result<(), CodexError> {
+async fn execute_ritual(
+    engine: &mut CodexEngine,
+    ritual_name: &str,
+    seed: Option<u64>,
+    dry_run: bool,
+    prepare: bool,
+) -> Result<(), CodexError> {
     println!(
         "\n{}",
         format!("🌟 Preparing to invoke ritual: {}", ritual_name)
@@ -123,7 +550,22 @@ async fn execute_ritual(engine: &mut CodexEngine, ritual_name: &str) -> Result<(
             .bold()
     );
 
-    match engine.execute_ritual(ritual_name).await {
+    let outcome = if dry_run {
+        engine.preview_ritual(ritual_name, seed).await
+    } else if prepare {
+        engine.execute_prepared_ritual(ritual_name, seed).await
+    } else {
+        engine.execute_ritual_with_seed(ritual_name, seed).await
+    };
+
+    match outcome {
+        Ok(_result) if dry_run => {
+            println!(
+                "\n{}",
+                "🔍 Dry run only: the state was not changed.".bright_yellow()
+            );
+            Ok(())
+        }
         Ok(_result) => {
             println!(
                 "\n{}",
@@ -151,6 +593,184 @@ async fn execute_ritual(engine: &mut CodexEngine, ritual_name: &str) -> Result<(
     }
 }
 
+async fn resume_ritual(engine: &mut CodexEngine, execution_id: Uuid) -> Result<(), CodexError> {
+    println!(
+        "\n{}",
+        format!("🌟 Resuming ritual execution: {}", execution_id)
+            .bright_cyan()
+            .bold()
+    );
+
+    match engine.resume_ritual(execution_id).await {
+        Ok(_result) => {
+            println!(
+                "\n{}",
+                "🎭 Ritual resumed. Use 'codex reflect' to gain deeper insights.".bright_green()
+            );
+            Ok(())
+        }
+        Err(e) => {
+            println!(
+                "\n{}",
+                format!("❌ Ritual resumption failed: {}", e).bright_red()
+            );
+            Err(e)
+        }
+    }
+}
+
+async fn execute_ritual_chain(
+    engine: &mut CodexEngine,
+    ritual_names: &[String],
+) -> Result<(), CodexError> {
+    println!(
+        "\n{}",
+        format!("🌟 Preparing ritual chain: {}", ritual_names.join(" → "))
+            .bright_cyan()
+            .bold()
+    );
+
+    let chain = engine.execute_chain(ritual_names).await?;
+
+    println!("\n{}", "📜 RITUAL CHAIN SUMMARY".bright_cyan().bold());
+    println!("{}", "═".repeat(50).bright_purple());
+    println!(
+        "{:<28} {:>10} {:>10}",
+        "Ritual".bright_yellow(),
+        "Resonance".bright_yellow(),
+        "Duration".bright_yellow()
+    );
+    for result in &chain.results {
+        println!(
+            "{:<28} {:>10.3} {:>8}ms",
+            result.ritual_name, result.resonance_level, result.duration_ms
+        );
+    }
+    println!("{}", "─".repeat(50).bright_purple());
+    println!(
+        "{:<28} {:>10.3} {:>8}ms",
+        "Mean / Total".bright_white().bold(),
+        chain.mean_resonance(),
+        chain.total_duration_ms()
+    );
+    println!("{}", "═".repeat(50).bright_purple());
+
+    if !chain.completed {
+        let skipped = ritual_names.len() - chain.results.len();
+        println!(
+            "\n{}",
+            format!(
+                "❌ The chain was broken by a failed ritual; {} remaining ritual(s) skipped.",
+                skipped
+            )
+            .bright_red()
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints how a ritual moved each archetype and energy and which symbols came and went
+fn show_state_diff(diff: &StateDiff) {
+    println!("\n{}", "🔀 STATE DIFF".bright_cyan().bold());
+    if diff.is_empty() {
+        println!("{}", "The state was left unchanged.".white());
+        return;
+    }
+
+    let mut archetypes: Vec<_> = diff.archetype_deltas.iter().collect();
+    archetypes.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, delta) in archetypes {
+        println!("  {:<18} {:+.3}", name.bright_yellow(), delta);
+    }
+
+    let mut energies: Vec<_> = diff.energy_deltas.iter().collect();
+    energies.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, delta) in energies {
+        println!(
+            "  {:<18} amplitude {:+.3}, frequency {:+.2} Hz",
+            name.bright_blue(),
+            delta.amplitude,
+            delta.frequency
+        );
+    }
+
+    for symbol in &diff.added_symbols {
+        println!("  {} {}", "+".bright_green(), symbol);
+    }
+    for symbol in &diff.removed_symbols {
+        println!("  {} {}", "-".bright_red(), symbol);
+    }
+    for integration in &diff.new_integrations {
+        println!("  {} {}", "Integrated:".bright_magenta(), integration);
+    }
+}
+
+fn show_history(engine: &CodexEngine, query: &HistoryQuery) {
+    let history = engine.history();
+    if history.is_empty() {
+        println!(
+            "\n{}",
+            "📜 No rituals have been performed yet.".bright_yellow()
+        );
+        return;
+    }
+
+    let selected = query.select(history);
+    if selected.is_empty() {
+        println!(
+            "\n{}",
+            "📜 No past rituals match this page or filter.".bright_yellow()
+        );
+        return;
+    }
+
+    println!("\n{}", "📜 RITUAL HISTORY".bright_cyan().bold());
+    println!("{}", "═".repeat(62).bright_purple());
+    println!(
+        "{:>5}  {:<18} {:<24} {:>10}",
+        "#".bright_yellow(),
+        "When".bright_yellow(),
+        "Ritual".bright_yellow(),
+        "Resonance".bright_yellow()
+    );
+    for (index, result) in selected {
+        println!(
+            "{:>5}  {:<18} {:<24} {:>10.3}",
+            index,
+            result.timestamp.format("%Y-%m-%d %H:%M"),
+            result.ritual_name,
+            result.resonance_level
+        );
+        if result.checkpoint.is_some() {
+            println!(
+                "       {}",
+                format!(
+                    "⏸  resume with: codex ritual resume {}",
+                    result.execution_id
+                )
+                .bright_yellow()
+            );
+        }
+    }
+    println!("{}", "═".repeat(62).bright_purple());
+    if query.limit.is_some() {
+        println!(
+            "{}",
+            format!(
+                "Page {} of {}",
+                query.page.max(1),
+                query.page_count(history)
+            )
+            .bright_white()
+        );
+    }
+    println!(
+        "{}",
+        "Use 'codex reflect --session <#>' to reflect on an earlier ritual.".bright_white()
+    );
+}
+
 fn show_state_summary(engine: &CodexEngine) {
     let state = engine.get_state();
 
@@ -188,6 +808,48 @@ fn show_state_summary(engine: &CodexEngine) {
     println!("{}", "═".repeat(50).bright_purple());
 }
 
+fn undo_last_ritual(engine: &mut CodexEngine) -> Result<(), CodexError> {
+    if engine.undo()? {
+        println!(
+            "{}",
+            "⏪ The last ritual has been unwound. The prior state is restored.".bright_green()
+        );
+        println!("{}", engine.get_state().get_activation_summary().white());
+    } else {
+        println!(
+            "{}",
+            "🔮 No earlier state remains to return to.".bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
+/// Resets the named archetype or energy with `reset` and saves the state, reporting
+/// when nothing by that name exists
+fn reset_entity(
+    engine: &mut CodexEngine,
+    kind: &str,
+    name: &str,
+    reset: fn(&mut CodexEngine, &str) -> bool,
+) -> Result<(), CodexError> {
+    if reset(engine, name) {
+        engine.save_state()?;
+        println!(
+            "{}",
+            format!("🌑 The {} {} has been returned to stillness.", kind, name).bright_green()
+        );
+        println!("{}", engine.get_state().get_activation_summary().white());
+    } else {
+        println!(
+            "{}",
+            format!("🔮 No {} named '{}' dwells in the state.", kind, name).bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
 fn initialize_system(engine: &mut CodexEngine, force: bool) -> Result<(), CodexError> {
     if !force {
         let state = engine.get_state();
@@ -230,15 +892,44 @@ Basic Commands:
   codex list                          # Show available rituals
   codex state view                    # View detailed symbolic state
   codex state summary                 # Quick state overview
+  codex state undo                    # Undo the last ritual
+  codex doctor                        # Diagnose setup problems
+  codex --format json state view      # Emit the state as JSON for scripts
+  codex --data-dir ./profile state view
+                                      # Use another profile (or set CODEX_DATA_DIR)
+  codex state export backup.toml      # Back up the state (.json/.toml/.yaml)
+  codex state import backup.toml      # Restore it (add --merge to combine)
+  codex state reset-archetype Shadow  # Return one archetype to dormancy
 
 Ritual Execution:
   codex ritual run shadow_integration    # Integrate shadow aspects
   codex ritual run energy_attunement     # Harmonize energies
   codex ritual run archetype_invocation  # Activate archetypes
   codex ritual run void_contemplation    # Enter emptiness
+  codex ritual run frequency_tuning      # Tune energies to Solfeggio tones
+  codex ritual run symbol_resolution     # Integrate the meaning of known symbols
+  codex ritual run shadow_integration --seed 42
+                                         # Repeatable run with fixed randomness
+  codex ritual run shadow_integration --dry-run
+                                         # Preview without saving the new state
+  codex ritual run shadow_integration --prepare
+                                         # Invoke faint required archetypes first
+  codex ritual chain energy_attunement archetype_invocation shadow_integration
+                                         # Run rituals in sequence
+  codex ritual add my_ritual.toml        # Define your own ritual from TOML
+  codex ritual run-wasm my_ritual.wasm   # Try a ritual module without saving the state
 
 Reflection:
   codex reflect                       # AI reflection on last ritual
+  codex history                       # List past rituals with their index
+  codex history --sort resonance --limit 10 --page 2
+                                      # Page through rituals, strongest first
+  codex history --ritual shadow_integration
+                                      # Only one ritual's executions
+  codex reflect --session 3           # Reflect on an earlier ritual
+  codex reflect --refresh             # Ask again instead of recalling the cache
+  codex reflect --format md > journal.md
+                                      # Keep the reflection as Markdown
 
 Workflow Example:
   codex init                          # 1. Initialize system
@@ -259,3 +950,125 @@ pub fn handle_interrupt() {
     );
     std::process::exit(0);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ritual::CompletionStatus;
+    use std::collections::HashMap;
+
+    fn past_ritual(name: &str, resonance_level: f64) -> RitualResult {
+        RitualResult {
+            ritual_name: name.to_string(),
+            execution_id: Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            duration_ms: 0,
+            symbolic_outputs: HashMap::new(),
+            state_changes: vec![],
+            emergent_symbols: vec![],
+            completion_status: CompletionStatus::Complete,
+            resonance_level,
+            checkpoint: None,
+        }
+    }
+
+    fn synthetic_history() -> Vec<RitualResult> {
+        vec![
+            past_ritual("shadow_integration", 0.4),
+            past_ritual("energy_attunement", 0.9),
+            past_ritual("shadow_integration", 0.7),
+            past_ritual("void_contemplation", 0.2),
+            past_ritual("shadow_integration", 0.9),
+        ]
+    }
+
+    fn indices(selected: &[(usize, &RitualResult)]) -> Vec<usize> {
+        selected.iter().map(|(index, _)| *index).collect()
+    }
+
+    #[test]
+    fn test_history_defaults_to_every_ritual_in_order() {
+        let history = synthetic_history();
+
+        let selected = HistoryQuery::default().select(&history);
+
+        assert_eq!(indices(&selected), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_history_sorts_by_resonance_highest_first() {
+        let history = synthetic_history();
+        let query = HistoryQuery {
+            sort: HistorySort::Resonance,
+            ..HistoryQuery::default()
+        };
+
+        let selected = query.select(&history);
+
+        // Equal resonances keep the order they were performed in
+        assert_eq!(indices(&selected), [1, 4, 2, 0, 3]);
+    }
+
+    #[test]
+    fn test_history_filters_by_ritual_name() {
+        let history = synthetic_history();
+        let query = HistoryQuery {
+            ritual: Some("shadow_integration".to_string()),
+            ..HistoryQuery::default()
+        };
+
+        let selected = query.select(&history);
+
+        assert_eq!(indices(&selected), [0, 2, 4]);
+        assert!(selected
+            .iter()
+            .all(|(_, result)| result.ritual_name == "shadow_integration"));
+    }
+
+    #[test]
+    fn test_history_pages_after_filtering_and_sorting() {
+        let history = synthetic_history();
+        let page = |page| HistoryQuery {
+            ritual: Some("shadow_integration".to_string()),
+            sort: HistorySort::Resonance,
+            limit: Some(2),
+            page,
+        };
+
+        assert_eq!(indices(&page(1).select(&history)), [4, 2]);
+        assert_eq!(indices(&page(2).select(&history)), [0]);
+        assert!(page(3).select(&history).is_empty());
+        assert_eq!(page(1).page_count(&history), 2);
+    }
+
+    #[test]
+    fn test_history_flags_parse() {
+        let cli = Cli::try_parse_from([
+            "codex",
+            "history",
+            "--sort",
+            "resonance",
+            "--limit",
+            "5",
+            "--page",
+            "2",
+            "--ritual",
+            "void_contemplation",
+        ])
+        .unwrap();
+
+        let Commands::History { query } = cli.command else {
+            panic!("expected the history command");
+        };
+        assert_eq!(
+            query,
+            HistoryQuery {
+                ritual: Some("void_contemplation".to_string()),
+                sort: HistorySort::Resonance,
+                limit: Some(5),
+                page: 2,
+            }
+        );
+        assert!(Cli::try_parse_from(["codex", "history", "--page", "2"]).is_err());
+    }
+}
@@ -1,29 +1,224 @@
-use sqlx::{migrate::MigrateDatabase, PgPool, Postgres};
-use std::env;
+use sqlx::{
+    migrate::{Migrate, MigrateDatabase, Migrator},
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    PgPool, Postgres, SqlitePool,
+};
+use std::{env, str::FromStr};
 
-pub async fn connect_database() -> Result<PgPool, sqlx::Error> {
+use crate::CodexError;
+
+static POSTGRES_MIGRATIONS: Migrator = sqlx::migrate!("./migrations");
+static SQLITE_MIGRATIONS: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+/// Set to `false` to refuse to start against an out-of-date schema instead of migrating it
+const AUTO_MIGRATE_ENV: &str = "CODEX_AUTO_MIGRATE";
+
+/// The database behind the web API: Postgres for shared deployments, or SQLite
+/// for a single practitioner running the API locally or offline.
+///
+/// Handlers write their SQL once in the subset both backends accept (`$1`
+/// placeholders, `RETURNING`, timestamps bound from Rust rather than `NOW()`)
+/// and run it on whichever pool is active with [`with_pool!`](crate::database::with_pool).
+#[derive(Clone, Debug)]
+pub enum Database {
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+}
+
+/// Runs `$body` with `$pool` bound to the active backend's pool.
+///
+/// The body is compiled once per backend, so it must evaluate to the same type
+/// for both; map backend-specific results such as rows or query results inside it.
+macro_rules! with_pool {
+    ($db:expr, |$pool:ident| $body:expr) => {
+        match $db {
+            $crate::database::Database::Postgres($pool) => $body,
+            $crate::database::Database::Sqlite($pool) => $body,
+        }
+    };
+}
+pub(crate) use with_pool;
+
+impl Database {
+    /// Opens a pool for `database_url`, choosing SQLite for `sqlite:` URLs and
+    /// Postgres otherwise, and creating the database if it does not exist yet
+    pub async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
+        if database_url.starts_with("sqlite:") {
+            let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+            let pool_options = if database_url.contains(":memory:") {
+                // Every connection to `:memory:` opens its own empty database,
+                // so keep exactly one connection alive for the life of the pool
+                SqlitePoolOptions::new()
+                    .max_connections(1)
+                    .idle_timeout(None)
+                    .max_lifetime(None)
+            } else {
+                SqlitePoolOptions::new()
+            };
+            return Ok(Database::Sqlite(pool_options.connect_with(options).await?));
+        }
+
+        if !Postgres::database_exists(database_url)
+            .await
+            .unwrap_or(false)
+        {
+            println!("🔮 Creating sacred database...");
+            Postgres::create_database(database_url).await?;
+        }
+
+        Ok(Database::Postgres(PgPool::connect(database_url).await?))
+    }
+
+    /// Applies `migrations/` to Postgres or `migrations_sqlite/` to SQLite
+    pub async fn migrate(&self) -> Result<(), sqlx::migrate::MigrateError> {
+        match self {
+            Database::Postgres(pool) => POSTGRES_MIGRATIONS.run(pool).await,
+            Database::Sqlite(pool) => SQLITE_MIGRATIONS.run(pool).await,
+        }
+    }
+
+    /// The newest migration version embedded in this build for the active backend
+    pub fn expected_schema_version(&self) -> i64 {
+        let migrator = match self {
+            Database::Postgres(_) => &POSTGRES_MIGRATIONS,
+            Database::Sqlite(_) => &SQLITE_MIGRATIONS,
+        };
+        migrator.iter().map(|migration| migration.version).max().unwrap_or(0)
+    }
+
+    /// The newest migration version successfully applied to the database, or
+    /// `None` when no migration has been applied yet. A fresh database gets the
+    /// empty migrations table that the first migration would create anyway.
+    pub async fn applied_schema_version(&self) -> Result<Option<i64>, sqlx::Error> {
+        with_pool!(self, |pool| {
+            let mut conn = pool.acquire().await?;
+            conn.ensure_migrations_table().await?;
+            sqlx::query_scalar::<_, Option<i64>>(
+                "SELECT MAX(version) FROM _sqlx_migrations WHERE success",
+            )
+            .fetch_one(&mut *conn)
+            .await
+        })
+    }
+
+    /// Confirms the database schema is exactly the version this build expects, so
+    /// queries against missing columns fail here rather than inside a request
+    pub async fn check_schema_version(&self) -> Result<(), CodexError> {
+        let expected = self.expected_schema_version();
+        let applied = self
+            .applied_schema_version()
+            .await
+            .map_err(|e| CodexError::Configuration {
+                reason: format!("Could not read the database schema version: {}", e),
+            })?;
+
+        match applied {
+            Some(version) if version == expected => Ok(()),
+            Some(version) if version > expected => Err(CodexError::Configuration {
+                reason: format!(
+                    "Database schema is at migration {} but this build only knows migrations up to {}; \
+                     deploy a newer build or restore a matching database",
+                    version, expected
+                ),
+            }),
+            applied => Err(CodexError::Configuration {
+                reason: format!(
+                    "Database schema is at migration {} but this build expects {}; \
+                     apply the pending migrations before starting the server",
+                    applied.map_or("none".to_string(), |version| version.to_string()),
+                    expected
+                ),
+            }),
+        }
+    }
+
+    /// Runs a trivial query to confirm the database is answering
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        with_pool!(self, |pool| sqlx::query("SELECT 1")
+            .execute(pool)
+            .await
+            .map(|_| ()))
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            Database::Postgres(_) => "postgres",
+            Database::Sqlite(_) => "sqlite",
+        }
+    }
+}
+
+impl From<PgPool> for Database {
+    fn from(pool: PgPool) -> Self {
+        Database::Postgres(pool)
+    }
+}
+
+impl From<SqlitePool> for Database {
+    fn from(pool: SqlitePool) -> Self {
+        Database::Sqlite(pool)
+    }
+}
+
+pub async fn connect_database() -> Result<Database, sqlx::Error> {
     let database_url = env::var("DATABASE_URL")
         .unwrap_or_else(|_| "postgresql://localhost/codex_sacred".to_string());
 
-    // Create database if it doesn't exist
-    if !Postgres::database_exists(&database_url)
+    let db = Database::connect(&database_url).await?;
+
+    println!("✨ Connected to sacred {} database", db.backend_name());
+    Ok(db)
+}
+
+/// Whether startup may apply pending migrations; on unless `CODEX_AUTO_MIGRATE` is
+/// `false`, `0`, `no` or `off`
+pub fn auto_migrate_from_env() -> bool {
+    env::var(AUTO_MIGRATE_ENV).map_or(true, |value| {
+        !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "false" | "0" | "no" | "off"
+        )
+    })
+}
+
+/// Brings the schema to the version this build expects before any traffic is served.
+///
+/// A database ahead of this build is refused with a description of the mismatch
+/// rather than sqlx's missing-migration error, and one that is behind is migrated
+/// only when `auto_migrate` allows it.
+pub async fn prepare_schema(db: &Database, auto_migrate: bool) -> Result<(), CodexError> {
+    let applied = db
+        .applied_schema_version()
         .await
-        .unwrap_or(false)
-    {
-        println!("🔮 Creating sacred database...");
-        Postgres::create_database(&database_url).await?;
-    }
+        .map_err(|e| CodexError::Configuration {
+            reason: format!("Could not read the database schema version: {}", e),
+        })?;
+    let expected = db.expected_schema_version();
 
-    // Connect to the database
-    let pool = PgPool::connect(&database_url).await?;
+    if applied == Some(expected) {
+        println!("✅ Sacred schema is current");
+        return Ok(());
+    }
+    if !auto_migrate || applied.is_some_and(|version| version > expected) {
+        return db.check_schema_version().await;
+    }
 
-    println!("✨ Connected to sacred database");
-    Ok(pool)
+    run_migrations(db)
+        .await
+        .map_err(|e| CodexError::Configuration {
+            reason: format!("Could not migrate the database: {}", e),
+        })?;
+    db.check_schema_version().await
 }
 
-pub async fn run_migrations(pool: &PgPool) -> Result<(), sqlx::migrate::MigrateError> {
-    println!("🏛️  Running sacred migrations...");
-    sqlx::migrate!("./migrations").run(pool).await?;
+pub async fn run_migrations(db: &Database) -> Result<(), sqlx::migrate::MigrateError> {
+    let applied = db.applied_schema_version().await.ok().flatten();
+    println!(
+        "🏛️  Running sacred migrations (schema at {}, build expects {})...",
+        applied.map_or("none".to_string(), |version| version.to_string()),
+        db.expected_schema_version()
+    );
+    db.migrate().await?;
     println!("✅ Sacred schema initialized");
     Ok(())
 }
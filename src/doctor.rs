@@ -0,0 +1,282 @@
+use crate::{
+    reflection::ReflectionConfig,
+    state_crypto::{self, STATE_KEY_ENV},
+    SymbolicState,
+};
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Worth fixing, but the engine still works
+    Warn,
+    /// The engine cannot run correctly until this is fixed
+    Fail,
+}
+
+/// The outcome of one `codex doctor` check
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosticCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DoctorReport {
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count()
+    }
+
+    pub fn check(&self, name: &str) -> Option<&DiagnosticCheck> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+}
+
+fn check(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name,
+        status,
+        detail: detail.into(),
+    }
+}
+
+/// Inspects the data directory and reflection setup without creating or
+/// repairing anything, so it can diagnose an engine that fails to start
+pub fn run_diagnostics(data_dir: &Path, reflection: &ReflectionConfig) -> DoctorReport {
+    DoctorReport {
+        checks: vec![
+            check_data_dir(data_dir),
+            check_state_file(
+                data_dir,
+                std::env::var(STATE_KEY_ENV)
+                    .ok()
+                    .filter(|key| !key.is_empty())
+                    .as_deref(),
+            ),
+            check_api_key(reflection),
+            check(
+                "reflection_provider",
+                CheckStatus::Pass,
+                format!(
+                    "{:?} with model {} at {}",
+                    reflection.provider, reflection.model, reflection.api_base_url
+                ),
+            ),
+        ],
+    }
+}
+
+fn check_data_dir(data_dir: &Path) -> DiagnosticCheck {
+    if !data_dir.is_dir() {
+        return check(
+            "data_dir",
+            CheckStatus::Fail,
+            format!("{} does not exist; run `codex init`", data_dir.display()),
+        );
+    }
+
+    // Only an actual write proves the directory is writable
+    let probe = data_dir.join(".doctor-probe");
+    match std::fs::write(&probe, b"").and_then(|_| std::fs::remove_file(&probe)) {
+        Ok(()) => check(
+            "data_dir",
+            CheckStatus::Pass,
+            format!("{} is writable", data_dir.display()),
+        ),
+        Err(e) => check(
+            "data_dir",
+            CheckStatus::Fail,
+            format!("{} is not writable: {}", data_dir.display(), e),
+        ),
+    }
+}
+
+fn check_state_file(data_dir: &Path, state_key: Option<&str>) -> DiagnosticCheck {
+    let state_file = data_dir.join("state.json");
+    if !state_file.exists() {
+        return check(
+            "state_file",
+            CheckStatus::Warn,
+            "No state.json yet; a primordial state will be created",
+        );
+    }
+
+    let content = match std::fs::read_to_string(&state_file) {
+        Ok(content) => content,
+        Err(e) => {
+            return check(
+                "state_file",
+                CheckStatus::Fail,
+                format!("{} is unreadable: {}", state_file.display(), e),
+            )
+        }
+    };
+    let content = if state_crypto::is_encrypted(&content) {
+        let Some(key) = state_key else {
+            return check(
+                "state_file",
+                CheckStatus::Warn,
+                format!("state.json is encrypted; set {} to check it", STATE_KEY_ENV),
+            );
+        };
+        match state_crypto::decrypt(&content, key) {
+            Ok(content) => content,
+            Err(e) => {
+                return check(
+                    "state_file",
+                    CheckStatus::Fail,
+                    format!("{} cannot be decrypted: {}", state_file.display(), e),
+                )
+            }
+        }
+    } else {
+        content
+    };
+    let mut state: SymbolicState = match serde_json::from_str(&content) {
+        Ok(state) => state,
+        Err(e) => {
+            return check(
+                "state_file",
+                CheckStatus::Fail,
+                format!("{} is corrupt: {}", state_file.display(), e),
+            )
+        }
+    };
+
+    let repairs = state.validate_and_clamp();
+    if repairs.is_empty() {
+        check(
+            "state_file",
+            CheckStatus::Pass,
+            "state.json parses and validates",
+        )
+    } else {
+        check(
+            "state_file",
+            CheckStatus::Warn,
+            format!(
+                "Out-of-range values will be repaired on load: {}",
+                repairs.join("; ")
+            ),
+        )
+    }
+}
+
+fn check_api_key(reflection: &ReflectionConfig) -> DiagnosticCheck {
+    if !reflection.provider.requires_api_key() {
+        check(
+            "api_key",
+            CheckStatus::Pass,
+            "Not needed for a local provider",
+        )
+    } else if reflection.api_key.is_empty() {
+        check(
+            "api_key",
+            CheckStatus::Warn,
+            "OPENROUTER_API_KEY is not set; reflections use offline interpretations",
+        )
+    } else {
+        check("api_key", CheckStatus::Pass, "OPENROUTER_API_KEY is set")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reflection::Provider;
+
+    fn config_with_key(api_key: &str) -> ReflectionConfig {
+        ReflectionConfig {
+            api_key: api_key.to_string(),
+            ..ReflectionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_healthy_setup_passes() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let state = serde_json::to_string(&SymbolicState::new()).unwrap();
+        std::fs::write(data_dir.path().join("state.json"), state).unwrap();
+
+        let report = run_diagnostics(data_dir.path(), &config_with_key("sk-test"));
+
+        assert_eq!(report.failures(), 0);
+        assert!(report.checks.iter().all(|c| c.status == CheckStatus::Pass));
+    }
+
+    #[test]
+    fn test_corrupt_state_file_fails() {
+        let data_dir = tempfile::tempdir().unwrap();
+        std::fs::write(data_dir.path().join("state.json"), "{ not json").unwrap();
+
+        let report = run_diagnostics(data_dir.path(), &config_with_key("sk-test"));
+
+        let state_check = report.check("state_file").unwrap();
+        assert_eq!(state_check.status, CheckStatus::Fail);
+        assert!(state_check.detail.contains("corrupt"));
+        assert_eq!(report.failures(), 1);
+    }
+
+    #[test]
+    fn test_encrypted_state_file_is_checked_with_its_key() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let state = serde_json::to_string(&SymbolicState::new()).unwrap();
+        let envelope = state_crypto::encrypt(&state, "open sesame").unwrap();
+        std::fs::write(data_dir.path().join("state.json"), envelope).unwrap();
+
+        let unlocked = check_state_file(data_dir.path(), Some("open sesame"));
+        assert_eq!(unlocked.status, CheckStatus::Pass);
+
+        let locked = check_state_file(data_dir.path(), None);
+        assert_eq!(locked.status, CheckStatus::Warn);
+        assert!(locked.detail.contains(STATE_KEY_ENV));
+
+        let wrong_key = check_state_file(data_dir.path(), Some("wrong"));
+        assert_eq!(wrong_key.status, CheckStatus::Fail);
+        assert!(wrong_key.detail.contains("cannot be decrypted"));
+    }
+
+    #[test]
+    fn test_missing_data_dir_fails() {
+        let parent = tempfile::tempdir().unwrap();
+        let data_dir = parent.path().join("missing");
+
+        let report = run_diagnostics(&data_dir, &config_with_key("sk-test"));
+
+        assert_eq!(report.check("data_dir").unwrap().status, CheckStatus::Fail);
+        assert_eq!(
+            report.check("state_file").unwrap().status,
+            CheckStatus::Warn
+        );
+        assert!(!data_dir.exists());
+    }
+
+    #[test]
+    fn test_missing_api_key_warns_unless_local() {
+        let data_dir = tempfile::tempdir().unwrap();
+
+        let report = run_diagnostics(data_dir.path(), &config_with_key(""));
+        assert_eq!(report.check("api_key").unwrap().status, CheckStatus::Warn);
+        assert_eq!(report.failures(), 0);
+
+        let local = ReflectionConfig::ollama("llama3.1".to_string());
+        assert_eq!(local.provider, Provider::OllamaLocal);
+        let report = run_diagnostics(data_dir.path(), &local);
+        assert_eq!(report.check("api_key").unwrap().status, CheckStatus::Pass);
+        assert!(report
+            .check("reflection_provider")
+            .unwrap()
+            .detail
+            .contains("llama3.1"));
+    }
+}
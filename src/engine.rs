@@ -1,10 +1,127 @@
+use crate::ritual::CompletionStatus;
+use crate::state_crypto::{self, STATE_KEY_ENV};
+use crate::symbols::interpret_symbol;
 use crate::{
-    Archetype, CodexError, Element, Energy, ReflectionResult, Reflector, Ritual, RitualDefinition,
-    RitualResult, SymbolicState,
+    Archetype, CodexError, Difficulty, Element, Energy, ReflectionResult, Reflector,
+    ResonanceWeights, Ritual, RitualDefinition, RitualResult, StateDiff, SymbolicState, WasmLimits,
+    WasmModuleCache,
 };
+use chrono::{DateTime, Utc};
 use dirs;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Environment variable that moves the data directory away from `~/.codex`
+pub const DATA_DIR_ENV: &str = "CODEX_DATA_DIR";
+
+/// How many pre-ritual snapshots are kept for undo
+const MAX_SNAPSHOTS: usize = 10;
+
+/// How many past ritual results are kept in `history.json` for later reflection
+pub const MAX_HISTORY: usize = 50;
+
+/// Rituals every engine registers; custom rituals may not take these names
+const FOUNDATIONAL_RITUALS: [&str; 6] = [
+    "shadow_integration",
+    "energy_attunement",
+    "archetype_invocation",
+    "void_contemplation",
+    "frequency_tuning",
+    "symbol_resolution",
+];
+
+/// A point-in-time copy of the symbolic state that can be rolled back to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub state: SymbolicState,
+}
+
+impl StateSnapshot {
+    fn file_name(&self) -> String {
+        format!("{}.json", self.taken_at.format("%Y%m%dT%H%M%S%.6fZ"))
+    }
+}
+
+/// File formats a symbolic state can be exported to or imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateFileFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl StateFileFormat {
+    /// Picks the format from the file extension, defaulting to JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("toml") => Self::Toml,
+            Some("yaml") | Some("yml") => Self::Yaml,
+            _ => Self::Json,
+        }
+    }
+
+    fn encode(self, state: &SymbolicState) -> Result<String, CodexError> {
+        match self {
+            Self::Json => Ok(serde_json::to_string_pretty(state)?),
+            Self::Toml => toml::to_string_pretty(state).map_err(|e| CodexError::StateCorruption {
+                reason: format!("Could not encode state as TOML: {}", e),
+            }),
+            Self::Yaml => serde_yaml::to_string(state).map_err(|e| CodexError::StateCorruption {
+                reason: format!("Could not encode state as YAML: {}", e),
+            }),
+        }
+    }
+
+    fn decode(self, content: &str) -> Result<SymbolicState, CodexError> {
+        let parsed = match self {
+            Self::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            Self::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| CodexError::StateCorruption {
+            reason: format!("Could not parse {:?} state file: {}", self, e),
+        })
+    }
+}
+
+/// What running a WASM module with `CodexEngine::run_wasm_file` did to a copy of the state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmRunOutcome {
+    pub result: RitualResult,
+    pub diff: StateDiff,
+    /// The state the module left behind, which is not saved
+    pub state: SymbolicState,
+}
+
+/// The results of rituals run back to back by `CodexEngine::execute_chain`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RitualChainResult {
+    pub results: Vec<RitualResult>,
+    /// False when a ritual ended in `CompletionStatus::Error` and the rest were skipped
+    pub completed: bool,
+}
+
+impl RitualChainResult {
+    pub fn total_duration_ms(&self) -> u64 {
+        self.results.iter().map(|r| r.duration_ms).sum()
+    }
+
+    /// Mean resonance across the rituals that ran
+    pub fn mean_resonance(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(|r| r.resonance_level).sum::<f64>() / self.results.len() as f64
+    }
+}
 
 /// The main engine that orchestrates the Codex Control system
 pub struct CodexEngine {
@@ -12,32 +129,90 @@ pub struct CodexEngine {
     rituals: HashMap<String, RitualDefinition>,
     reflector: Reflector,
     data_dir: PathBuf,
-    last_ritual_result: Option<RitualResult>,
+    /// Past ritual results, oldest first, capped at `MAX_HISTORY`
+    history: Vec<RitualResult>,
+    wasm_cache: WasmModuleCache,
+    snapshots: VecDeque<StateSnapshot>,
+    decay_half_life_days: Option<f64>,
+    /// Passphrase from `CODEX_STATE_KEY`; when set, `state.json` is encrypted at rest
+    state_key: Option<String>,
+    /// Suppresses progress messages so stdout can carry machine-readable output
+    quiet: bool,
+    /// Why the last ritual's changes could not be written to the data directory
+    persistence_warning: Option<String>,
 }
 
 impl CodexEngine {
     pub fn new() -> Result<Self, CodexError> {
-        let data_dir = Self::get_data_directory()?;
-        std::fs::create_dir_all(&data_dir)?;
+        Self::new_with_data_dir(Self::get_data_directory()?)
+    }
+
+    /// Creates an engine that prints nothing while loading or running rituals
+    pub fn new_quiet() -> Result<Self, CodexError> {
+        Self::new_quiet_with_data_dir(Self::get_data_directory()?)
+    }
+
+    /// Creates an engine whose state and snapshots live under `data_dir`
+    pub fn new_with_data_dir(data_dir: PathBuf) -> Result<Self, CodexError> {
+        Self::open(data_dir, false)
+    }
+
+    /// Like `new_with_data_dir`, but prints nothing while loading or running rituals
+    pub fn new_quiet_with_data_dir(data_dir: PathBuf) -> Result<Self, CodexError> {
+        Self::open(data_dir, true)
+    }
+
+    fn open(data_dir: PathBuf, quiet: bool) -> Result<Self, CodexError> {
+        Self::open_with_state_key(data_dir, quiet, Self::configured_state_key())
+    }
+
+    fn open_with_state_key(
+        data_dir: PathBuf,
+        quiet: bool,
+        state_key: Option<String>,
+    ) -> Result<Self, CodexError> {
+        // A read-only data directory still lets rituals run in memory; the
+        // failure to persist them is reported after each ritual instead
+        if let Err(e) = std::fs::create_dir_all(&data_dir) {
+            tracing::warn!(
+                "Could not create data directory {}: {}",
+                data_dir.display(),
+                e
+            );
+        }
 
         let mut engine = Self {
             state: SymbolicState::new(),
             rituals: HashMap::new(),
             reflector: Reflector::new_with_defaults(),
             data_dir,
-            last_ritual_result: None,
+            history: Vec::new(),
+            wasm_cache: WasmModuleCache::new()?,
+            snapshots: VecDeque::new(),
+            decay_half_life_days: Self::configured_decay_half_life(),
+            state_key,
+            quiet,
+            persistence_warning: None,
         };
 
         // Load existing state if it exists
         engine.load_state()?;
+        engine.load_snapshots()?;
+        engine.load_history()?;
 
-        // Initialize with foundational rituals
+        // Initialize with foundational rituals, then the practitioner's own
         engine.register_foundational_rituals();
+        engine.load_custom_rituals()?;
 
         Ok(engine)
     }
 
-    fn get_data_directory() -> Result<PathBuf, CodexError> {
+    /// The data directory named by `CODEX_DATA_DIR`, or `~/.codex` by default
+    pub fn get_data_directory() -> Result<PathBuf, CodexError> {
+        if let Some(data_dir) = std::env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(data_dir));
+        }
+
         let home_dir = dirs::home_dir().ok_or_else(|| CodexError::StateCorruption {
             reason: "Could not find home directory".to_string(),
         })?;
@@ -45,29 +220,271 @@ impl CodexEngine {
         Ok(home_dir.join(".codex"))
     }
 
+    /// Half-life for activation decay on load, read from `CODEX_DECAY_HALF_LIFE_DAYS`
+    fn configured_decay_half_life() -> Option<f64> {
+        std::env::var("CODEX_DECAY_HALF_LIFE_DAYS")
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|days| days.is_finite() && *days > 0.0)
+    }
+
+    /// Passphrase for encrypting `state.json`, read from `CODEX_STATE_KEY`
+    fn configured_state_key() -> Option<String> {
+        std::env::var(STATE_KEY_ENV)
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+
+    /// Sets the half-life used to fade activations when state is loaded; `None` disables decay
+    pub fn set_decay_half_life(&mut self, half_life_days: Option<f64>) {
+        self.decay_half_life_days = half_life_days;
+    }
+
     pub fn load_state(&mut self) -> Result<(), CodexError> {
         let state_file = self.data_dir.join("state.json");
 
         if state_file.exists() {
-            let content = std::fs::read_to_string(&state_file)?;
+            let content = self.unseal(std::fs::read_to_string(&state_file)?, "state.json")?;
             self.state = serde_json::from_str(&content)?;
-            println!("🔮 Symbolic state loaded from previous session");
+            if !self.quiet {
+                println!("🔮 Symbolic state loaded from previous session");
+            }
+
+            // A hand-edited or damaged file must not leak out-of-range values into resonance math
+            for warning in self.state.validate_and_clamp() {
+                tracing::warn!("Repaired loaded state: {}", warning);
+                if !self.quiet {
+                    println!("⚠️  {}", warning);
+                }
+            }
+
+            if let Some(half_life_days) = self.decay_half_life_days {
+                self.state.apply_decay(half_life_days);
+            }
         } else {
             // Initialize with primordial archetypes
             self.initialize_primordial_state();
-            println!("🌟 Primordial state initialized");
+            if !self.quiet {
+                println!("🌟 Primordial state initialized");
+            }
         }
 
         Ok(())
     }
 
     pub fn save_state(&self) -> Result<(), CodexError> {
-        let state_file = self.data_dir.join("state.json");
-        let content = serde_json::to_string_pretty(&self.state)?;
-        std::fs::write(&state_file, content)?;
+        self.save_state_to(&self.data_dir.join("state.json"))
+    }
+
+    /// Writes the current state to `path` as `state.json` would hold it, for
+    /// when the data directory can't be written
+    pub fn save_state_to(&self, path: &Path) -> Result<(), CodexError> {
+        let content = self.seal(serde_json::to_string_pretty(&self.state)?)?;
+        std::fs::write(path, content)?;
         Ok(())
     }
 
+    /// Encrypts a data file's contents when a state key is configured
+    fn seal(&self, content: String) -> Result<String, CodexError> {
+        match &self.state_key {
+            Some(key) => state_crypto::encrypt(&content, key),
+            None => Ok(content),
+        }
+    }
+
+    /// Decrypts a data file written by `seal`. Plaintext files from before encryption
+    /// was enabled still load, and are encrypted the next time they are written.
+    fn unseal(&self, content: String, file_name: &str) -> Result<String, CodexError> {
+        if !state_crypto::is_encrypted(&content) {
+            return Ok(content);
+        }
+        let key = self
+            .state_key
+            .as_deref()
+            .ok_or_else(|| CodexError::StateCorruption {
+                reason: format!(
+                    "{} is encrypted; set {} to load it",
+                    file_name, STATE_KEY_ENV
+                ),
+            })?;
+        state_crypto::decrypt(&content, key)
+    }
+
+    /// Why the last ritual's changes were kept in memory only, if they were
+    pub fn persistence_warning(&self) -> Option<&str> {
+        self.persistence_warning.as_deref()
+    }
+
+    /// Writes the current state to `path` in the format implied by its extension
+    pub fn export_state(&self, path: &Path) -> Result<(), CodexError> {
+        let content = StateFileFormat::from_path(path).encode(&self.state)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Loads a state file, either replacing the current state or merging into it.
+    ///
+    /// The file is validated before anything changes, and the prior state is kept
+    /// as a snapshot so the import can be undone.
+    pub fn import_state(&mut self, path: &Path, merge: bool) -> Result<(), CodexError> {
+        let content = std::fs::read_to_string(path)?;
+        let imported = StateFileFormat::from_path(path).decode(&content)?;
+        imported.validate()?;
+
+        let pre_import_snapshot = self.snapshot();
+        if merge {
+            self.state.merge_from(imported);
+        } else {
+            self.state = imported;
+        }
+        self.push_snapshot(pre_import_snapshot)?;
+        self.save_state()
+    }
+
+    fn history_file(&self) -> PathBuf {
+        self.data_dir.join("history.json")
+    }
+
+    fn load_history(&mut self) -> Result<(), CodexError> {
+        let history_file = self.history_file();
+        if !history_file.exists() {
+            return Ok(());
+        }
+
+        let content = self.unseal(std::fs::read_to_string(&history_file)?, "history.json")?;
+        match serde_json::from_str::<Vec<RitualResult>>(&content) {
+            Ok(history) => self.history = history,
+            Err(e) => tracing::warn!("Ignoring unreadable ritual history: {}", e),
+        }
+        self.truncate_history();
+
+        Ok(())
+    }
+
+    /// Appends a ritual result to the history and persists it, dropping the oldest beyond the cap
+    fn record_history(&mut self, result: RitualResult) -> Result<(), CodexError> {
+        self.history.push(result);
+        self.truncate_history();
+
+        let content = self.seal(serde_json::to_string_pretty(&self.history)?)?;
+        std::fs::write(self.history_file(), content)?;
+        Ok(())
+    }
+
+    fn truncate_history(&mut self) {
+        let excess = self.history.len().saturating_sub(MAX_HISTORY);
+        self.history.drain(..excess);
+    }
+
+    /// Past ritual results, oldest first
+    pub fn history(&self) -> &[RitualResult] {
+        &self.history
+    }
+
+    fn snapshot_dir(&self) -> PathBuf {
+        self.data_dir.join("snapshots")
+    }
+
+    fn load_snapshots(&mut self) -> Result<(), CodexError> {
+        let snapshot_dir = self.snapshot_dir();
+        if !snapshot_dir.exists() {
+            return Ok(());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&snapshot_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let content = self.unseal(std::fs::read_to_string(&path)?, "snapshot")?;
+            match serde_json::from_str::<StateSnapshot>(&content) {
+                Ok(snapshot) => self.snapshots.push_back(snapshot),
+                Err(e) => tracing::warn!("Skipping unreadable snapshot {}: {}", path.display(), e),
+            }
+        }
+
+        while self.snapshots.len() > MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+
+        Ok(())
+    }
+
+    /// Captures the current state without recording it for undo
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            taken_at: Utc::now(),
+            state: self.state.clone(),
+        }
+    }
+
+    /// Replaces the current state with the snapshot and persists it
+    pub fn rollback(&mut self, snapshot: StateSnapshot) -> Result<(), CodexError> {
+        self.state = snapshot.state;
+        self.save_state()
+    }
+
+    /// Records a snapshot on disk and in the undo ring, evicting the oldest beyond the limit
+    fn push_snapshot(&mut self, snapshot: StateSnapshot) -> Result<(), CodexError> {
+        let snapshot_dir = self.snapshot_dir();
+        let content = self.seal(serde_json::to_string_pretty(&snapshot)?)?;
+        let snapshot_file = snapshot_dir.join(snapshot.file_name());
+
+        // Keep the snapshot for undo in this session even if it can't be written
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > MAX_SNAPSHOTS {
+            if let Some(evicted) = self.snapshots.pop_front() {
+                let _ = std::fs::remove_file(snapshot_dir.join(evicted.file_name()));
+            }
+        }
+
+        std::fs::create_dir_all(&snapshot_dir)?;
+        std::fs::write(snapshot_file, content)?;
+        Ok(())
+    }
+
+    /// Rolls back to the state before the most recent ritual, returning false if there is none
+    pub fn undo(&mut self) -> Result<bool, CodexError> {
+        let Some(snapshot) = self.snapshots.pop_back() else {
+            return Ok(false);
+        };
+
+        let _ = std::fs::remove_file(self.snapshot_dir().join(snapshot.file_name()));
+        self.rollback(snapshot)?;
+        Ok(true)
+    }
+
+    pub fn snapshot_count(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Zeroes one archetype's activation while keeping its aspects and evolution count,
+    /// returning false if there is no archetype of that name
+    pub fn reset_archetype(&mut self, name: &str) -> bool {
+        match self.state.archetypes.get_mut(name) {
+            Some(archetype) => {
+                archetype.activation_level = 0.0;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Zeroes one energy's amplitude while keeping its frequency and polarity, returning
+    /// false if there is no energy of that name
+    pub fn reset_energy(&mut self, name: &str) -> bool {
+        match self.state.energies.get_mut(name) {
+            Some(energy) => {
+                energy.amplitude = 0.0;
+                energy.last_shifted = Utc::now();
+                true
+            }
+            None => false,
+        }
+    }
+
     fn initialize_primordial_state(&mut self) {
         // Add foundational archetypes
         let sage = Archetype::new(
@@ -95,25 +512,13 @@ impl CodexEngine {
         self.state.add_archetype(creator);
 
         // Add foundational energies
-        let earth_energy = Energy::new(
-            "Earth".to_string(),
-            3.5, // Grounding frequency
-            Element::Earth,
-        );
+        let earth_energy = Energy::attuned("Earth".to_string(), Element::Earth);
         self.state.add_energy(earth_energy);
 
-        let fire_energy = Energy::new(
-            "Fire".to_string(),
-            9.2, // Transformative frequency
-            Element::Fire,
-        );
+        let fire_energy = Energy::attuned("Fire".to_string(), Element::Fire);
         self.state.add_energy(fire_energy);
 
-        let void_energy = Energy::new(
-            "Void".to_string(),
-            0.1, // Stillness frequency
-            Element::Void,
-        );
+        let void_energy = Energy::attuned("Void".to_string(), Element::Void);
         self.state.add_energy(void_energy);
     }
 
@@ -131,6 +536,13 @@ impl CodexEngine {
             wasm_module_path: None,
             native_handler: Some("shadow_integration".to_string()),
             parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Intermediate,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
         };
         self.rituals
             .insert("shadow_integration".to_string(), shadow_ritual);
@@ -145,6 +557,13 @@ impl CodexEngine {
             wasm_module_path: None,
             native_handler: Some("energy_attunement".to_string()),
             parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
         };
         self.rituals
             .insert("energy_attunement".to_string(), attunement_ritual);
@@ -159,6 +578,13 @@ impl CodexEngine {
             wasm_module_path: None,
             native_handler: Some("archetype_invocation".to_string()),
             parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
         };
         self.rituals
             .insert("archetype_invocation".to_string(), invocation_ritual);
@@ -173,12 +599,272 @@ impl CodexEngine {
             wasm_module_path: None,
             native_handler: Some("void_contemplation".to_string()),
             parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Advanced,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
         };
         self.rituals
             .insert("void_contemplation".to_string(), void_ritual);
+
+        // Frequency Tuning Ritual
+        let tuning_ritual = RitualDefinition {
+            name: "frequency_tuning".to_string(),
+            description: "A ritual to tune each energy to the Solfeggio tones".to_string(),
+            intent: "To bring every energetic frequency into harmonic pitch".to_string(),
+            required_archetypes: vec!["Sage".to_string()],
+            energy_requirements: HashMap::from([("Fire".to_string(), 0.3)]),
+            wasm_module_path: None,
+            native_handler: Some("frequency_tuning".to_string()),
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Intermediate,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        };
+        self.rituals
+            .insert("frequency_tuning".to_string(), tuning_ritual);
+
+        // Symbol Resolution Ritual
+        let resolution_ritual = RitualDefinition {
+            name: "symbol_resolution".to_string(),
+            description: "A ritual to integrate the meaning of unresolved symbols".to_string(),
+            intent: "To bring what the symbols carry into conscious understanding".to_string(),
+            required_archetypes: vec!["Sage".to_string()],
+            energy_requirements: HashMap::new(),
+            wasm_module_path: None,
+            native_handler: Some("symbol_resolution".to_string()),
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        };
+        self.rituals
+            .insert("symbol_resolution".to_string(), resolution_ritual);
     }
 
     pub async fn execute_ritual(&mut self, ritual_name: &str) -> Result<RitualResult, CodexError> {
+        self.execute_ritual_with_seed(ritual_name, None).await
+    }
+
+    /// Like `execute_ritual`, but seeds the ritual's randomness so runs from the same state repeat
+    pub async fn execute_ritual_with_seed(
+        &mut self,
+        ritual_name: &str,
+        seed: Option<u64>,
+    ) -> Result<RitualResult, CodexError> {
+        let ritual = self.prepare_ritual(ritual_name, seed)?;
+
+        let pre_ritual_snapshot = self.snapshot();
+        let result = ritual.execute(&mut self.state).await?;
+        self.commit_ritual_result(pre_ritual_snapshot, &result);
+
+        Ok(result)
+    }
+
+    /// Like `execute_ritual_with_seed`, but first invokes each required archetype below
+    /// the ritual's threshold up to it. The steps taken are listed in the result's
+    /// `preparation` output, and undoing the ritual undoes them too.
+    pub async fn execute_prepared_ritual(
+        &mut self,
+        ritual_name: &str,
+        seed: Option<u64>,
+    ) -> Result<RitualResult, CodexError> {
+        let ritual = self.prepare_ritual(ritual_name, seed)?;
+
+        let pre_ritual_snapshot = self.snapshot();
+        let preparation = ritual.invoke_prerequisites(&mut self.state);
+        if !self.quiet {
+            for step in &preparation {
+                println!("🪄 Preparation: {}", step);
+            }
+        }
+        let mut result = ritual.execute(&mut self.state).await?;
+        if !preparation.is_empty() {
+            result
+                .symbolic_outputs
+                .insert("preparation".to_string(), serde_json::json!(preparation));
+        }
+        self.commit_ritual_result(pre_ritual_snapshot, &result);
+
+        Ok(result)
+    }
+
+    /// Continues a WASM ritual that was interrupted with a checkpoint, identified by
+    /// the execution id of its interrupted result in `history()`.
+    ///
+    /// The checkpoint is consumed: the interrupted entry can only be resumed once,
+    /// though the resumed run may itself be interrupted and checkpoint again.
+    pub async fn resume_ritual(&mut self, execution_id: Uuid) -> Result<RitualResult, CodexError> {
+        let index = self
+            .history
+            .iter()
+            .rposition(|result| result.execution_id == execution_id)
+            .ok_or_else(|| CodexError::StateCorruption {
+                reason: format!("No ritual execution {} in the history", execution_id),
+            })?;
+        let interrupted = &self.history[index];
+        let checkpoint =
+            interrupted
+                .checkpoint
+                .clone()
+                .ok_or_else(|| CodexError::StateCorruption {
+                    reason: format!(
+                        "Ritual execution {} has no checkpoint to resume from",
+                        execution_id
+                    ),
+                })?;
+        let ritual = self.prepare_ritual(&interrupted.ritual_name.clone(), None)?;
+
+        let pre_ritual_snapshot = self.snapshot();
+        let result = ritual.resume(&mut self.state, &checkpoint).await?;
+        self.history[index].checkpoint = None;
+        self.commit_ritual_result(pre_ritual_snapshot, &result);
+
+        Ok(result)
+    }
+
+    /// Records a finished ritual's snapshot, history entry and new state, then reports it
+    fn commit_ritual_result(&mut self, pre_ritual_snapshot: StateSnapshot, result: &RitualResult) {
+        let milestones = self.state.reach_evolution_milestones();
+
+        // The ritual has already changed the state, so a disk failure only
+        // costs persistence: the snapshot, history and state stay in memory
+        let persisted = [
+            self.push_snapshot(pre_ritual_snapshot),
+            // Save the result for potential reflection
+            self.record_history(result.clone()),
+            // Auto-save state after ritual execution
+            self.save_state(),
+        ]
+        .into_iter()
+        .collect::<Result<(), CodexError>>();
+        self.persistence_warning = persisted.err().map(|e| {
+            format!(
+                "Ritual changes were not saved to {}: {}",
+                self.data_dir.display(),
+                e
+            )
+        });
+        if let Some(warning) = &self.persistence_warning {
+            tracing::warn!("{}", warning);
+            // Stderr, so JSON output on stdout stays parseable
+            eprintln!("⚠️  {} (kept in memory for this session only)", warning);
+        }
+
+        if !self.quiet {
+            println!(
+                "✨ Ritual completed with resonance: {:.3}",
+                result.resonance_level
+            );
+            self.display_ritual_result(result);
+            for (cycle, archetype) in &milestones {
+                println!(
+                    "🌟 Evolution milestone: cycle {} awakens the {}",
+                    cycle, archetype
+                );
+            }
+        }
+    }
+
+    /// Runs a ritual on a copy of the state to preview its effect, leaving the
+    /// state, undo stack and history untouched
+    pub async fn preview_ritual(
+        &self,
+        ritual_name: &str,
+        seed: Option<u64>,
+    ) -> Result<RitualResult, CodexError> {
+        let ritual = self.prepare_ritual(ritual_name, seed)?;
+
+        let mut preview_state = self.state.clone();
+        let result = ritual.execute(&mut preview_state).await?;
+
+        if !self.quiet {
+            println!(
+                "🔍 Dry run completed with resonance: {:.3} (state not saved)",
+                result.resonance_level
+            );
+            self.display_ritual_result(&result);
+        }
+
+        Ok(result)
+    }
+
+    /// Runs a WASM ritual module straight from disk, for authors testing a module
+    /// before uploading it. It runs on the state in `state_file` (JSON, TOML or YAML)
+    /// or else on a copy of the current state, and nothing is saved either way.
+    pub async fn run_wasm_file(
+        &self,
+        module_path: &Path,
+        state_file: Option<&Path>,
+        seed: Option<u64>,
+    ) -> Result<WasmRunOutcome, CodexError> {
+        let mut state = match state_file {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)?;
+                let state = StateFileFormat::from_path(path).decode(&content)?;
+                state.validate()?;
+                state
+            }
+            None => self.state.clone(),
+        };
+
+        let name = module_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("wasm_ritual")
+            .to_string();
+        let mut ritual = Ritual::new(RitualDefinition {
+            name,
+            description: format!("WASM module {}", module_path.display()),
+            intent: "Test a ritual module offline".to_string(),
+            required_archetypes: Vec::new(),
+            energy_requirements: HashMap::new(),
+            wasm_module_path: None,
+            native_handler: None,
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        })
+        .with_seed(seed);
+        ritual.load_wasm_module_from_bytes(&std::fs::read(module_path)?)?;
+        ritual.validate_wasm_exports()?;
+
+        let before = state.clone();
+        let result = ritual.execute(&mut state).await?;
+        let diff = before.diff(&state);
+
+        if !self.quiet {
+            println!(
+                "🧪 WASM module ran with resonance: {:.3} (state not saved)",
+                result.resonance_level
+            );
+            self.display_ritual_result(&result);
+        }
+
+        Ok(WasmRunOutcome {
+            result,
+            diff,
+            state,
+        })
+    }
+
+    fn prepare_ritual(&self, ritual_name: &str, seed: Option<u64>) -> Result<Ritual, CodexError> {
         let ritual_def = self
             .rituals
             .get(ritual_name)
@@ -187,52 +873,179 @@ impl CodexEngine {
             })?
             .clone();
 
-        println!("🔥 Invoking ritual: {}", ritual_name);
-        println!("💫 Intent: {}", ritual_def.intent);
+        if !self.quiet {
+            println!("🔥 Invoking ritual: {}", ritual_name);
+            println!("💫 Intent: {}", ritual_def.intent);
+        }
 
-        let mut ritual = Ritual::new(ritual_def);
+        let mut ritual = Ritual::new(ritual_def).with_seed(seed);
 
         // Load WASM module if specified
         if ritual.definition.wasm_module_path.is_some() {
             ritual.load_wasm_module()?;
         }
 
-        let result = ritual.execute(&mut self.state).await?;
+        Ok(ritual)
+    }
 
-        // Save the result for potential reflection
-        self.last_ritual_result = Some(result.clone());
+    /// Run rituals in order on the evolving state, stopping after the first that errors
+    pub async fn execute_chain(
+        &mut self,
+        ritual_names: &[String],
+    ) -> Result<RitualChainResult, CodexError> {
+        let mut results = Vec::with_capacity(ritual_names.len());
 
-        // Auto-save state after ritual execution
-        self.save_state()?;
+        for name in ritual_names {
+            let result = self.execute_ritual(name).await?;
+            let failed = matches!(result.completion_status, CompletionStatus::Error(_));
+            results.push(result);
 
-        println!(
-            "✨ Ritual completed with resonance: {:.3}",
-            result.resonance_level
-        );
-        self.display_ritual_result(&result);
+            if failed {
+                return Ok(RitualChainResult {
+                    results,
+                    completed: false,
+                });
+            }
+        }
 
-        Ok(result)
+        Ok(RitualChainResult {
+            results,
+            completed: true,
+        })
     }
 
-    pub async fn reflect(&self) -> Result<ReflectionResult, CodexError> {
-        if let Some(last_result) = &self.last_ritual_result {
-            println!("🔮 Seeking reflection on the recent ritual...");
-            let reflection = self
-                .reflector
-                .reflect_on_ritual(last_result, &self.state)
-                .await?;
+    /// Reflects on the latest ritual; see `reflect_on` for how `refresh` treats the cache
+    pub async fn reflect(&self, refresh: bool) -> Result<ReflectionResult, CodexError> {
+        match self.history.len().checked_sub(1) {
+            Some(latest) => self.reflect_on(latest, refresh).await,
+            None => Err(CodexError::StateCorruption {
+                reason: "No ritual has been performed to reflect upon".to_string(),
+            }),
+        }
+    }
 
-            // Display the reflection
-            println!("{}", self.reflector.format_reflection_output(&reflection));
+    /// Reflects on the ritual at `index` in `history()`, where 0 is the oldest kept.
+    ///
+    /// Oracle reflections are cached under `reflections/` per execution and model,
+    /// so asking again costs nothing; `refresh` skips the cache and replaces it.
+    pub async fn reflect_on(
+        &self,
+        index: usize,
+        refresh: bool,
+    ) -> Result<ReflectionResult, CodexError> {
+        let ritual_result = self
+            .history
+            .get(index)
+            .ok_or_else(|| CodexError::StateCorruption {
+                reason: format!(
+                    "No ritual at history index {} ({} recorded)",
+                    index,
+                    self.history.len()
+                ),
+            })?;
 
-            Ok(reflection)
+        let cache_file = self.reflection_cache_file(ritual_result);
+        let cached = if refresh {
+            None
         } else {
-            Err(CodexError::StateCorruption {
-                reason: "No ritual has been performed to reflect upon".to_string(),
+            self.read_cached_reflection(&cache_file)
+        };
+
+        let reflection = match cached {
+            Some(reflection) => {
+                if !self.quiet {
+                    println!(
+                        "📜 Recalling the reflection on {} (use --refresh to ask again)",
+                        ritual_result.ritual_name
+                    );
+                }
+                reflection
+            }
+            None => {
+                if !self.quiet {
+                    println!(
+                        "🔮 Seeking reflection on {} from {}...",
+                        ritual_result.ritual_name,
+                        ritual_result.timestamp.format("%Y-%m-%d %H:%M")
+                    );
+                }
+                let (reflection, from_oracle) = self
+                    .reflector
+                    .reflect_with_origin(ritual_result, &self.state)
+                    .await?;
+
+                // A fallback stands in for the oracle; keep asking it until it answers
+                if from_oracle {
+                    std::fs::create_dir_all(self.reflection_cache_dir())?;
+                    let content = self.seal(serde_json::to_string_pretty(&reflection)?)?;
+                    std::fs::write(&cache_file, content)?;
+                }
+                reflection
+            }
+        };
+
+        // Display the reflection
+        if !self.quiet {
+            println!("{}", self.reflector.format_reflection_output(&reflection));
+        }
+
+        Ok(reflection)
+    }
+
+    fn reflection_cache_dir(&self) -> PathBuf {
+        self.data_dir.join("reflections")
+    }
+
+    /// Where the reflection on `ritual_result` by the current model is cached
+    fn reflection_cache_file(&self, ritual_result: &RitualResult) -> PathBuf {
+        let model: String = self
+            .reflector
+            .model()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
             })
+            .collect();
+        self.reflection_cache_dir()
+            .join(format!("{}-{}.json", ritual_result.execution_id, model))
+    }
+
+    fn read_cached_reflection(&self, path: &Path) -> Option<ReflectionResult> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let content = match self.unseal(content, "cached reflection") {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::warn!("Ignoring cached reflection {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match serde_json::from_str(&content) {
+            Ok(reflection) => Some(reflection),
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring unreadable cached reflection {}: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
         }
     }
 
+    /// The reflector reflections are asked of, e.g. to format them
+    pub fn reflector(&self) -> &Reflector {
+        &self.reflector
+    }
+
+    /// Replaces the reflector, e.g. to reflect with a different model or provider
+    pub fn set_reflector(&mut self, reflector: Reflector) {
+        self.reflector = reflector;
+    }
+
     pub fn view_state(&self) {
         use colored::*;
 
@@ -247,6 +1060,23 @@ impl CodexEngine {
             self.state.evolution_cycle.to_string().bright_green()
         );
 
+        let report = self.state.coherence_report();
+        println!("\n{}", "🧭 COHERENCE".bright_yellow().bold());
+        println!(
+            "  Score: {:.3} {}",
+            report.coherence_score,
+            self.create_bar(report.coherence_score, 20)
+        );
+        println!(
+            "  Archetype activation: mean {:.3}, variance {:.3}",
+            report.archetype_mean, report.archetype_variance
+        );
+        println!("  Energy balance: variance {:.3}", report.energy_variance);
+        println!("  Unresolved symbols: {}", report.unresolved_symbols);
+        if let Some(dominant) = &report.dominant_archetype {
+            println!("  Dominant archetype: {}", dominant.bright_white().bold());
+        }
+
         // Display active archetypes
         let active_archetypes: Vec<_> = self
             .state
@@ -288,6 +1118,15 @@ impl CodexEngine {
                     amplitude_bar
                 );
             }
+
+            let balance = self.state.elemental_balance();
+            if !balance.is_empty() {
+                println!(
+                    "  {} {}",
+                    "Elemental balance:".bright_white(),
+                    format_elemental_balance(&balance)
+                );
+            }
         }
 
         // Display integrations
@@ -307,7 +1146,16 @@ impl CodexEngine {
         if !self.state.unresolved_symbols.is_empty() {
             println!("\n{}", "🔍 UNRESOLVED SYMBOLS".bright_red().bold());
             for symbol in &self.state.unresolved_symbols {
-                println!("  {}", symbol.bright_yellow());
+                match interpret_symbol(symbol) {
+                    Some(meaning) => {
+                        println!(
+                            "  {} {}",
+                            symbol.bright_yellow(),
+                            format!("— {}", meaning).white()
+                        )
+                    }
+                    None => println!("  {}", symbol.bright_yellow()),
+                }
             }
         }
 
@@ -383,6 +1231,17 @@ impl CodexEngine {
             }
         }
 
+        if result.checkpoint.is_some() {
+            println!(
+                "\n{}",
+                format!(
+                    "⏸  Progress was checkpointed. Resume with: codex ritual resume {}",
+                    result.execution_id
+                )
+                .bright_yellow()
+            );
+        }
+
         println!("{}", "━".repeat(50).bright_blue());
     }
 
@@ -396,9 +1255,191 @@ impl CodexEngine {
         format!("{}{}", filled.bright_cyan(), empty.dimmed())
     }
 
-    pub fn add_custom_ritual(&mut self, ritual: RitualDefinition) {
+    /// All registered ritual definitions, ordered by name
+    pub fn ritual_definitions(&self) -> Vec<&RitualDefinition> {
+        let mut definitions: Vec<&RitualDefinition> = self.rituals.values().collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
+    /// Registers `ritual`, merged with the ritual it extends, after checking it with
+    /// [`RitualDefinition::validate`]
+    pub fn add_custom_ritual(&mut self, ritual: RitualDefinition) -> Result<(), CodexError> {
+        let ritual = self.resolve_inheritance(ritual)?;
+        ritual.validate()?;
         let name = ritual.name.clone();
         self.rituals.insert(name, ritual);
+        Ok(())
+    }
+
+    /// `ritual` with the requirements and parameters of the ritual it extends merged in.
+    ///
+    /// The base must already be registered, and registered rituals are stored merged,
+    /// so only the direct base is merged. The chain of `extends` is still followed to
+    /// reject a ritual that would end up extending itself.
+    fn resolve_inheritance(
+        &self,
+        mut ritual: RitualDefinition,
+    ) -> Result<RitualDefinition, CodexError> {
+        let Some(base_name) = ritual.extends.clone() else {
+            return Ok(ritual);
+        };
+        let invalid = |reason: String| CodexError::InvalidRitual {
+            name: ritual.name.clone(),
+            reason,
+        };
+
+        let mut chain = vec![ritual.name.clone()];
+        let mut next = Some(base_name.clone());
+        while let Some(name) = next {
+            if chain.contains(&name) {
+                chain.push(name);
+                return Err(invalid(format!("inheritance cycle {}", chain.join(" -> "))));
+            }
+            let base = self
+                .rituals
+                .get(&name)
+                .ok_or_else(|| invalid(format!("extends unknown ritual '{}'", name)))?;
+            next = base.extends.clone();
+            chain.push(name);
+        }
+
+        let base = &self.rituals[&base_name];
+        ritual.inherit_from(base);
+        Ok(ritual)
+    }
+
+    fn custom_ritual_dir(&self) -> PathBuf {
+        self.data_dir.join("rituals")
+    }
+
+    /// Registers every saved custom ritual, skipping files that no longer parse
+    /// or validate and any that would replace a foundational ritual
+    fn load_custom_rituals(&mut self) -> Result<(), CodexError> {
+        let ritual_dir = self.custom_ritual_dir();
+        if !ritual_dir.exists() {
+            return Ok(());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&ritual_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut pending = Vec::new();
+        for path in paths {
+            let content = std::fs::read_to_string(&path)?;
+            let ritual = match toml::from_str::<RitualDefinition>(&content) {
+                Ok(ritual) => ritual,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable ritual {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if FOUNDATIONAL_RITUALS.contains(&ritual.name.as_str()) {
+                tracing::warn!(
+                    "Skipping {}: '{}' is a foundational ritual",
+                    path.display(),
+                    ritual.name
+                );
+                continue;
+            }
+            pending.push((path, ritual));
+        }
+
+        // Register bases before the rituals extending them, whatever their file order
+        while !pending.is_empty() {
+            let waiting: HashSet<String> = pending
+                .iter()
+                .map(|(_, ritual)| ritual.name.clone())
+                .collect();
+            let (ready, blocked): (Vec<_>, Vec<_>) =
+                pending.into_iter().partition(|(_, ritual)| {
+                    ritual
+                        .extends
+                        .as_ref()
+                        .is_none_or(|base| *base == ritual.name || !waiting.contains(base))
+                });
+
+            if ready.is_empty() {
+                for (path, ritual) in blocked {
+                    tracing::warn!(
+                        "Skipping {}: '{}' is part of an inheritance cycle",
+                        path.display(),
+                        ritual.name
+                    );
+                }
+                break;
+            }
+            for (path, ritual) in ready {
+                if let Err(e) = self.add_custom_ritual(ritual) {
+                    tracing::warn!("Skipping invalid ritual {}: {}", path.display(), e);
+                }
+            }
+            pending = blocked;
+        }
+
+        Ok(())
+    }
+
+    /// Registers the ritual defined in a TOML file and saves a copy under
+    /// `rituals/` in the data directory so later sessions load it too.
+    ///
+    /// Adding a ritual with the name of an earlier custom one replaces it. The returned
+    /// definition is merged with the ritual it extends, if any.
+    pub fn add_ritual_from_file(&mut self, path: &Path) -> Result<RitualDefinition, CodexError> {
+        let content = std::fs::read_to_string(path)?;
+        let mut ritual: RitualDefinition =
+            toml::from_str(&content).map_err(|e| CodexError::Configuration {
+                reason: format!("Invalid ritual file {}: {}", path.display(), e),
+            })?;
+        ritual.validate()?;
+
+        let invalid = |reason: &str| CodexError::InvalidRitual {
+            name: ritual.name.clone(),
+            reason: reason.to_string(),
+        };
+        if FOUNDATIONAL_RITUALS.contains(&ritual.name.as_str()) {
+            return Err(invalid("a foundational ritual already has this name"));
+        }
+        // The name becomes the saved file's name
+        if ritual.name.contains(['/', '\\']) || ritual.name.starts_with('.') {
+            return Err(invalid(
+                "name must not contain path separators or start with '.'",
+            ));
+        }
+
+        // The saved copy lives elsewhere, so anchor a relative module path to this file
+        if let Some(module_path) = &ritual.wasm_module_path {
+            if Path::new(module_path).is_relative() {
+                let base = path.parent().unwrap_or(Path::new("."));
+                ritual.wasm_module_path = Some(base.join(module_path).display().to_string());
+            }
+        }
+
+        // The saved copy keeps `extends` so later sessions merge with the base anew
+        let resolved = self.resolve_inheritance(ritual.clone())?;
+        resolved.validate()?;
+
+        let ritual_dir = self.custom_ritual_dir();
+        std::fs::create_dir_all(&ritual_dir)?;
+        let saved = toml::to_string_pretty(&ritual).map_err(|e| CodexError::Configuration {
+            reason: format!("Could not save ritual '{}': {}", ritual.name, e),
+        })?;
+        std::fs::write(ritual_dir.join(format!("{}.toml", ritual.name)), saved)?;
+
+        self.rituals.insert(resolved.name.clone(), resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Compiled WASM modules shared by every execution through this engine
+    pub fn wasm_cache(&self) -> &WasmModuleCache {
+        &self.wasm_cache
+    }
+
+    pub fn clear_wasm_cache(&self) {
+        self.wasm_cache.clear();
     }
 
     pub fn get_state(&self) -> &SymbolicState {
@@ -409,3 +1450,761 @@ impl CodexEngine {
         &mut self.state
     }
 }
+
+/// Renders an elemental balance as `Fire 30% / Water 25% / ...`, largest share first
+fn format_elemental_balance(balance: &HashMap<Element, f64>) -> String {
+    let mut shares: Vec<(String, f64)> = balance
+        .iter()
+        .map(|(element, share)| (format!("{:?}", element), *share))
+        .collect();
+    shares.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    shares
+        .iter()
+        .map(|(element, share)| format!("{} {:.0}%", element, share * 100.0))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_archetype_keeps_its_evolution() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        let shadow = engine.get_state_mut().archetypes.get_mut("Shadow").unwrap();
+        shadow.activation_level = 0.8;
+        shadow.evolution_count = 3;
+
+        assert!(engine.reset_archetype("Shadow"));
+
+        let shadow = &engine.get_state().archetypes["Shadow"];
+        assert_eq!(shadow.activation_level, 0.0);
+        assert_eq!(shadow.evolution_count, 3);
+    }
+
+    #[test]
+    fn test_reset_unknown_archetype_or_energy_changes_nothing() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        let before = engine.get_state().clone();
+
+        assert!(!engine.reset_archetype("Trickster"));
+        assert!(!engine.reset_energy("Lightning"));
+        assert_eq!(engine.get_state().archetypes.len(), before.archetypes.len());
+        assert_eq!(
+            engine.get_state().archetypes["Sage"].activation_level,
+            before.archetypes["Sage"].activation_level
+        );
+    }
+
+    #[test]
+    fn test_reset_energy_zeroes_amplitude() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        let name = engine.get_state().energies.keys().next().unwrap().clone();
+        let frequency = engine.get_state().energies[&name].frequency;
+
+        assert!(engine.reset_energy(&name));
+
+        let energy = &engine.get_state().energies[&name];
+        assert_eq!(energy.amplitude, 0.0);
+        assert_eq!(energy.frequency, frequency);
+    }
+
+    #[tokio::test]
+    async fn test_undo_restores_pre_ritual_state() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        let shadow_before = engine.get_state().archetypes["Shadow"].activation_level;
+
+        engine.execute_ritual("shadow_integration").await.unwrap();
+        assert_ne!(
+            engine.get_state().archetypes["Shadow"].activation_level,
+            shadow_before
+        );
+        assert_eq!(engine.snapshot_count(), 1);
+
+        assert!(engine.undo().unwrap());
+        assert_eq!(
+            engine.get_state().archetypes["Shadow"].activation_level,
+            shadow_before
+        );
+        assert!(!engine.undo().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_ritual_result_survives_read_only_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        // Nothing can be created beneath a regular file, not even by root
+        let blocker = dir.path().join("codex");
+        std::fs::write(&blocker, "").unwrap();
+        let mut engine = CodexEngine::open(blocker.join("data"), true).unwrap();
+        let shadow_before = engine.get_state().archetypes["Shadow"].activation_level;
+
+        let result = engine
+            .execute_ritual_with_seed("shadow_integration", Some(7))
+            .await
+            .unwrap();
+        assert_eq!(result.ritual_name, "shadow_integration");
+        let warning = engine.persistence_warning().unwrap();
+        assert!(warning.contains("not saved"), "{}", warning);
+
+        // The ritual's effects stay in memory, undo included
+        assert_eq!(engine.history().len(), 1);
+        assert_eq!(engine.snapshot_count(), 1);
+        let shadow_after = engine.get_state().archetypes["Shadow"].activation_level;
+        assert_ne!(shadow_after, shadow_before);
+
+        let redirected = dir.path().join("state.json");
+        engine.save_state_to(&redirected).unwrap();
+        let restored = CodexEngine::open(dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(
+            restored.get_state().archetypes["Shadow"].activation_level,
+            shadow_after
+        );
+    }
+
+    #[tokio::test]
+    async fn test_successful_save_clears_persistence_warning() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        engine.persistence_warning = Some("stale".to_string());
+
+        engine.execute_ritual("shadow_integration").await.unwrap();
+        assert_eq!(engine.persistence_warning(), None);
+    }
+
+    #[test]
+    fn test_add_custom_ritual_rejects_invalid_definition() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        let mut ritual = engine.rituals["shadow_integration"].clone();
+        ritual.name = "overcharged_shadow".to_string();
+        ritual.energy_requirements.insert("Earth".to_string(), 2.0);
+
+        assert!(matches!(
+            engine.add_custom_ritual(ritual.clone()),
+            Err(CodexError::InvalidRitual { .. })
+        ));
+        assert!(!engine.rituals.contains_key("overcharged_shadow"));
+
+        ritual.energy_requirements.insert("Earth".to_string(), 0.4);
+        engine.add_custom_ritual(ritual).unwrap();
+        assert!(engine.rituals.contains_key("overcharged_shadow"));
+    }
+
+    fn magician_engine(data_dir: &Path) -> CodexEngine {
+        let mut engine = CodexEngine::new_quiet_with_data_dir(data_dir.to_path_buf()).unwrap();
+        let mut ritual = engine.rituals["archetype_invocation"].clone();
+        ritual.name = "magician_rite".to_string();
+        ritual.required_archetypes = vec!["Magician".to_string()];
+        engine.add_custom_ritual(ritual).unwrap();
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Magician", 0.0);
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_prepared_ritual_invokes_missing_archetypes() {
+        let data_dir = tempfile::tempdir().unwrap();
+
+        let mut unprepared = magician_engine(&data_dir.path().join("unprepared"));
+        let result = unprepared
+            .execute_ritual_with_seed("magician_rite", Some(1))
+            .await
+            .unwrap();
+        assert!(matches!(
+            &result.completion_status,
+            CompletionStatus::PrerequisitesNotMet { missing } if missing == &["Magician"]
+        ));
+
+        let mut prepared = magician_engine(&data_dir.path().join("prepared"));
+        let result = prepared
+            .execute_prepared_ritual("magician_rite", Some(1))
+            .await
+            .unwrap();
+        assert!(matches!(
+            result.completion_status,
+            CompletionStatus::Complete
+        ));
+        assert_eq!(
+            result.symbolic_outputs["preparation"],
+            serde_json::json!(["Invoked Magician from 0.00 to 0.30"])
+        );
+
+        assert!(prepared.undo().unwrap());
+        assert_eq!(
+            prepared.get_state().archetypes["Magician"].activation_level,
+            0.0
+        );
+    }
+
+    const DAWN_RITUAL_TOML: &str = r#"
+name = "dawn_attunement"
+description = "Greet the rising light"
+intent = "To kindle fire with clear air"
+required_archetypes = ["Sage", "Creator"]
+
+[energy_requirements]
+Fire = 0.5
+Air = 0.4
+"#;
+
+    #[tokio::test]
+    async fn test_custom_ritual_from_toml_runs_and_persists() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let ritual_file = data_dir.path().join("dawn.toml");
+        std::fs::write(&ritual_file, DAWN_RITUAL_TOML).unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().join("data"), true).unwrap();
+
+        let ritual = engine.add_ritual_from_file(&ritual_file).unwrap();
+        assert_eq!(ritual.required_archetypes, ["Sage", "Creator"]);
+        assert_eq!(ritual.energy_requirements["Fire"], 0.5);
+
+        let result = engine.execute_ritual("dawn_attunement").await.unwrap();
+        assert_eq!(result.emergent_symbols, ["✨"]);
+        assert!(result.resonance_level > 0.0 && result.resonance_level <= 1.0);
+
+        let restarted = CodexEngine::open(data_dir.path().join("data"), true).unwrap();
+        assert_eq!(
+            restarted.rituals["dawn_attunement"].intent,
+            "To kindle fire with clear air"
+        );
+    }
+
+    #[test]
+    fn test_custom_ritual_cannot_replace_foundational_ritual() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let ritual_file = data_dir.path().join("shadow.toml");
+        std::fs::write(
+            &ritual_file,
+            DAWN_RITUAL_TOML.replace("dawn_attunement", "shadow_integration"),
+        )
+        .unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().join("data"), true).unwrap();
+
+        assert!(matches!(
+            engine.add_ritual_from_file(&ritual_file),
+            Err(CodexError::InvalidRitual { .. })
+        ));
+        assert_ne!(
+            engine.rituals["shadow_integration"].intent,
+            "To kindle fire with clear air"
+        );
+        assert!(!data_dir.path().join("data/rituals").exists());
+    }
+
+    #[test]
+    fn test_custom_ritual_inherits_and_overrides_base() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let base_file = data_dir.path().join("dawn.toml");
+        std::fs::write(&base_file, DAWN_RITUAL_TOML).unwrap();
+        let child_file = data_dir.path().join("aurora.toml");
+        std::fs::write(
+            &child_file,
+            r#"
+name = "aurora_kindling"
+description = "Feed the dawn fire"
+intent = "To burn brighter than the dawn"
+extends = "dawn_attunement"
+required_archetypes = ["Warrior", "Sage"]
+
+[energy_requirements]
+Fire = 0.9
+"#,
+        )
+        .unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().join("data"), true).unwrap();
+        engine.add_ritual_from_file(&base_file).unwrap();
+
+        let child = engine.add_ritual_from_file(&child_file).unwrap();
+        assert_eq!(child.required_archetypes, ["Sage", "Creator", "Warrior"]);
+        assert_eq!(child.energy_requirements["Fire"], 0.9);
+        assert_eq!(child.energy_requirements["Air"], 0.4);
+
+        // The child's saved file sorts before its base's but still loads merged
+        let restarted = CodexEngine::open(data_dir.path().join("data"), true).unwrap();
+        let reloaded = &restarted.rituals["aurora_kindling"];
+        assert_eq!(reloaded.required_archetypes, child.required_archetypes);
+        assert_eq!(reloaded.energy_requirements, child.energy_requirements);
+        assert_eq!(reloaded.extends.as_deref(), Some("dawn_attunement"));
+    }
+
+    #[test]
+    fn test_inheritance_cycle_and_missing_base_are_rejected() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        let ritual = |name: &str, extends: Option<&str>| {
+            let mut ritual: RitualDefinition =
+                toml::from_str(&DAWN_RITUAL_TOML.replace("dawn_attunement", name)).unwrap();
+            ritual.extends = extends.map(str::to_string);
+            ritual
+        };
+        let rejection = |result: Result<(), CodexError>| match result {
+            Err(CodexError::InvalidRitual { reason, .. }) => reason,
+            other => panic!("expected an invalid ritual, got {:?}", other),
+        };
+
+        engine.add_custom_ritual(ritual("first", None)).unwrap();
+        engine
+            .add_custom_ritual(ritual("second", Some("first")))
+            .unwrap();
+        assert_eq!(
+            rejection(engine.add_custom_ritual(ritual("first", Some("second")))),
+            "inheritance cycle first -> second -> first"
+        );
+        assert_eq!(engine.rituals["first"].extends, None);
+
+        assert_eq!(
+            rejection(engine.add_custom_ritual(ritual("lonely", Some("lonely")))),
+            "inheritance cycle lonely -> lonely"
+        );
+        assert_eq!(
+            rejection(engine.add_custom_ritual(ritual("orphan", Some("no_such_ritual")))),
+            "extends unknown ritual 'no_such_ritual'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_ritual_leaves_state_untouched() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        let state_before = engine.get_state().clone();
+
+        let preview = engine
+            .preview_ritual("shadow_integration", Some(3))
+            .await
+            .unwrap();
+
+        assert!(!preview.state_changes.is_empty());
+        assert!(engine.get_state().diff(&state_before).is_empty());
+        assert!(engine.history().is_empty());
+        assert!(!data_dir.path().join("history.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_survive_restart() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        let shadow_before = engine.get_state().archetypes["Shadow"].activation_level;
+        engine.execute_ritual("shadow_integration").await.unwrap();
+
+        let mut restarted = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+        assert_eq!(restarted.snapshot_count(), 1);
+        assert!(restarted.undo().unwrap());
+        assert_eq!(
+            restarted.get_state().archetypes["Shadow"].activation_level,
+            shadow_before
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_requires_a_checkpointed_execution() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        let completed = engine.execute_ritual("void_contemplation").await.unwrap();
+
+        for execution_id in [completed.execution_id, Uuid::new_v4()] {
+            let error = engine.resume_ritual(execution_id).await.unwrap_err();
+            assert!(error.to_string().contains(&execution_id.to_string()));
+        }
+        assert_eq!(engine.history().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_history_is_truncated_at_cap() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        engine.execute_ritual("shadow_integration").await.unwrap();
+        for _ in 0..MAX_HISTORY {
+            engine.execute_ritual("void_contemplation").await.unwrap();
+        }
+
+        assert_eq!(engine.history().len(), MAX_HISTORY);
+        assert!(engine
+            .history()
+            .iter()
+            .all(|result| result.ritual_name == "void_contemplation"));
+
+        let restarted = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(restarted.history().len(), MAX_HISTORY);
+    }
+
+    #[tokio::test]
+    async fn test_reflect_on_earlier_history_entry() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        engine.execute_ritual("shadow_integration").await.unwrap();
+        engine.execute_ritual("energy_attunement").await.unwrap();
+
+        let earlier = engine.reflect_on(0, false).await.unwrap();
+        let latest = engine.reflect(false).await.unwrap();
+
+        assert_eq!(earlier.ritual_name, "shadow_integration");
+        assert_eq!(latest.ritual_name, "energy_attunement");
+        assert!(engine.reflect_on(2, false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fifth_cycle_awakens_mystic_once() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        assert!(!engine.state.archetypes.contains_key("Mystic"));
+
+        let milestone_count = |engine: &CodexEngine| {
+            engine
+                .state
+                .integrations
+                .keys()
+                .filter(|name| name.starts_with("Evolution Milestone"))
+                .count()
+        };
+
+        for _ in 0..4 {
+            engine.execute_ritual("energy_attunement").await.unwrap();
+        }
+        assert_eq!(engine.state.evolution_cycle, 4);
+        assert!(!engine.state.archetypes.contains_key("Mystic"));
+        assert_eq!(milestone_count(&engine), 0);
+
+        engine.execute_ritual("energy_attunement").await.unwrap();
+        assert_eq!(engine.state.evolution_cycle, 5);
+        assert_eq!(
+            engine.state.archetypes["Mystic"].activation_level,
+            crate::state::MILESTONE_ARCHETYPE_ACTIVATION
+        );
+        assert!(engine
+            .state
+            .integrations
+            .contains_key("Evolution Milestone: Cycle 5"));
+
+        engine.execute_ritual("energy_attunement").await.unwrap();
+        assert_eq!(milestone_count(&engine), 1);
+
+        // The milestone is part of the saved state
+        let restarted = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        assert!(restarted.state.archetypes.contains_key("Mystic"));
+        assert_eq!(milestone_count(&restarted), 1);
+    }
+
+    /// Answers every chat completion request, counting how many arrive
+    async fn spawn_counting_oracle() -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use axum::{routing::post, Json, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/chat/completions",
+            post(move || {
+                let counter = counter.clone();
+                async move {
+                    let count = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    Json(serde_json::json!({
+                        "choices": [{"message": {
+                            "content": format!("ARCHETYPAL_INTERPRETATION: Answer {}", count)
+                        }}]
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    fn oracle_reflector(base_url: &str, model: &str) -> Reflector {
+        Reflector::new(crate::reflection::ReflectionConfig {
+            api_base_url: base_url.to_string(),
+            api_key: "test-key".to_string(),
+            model: model.to_string(),
+            temperature: 0.5,
+            max_tokens: 800,
+            provider: crate::Provider::OpenAI,
+            max_retries: 0,
+            retry_base_delay_ms: 1,
+            ritual_overrides: HashMap::new(),
+            persona: Default::default(),
+            system_prompt_template: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_reflection_is_cached_per_execution_and_model() {
+        use std::sync::atomic::Ordering;
+
+        let (base_url, hits) = spawn_counting_oracle().await;
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        engine.set_reflector(oracle_reflector(&base_url, "oracle/one"));
+        engine.execute_ritual("shadow_integration").await.unwrap();
+
+        let first = engine.reflect(false).await.unwrap();
+        let second = engine.reflect(false).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(first.archetypal_interpretation, "Answer 1");
+        assert_eq!(second.archetypal_interpretation, "Answer 1");
+
+        let refreshed = engine.reflect(true).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(refreshed.archetypal_interpretation, "Answer 2");
+        let recalled = engine.reflect(false).await.unwrap();
+        assert_eq!(recalled.archetypal_interpretation, "Answer 2");
+
+        engine.set_reflector(oracle_reflector(&base_url, "oracle/two"));
+        engine.reflect(false).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_reflection_is_not_cached() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        // Nothing listens here, so every reflection falls back to the local one
+        engine.set_reflector(oracle_reflector("http://127.0.0.1:9", "oracle/one"));
+        engine.execute_ritual("shadow_integration").await.unwrap();
+
+        engine.reflect(false).await.unwrap();
+
+        assert!(!data_dir.path().join("reflections").exists());
+    }
+
+    #[test]
+    fn test_load_state_clamps_out_of_range_values() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        engine.get_state_mut().set_energy_amplitude("Fire", 0.5);
+        engine.save_state().unwrap();
+
+        let state_file = data_dir.path().join("state.json");
+        let mut raw: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&state_file).unwrap()).unwrap();
+        raw["archetypes"]["Shadow"]["activation_level"] = serde_json::json!(5.0);
+        raw["energies"]["Fire"]["amplitude"] = serde_json::json!(-2.0);
+        std::fs::write(&state_file, raw.to_string()).unwrap();
+
+        let reloaded = CodexEngine::open(data_dir.path().to_path_buf(), true).unwrap();
+        assert_eq!(
+            reloaded.get_state().archetypes["Shadow"].activation_level,
+            1.0
+        );
+        assert_eq!(reloaded.get_state().energies["Fire"].amplitude, 0.0);
+    }
+
+    #[test]
+    fn test_encrypted_state_round_trips() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let open = || {
+            CodexEngine::open_with_state_key(
+                data_dir.path().to_path_buf(),
+                true,
+                Some("open sesame".to_string()),
+            )
+        };
+        let mut engine = open().unwrap();
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Shadow", 0.42);
+        engine.save_state().unwrap();
+
+        let on_disk = std::fs::read_to_string(data_dir.path().join("state.json")).unwrap();
+        assert!(state_crypto::is_encrypted(&on_disk));
+        assert!(!on_disk.contains("Shadow"));
+
+        let reloaded = open().unwrap();
+        assert!(engine.get_state().diff(reloaded.get_state()).is_empty());
+    }
+
+    #[test]
+    fn test_tampered_encrypted_state_is_reported() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let key = || Some("open sesame".to_string());
+        let engine =
+            CodexEngine::open_with_state_key(data_dir.path().to_path_buf(), true, key()).unwrap();
+        engine.save_state().unwrap();
+
+        let state_file = data_dir.path().join("state.json");
+        let mut envelope: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&state_file).unwrap()).unwrap();
+        let ciphertext = envelope["ciphertext"].as_str().unwrap();
+        let flipped = if ciphertext.starts_with('0') {
+            "1"
+        } else {
+            "0"
+        };
+        envelope["ciphertext"] = serde_json::json!(format!("{}{}", flipped, &ciphertext[1..]));
+        std::fs::write(&state_file, envelope.to_string()).unwrap();
+
+        let err = match CodexEngine::open_with_state_key(data_dir.path().to_path_buf(), true, key())
+        {
+            Err(err) => err,
+            Ok(_) => panic!("tampered state loaded"),
+        };
+        assert!(err.to_string().contains("failed authentication"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_history_and_snapshots_are_encrypted_with_a_key_set() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let open = || {
+            CodexEngine::open_with_state_key(
+                data_dir.path().to_path_buf(),
+                true,
+                Some("open sesame".to_string()),
+            )
+        };
+        let mut engine = open().unwrap();
+        engine.execute_ritual("shadow_integration").await.unwrap();
+
+        let history = std::fs::read_to_string(data_dir.path().join("history.json")).unwrap();
+        assert!(state_crypto::is_encrypted(&history));
+        assert!(!history.contains("shadow_integration"));
+        let snapshot_dir = data_dir.path().join("snapshots");
+        for entry in std::fs::read_dir(snapshot_dir).unwrap() {
+            let snapshot = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert!(state_crypto::is_encrypted(&snapshot));
+            assert!(!snapshot.contains("Shadow"));
+        }
+
+        let reloaded = open().unwrap();
+        assert_eq!(reloaded.history().len(), 1);
+        assert_eq!(reloaded.history()[0].ritual_name, "shadow_integration");
+        assert_eq!(reloaded.snapshot_count(), 1);
+
+        let err = match CodexEngine::open_with_state_key(data_dir.path().to_path_buf(), true, None)
+        {
+            Err(err) => err,
+            Ok(_) => panic!("encrypted data loaded without a key"),
+        };
+        assert!(err.to_string().contains(STATE_KEY_ENV), "{}", err);
+    }
+
+    #[test]
+    fn test_plaintext_state_loads_with_a_key_set() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine =
+            CodexEngine::open_with_state_key(data_dir.path().to_path_buf(), true, None).unwrap();
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Shadow", 0.42);
+        engine.save_state().unwrap();
+
+        let reloaded = CodexEngine::open_with_state_key(
+            data_dir.path().to_path_buf(),
+            true,
+            Some("open sesame".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            reloaded.get_state().archetypes["Shadow"].activation_level,
+            0.42
+        );
+    }
+
+    fn round_trip(file_name: &str) {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().join("data")).unwrap();
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Shadow", 0.61);
+        engine
+            .get_state_mut()
+            .add_unresolved_symbol("🜁".to_string());
+        let path = data_dir.path().join(file_name);
+
+        engine.export_state(&path).unwrap();
+        let exported = engine.get_state().clone();
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Shadow", 0.1);
+        engine.import_state(&path, false).unwrap();
+
+        assert!(exported.diff(engine.get_state()).is_empty());
+        assert_eq!(
+            engine.get_state().archetypes["Shadow"].evolution_count,
+            exported.archetypes["Shadow"].evolution_count
+        );
+    }
+
+    #[test]
+    fn test_state_round_trips_through_json() {
+        round_trip("state.json");
+    }
+
+    #[test]
+    fn test_state_round_trips_through_toml() {
+        round_trip("state.toml");
+    }
+
+    #[test]
+    fn test_state_round_trips_through_yaml() {
+        round_trip("state.yaml");
+    }
+
+    #[test]
+    fn test_import_merge_keeps_stronger_activation() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().join("data")).unwrap();
+        let path = data_dir.path().join("other.json");
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Shadow", 0.9);
+        engine.get_state_mut().set_archetype_activation("Sage", 0.1);
+        engine.export_state(&path).unwrap();
+
+        engine
+            .get_state_mut()
+            .set_archetype_activation("Shadow", 0.2);
+        engine.get_state_mut().set_archetype_activation("Sage", 0.8);
+        engine.import_state(&path, true).unwrap();
+
+        assert_eq!(
+            engine.get_state().archetypes["Shadow"].activation_level,
+            0.9
+        );
+        assert_eq!(engine.get_state().archetypes["Sage"].activation_level, 0.8);
+    }
+
+    #[test]
+    fn test_import_rejects_corrupt_files() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let mut engine = CodexEngine::new_with_data_dir(data_dir.path().join("data")).unwrap();
+        let garbage = data_dir.path().join("garbage.yaml");
+        std::fs::write(&garbage, "archetypes: [not, a, map").unwrap();
+        assert!(matches!(
+            engine.import_state(&garbage, false),
+            Err(CodexError::StateCorruption { .. })
+        ));
+
+        let mut state = engine.get_state().clone();
+        state.archetypes.get_mut("Shadow").unwrap().activation_level = 3.0;
+        let out_of_range = data_dir.path().join("out_of_range.json");
+        std::fs::write(&out_of_range, serde_json::to_string(&state).unwrap()).unwrap();
+        assert!(matches!(
+            engine.import_state(&out_of_range, false),
+            Err(CodexError::StateCorruption { .. })
+        ));
+        assert_eq!(engine.snapshot_count(), 0);
+    }
+
+    #[test]
+    fn test_format_elemental_balance_orders_by_share() {
+        let balance = HashMap::from([
+            (Element::Water, 0.25),
+            (Element::Fire, 0.3),
+            (Element::Air, 0.2),
+            (Element::Earth, 0.25),
+        ]);
+
+        assert_eq!(
+            format_elemental_balance(&balance),
+            "Fire 30% / Earth 25% / Water 25% / Air 20%"
+        );
+    }
+}
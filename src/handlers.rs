@@ -1,27 +1,42 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json, Response,
+    },
     Extension,
 };
 use serde_json::json;
+use std::collections::HashMap;
 use std::time::Instant;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 use uuid::Uuid;
 
 use crate::{
-    auth::{create_auth_response, hash_password, verify_password},
+    api_error::ApiError,
+    api_key_crypto::{api_key_sealer, ApiKeyError},
+    auth::{
+        authenticate_token, create_auth_response, find_active_practitioner, hash_password, redeem_refresh_token, require_role,
+        revoke_refresh_token, store_refresh_token, verify_password, Role,
+    },
+    database::{with_pool, Database},
+    locks::PractitionerLocks,
     models::*,
+    ratelimit::AuthRateLimiters,
     reflection::{Reflector, ReflectionConfig},
-    ritual::{Ritual, RitualDefinition},
-    state::{ArchetypalState, SymbolicState},
+    retention::prune_state_history,
+    similarity::rank_by_similarity,
+    ritual::{Difficulty, ResonanceWeights, Ritual, RitualDefinition, StateChange, WasmLimits},
+    state::{ArchetypalState, Element, SymbolicState},
+    streams::StateStreams,
+    CodexError,
 };
 
-#[derive(serde::Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
-}
-
-#[derive(serde::Serialize)]
+#[derive(Debug, serde::Serialize)]
 pub struct SuccessResponse<T> {
     pub success: bool,
     pub data: T,
@@ -38,82 +53,62 @@ impl<T> SuccessResponse<T> {
 
 #[derive(Clone)]
 pub struct AppState {
-    pub db: sqlx::PgPool,
+    pub db: Database,
     pub engine: std::sync::Arc<crate::CodexEngine>,
+    /// Live state updates for `/api/state/stream` subscribers
+    pub state_streams: StateStreams,
+    /// Brute-force protection for login, registration and account restore
+    pub auth_rate_limiters: AuthRateLimiters,
+    /// Serializes state transformations per practitioner
+    pub practitioner_locks: PractitionerLocks,
+    /// Most rituals `/api/rituals/execute-batch` runs in one request
+    pub ritual_batch_limit: usize,
 }
 
 pub async fn register_user(
     State(app_state): State<AppState>,
     Json(registration): Json<PractitionerRegistration>,
-) -> Result<Json<SuccessResponse<AuthToken>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<SuccessResponse<AuthToken>>, ApiError> {
     // Validate email doesn't already exist
-    let existing = sqlx::query("SELECT id FROM practitioners WHERE email = $1")
-        .bind(&registration.email)
-        .fetch_optional(&app_state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("Database error: {}", e),
-                }),
-            )
-        })?;
+    let existing: Option<Uuid> = with_pool!(&app_state.db, |pool| {
+        sqlx::query_scalar("SELECT id FROM practitioners WHERE email = $1")
+            .bind(&registration.email)
+            .fetch_optional(pool)
+            .await
+    })
+    .map_err(|e| ApiError::internal("Database error", e))?;
 
     if existing.is_some() {
-        return Err((
-            StatusCode::CONFLICT,
-            Json(ErrorResponse {
-                error: "Sacred practitioner with this email already exists".to_string(),
-            }),
-        ));
+        return Err(ApiError::Conflict("Sacred practitioner with this email already exists".to_string()));
     }
 
     // Hash password
-    let password_hash = hash_password(&registration.password).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Password hashing failed: {}", e),
-            }),
-        )
-    })?;
+    let password_hash = hash_password(&registration.password)
+        .map_err(|e| ApiError::internal("Password hashing failed", e))?;
 
     // Create new practitioner
     let practitioner_id = Uuid::new_v4();
-    let practitioner = sqlx::query_as::<_, Practitioner>(
-        r#"
-        INSERT INTO practitioners (id, email, password_hash, spiritual_name, sacred_path, 
-                                 archetypal_preferences, energy_alignments, privacy_level)
-        VALUES ($1, $2, $3, $4, $5, '{}', '{}', 'private')
-        RETURNING *
-        "#,
-    )
-    .bind(practitioner_id)
-    .bind(&registration.email)
-    .bind(&password_hash)
-    .bind(&registration.spiritual_name)
-    .bind(&registration.sacred_path)
-    .fetch_one(&app_state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to create sacred practitioner: {}", e),
-            }),
+    let practitioner = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, Practitioner>(
+            r#"
+            INSERT INTO practitioners (id, email, password_hash, spiritual_name, sacred_path, 
+                                     archetypal_preferences, energy_alignments, privacy_level)
+            VALUES ($1, $2, $3, $4, $5, '{}', '{}', 'private')
+            RETURNING *
+            "#,
         )
-    })?;
+        .bind(practitioner_id)
+        .bind(&registration.email)
+        .bind(&password_hash)
+        .bind(&registration.spiritual_name)
+        .bind(&registration.sacred_path)
+        .fetch_one(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to create sacred practitioner", e))?;
 
     // Create authentication token
-    let auth_token = create_auth_response(&practitioner).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Token creation failed: {}", e),
-            }),
-        )
-    })?;
+    let auth_token = issue_auth_token(&app_state.db, &practitioner).await?;
 
     Ok(Json(SuccessResponse::new(auth_token)))
 }
@@ -121,51 +116,158 @@ pub async fn register_user(
 pub async fn login_user(
     State(app_state): State<AppState>,
     Json(login): Json<PractitionerLogin>,
-) -> Result<Json<SuccessResponse<AuthToken>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<SuccessResponse<AuthToken>>, ApiError> {
     // Find practitioner by email
-    let practitioner =
-        sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE email = $1")
+    let practitioner = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, Practitioner>(
+            "SELECT * FROM practitioners WHERE email = $1 AND deleted_at IS NULL"
+        )
             .bind(&login.email)
-            .fetch_one(&app_state.db)
+            .fetch_one(pool)
             .await
-            .map_err(|_| {
-                (
-                    StatusCode::UNAUTHORIZED,
-                    Json(ErrorResponse {
-                        error: "Invalid sacred credentials".to_string(),
-                    }),
-                )
-            })?;
+    })
+    .map_err(|_| ApiError::Unauthorized("Invalid sacred credentials".to_string()))?;
 
     // Verify password
-    let password_valid =
-        verify_password(&login.password, &practitioner.password_hash).map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("Password verification failed: {}", e),
-                }),
-            )
-        })?;
+    let password_valid = verify_password(&login.password, &practitioner.password_hash)
+        .map_err(|e| ApiError::internal("Password verification failed", e))?;
 
     if !password_valid {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Invalid sacred credentials".to_string(),
-            }),
-        ));
+        return Err(ApiError::Unauthorized("Invalid sacred credentials".to_string()));
     }
 
     // Create authentication token
-    let auth_token = create_auth_response(&practitioner).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Token creation failed: {}", e),
-            }),
+    let auth_token = issue_auth_token(&app_state.db, &practitioner).await?;
+
+    Ok(Json(SuccessResponse::new(auth_token)))
+}
+
+/// Signs an access token and stores a fresh refresh token for the practitioner
+async fn issue_auth_token(
+    db: &Database,
+    practitioner: &Practitioner,
+) -> Result<AuthToken, ApiError> {
+    let refresh_token = store_refresh_token(db, practitioner.id)
+        .await
+        .map_err(|e| ApiError::internal("Database error", e))?;
+
+    create_auth_response(practitioner, refresh_token).map_err(|e| ApiError::internal("Token creation failed", e))
+}
+
+pub async fn refresh_token(
+    State(app_state): State<AppState>,
+    Json(request): Json<RefreshTokenRequest>,
+) -> Result<Json<SuccessResponse<AuthToken>>, ApiError> {
+    let practitioner_id = redeem_refresh_token(&app_state.db, &request.refresh_token)
+        .await
+        .map_err(|e| ApiError::internal("Database error", e))?
+        .ok_or_else(|| ApiError::Unauthorized("Refresh token is invalid, expired or revoked".to_string()))?;
+
+    let practitioner = find_active_practitioner(&app_state.db, practitioner_id)
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| ApiError::Unauthorized("Refresh token is invalid, expired or revoked".to_string()))?;
+
+    // The refresh token stays valid until it expires or the practitioner logs out
+    let auth_token = create_auth_response(&practitioner, request.refresh_token)
+        .map_err(|e| ApiError::internal("Token creation failed", e))?;
+
+    Ok(Json(SuccessResponse::new(auth_token)))
+}
+
+pub async fn logout_user(
+    State(app_state): State<AppState>,
+    Json(request): Json<RefreshTokenRequest>,
+) -> Result<Json<SuccessResponse<serde_json::Value>>, ApiError> {
+    let revoked = revoke_refresh_token(&app_state.db, &request.refresh_token)
+        .await
+        .map_err(|e| ApiError::internal("Database error", e))?;
+
+    Ok(Json(SuccessResponse::new(json!({ "revoked": revoked }))))
+}
+
+/// Soft-deletes the practitioner's account and signs them out everywhere; the
+/// account can be restored for `ACCOUNT_RESTORE_GRACE_DAYS`
+pub async fn delete_account(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+) -> Result<Json<SuccessResponse<serde_json::Value>>, ApiError> {
+    let deleted_at = chrono::Utc::now();
+
+    with_pool!(&app_state.db, |pool| {
+        async {
+            let mut tx = pool.begin().await?;
+            sqlx::query("UPDATE practitioners SET deleted_at = $2 WHERE id = $1")
+                .bind(practitioner.id)
+                .bind(deleted_at)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM refresh_tokens WHERE practitioner_id = $1")
+                .bind(practitioner.id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await
+        }
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to delete account", e))?;
+
+    Ok(Json(SuccessResponse::new(json!({
+        "deleted_at": deleted_at,
+        "restorable_until": deleted_at + chrono::Duration::days(ACCOUNT_RESTORE_GRACE_DAYS),
+    }))))
+}
+
+/// Restores a soft-deleted account from its credentials, as long as it was
+/// deleted within the grace window, and signs the practitioner back in
+pub async fn restore_account(
+    State(app_state): State<AppState>,
+    Json(login): Json<PractitionerLogin>,
+) -> Result<Json<SuccessResponse<AuthToken>>, ApiError> {
+    let invalid = || {
+        ApiError::Unauthorized("No deleted account matches these sacred credentials".to_string())
+    };
+
+    let practitioner = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, Practitioner>(
+            "SELECT * FROM practitioners WHERE email = $1 AND deleted_at IS NOT NULL"
         )
-    })?;
+        .bind(&login.email)
+        .fetch_optional(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Database error", e))?
+    .ok_or_else(invalid)?;
+
+    let password_valid = verify_password(&login.password, &practitioner.password_hash)
+        .map_err(|e| ApiError::internal("Password verification failed", e))?;
+    if !password_valid {
+        return Err(invalid());
+    }
+
+    let grace_period = chrono::Duration::days(ACCOUNT_RESTORE_GRACE_DAYS);
+    if practitioner
+        .deleted_at
+        .is_some_and(|deleted_at| deleted_at + grace_period < chrono::Utc::now())
+    {
+        return Err(ApiError::Gone(format!(
+            "Accounts can only be restored within {} days of deletion",
+            ACCOUNT_RESTORE_GRACE_DAYS
+        )));
+    }
+
+    let practitioner = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, Practitioner>(
+            "UPDATE practitioners SET deleted_at = NULL WHERE id = $1 RETURNING *"
+        )
+        .bind(practitioner.id)
+        .fetch_one(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to restore account", e))?;
+
+    let auth_token = issue_auth_token(&app_state.db, &practitioner).await?;
 
     Ok(Json(SuccessResponse::new(auth_token)))
 }
@@ -181,48 +283,349 @@ pub async fn get_profile(
         energy_alignments: practitioner.energy_alignments,
         privacy_level: practitioner.privacy_level,
         sacred_path: practitioner.sacred_path,
+        role: practitioner.role,
         member_since: practitioner.created_at,
     };
 
     Json(SuccessResponse::new(profile))
 }
 
+/// The practitioner's stored `ReflectionPreferences`; unreadable ones count as unset
+fn reflection_preferences_of(practitioner: &Practitioner) -> ReflectionPreferences {
+    serde_json::from_value(practitioner.reflection_preferences.clone()).unwrap_or_default()
+}
+
+/// The oracle configuration for `practitioner`: the server's defaults, overridden by
+/// their reflection preferences and by their own API key if they stored one
+fn reflection_config_for(practitioner: &Practitioner) -> Result<ReflectionConfig, ApiError> {
+    let preferences = reflection_preferences_of(practitioner);
+    let mut config = ReflectionConfig::default();
+    if let Some(provider) = preferences.provider {
+        config = config.with_provider(provider);
+    }
+    if let Some(model) = preferences.model {
+        config.model = model;
+    }
+    if let Some(temperature) = preferences.temperature {
+        config.temperature = temperature;
+    }
+
+    if let Some(encrypted_key) = &practitioner.reflection_api_key {
+        config.api_key = api_key_sealer()
+            .and_then(|sealer| sealer.open(encrypted_key))
+            .map_err(|e| {
+                tracing::warn!("Could not decrypt the oracle API key of practitioner {}: {}", practitioner.id, e);
+                ApiError::Internal(CodexError::Configuration {
+                    reason: "Your stored API key could not be decrypted; please set it again".to_string(),
+                })
+            })?;
+    }
+
+    Ok(config)
+}
+
+pub async fn get_reflection_preferences(
+    Extension(practitioner): Extension<Practitioner>,
+) -> Json<SuccessResponse<ReflectionPreferencesView>> {
+    Json(SuccessResponse::new(ReflectionPreferencesView {
+        preferences: reflection_preferences_of(&practitioner),
+        has_api_key: practitioner.reflection_api_key.is_some(),
+    }))
+}
+
+/// Replaces the practitioner's oracle provider, model and temperature, and stores,
+/// keeps or removes their own API key. The key is encrypted with `REFLECTION_KEY_SECRET`
+/// before it is stored and is never returned.
+pub async fn update_reflection_preferences(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Json(update): Json<ReflectionPreferencesUpdate>,
+) -> Result<Json<SuccessResponse<ReflectionPreferencesView>>, ApiError> {
+    let mut preferences = update.preferences;
+    preferences.model = preferences.model.map(|model| model.trim().to_string());
+    if preferences.model.as_deref() == Some("") {
+        return Err(ApiError::BadRequest("Model must not be empty".to_string()));
+    }
+    if let Some(temperature) = preferences.temperature {
+        if !(0.0..=MAX_REFLECTION_TEMPERATURE).contains(&temperature) {
+            return Err(ApiError::BadRequest(format!(
+                "Temperature must be between 0 and {}",
+                MAX_REFLECTION_TEMPERATURE
+            )));
+        }
+    }
+
+    let api_key = match update.api_key.as_deref().map(str::trim) {
+        None => practitioner.reflection_api_key.clone(),
+        Some("") => None,
+        Some(key) => {
+            let sealer = api_key_sealer().map_err(|e| match e {
+                ApiKeyError::NotConfigured(env) => ApiError::ServiceUnavailable(format!(
+                    "This server cannot store API keys until {} is set",
+                    env
+                )),
+                e => ApiError::internal("Failed to prepare API key encryption", e),
+            })?;
+            let encrypted = sealer
+                .seal(key)
+                .map_err(|e| ApiError::internal("Failed to encrypt API key", e))?;
+            Some(encrypted)
+        }
+    };
+
+    let stored_preferences = json!(preferences);
+    with_pool!(&app_state.db, |pool| {
+        sqlx::query(
+            "UPDATE practitioners SET reflection_preferences = $1, reflection_api_key = $2 WHERE id = $3"
+        )
+        .bind(&stored_preferences)
+        .bind(&api_key)
+        .bind(practitioner.id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    })
+    .map_err(|e| ApiError::internal("Failed to save reflection preferences", e))?;
+
+    Ok(Json(SuccessResponse::new(ReflectionPreferencesView {
+        preferences,
+        has_api_key: api_key.is_some(),
+    })))
+}
+
 pub async fn execute_ritual(
     State(app_state): State<AppState>,
     Extension(practitioner): Extension<Practitioner>,
+    headers: HeaderMap,
     Json(request): Json<RitualExecutionRequest>,
-) -> Result<Json<SuccessResponse<TransformationResult>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<SuccessResponse<TransformationResult>>, ApiError> {
+    let idempotency_key = idempotency_key(&headers)?;
+
+    // Hold the practitioner's lock until the new state is stored so a concurrent
+    // execution builds on this one instead of overwriting it
+    let state_guard = app_state.practitioner_locks.lock(practitioner.id).await;
+
+    // A retried request gets the first attempt's result instead of running again
+    if let Some(key) = &idempotency_key {
+        if let Some(result) = find_idempotent_result(&app_state.db, practitioner.id, key).await? {
+            return Ok(Json(SuccessResponse::new(result)));
+        }
+    }
+
+    let auto_reflect = request.auto_reflect;
+    let (state_id, mut symbolic_state) = get_practitioner_symbolic_state(&app_state.db, practitioner.id).await?;
+    let mut execution = run_ritual_request(&app_state, practitioner.id, &mut symbolic_state, request).await?;
+
+    // A dry run is a preview: leave the stored state, sessions and usage count as they were
+    if !execution.result.dry_run {
+        store_executions(&app_state.db, practitioner.id, state_id, std::slice::from_ref(&execution)).await?;
+        app_state.state_streams.publish(practitioner.id, &execution.result.post_state);
+    }
+
+    if let Some(key) = &idempotency_key {
+        store_idempotent_result(&app_state.db, practitioner.id, key, &execution.result).await?;
+    }
+
+    // The oracle may take its whole retry budget to answer, so it is consulted only
+    // once the session is stored and other requests can use the state again
+    drop(state_guard);
+
+    // Only consult the oracle for stored sessions, and only when asked to
+    if !execution.result.dry_run && auto_reflect && execution.result.oracle_consultation_recommended {
+        execution.result.oracle_insight =
+            auto_reflection(&app_state, &practitioner, execution.result.session_id).await;
+
+        // A retry also gets the insight, unless it arrived before the oracle answered
+        if let (Some(key), Some(_)) = (&idempotency_key, &execution.result.oracle_insight) {
+            store_idempotent_result(&app_state.db, practitioner.id, key, &execution.result).await?;
+        }
+    }
+
+    Ok(Json(SuccessResponse::new(execution.result)))
+}
+
+/// Runs a planned sequence of rituals in one request, each building on the
+/// state the one before it left.
+///
+/// Nothing is stored unless every ritual succeeds, and then all of them are
+/// stored in a single transaction.
+pub async fn execute_ritual_batch(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Json(requests): Json<Vec<RitualExecutionRequest>>,
+) -> Result<Json<SuccessResponse<Vec<TransformationResult>>>, ApiError> {
+    if requests.is_empty() || requests.len() > app_state.ritual_batch_limit {
+        return Err(ApiError::BadRequest(format!(
+            "A batch must hold between 1 and {} rituals",
+            app_state.ritual_batch_limit
+        )));
+    }
+
+    let _state_guard = app_state.practitioner_locks.lock(practitioner.id).await;
+
+    let (state_id, mut symbolic_state) = get_practitioner_symbolic_state(&app_state.db, practitioner.id).await?;
+    let mut executions = Vec::with_capacity(requests.len());
+    for request in requests {
+        executions.push(run_ritual_request(&app_state, practitioner.id, &mut symbolic_state, request).await?);
+    }
+
+    let stored: Vec<ExecutedRitual> = executions.iter()
+        .filter(|execution| !execution.result.dry_run)
+        .cloned()
+        .collect();
+    if let Some(last) = stored.last() {
+        store_executions(&app_state.db, practitioner.id, state_id, &stored).await?;
+        app_state.state_streams.publish(practitioner.id, &last.result.post_state);
+    }
+
+    let results = executions.into_iter().map(|execution| execution.result).collect();
+    Ok(Json(SuccessResponse::new(results)))
+}
+
+/// Reflects on a just-stored session, logging rather than failing the
+/// execution when the oracle cannot be reached
+async fn auto_reflection(
+    app_state: &AppState,
+    practitioner: &Practitioner,
+    session_id: Uuid,
+) -> Option<OracleInsight> {
+    let request = ReflectionRequest {
+        session_id: Some(session_id),
+        custom_query: None,
+    };
+    match request_reflection(State(app_state.clone()), Extension(practitioner.clone()), Json(request)).await {
+        Ok(Json(response)) => Some(response.data),
+        Err(error) => {
+            tracing::warn!("Automatic reflection on session {} failed: {}", session_id, error);
+            None
+        }
+    }
+}
+
+/// The request's `Idempotency-Key`, if it sent one
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, ApiError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    match value.to_str() {
+        Ok(key) if !key.trim().is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => Ok(Some(key.to_string())),
+        _ => Err(ApiError::BadRequest(format!(
+            "Idempotency-Key must be 1 to {} visible ASCII characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        ))),
+    }
+}
+
+/// The result first returned for `key`, unless it has expired
+async fn find_idempotent_result(
+    db: &Database,
+    practitioner_id: Uuid,
+    key: &str,
+) -> Result<Option<TransformationResult>, ApiError> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS);
+    let cached = with_pool!(db, |pool| {
+        sqlx::query_scalar::<_, serde_json::Value>(
+            "SELECT result FROM idempotency_keys
+             WHERE practitioner_id = $1 AND idempotency_key = $2 AND created_at > $3",
+        )
+        .bind(practitioner_id)
+        .bind(key)
+        .bind(cutoff)
+        .fetch_optional(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to look up idempotency key", e))?;
+
+    // A result that no longer deserializes is treated like an unknown key
+    Ok(cached.and_then(|result| serde_json::from_value(result).ok()))
+}
+
+/// Remembers the result returned for `key`, replacing an expired use of the same
+/// key and forgetting the practitioner's other expired keys
+async fn store_idempotent_result(
+    db: &Database,
+    practitioner_id: Uuid,
+    key: &str,
+    result: &TransformationResult,
+) -> Result<(), ApiError> {
+    let now = chrono::Utc::now();
+    let cutoff = now - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS);
+
+    with_pool!(db, |pool| {
+        async {
+            sqlx::query("DELETE FROM idempotency_keys WHERE practitioner_id = $1 AND created_at <= $2")
+                .bind(practitioner_id)
+                .bind(cutoff)
+                .execute(pool)
+                .await?;
+
+            sqlx::query(
+                "INSERT INTO idempotency_keys (practitioner_id, idempotency_key, result, created_at)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (practitioner_id, idempotency_key)
+                 DO UPDATE SET result = excluded.result, created_at = excluded.created_at",
+            )
+            .bind(practitioner_id)
+            .bind(key)
+            .bind(serde_json::to_value(result).unwrap())
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|_| ())
+        }
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to store idempotency key", e))
+}
+
+/// A ritual that has run on the practitioner's state but not been stored yet
+#[derive(Clone)]
+struct ExecutedRitual {
+    ritual_id: Uuid,
+    intention: String,
+    /// What the ritual ran with, recorded so the session can be replayed
+    parameters: HashMap<String, serde_json::Value>,
+    seed: u64,
+    duration_ms: u64,
+    state_changes: Vec<StateChange>,
+    post_state: SymbolicState,
+    result: TransformationResult,
+}
+
+/// Runs one requested ritual on `symbolic_state`, or on a copy of it for a dry run
+async fn run_ritual_request(
+    app_state: &AppState,
+    practitioner_id: Uuid,
+    symbolic_state: &mut SymbolicState,
+    request: RitualExecutionRequest,
+) -> Result<ExecutedRitual, ApiError> {
     let execution_start = Instant::now();
 
     // Fetch the ritual definition from the database
-    let sacred_ritual = sqlx::query_as::<_, SacredRitual>(
-        "SELECT * FROM sacred_rituals WHERE name = $1 AND (is_public = true OR author_id = $2)"
-    )
-    .bind(&request.ritual_name)
-    .bind(practitioner.id)
-    .fetch_optional(&app_state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to fetch ritual: {}", e),
-            }),
-        )
-    })?;
-
-    let ritual_record = sacred_ritual.ok_or_else(|| {
-        (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Ritual '{}' not found", request.ritual_name),
-            }),
+    let sacred_ritual = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, SacredRitual>(
+            "SELECT * FROM sacred_rituals WHERE name = $1 AND (is_public = true OR author_id = $2)"
         )
-    })?;
+        .bind(&request.ritual_name)
+        .bind(practitioner_id)
+        .fetch_optional(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch ritual", e))?;
 
-    // Get current practitioner state and convert to SymbolicState
-    let current_archetypal_state = get_practitioner_current_state(&app_state.db, practitioner.id).await?;
-    let mut symbolic_state = convert_archetypal_to_symbolic(&current_archetypal_state);
+    let ritual_record = sacred_ritual
+        .ok_or_else(|| ApiError::NotFound(format!("Ritual '{}' not found", request.ritual_name)))?;
+
+    // A preview must not leak into the state later rituals in a batch build on
+    let mut preview_state;
+    let symbolic_state = if request.dry_run {
+        preview_state = symbolic_state.clone();
+        &mut preview_state
+    } else {
+        symbolic_state
+    };
+    let current_archetypal_state = ArchetypalState::from_symbolic_state(symbolic_state);
 
     // Create ritual definition from database record
     let ritual_definition = RitualDefinition {
@@ -244,14 +647,46 @@ pub async fn execute_ritual(
         wasm_module_path: None, // WASM data is in database, not file path
         native_handler: Some(ritual_record.name.clone()), // Use name as native handler
         parameters: request.parameters.clone(),
+        wasm_limits: WasmLimits::default(),
+        archetype_synergies: std::collections::HashMap::new(),
+        min_archetype_resonance: None,
+        parameter_schema: serde_json::from_value(ritual_record.parameter_schema.clone())
+            .unwrap_or_default(),
+        difficulty: Difficulty::from_name(&ritual_record.difficulty_level),
+        resonance_weights: ResonanceWeights::default(),
+        extends: None,
     };
 
-    // Create and configure the ritual
-    let mut ritual = Ritual::new(ritual_definition);
+    // Refuse parameters the ritual would misread before anything runs
+    let mismatches = ritual_definition.parameter_mismatches(&request.parameters);
+    if !mismatches.is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid parameters for ritual '{}': {}",
+            ritual_record.name,
+            mismatches.join("; ")
+        )));
+    }
+
+    // Unseeded requests still run with a seed, so the session can be replayed exactly
+    let seed = request.seed.unwrap_or_else(rand::random);
+    let mut ritual = Ritual::new(ritual_definition).with_seed(Some(seed));
 
-    // Load WASM module if available
+    // Load WASM module if available, reusing the compiled module when its hash is known
     if let Some(wasm_data) = ritual_record.wasm_module_data {
-        match load_wasm_from_bytes(&mut ritual, &wasm_data) {
+        // Refuse to run bytes that no longer match the hash recorded at upload
+        if let Some(hash) = &ritual_record.wasm_module_hash {
+            Ritual::verify_wasm_module_hash(&wasm_data, hash)
+                .map_err(|e| ApiError::internal("Ritual module integrity check failed", e))?;
+        }
+
+        let load_result = match &ritual_record.wasm_module_hash {
+            Some(hash) => app_state
+                .engine
+                .wasm_cache()
+                .load_into(&mut ritual, hash, &wasm_data),
+            None => load_wasm_from_bytes(&mut ritual, &wasm_data),
+        };
+        match load_result {
             Ok(_) => {
                 tracing::info!("Loaded WASM module for ritual: {}", ritual_record.name);
             }
@@ -263,68 +698,17 @@ pub async fn execute_ritual(
     }
 
     // Execute the ritual
-    let ritual_result = ritual.execute(&mut symbolic_state).await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("Ritual execution failed: {}", e),
-                }),
-            )
-        })?;
+    let ritual_result = ritual.execute(symbolic_state).await
+        .map_err(|e| ApiError::internal("Ritual execution failed", e))?;
 
     // Convert symbolic state back to archetypal state
-    let post_state = convert_symbolic_to_archetypal(&symbolic_state);
+    let post_state = ArchetypalState::from_symbolic_state(symbolic_state);
     let execution_duration = execution_start.elapsed();
 
     // Calculate transformation intensity based on ritual result
     let transformation_intensity = ritual_result.resonance_level;
 
-    // Store the new state
-    let post_state_id = store_archetypal_state(&app_state.db, practitioner.id, &post_state).await?;
-
-    // Create session record with actual ritual data
     let session_id = ritual_result.execution_id;
-    sqlx::query(
-        r#"
-        INSERT INTO ritual_sessions (id, practitioner_id, ritual_id, pre_state_id, post_state_id,
-                                   execution_duration_ms, transformation_intensity, subjective_experience,
-                                   integration_notes, effectiveness_rating)
-        VALUES ($1, $2, $3, 
-                (SELECT id FROM archetypal_states WHERE practitioner_id = $4 ORDER BY created_at DESC LIMIT 1 OFFSET 1),
-                $5, $6, $7, $8, $9, $10)
-        "#,
-    )
-    .bind(session_id)
-    .bind(practitioner.id)
-    .bind(ritual_record.id)
-    .bind(practitioner.id)
-    .bind(post_state_id)
-    .bind(ritual_result.duration_ms as i32)
-    .bind(transformation_intensity)
-    .bind(request.intention)
-    .bind(format!("Ritual completed with {} state changes", ritual_result.state_changes.len()))
-    .bind((transformation_intensity * 5.0) as i32) // Convert to 1-5 scale
-    .execute(&app_state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to record ritual session: {}", e),
-            }),
-        )
-    })?;
-
-    // Update ritual usage count
-    if let Err(e) = sqlx::query("UPDATE sacred_rituals SET usage_count = usage_count + 1 WHERE id = $1")
-        .bind(ritual_record.id)
-        .execute(&app_state.db)
-        .await
-    {
-        tracing::warn!("Failed to update ritual usage count: {}", e);
-        // Don't fail the request for this
-    }
 
     // Generate integration suggestions based on ritual results
     let integration_required = ritual_result.state_changes.iter()
@@ -333,224 +717,876 @@ pub async fn execute_ritual(
 
     let next_rituals_suggested = suggest_next_rituals_from_result(&ritual_result);
 
+    // The ritual's own symbols come first, then those the new state itself gives rise to
+    let mut emerged_symbols = ritual_result.emergent_symbols;
+    for symbol in generate_emerged_symbols(&post_state) {
+        if !emerged_symbols.contains(&symbol) {
+            emerged_symbols.push(symbol);
+        }
+    }
+
     let result = TransformationResult {
         session_id,
         pre_state: current_archetypal_state,
         post_state,
         transformation_intensity,
-        emerged_symbols: ritual_result.emergent_symbols,
+        emerged_symbols,
         integration_required,
         next_rituals_suggested,
         oracle_consultation_recommended: transformation_intensity > 0.7,
         execution_duration_ms: execution_duration.as_millis(),
+        dry_run: request.dry_run,
+        oracle_insight: None,
+    };
+
+    Ok(ExecutedRitual {
+        ritual_id: ritual_record.id,
+        intention: request.intention,
+        parameters: request.parameters,
+        seed,
+        duration_ms: ritual_result.duration_ms,
+        state_changes: ritual_result.state_changes,
+        post_state: symbolic_state.clone(),
+        result,
+    })
+}
+
+/// How long the readiness check waits on the database before reporting it degraded
+const DATABASE_HEALTH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Liveness: the process is up and serving, whatever the state of its dependencies
+pub async fn health_live() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "Sacred systems operational",
+        "version": env!("CARGO_PKG_VERSION"),
+        "message": "🔮 The Codex Control Engine serves"
+    }))
+}
+
+/// Readiness: the database answers `SELECT 1` within `DATABASE_HEALTH_TIMEOUT`,
+/// otherwise `503` so load balancers stop routing here
+pub async fn health_ready(State(app_state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let started = std::time::Instant::now();
+    let outcome = tokio::time::timeout(DATABASE_HEALTH_TIMEOUT, app_state.db.ping()).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let failure = match outcome {
+        Ok(Ok(())) => {
+            return (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "status": "operational",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "database": {
+                        "backend": app_state.db.backend_name(),
+                        "latency_ms": latency_ms,
+                    },
+                })),
+            )
+        }
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("no response within {}ms", DATABASE_HEALTH_TIMEOUT.as_millis()),
     };
 
-    Ok(Json(SuccessResponse::new(result)))
+    tracing::warn!("Readiness check failed: database {}", failure);
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(serde_json::json!({
+            "status": "degraded",
+            "version": env!("CARGO_PKG_VERSION"),
+            "database": {
+                "backend": app_state.db.backend_name(),
+                "latency_ms": latency_ms,
+                "error": failure,
+            },
+        })),
+    )
 }
 
 pub async fn get_ritual_catalog(
     State(app_state): State<AppState>,
-) -> Result<Json<SuccessResponse<Vec<SacredRitual>>>, (StatusCode, Json<ErrorResponse>)> {
-    let rituals = sqlx::query_as::<_, SacredRitual>(
-        "SELECT id, name, description, intent, tradition, difficulty_level, required_archetypes, 
-         energy_requirements, wasm_module_data, wasm_module_hash, module_language, author_id,
-         usage_count, effectiveness_rating, 
-         rating_count, is_public, tags, created_at, updated_at 
-         FROM sacred_rituals WHERE is_public = true ORDER BY usage_count DESC, created_at DESC"
-    )
-    .fetch_all(&app_state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to fetch ritual catalog: {}", e),
-            }),
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<SuccessResponse<PaginatedResponse<SacredRitual>>>, ApiError> {
+    let (limit, offset) = resolve_pagination(&pagination)?;
+
+    let total: i64 = with_pool!(&app_state.db, |pool| {
+        sqlx::query_scalar("SELECT COUNT(*) FROM sacred_rituals WHERE is_public = true")
+            .fetch_one(pool)
+            .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch ritual catalog", e))?;
+
+    let rituals = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, SacredRitual>(
+            "SELECT id, name, description, intent, tradition, difficulty_level, required_archetypes, 
+             energy_requirements, parameter_schema, wasm_module_data, wasm_module_hash,
+             module_language, author_id, usage_count, effectiveness_rating, 
+             rating_count, is_public, tags, created_at, updated_at 
+             FROM sacred_rituals WHERE is_public = true ORDER BY usage_count DESC, created_at DESC, id
+             LIMIT $1 OFFSET $2"
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch ritual catalog", e))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        items: rituals,
+        total,
+        limit,
+        offset,
+    })))
+}
+
+/// Searches the public catalog by tradition, difficulty, required archetype
+/// and free text, ordered and paginated like the catalog itself
+pub async fn search_rituals(
+    State(app_state): State<AppState>,
+    Query(search): Query<RitualSearchParams>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<SuccessResponse<PaginatedResponse<SacredRitual>>>, ApiError> {
+    let (limit, offset) = resolve_pagination(&pagination)?;
+    let (filters, binds) = ritual_search_filters(&search);
+
+    let count_query = format!("SELECT COUNT(*) FROM sacred_rituals WHERE {}", filters);
+    let total: i64 = with_pool!(&app_state.db, |pool| {
+        let mut query = sqlx::query_scalar(&count_query);
+        for value in &binds {
+            query = query.bind(value);
+        }
+        query.fetch_one(pool).await
+    })
+    .map_err(|e| ApiError::internal("Failed to search rituals", e))?;
+
+    let select_query = format!(
+        "SELECT id, name, description, intent, tradition, difficulty_level, required_archetypes,
+         energy_requirements, parameter_schema, wasm_module_data, wasm_module_hash,
+         module_language, author_id, usage_count, effectiveness_rating,
+         rating_count, is_public, tags, created_at, updated_at
+         FROM sacred_rituals WHERE {} ORDER BY usage_count DESC, created_at DESC, id
+         LIMIT ${} OFFSET ${}",
+        filters,
+        binds.len() + 1,
+        binds.len() + 2
+    );
+    let rituals = with_pool!(&app_state.db, |pool| {
+        let mut query = sqlx::query_as::<_, SacredRitual>(&select_query);
+        for value in &binds {
+            query = query.bind(value);
+        }
+        query.bind(limit).bind(offset).fetch_all(pool).await
+    })
+    .map_err(|e| ApiError::internal("Failed to search rituals", e))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        items: rituals,
+        total,
+        limit,
+        offset,
+    })))
+}
+
+/// Builds the `WHERE` clause for a ritual search along with the values for its
+/// numbered placeholders. Every filter value is bound, never spliced into the SQL.
+///
+/// JSON columns are matched through their text form so the same query runs on
+/// both Postgres and SQLite.
+fn ritual_search_filters(search: &RitualSearchParams) -> (String, Vec<String>) {
+    let mut clauses = vec!["is_public = true".to_string()];
+    let mut binds = Vec::new();
+
+    let present = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    if let Some(tradition) = present(&search.tradition) {
+        binds.push(tradition.to_lowercase());
+        clauses.push(format!("LOWER(tradition) = ${}", binds.len()));
+    }
+    if let Some(difficulty) = present(&search.difficulty) {
+        binds.push(difficulty.to_lowercase());
+        clauses.push(format!("LOWER(difficulty_level) = ${}", binds.len()));
+    }
+    if let Some(archetype) = present(&search.archetype) {
+        // The archetype as a complete JSON string element, so "Sage" does not match "Sagehood"
+        let element = serde_json::Value::String(archetype).to_string();
+        binds.push(format!("%{}%", escape_like(&element)));
+        clauses.push(format!(
+            "CAST(required_archetypes AS TEXT) LIKE ${} ESCAPE '\\'",
+            binds.len()
+        ));
+    }
+    if let Some(text) = present(&search.q) {
+        binds.push(format!("%{}%", escape_like(&text.to_lowercase())));
+        clauses.push(format!(
+            "LOWER(name || ' ' || description || ' ' || intent || ' ' \
+             || COALESCE(CAST(tags AS TEXT), '')) LIKE ${} ESCAPE '\\'",
+            binds.len()
+        ));
+    }
+
+    (clauses.join(" AND "), binds)
+}
+
+/// Escapes `LIKE` wildcards so user input only ever matches literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+pub async fn upload_ritual(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Json(upload): Json<RitualUpload>,
+) -> Result<Json<SuccessResponse<SacredRitual>>, ApiError> {
+    let ritual_id = Uuid::new_v4();
+
+    // Only accept modules the runtime can actually load, and record their hash
+    let wasm_module_hash = match &upload.wasm_module {
+        Some(wasm_data) => {
+            Ritual::validate_wasm_module(wasm_data)
+                .map_err(|e| ApiError::BadRequest(format!("Uploaded ritual module is not valid WASM: {}", e)))?;
+
+            // Reject modules without the entry points the runtime calls
+            let mut candidate = Ritual::new(RitualDefinition {
+                name: upload.name.clone(),
+                description: upload.description.clone(),
+                intent: upload.intent.clone(),
+                required_archetypes: upload.required_archetypes.clone(),
+                energy_requirements: upload.energy_requirements.clone(),
+                wasm_module_path: None,
+                native_handler: None,
+                parameters: std::collections::HashMap::new(),
+                wasm_limits: WasmLimits::default(),
+                archetype_synergies: std::collections::HashMap::new(),
+                min_archetype_resonance: None,
+                parameter_schema: HashMap::new(),
+                difficulty: Difficulty::from_name(&upload.difficulty_level),
+                resonance_weights: ResonanceWeights::default(),
+                extends: None,
+            });
+            candidate
+                .load_wasm_module_from_bytes(wasm_data)
+                .and_then(|_| candidate.validate_wasm_exports())
+                .map_err(|e| ApiError::BadRequest(format!("Uploaded ritual module is unusable: {}", e)))?;
+            Some(Ritual::hash_wasm_module(wasm_data))
+        }
+        None => None,
+    };
+
+    let ritual = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, SacredRitual>(
+            r#"
+            INSERT INTO sacred_rituals (id, name, description, intent, tradition, difficulty_level,
+                                      required_archetypes, energy_requirements, parameter_schema,
+                                      wasm_module_data, wasm_module_hash, module_language, author_id,
+                                      is_public, tags)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING *
+            "#,
         )
-    })?;
+        .bind(ritual_id)
+        .bind(&upload.name)
+        .bind(&upload.description)
+        .bind(&upload.intent)
+        .bind(&upload.tradition)
+        .bind(&upload.difficulty_level)
+        .bind(serde_json::to_value(&upload.required_archetypes).unwrap())
+        .bind(serde_json::to_value(&upload.energy_requirements).unwrap())
+        .bind(serde_json::to_value(&upload.parameter_schema).unwrap())
+        .bind(upload.wasm_module.as_deref())
+        .bind(wasm_module_hash)
+        .bind(upload.module_language.as_deref())
+        .bind(practitioner.id)
+        .bind(upload.is_public)
+        .bind(serde_json::to_value(&upload.tags).unwrap())
+        .fetch_one(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to upload ritual", e))?;
+
+    Ok(Json(SuccessResponse::new(ritual)))
+}
+
+pub async fn get_ritual_details(
+    State(app_state): State<AppState>,
+    Path(ritual_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<SacredRitual>>, ApiError> {
+    let ritual = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, SacredRitual>("SELECT * FROM sacred_rituals WHERE id = $1")
+            .bind(ritual_id)
+            .fetch_one(pool)
+            .await
+    })
+    .map_err(|_| ApiError::NotFound("Sacred ritual not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(ritual)))
+}
 
-    Ok(Json(SuccessResponse::new(rituals)))
+/// Deletes a ritual; allowed for its author and for moderators or admins
+pub async fn delete_ritual(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Path(ritual_id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<serde_json::Value>>, ApiError> {
+    let author_id: Option<Uuid> = with_pool!(&app_state.db, |pool| {
+        sqlx::query_scalar("SELECT author_id FROM sacred_rituals WHERE id = $1")
+            .bind(ritual_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .map_err(|e| ApiError::internal("Database error", e))?
+    .ok_or_else(|| ApiError::NotFound("Sacred ritual not found".to_string()))?;
+
+    if author_id != Some(practitioner.id) {
+        require_role(&practitioner, Role::Moderator)
+            .map_err(|_| ApiError::Forbidden("Only the ritual's author or a moderator may delete it".to_string()))?;
+    }
+
+    with_pool!(&app_state.db, |pool| {
+        sqlx::query("DELETE FROM sacred_rituals WHERE id = $1")
+            .bind(ritual_id)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    })
+    .map_err(|e| ApiError::internal("Failed to delete sacred ritual", e))?;
+
+    Ok(Json(SuccessResponse::new(json!({ "deleted": ritual_id }))))
+}
+
+/// Records the practitioner's 1-5 rating of a ritual and recomputes its average
+pub async fn rate_ritual(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Path(ritual_id): Path<Uuid>,
+    Json(request): Json<RitualRatingRequest>,
+) -> Result<Json<SuccessResponse<RitualRatingSummary>>, ApiError> {
+    if !(MIN_RITUAL_RATING..=MAX_RITUAL_RATING).contains(&request.rating) {
+        return Err(ApiError::BadRequest(format!(
+            "Rating must be between {} and {}",
+            MIN_RITUAL_RATING, MAX_RITUAL_RATING
+        )));
+    }
+
+    let db_error = |e: sqlx::Error| ApiError::internal("Failed to rate sacred ritual", e);
+
+    let now = chrono::Utc::now();
+    let aggregate: Option<(f64, i32)> = with_pool!(&app_state.db, |pool| {
+        async {
+            let mut tx = pool.begin().await?;
+
+            // Touching the ritual row locks it, so concurrent ratings recompute the average in turn
+            let touched = sqlx::query("UPDATE sacred_rituals SET updated_at = $2 WHERE id = $1")
+                .bind(ritual_id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if touched == 0 {
+                return Ok(None);
+            }
+
+            sqlx::query(
+                "INSERT INTO ritual_ratings (ritual_id, practitioner_id, rating, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $4)
+                 ON CONFLICT (ritual_id, practitioner_id)
+                 DO UPDATE SET rating = excluded.rating, updated_at = excluded.updated_at",
+            )
+            .bind(ritual_id)
+            .bind(practitioner.id)
+            .bind(request.rating)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            let aggregate = sqlx::query_as::<_, (f64, i32)>(
+                "UPDATE sacred_rituals
+                 SET effectiveness_rating = (
+                         SELECT CAST(AVG(rating) AS DOUBLE PRECISION)
+                         FROM ritual_ratings WHERE ritual_id = $1
+                     ),
+                     rating_count = (SELECT COUNT(*) FROM ritual_ratings WHERE ritual_id = $1)
+                 WHERE id = $1
+                 RETURNING effectiveness_rating, rating_count",
+            )
+            .bind(ritual_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok::<_, sqlx::Error>(Some(aggregate))
+        }
+        .await
+    })
+    .map_err(db_error)?;
+
+    let (effectiveness_rating, rating_count) = aggregate
+        .ok_or_else(|| ApiError::NotFound("Sacred ritual not found".to_string()))?;
+
+    let summary = RitualRatingSummary {
+        ritual_id,
+        rating: request.rating,
+        effectiveness_rating,
+        rating_count,
+    };
+
+    Ok(Json(SuccessResponse::new(summary)))
+}
+
+pub async fn get_current_state(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+) -> Result<Json<SuccessResponse<crate::state::ArchetypalState>>, ApiError> {
+    let state = get_practitioner_current_state(&app_state.db, practitioner.id).await?;
+    Ok(Json(SuccessResponse::new(state)))
+}
+
+pub async fn transform_state(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Json(request): Json<StateTransformationRequest>,
+) -> Result<Json<SuccessResponse<crate::state::ArchetypalState>>, ApiError> {
+    let _state_guard = app_state.practitioner_locks.lock(practitioner.id).await;
+
+    // Get current state, keeping the full symbolic state to carry the change back onto
+    let (_, mut symbolic_state) = get_practitioner_symbolic_state(&app_state.db, practitioner.id).await?;
+    let mut current_state = ArchetypalState::from_symbolic_state(&symbolic_state);
+
+    // Apply transformation based on type
+    match request.transformation_type.as_str() {
+        "archetype_activation" => {
+            if let Some(archetype_name) = request.parameters.get("archetype") {
+                if let Some(intensity) = request.parameters.get("intensity") {
+                    let archetype = archetype_name.as_str().unwrap_or("");
+                    let intensity_val = intensity.as_f64().unwrap_or(0.1);
+                    current_state
+                        .archetypes
+                        .insert(archetype.to_string(), intensity_val);
+                }
+            }
+        }
+        "energy_adjustment" => {
+            if let Some(energy_type) = request.parameters.get("energy_type") {
+                if let Some(adjustment) = request.parameters.get("adjustment") {
+                    let energy = energy_type.as_str().unwrap_or("");
+                    let adjustment_val = adjustment.as_f64().unwrap_or(0.0);
+                    let current_val = current_state.energies.get(energy).unwrap_or(&0.0);
+                    current_state
+                        .energies
+                        .insert(energy.to_string(), current_val + adjustment_val);
+                }
+            }
+        }
+        _ => {
+            return Err(ApiError::BadRequest("Unknown transformation type".to_string()));
+        }
+    }
+
+    // Store the updated state
+    apply_archetypal_levels(&mut symbolic_state, &current_state);
+    store_symbolic_state(&app_state.db, practitioner.id, &symbolic_state).await?;
+    app_state.state_streams.publish(practitioner.id, &current_state);
+
+    Ok(Json(SuccessResponse::new(current_state)))
+}
+
+/// Streams the practitioner's state as a `state` event each time a ritual or
+/// transformation changes it; a subscriber that falls behind skips to the newest updates
+pub async fn stream_state(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let updates = BroadcastStream::new(app_state.state_streams.subscribe(practitioner.id))
+        .filter_map(|update| update.ok())
+        .map(|state| Event::default().event("state").json_data(state));
+
+    Sse::new(updates).keep_alive(KeepAlive::default())
+}
+
+/// Opens an interactive session in which the practitioner sends `execute` and
+/// `reflect` commands and gets each result pushed back as a frame; the access
+/// token comes as `?token=` and is checked before the upgrade
+pub async fn ws_session(
+    State(app_state): State<AppState>,
+    Query(params): Query<WsAuthParams>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let (practitioner, _) = authenticate_token(&app_state.db, &params.token).await?;
+
+    Ok(upgrade.on_upgrade(move |socket| run_ws_session(socket, app_state, practitioner)))
+}
+
+async fn run_ws_session(mut socket: WebSocket, app_state: AppState, practitioner: Practitioner) {
+    // What a `reflect` without a session id reflects on
+    let mut last_session = None;
+
+    while let Some(Ok(message)) = socket.recv().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let frame = match serde_json::from_str::<WsCommand>(&text) {
+            Ok(command) => {
+                handle_ws_command(&app_state, &practitioner, command, &mut last_session).await
+            }
+            Err(e) => WsFrame::Error(format!("Invalid command: {}", e)),
+        };
+        let Ok(reply) = serde_json::to_string(&frame) else {
+            continue;
+        };
+        if socket.send(Message::Text(reply)).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Runs one websocket command through the same handler its REST route uses
+async fn handle_ws_command(
+    app_state: &AppState,
+    practitioner: &Practitioner,
+    command: WsCommand,
+    last_session: &mut Option<Uuid>,
+) -> WsFrame {
+    match command {
+        WsCommand::Execute {
+            ritual_name,
+            intention,
+            parameters,
+            seed,
+            dry_run,
+            auto_reflect,
+        } => {
+            let request = RitualExecutionRequest {
+                ritual_name,
+                parameters,
+                intention,
+                seed,
+                dry_run,
+                auto_reflect,
+            };
+            match execute_ritual(
+                State(app_state.clone()),
+                Extension(practitioner.clone()),
+                HeaderMap::new(),
+                Json(request),
+            )
+            .await
+            {
+                Ok(Json(response)) => {
+                    // A dry run records no session to reflect on
+                    if !response.data.dry_run {
+                        *last_session = Some(response.data.session_id);
+                    }
+                    WsFrame::TransformationResult(Box::new(response.data))
+                }
+                Err(error) => WsFrame::Error(error.to_string()),
+            }
+        }
+        WsCommand::Reflect {
+            session_id,
+            custom_query,
+        } => {
+            let request = ReflectionRequest {
+                session_id: session_id.or(*last_session),
+                custom_query,
+            };
+            match request_reflection(
+                State(app_state.clone()),
+                Extension(practitioner.clone()),
+                Json(request),
+            )
+            .await
+            {
+                Ok(Json(response)) => WsFrame::OracleInsight(response.data),
+                Err(error) => WsFrame::Error(error.to_string()),
+            }
+        }
+    }
 }
 
-pub async fn upload_ritual(
+/// Replays the practitioner's ritual sessions, in the order they ran, from their
+/// earliest stored state and reports whether that reproduces their current state.
+///
+/// Each session reruns with the seed and parameters it recorded. States stored by
+/// `/api/state/transform` have no session behind them, so a history containing one
+/// replays to a mismatch.
+pub async fn replay_state(
     State(app_state): State<AppState>,
     Extension(practitioner): Extension<Practitioner>,
-    Json(upload): Json<RitualUpload>,
-) -> Result<Json<SuccessResponse<SacredRitual>>, (StatusCode, Json<ErrorResponse>)> {
-    let ritual_id = Uuid::new_v4();
+) -> Result<Json<SuccessResponse<StateReplay>>, ApiError> {
+    // Keep a ritual from storing a new state while the history is replayed
+    let _state_guard = app_state.practitioner_locks.lock(practitioner.id).await;
 
-    let ritual = sqlx::query_as::<_, SacredRitual>(
-        r#"
-        INSERT INTO sacred_rituals (id, name, description, intent, tradition, difficulty_level,
-                                  required_archetypes, energy_requirements, wasm_module_data,
-                                  module_language, author_id, is_public)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-        RETURNING *
-        "#,
-    )
-    .bind(ritual_id)
-    .bind(&upload.name)
-    .bind(&upload.description)
-    .bind(&upload.intent)
-    .bind(&upload.tradition)
-    .bind(&upload.difficulty_level)
-    .bind(serde_json::to_value(&upload.required_archetypes).unwrap())
-    .bind(serde_json::to_value(&upload.energy_requirements).unwrap())
-    .bind(upload.wasm_module.as_deref())
-    .bind(upload.module_language.as_deref())
-    .bind(practitioner.id)
-    .bind(upload.is_public)
-    .fetch_one(&app_state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to upload ritual: {}", e),
-            }),
+    let earliest = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, StoredState>(
+            "SELECT * FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at, id LIMIT 1"
+        )
+        .bind(practitioner.id)
+        .fetch_optional(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch state history", e))?
+    .ok_or_else(|| ApiError::NotFound("No stored state to replay from".to_string()))?;
+    let current = fetch_latest_state(&app_state.db, practitioner.id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("No stored state to replay from".to_string()))?;
+
+    // Sessions whose resulting state was pruned ran before the earliest state
+    let sessions: Vec<(Uuid, Option<String>, serde_json::Value, Option<i64>)> = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as(
+            "SELECT s.id, r.name, s.ritual_parameters, s.seed FROM ritual_sessions s
+             JOIN archetypal_states post ON post.id = s.post_state_id
+             LEFT JOIN sacred_rituals r ON r.id = s.ritual_id
+             WHERE s.practitioner_id = $1 AND post.id <> $2
+             ORDER BY post.created_at, post.id"
         )
-    })?;
+        .bind(practitioner.id)
+        .bind(earliest.id)
+        .fetch_all(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch ritual sessions", e))?;
+
+    let mut replayed = symbolic_state_of(&earliest);
+    for (session_id, ritual_name, parameters, seed) in &sessions {
+        let unreplayable =
+            |reason: &str| ApiError::Conflict(format!("Session {} cannot be replayed: {}", session_id, reason));
+        let ritual_name = ritual_name.clone().ok_or_else(|| unreplayable("its ritual no longer exists"))?;
+        let seed = seed.ok_or_else(|| unreplayable("it was recorded without its seed"))?;
+
+        let request = RitualExecutionRequest {
+            ritual_name,
+            parameters: serde_json::from_value(parameters.clone()).unwrap_or_default(),
+            intention: String::new(),
+            seed: Some(seed as u64),
+            dry_run: false,
+            auto_reflect: false,
+        };
+        run_ritual_request(&app_state, practitioner.id, &mut replayed, request).await?;
+    }
 
-    Ok(Json(SuccessResponse::new(ritual)))
+    let diff = replayed.diff(&symbolic_state_of(&current));
+    Ok(Json(SuccessResponse::new(StateReplay {
+        from_state_id: earliest.id,
+        current_state_id: current.id,
+        sessions_replayed: sessions.len(),
+        matches: diff.is_empty(),
+        diff: (!diff.is_empty()).then_some(diff),
+    })))
 }
 
-pub async fn get_ritual_details(
+/// Deletes all but the practitioner's newest `keep_last` states (at least one),
+/// unlinking any ritual sessions that pointed at them
+pub async fn prune_state(
     State(app_state): State<AppState>,
-    Path(ritual_id): Path<Uuid>,
-) -> Result<Json<SuccessResponse<SacredRitual>>, (StatusCode, Json<ErrorResponse>)> {
-    let ritual = sqlx::query_as::<_, SacredRitual>("SELECT * FROM sacred_rituals WHERE id = $1")
-        .bind(ritual_id)
-        .fetch_one(&app_state.db)
+    Extension(practitioner): Extension<Practitioner>,
+    Json(request): Json<StatePruneRequest>,
+) -> Result<Json<SuccessResponse<StatePruneResult>>, ApiError> {
+    let prune_failed = |e: sqlx::Error| ApiError::internal("Failed to prune state history", e);
+
+    // Keep a ritual from storing a state on top of one being pruned
+    let _state_guard = app_state.practitioner_locks.lock(practitioner.id).await;
+
+    let deleted = prune_state_history(&app_state.db, practitioner.id, request.keep_last)
         .await
-        .map_err(|_| {
-            (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "Sacred ritual not found".to_string(),
-                }),
-            )
-        })?;
+        .map_err(prune_failed)?;
+    let remaining: i64 = with_pool!(&app_state.db, |pool| {
+        sqlx::query_scalar("SELECT COUNT(*) FROM archetypal_states WHERE practitioner_id = $1")
+            .bind(practitioner.id)
+            .fetch_one(pool)
+            .await
+    })
+    .map_err(prune_failed)?;
 
-    Ok(Json(SuccessResponse::new(ritual)))
+    Ok(Json(SuccessResponse::new(StatePruneResult { deleted, remaining })))
 }
 
-pub async fn get_current_state(
+/// Diffs two of the practitioner's stored states, `from` being the earlier one
+pub async fn compare_states(
     State(app_state): State<AppState>,
     Extension(practitioner): Extension<Practitioner>,
-) -> Result<Json<SuccessResponse<crate::state::ArchetypalState>>, (StatusCode, Json<ErrorResponse>)>
-{
-    let state = get_practitioner_current_state(&app_state.db, practitioner.id).await?;
-    Ok(Json(SuccessResponse::new(state)))
+    Query(params): Query<StateComparisonParams>,
+) -> Result<Json<SuccessResponse<StateComparison>>, ApiError> {
+    let from = fetch_stored_state(&app_state.db, params.from).await?;
+    let to = fetch_stored_state(&app_state.db, params.to).await?;
+
+    if from.practitioner_id != practitioner.id || to.practitioner_id != practitioner.id {
+        return Err(ApiError::Forbidden("Only your own states can be compared".to_string()));
+    }
+
+    // Both sides go through the same simplified form so rows stored before
+    // `state_data` held the full state compare evenly with newer ones
+    let before = reconstruct_archetypal_state(&from).to_symbolic_state();
+    let after = reconstruct_archetypal_state(&to).to_symbolic_state();
+
+    Ok(Json(SuccessResponse::new(StateComparison {
+        from_state_id: from.id,
+        to_state_id: to.id,
+        from_created_at: from.created_at,
+        to_created_at: to.created_at,
+        diff: before.diff(&after),
+    })))
 }
 
-pub async fn transform_state(
+pub async fn get_state_history(
     State(app_state): State<AppState>,
     Extension(practitioner): Extension<Practitioner>,
-    Json(request): Json<StateTransformationRequest>,
-) -> Result<Json<SuccessResponse<crate::state::ArchetypalState>>, (StatusCode, Json<ErrorResponse>)>
-{
-    // Get current state
-    let mut current_state = get_practitioner_current_state(&app_state.db, practitioner.id).await?;
-
-    // Apply transformation based on type
-    match request.transformation_type.as_str() {
-        "archetype_activation" => {
-            if let Some(archetype_name) = request.parameters.get("archetype") {
-                if let Some(intensity) = request.parameters.get("intensity") {
-                    let archetype = archetype_name.as_str().unwrap_or("");
-                    let intensity_val = intensity.as_f64().unwrap_or(0.1);
-                    current_state
-                        .archetypes
-                        .insert(archetype.to_string(), intensity_val);
-                }
-            }
-        }
-        "energy_adjustment" => {
-            if let Some(energy_type) = request.parameters.get("energy_type") {
-                if let Some(adjustment) = request.parameters.get("adjustment") {
-                    let energy = energy_type.as_str().unwrap_or("");
-                    let adjustment_val = adjustment.as_f64().unwrap_or(0.0);
-                    let current_val = current_state.energies.get(energy).unwrap_or(&0.0);
-                    current_state
-                        .energies
-                        .insert(energy.to_string(), current_val + adjustment_val);
-                }
-            }
-        }
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "Unknown transformation type".to_string(),
-                }),
-            ));
-        }
-    }
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<SuccessResponse<PaginatedResponse<StoredState>>>, ApiError> {
+    let (limit, offset) = resolve_pagination(&pagination)?;
+
+    let total: i64 = with_pool!(&app_state.db, |pool| {
+        sqlx::query_scalar("SELECT COUNT(*) FROM archetypal_states WHERE practitioner_id = $1")
+            .bind(practitioner.id)
+            .fetch_one(pool)
+            .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch state history", e))?;
 
-    // Store the updated state
-    store_archetypal_state(&app_state.db, practitioner.id, &current_state).await?;
+    let states = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, StoredState>(
+            "SELECT * FROM archetypal_states WHERE practitioner_id = $1
+             ORDER BY created_at DESC, id LIMIT $2 OFFSET $3"
+        )
+        .bind(practitioner.id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch state history", e))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        items: states,
+        total,
+        limit,
+        offset,
+    })))
+}
 
-    Ok(Json(SuccessResponse::new(current_state)))
+/// The practitioner's state change log, newest session first and each session's
+/// changes in the order its ritual made them
+pub async fn get_state_audit(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<SuccessResponse<PaginatedResponse<StateTransformation>>>, ApiError> {
+    let (limit, offset) = resolve_pagination(&pagination)?;
+
+    let total: i64 = with_pool!(&app_state.db, |pool| {
+        sqlx::query_scalar("SELECT COUNT(*) FROM state_transformations WHERE practitioner_id = $1")
+            .bind(practitioner.id)
+            .fetch_one(pool)
+            .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch state audit log", e))?;
+
+    let transformations = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, StateTransformation>(
+            "SELECT t.id, t.session_id, r.name AS ritual_name, t.change_type, t.description, t.magnitude,
+                    t.created_at
+             FROM state_transformations t
+             JOIN ritual_sessions s ON s.id = t.session_id
+             LEFT JOIN sacred_rituals r ON r.id = s.ritual_id
+             WHERE t.practitioner_id = $1
+             ORDER BY t.created_at DESC, t.session_id, t.position LIMIT $2 OFFSET $3"
+        )
+        .bind(practitioner.id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch state audit log", e))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        items: transformations,
+        total,
+        limit,
+        offset,
+    })))
 }
 
-pub async fn get_state_history(
+/// How often each symbol emerged from the practitioner's stored ritual sessions and
+/// the average resonance of those sessions, most frequent first. Moderators may ask
+/// for every practitioner's sessions with `?all=true`.
+pub async fn get_symbol_stats(
     State(app_state): State<AppState>,
     Extension(practitioner): Extension<Practitioner>,
-) -> Result<Json<SuccessResponse<Vec<StoredState>>>, (StatusCode, Json<ErrorResponse>)> {
-    let states = sqlx::query_as::<_, StoredState>(
-        "SELECT * FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at DESC LIMIT 20"
-    )
-    .bind(practitioner.id)
-    .fetch_all(&app_state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to fetch state history: {}", e),
-            }),
+    Query(params): Query<SymbolStatsParams>,
+) -> Result<Json<SuccessResponse<Vec<SymbolStatistic>>>, ApiError> {
+    if params.all {
+        require_role(&practitioner, Role::Moderator).map_err(|_| {
+            ApiError::Forbidden("Only moderators may see symbol statistics across all practitioners".to_string())
+        })?;
+    }
+
+    let statistics = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, SymbolStatistic>(
+            "SELECT ss.symbol, COUNT(*) AS occurrences,
+                    COALESCE(AVG(s.transformation_intensity), 0.0) AS average_resonance
+             FROM session_symbols ss
+             JOIN ritual_sessions s ON s.id = ss.session_id
+             WHERE $1 OR ss.practitioner_id = $2
+             GROUP BY ss.symbol
+             ORDER BY occurrences DESC, ss.symbol"
         )
-    })?;
+        .bind(params.all)
+        .bind(practitioner.id)
+        .fetch_all(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to compute symbol statistics", e))?;
 
-    Ok(Json(SuccessResponse::new(states)))
+    Ok(Json(SuccessResponse::new(statistics)))
+}
+
+fn resolve_pagination(pagination: &PaginationParams) -> Result<(i64, i64), ApiError> {
+    pagination.resolve().map_err(ApiError::BadRequest)
 }
 
 pub async fn request_reflection(
     State(app_state): State<AppState>,
     Extension(practitioner): Extension<Practitioner>,
     Json(request): Json<ReflectionRequest>,
-) -> Result<Json<SuccessResponse<OracleInsight>>, (StatusCode, Json<ErrorResponse>)> {
-    // Create AI reflector with configuration
-    let reflection_config = ReflectionConfig::default();
+) -> Result<Json<SuccessResponse<OracleInsight>>, ApiError> {
+    // Reflect with the practitioner's chosen oracle, or the server's by default
+    let reflection_config = reflection_config_for(&practitioner)?;
     let reflector = Reflector::new(reflection_config);
     
     // If session_id is provided, fetch ritual session for context
     let ritual_context = if let Some(session_id) = request.session_id {
         // Get ritual session from database
-        match sqlx::query_as::<_, RitualSessionRecord>(
-            "SELECT * FROM ritual_sessions WHERE id = $1 AND practitioner_id = $2"
-        )
-        .bind(session_id)
-        .bind(practitioner.id)
-        .fetch_optional(&app_state.db)
-        .await
-        {
+        match with_pool!(&app_state.db, |pool| {
+            sqlx::query_as::<_, RitualSessionRecord>(
+                "SELECT * FROM ritual_sessions WHERE id = $1 AND practitioner_id = $2"
+            )
+            .bind(session_id)
+            .bind(practitioner.id)
+            .fetch_optional(pool)
+            .await
+        }) {
             Ok(Some(session)) => {
                 // Get the ritual details
-                let ritual = sqlx::query_as::<_, SacredRitual>(
-                    "SELECT * FROM sacred_rituals WHERE id = $1"
-                )
-                .bind(session.ritual_id)
-                .fetch_optional(&app_state.db)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ErrorResponse {
-                            error: format!("Failed to fetch ritual: {}", e),
-                        }),
+                let ritual = with_pool!(&app_state.db, |pool| {
+                    sqlx::query_as::<_, SacredRitual>(
+                        "SELECT * FROM sacred_rituals WHERE id = $1"
                     )
-                })?;
+                    .bind(session.ritual_id)
+                    .fetch_optional(pool)
+                    .await
+                })
+                .map_err(|e| ApiError::internal("Failed to fetch ritual", e))?;
                 
                 if let Some(ritual) = ritual {
                     Some((session, ritual))
@@ -560,20 +1596,25 @@ pub async fn request_reflection(
             },
             Ok(None) => None,
             Err(e) => {
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: format!("Failed to fetch ritual session: {}", e),
-                    }),
-                ));
+                return Err(ApiError::internal("Failed to fetch ritual session", e));
             }
         }
     } else {
         None
     };
     
+    // A practitioner's own question about their state, if they asked one
+    let custom_query = request
+        .custom_query
+        .as_deref()
+        .map(str::trim)
+        .filter(|query| !query.is_empty());
+
     // Get practitioner's current state
-    let _current_state = get_practitioner_current_state(&app_state.db, practitioner.id).await.ok();
+    let symbolic_state = get_practitioner_symbolic_state(&app_state.db, practitioner.id)
+        .await
+        .map(|(_, state)| state)
+        .unwrap_or_default();
     
     // Create mock ritual result for AI analysis (in future, this would come from actual ritual execution)
     let ritual_result = if let Some((session, ritual)) = ritual_context {
@@ -587,7 +1628,11 @@ pub async fn request_reflection(
             emergent_symbols: vec!["🔮".to_string(), "∞".to_string(), "⚡".to_string()],
             completion_status: crate::ritual::CompletionStatus::Complete,
             resonance_level: session.transformation_intensity.unwrap_or(0.5),
+            checkpoint: None,
         }
+    } else if custom_query.is_some() {
+        // Reflect on the question and the state it is asked about
+        crate::reflection::query_ritual_result(&symbolic_state)
     } else {
         // Create a generic reflection request
         crate::ritual::RitualResult {
@@ -600,16 +1645,16 @@ pub async fn request_reflection(
             emergent_symbols: vec!["🔮".to_string(), "∞".to_string(), "⚡".to_string()],
             completion_status: crate::ritual::CompletionStatus::Complete,
             resonance_level: 0.7,
+            checkpoint: None,
         }
     };
     
-    // Create a SymbolicState for reflection analysis 
-    // In the future, this would be converted from ArchetypalState or retrieved directly
-    let symbolic_state = crate::state::SymbolicState::new();
-    
     // Get AI reflection
-    match reflector.reflect_on_ritual(&ritual_result, &symbolic_state).await {
-        Ok(reflection) => {
+    match reflector
+        .reflect_with_query(&ritual_result, &symbolic_state, custom_query)
+        .await
+    {
+        Ok((reflection, _)) => {
             // Convert ReflectionResult to OracleInsight and store in database
             let insight_id = Uuid::new_v4();
             
@@ -620,7 +1665,8 @@ pub async fn request_reflection(
                 archetypal_analysis: json!({
                     "interpretation": reflection.archetypal_interpretation,
                     "symbolic_meaning": reflection.symbolic_meaning,
-                    "resonance_level": ritual_result.resonance_level
+                    "resonance_level": ritual_result.resonance_level,
+                    "query": custom_query
                 }),
                 integration_suggestions: json!({
                     "guidance": reflection.integration_guidance,
@@ -631,129 +1677,403 @@ pub async fn request_reflection(
                     "symbols": ritual_result.emergent_symbols,
                     "resonance_analysis": reflection.resonance_analysis
                 }),
-                oracle_model: std::env::var("DEFAULT_AI_MODEL").unwrap_or("anthropic/claude-3-haiku".to_string()),
+                oracle_model: reflection_preferences_of(&practitioner)
+                    .model
+                    .unwrap_or_else(|| std::env::var("DEFAULT_AI_MODEL").unwrap_or("anthropic/claude-3-haiku".to_string())),
                 confidence_score: 0.85,
                 created_at: chrono::Utc::now(),
             };
             
             // Store insight in database
-            sqlx::query(
-                r#"INSERT INTO oracle_insights 
-                   (id, session_id, insight_type, archetypal_analysis, integration_suggestions, 
-                    symbolic_emergence, oracle_model, confidence_score, created_at)
-                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#
-            )
-            .bind(oracle_insight.id)
-            .bind(oracle_insight.session_id)
-            .bind(&oracle_insight.insight_type)
-            .bind(&oracle_insight.archetypal_analysis)
-            .bind(&oracle_insight.integration_suggestions)
-            .bind(&oracle_insight.symbolic_emergence)
-            .bind(&oracle_insight.oracle_model)
-            .bind(oracle_insight.confidence_score)
-            .bind(oracle_insight.created_at)
-            .execute(&app_state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: format!("Failed to store oracle insight: {}", e),
-                    }),
+            with_pool!(&app_state.db, |pool| {
+                sqlx::query(
+                    r#"INSERT INTO oracle_insights 
+                       (id, session_id, practitioner_id, insight_type, archetypal_analysis, integration_suggestions, 
+                        symbolic_emergence, oracle_model, confidence_score, created_at)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#
                 )
-            })?;
+                .bind(oracle_insight.id)
+                .bind(oracle_insight.session_id)
+                .bind(practitioner.id)
+                .bind(&oracle_insight.insight_type)
+                .bind(&oracle_insight.archetypal_analysis)
+                .bind(&oracle_insight.integration_suggestions)
+                .bind(&oracle_insight.symbolic_emergence)
+                .bind(&oracle_insight.oracle_model)
+                .bind(oracle_insight.confidence_score)
+                .bind(oracle_insight.created_at)
+                .execute(pool)
+                .await
+                .map(|_| ())
+            })
+            .map_err(|e| ApiError::internal("Failed to store oracle insight", e))?;
             
             Ok(Json(SuccessResponse::new(oracle_insight)))
         }
         Err(e) => {
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: format!("AI reflection failed: {}", e),
-                }),
-            ))
+            Err(ApiError::internal("AI reflection failed", e))
+        }
+    }
+}
+
+/// Columns that make up an `OracleInsight`
+const ORACLE_INSIGHT_COLUMNS: &str = "id, session_id, insight_type, archetypal_analysis, integration_suggestions, \
+     symbolic_emergence, oracle_model, confidence_score, created_at";
+
+/// The practitioner's past insights, newest first, optionally only those from one session.
+///
+/// Insights tied to a session are only listed when that session is the
+/// practitioner's own, so another practitioner's session id reveals nothing.
+pub async fn list_insights(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Query(params): Query<InsightListParams>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<SuccessResponse<PaginatedResponse<OracleInsight>>>, ApiError> {
+    let (limit, offset) = resolve_pagination(&pagination)?;
+
+    let mut filters = "practitioner_id = $1 AND (session_id IS NULL OR session_id IN \
+         (SELECT id FROM ritual_sessions WHERE practitioner_id = $2))"
+        .to_string();
+    if params.session_id.is_some() {
+        filters.push_str(" AND session_id = $3");
+    }
+    let next_placeholder = if params.session_id.is_some() { 4 } else { 3 };
+
+    let count_query = format!("SELECT COUNT(*) FROM oracle_insights WHERE {}", filters);
+    let total: i64 = with_pool!(&app_state.db, |pool| {
+        let mut query = sqlx::query_scalar(&count_query)
+            .bind(practitioner.id)
+            .bind(practitioner.id);
+        if let Some(session_id) = params.session_id {
+            query = query.bind(session_id);
+        }
+        query.fetch_one(pool).await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch insights", e))?;
+
+    let select_query = format!(
+        "SELECT {} FROM oracle_insights WHERE {} ORDER BY created_at DESC, id LIMIT ${} OFFSET ${}",
+        ORACLE_INSIGHT_COLUMNS,
+        filters,
+        next_placeholder,
+        next_placeholder + 1
+    );
+    let insights = with_pool!(&app_state.db, |pool| {
+        let mut query = sqlx::query_as::<_, OracleInsight>(&select_query)
+            .bind(practitioner.id)
+            .bind(practitioner.id);
+        if let Some(session_id) = params.session_id {
+            query = query.bind(session_id);
         }
+        query.bind(limit).bind(offset).fetch_all(pool).await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch insights", e))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        items: insights,
+        total,
+        limit,
+        offset,
+    })))
+}
+
+/// Ranks the practitioner's other insights by how textually similar they are
+/// to the latest insight from `session_id`, among their `MAX_SIMILARITY_CORPUS`
+/// most recent ones
+pub async fn get_similar_insights(
+    State(app_state): State<AppState>,
+    Extension(practitioner): Extension<Practitioner>,
+    Query(query): Query<SimilarInsightsQuery>,
+) -> Result<Json<SuccessResponse<Vec<SimilarInsight>>>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_SIMILAR_INSIGHTS);
+    if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_PAGE_LIMIT)));
     }
+
+    let reference = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, OracleInsight>(&format!(
+            "SELECT {} FROM oracle_insights WHERE practitioner_id = $1 AND session_id = $2 \
+             ORDER BY created_at DESC LIMIT 1",
+            ORACLE_INSIGHT_COLUMNS
+        ))
+        .bind(practitioner.id)
+        .bind(query.session_id)
+        .fetch_optional(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch insights", e))?
+    .ok_or_else(|| ApiError::NotFound("No insight found for this session".to_string()))?;
+
+    let insights = with_pool!(&app_state.db, |pool| {
+        sqlx::query_as::<_, OracleInsight>(&format!(
+            "SELECT {} FROM oracle_insights WHERE practitioner_id = $1 AND id <> $2 \
+             ORDER BY created_at DESC LIMIT $3",
+            ORACLE_INSIGHT_COLUMNS
+        ))
+        .bind(practitioner.id)
+        .bind(reference.id)
+        .bind(MAX_SIMILARITY_CORPUS)
+        .fetch_all(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch insights", e))?;
+
+    let documents: Vec<String> = insights.iter().map(OracleInsight::searchable_text).collect();
+    let similar = rank_by_similarity(&reference.searchable_text(), &documents)
+        .into_iter()
+        .take(limit as usize)
+        .map(|(index, similarity)| SimilarInsight {
+            insight: insights[index].clone(),
+            similarity,
+        })
+        .collect();
+
+    Ok(Json(SuccessResponse::new(similar)))
 }
 
 // Helper functions
 
-async fn get_practitioner_current_state(
-    db: &sqlx::PgPool,
+/// The practitioner's most recently stored state row, if any
+async fn fetch_latest_state(
+    db: &Database,
     practitioner_id: Uuid,
-) -> Result<crate::state::ArchetypalState, (StatusCode, Json<ErrorResponse>)> {
-    let stored_state = sqlx::query_as::<_, StoredState>(
-        "SELECT * FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at DESC LIMIT 1"
-    )
-    .bind(practitioner_id)
-    .fetch_optional(db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to fetch current state: {}", e),
-            }),
+) -> Result<Option<StoredState>, ApiError> {
+    with_pool!(db, |pool| {
+        sqlx::query_as::<_, StoredState>(
+            "SELECT * FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at DESC LIMIT 1"
         )
-    })?;
-
-    match stored_state {
-        Some(state) => {
-            // Convert stored state to ArchetypalState
-            let archetypal_state = crate::state::ArchetypalState {
-                archetypes: serde_json::from_value(state.archetypes).unwrap_or_default(),
-                energies: serde_json::from_value(state.energies).unwrap_or_default(),
-                integrations: serde_json::from_value(state.integrations).unwrap_or_default(),
-                symbols: serde_json::from_value(state.symbols).unwrap_or_default(),
-                transformations: serde_json::from_value(state.transformations).unwrap_or_default(),
-            };
-            Ok(archetypal_state)
-        }
+        .bind(practitioner_id)
+        .fetch_optional(pool)
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch current state", e))
+}
+
+/// A stored state row by id, whoever it belongs to
+async fn fetch_stored_state(
+    db: &Database,
+    state_id: Uuid,
+) -> Result<StoredState, ApiError> {
+    let stored_state = with_pool!(db, |pool| {
+        sqlx::query_as::<_, StoredState>("SELECT * FROM archetypal_states WHERE id = $1")
+            .bind(state_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .map_err(|e| ApiError::internal("Failed to fetch state", e))?;
+
+    stored_state.ok_or_else(|| ApiError::NotFound(format!("State {} not found", state_id)))
+}
+
+/// The full `SymbolicState` held in `state_data`, or `None` for rows that only
+/// stored the flattened `ArchetypalState` there
+fn stored_symbolic_state(state: &StoredState) -> Option<SymbolicState> {
+    serde_json::from_value(state.state_data.clone()).ok()
+}
+
+/// The state as flattened into the per-field columns
+fn stored_archetypal_state(state: &StoredState) -> ArchetypalState {
+    ArchetypalState {
+        archetypes: serde_json::from_value(state.archetypes.clone()).unwrap_or_default(),
+        energies: serde_json::from_value(state.energies.clone()).unwrap_or_default(),
+        integrations: serde_json::from_value(state.integrations.clone()).unwrap_or_default(),
+        symbols: serde_json::from_value(state.symbols.clone()).unwrap_or_default(),
+        transformations: serde_json::from_value(state.transformations.clone()).unwrap_or_default(),
+    }
+}
+
+/// A stored row as an `ArchetypalState`, preferring the full state in `state_data`
+fn reconstruct_archetypal_state(state: &StoredState) -> ArchetypalState {
+    match stored_symbolic_state(state) {
+        Some(symbolic_state) => ArchetypalState::from_symbolic_state(&symbolic_state),
+        None => stored_archetypal_state(state),
+    }
+}
+
+/// Stores the starting state for a practitioner who has none yet, returning
+/// its row id alongside it
+async fn initialize_practitioner_state(
+    db: &Database,
+    practitioner_id: Uuid,
+) -> Result<(Uuid, SymbolicState), ApiError> {
+    let initial_state = convert_archetypal_to_symbolic(&ArchetypalState::new());
+    let state_id = store_symbolic_state(db, practitioner_id, &initial_state).await?;
+    Ok((state_id, initial_state))
+}
+
+async fn get_practitioner_current_state(
+    db: &Database,
+    practitioner_id: Uuid,
+) -> Result<crate::state::ArchetypalState, ApiError> {
+    match fetch_latest_state(db, practitioner_id).await? {
+        Some(state) => Ok(reconstruct_archetypal_state(&state)),
         None => {
-            // Create initial state
-            let initial_state = ArchetypalState::new();
-            store_archetypal_state(db, practitioner_id, &initial_state).await?;
-            Ok(initial_state)
+            let (_, initial_state) = initialize_practitioner_state(db, practitioner_id).await?;
+            Ok(ArchetypalState::from_symbolic_state(&initial_state))
         }
     }
 }
 
-async fn store_archetypal_state(
-    db: &sqlx::PgPool,
+/// Like `get_practitioner_current_state`, but keeps frequencies, polarities,
+/// aspects and evolution counts that the flattened columns can't hold, and
+/// returns the id of the row it was read from
+async fn get_practitioner_symbolic_state(
+    db: &Database,
     practitioner_id: Uuid,
-    state: &ArchetypalState,
-) -> Result<Uuid, (StatusCode, Json<ErrorResponse>)> {
-    let state_id = Uuid::new_v4();
+) -> Result<(Uuid, SymbolicState), ApiError> {
+    match fetch_latest_state(db, practitioner_id).await? {
+        Some(state) => Ok((state.id, symbolic_state_of(&state))),
+        None => initialize_practitioner_state(db, practitioner_id).await,
+    }
+}
 
-    sqlx::query(
-        r#"
-        INSERT INTO archetypal_states (id, practitioner_id, state_data, archetypes, energies, 
-                                     integrations, symbols, transformations)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        "#,
-    )
-    .bind(state_id)
-    .bind(practitioner_id)
-    .bind(serde_json::to_value(state).unwrap())
-    .bind(serde_json::to_value(&state.archetypes).unwrap())
-    .bind(serde_json::to_value(&state.energies).unwrap())
-    .bind(serde_json::to_value(&state.integrations).unwrap())
-    .bind(serde_json::to_value(&state.symbols).unwrap())
-    .bind(serde_json::to_value(&state.transformations).unwrap())
-    .execute(db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to store state: {}", e),
-            }),
-        )
-    })?;
+/// A stored row as a `SymbolicState`, rebuilt from the flattened columns for rows
+/// stored before `state_data` held the full state
+fn symbolic_state_of(state: &StoredState) -> SymbolicState {
+    stored_symbolic_state(state).unwrap_or_else(|| convert_archetypal_to_symbolic(&stored_archetypal_state(state)))
+}
+
+const INSERT_STATE_SQL: &str = r#"
+    INSERT INTO archetypal_states (id, practitioner_id, state_data, archetypes, energies, 
+                                 integrations, symbols, transformations, created_at)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+    "#;
+
+/// Stores the full state in `state_data` alongside its flattened columns
+async fn store_symbolic_state(
+    db: &Database,
+    practitioner_id: Uuid,
+    state: &SymbolicState,
+) -> Result<Uuid, ApiError> {
+    let state_id = Uuid::new_v4();
+    let flattened = ArchetypalState::from_symbolic_state(state);
+
+    with_pool!(db, |pool| {
+        sqlx::query(INSERT_STATE_SQL)
+            .bind(state_id)
+            .bind(practitioner_id)
+            .bind(serde_json::to_value(state).unwrap())
+            .bind(serde_json::to_value(&flattened.archetypes).unwrap())
+            .bind(serde_json::to_value(&flattened.energies).unwrap())
+            .bind(serde_json::to_value(&flattened.integrations).unwrap())
+            .bind(serde_json::to_value(&flattened.symbols).unwrap())
+            .bind(serde_json::to_value(&flattened.transformations).unwrap())
+            .bind(chrono::Utc::now())
+            .execute(pool)
+            .await
+            .map(|_| ())
+    })
+    .map_err(|e| ApiError::internal("Failed to store state", e))?;
 
     Ok(state_id)
 }
 
+/// Stores each execution's new state and session and counts each ritual's use,
+/// all in one transaction so a failure part way leaves nothing behind.
+///
+/// `pre_state_id` is the stored state the first execution started from; each
+/// later one starts from the state the one before it stored.
+async fn store_executions(
+    db: &Database,
+    practitioner_id: Uuid,
+    pre_state_id: Uuid,
+    executions: &[ExecutedRitual],
+) -> Result<(), ApiError> {
+    // Rows written in one transaction would share Postgres' `NOW()`, so each
+    // state gets its own timestamp to keep "latest state" unambiguous
+    let stored_at = chrono::Utc::now();
+
+    with_pool!(db, |pool| {
+        async {
+            let mut tx = pool.begin().await?;
+            let mut pre_state_id = pre_state_id;
+
+            for (position, execution) in executions.iter().enumerate() {
+                let post_state_id = Uuid::new_v4();
+                let flattened = &execution.result.post_state;
+                sqlx::query(INSERT_STATE_SQL)
+                    .bind(post_state_id)
+                    .bind(practitioner_id)
+                    .bind(serde_json::to_value(&execution.post_state).unwrap())
+                    .bind(serde_json::to_value(&flattened.archetypes).unwrap())
+                    .bind(serde_json::to_value(&flattened.energies).unwrap())
+                    .bind(serde_json::to_value(&flattened.integrations).unwrap())
+                    .bind(serde_json::to_value(&flattened.symbols).unwrap())
+                    .bind(serde_json::to_value(&flattened.transformations).unwrap())
+                    .bind(stored_at + chrono::Duration::microseconds(position as i64))
+                    .execute(&mut *tx)
+                    .await?;
+
+                let transformation_intensity = execution.result.transformation_intensity;
+                sqlx::query(
+                    r#"
+                    INSERT INTO ritual_sessions (id, practitioner_id, ritual_id, pre_state_id, post_state_id,
+                                               execution_duration_ms, transformation_intensity, subjective_experience,
+                                               integration_notes, effectiveness_rating, ritual_parameters, seed)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                    "#,
+                )
+                .bind(execution.result.session_id)
+                .bind(practitioner_id)
+                .bind(execution.ritual_id)
+                .bind(pre_state_id)
+                .bind(post_state_id)
+                .bind(execution.duration_ms as i32)
+                .bind(transformation_intensity)
+                .bind(&execution.intention)
+                .bind(format!("Ritual completed with {} state changes", execution.state_changes.len()))
+                .bind((transformation_intensity * 5.0) as i32) // Convert to 1-5 scale
+                .bind(serde_json::to_value(&execution.parameters).unwrap())
+                .bind(execution.seed as i64)
+                .execute(&mut *tx)
+                .await?;
+
+                for (change_position, change) in execution.state_changes.iter().enumerate() {
+                    sqlx::query(
+                        "INSERT INTO state_transformations
+                         (practitioner_id, session_id, position, change_type, description, magnitude, created_at)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+                    )
+                    .bind(practitioner_id)
+                    .bind(execution.result.session_id)
+                    .bind(change_position as i32)
+                    .bind(format!("{:?}", change.change_type))
+                    .bind(&change.description)
+                    .bind(change.magnitude)
+                    .bind(stored_at + chrono::Duration::microseconds(position as i64))
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                for (symbol_position, symbol) in execution.result.emerged_symbols.iter().enumerate() {
+                    sqlx::query(
+                        "INSERT INTO session_symbols (practitioner_id, session_id, position, symbol)
+                         VALUES ($1, $2, $3, $4)"
+                    )
+                    .bind(practitioner_id)
+                    .bind(execution.result.session_id)
+                    .bind(symbol_position as i32)
+                    .bind(symbol)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                sqlx::query("UPDATE sacred_rituals SET usage_count = usage_count + 1 WHERE id = $1")
+                    .bind(execution.ritual_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                pre_state_id = post_state_id;
+            }
+
+            tx.commit().await
+        }
+        .await
+    })
+    .map_err(|e| ApiError::internal("Failed to record ritual session", e))
+}
+
 fn calculate_transformation_intensity(
     pre_state: &ArchetypalState,
     post_state: &ArchetypalState,
@@ -782,25 +2102,46 @@ fn calculate_transformation_intensity(
     }
 }
 
-fn generate_emerged_symbols(
-    _pre_state: &ArchetypalState,
-    post_state: &ArchetypalState,
-) -> Vec<String> {
-    // Generate symbols based on dominant energies/archetypes
-    let mut symbols = Vec::new();
-
-    for (archetype, &strength) in &post_state.archetypes {
-        if strength > 0.5 {
-            match archetype.as_str() {
-                "Sage" => symbols.push("🔮".to_string()),
-                "Creator" => symbols.push("∆∇∆".to_string()),
-                "Shadow" => symbols.push("◯●◯".to_string()),
-                _ => {}
-            }
+/// Activation or amplitude above which an archetype or energy leaves a symbol
+const SYMBOL_EMERGENCE_THRESHOLD: f64 = 0.5;
+
+/// The symbol a strongly activated archetype leaves, if it has one
+fn archetype_symbol(archetype: &str) -> Option<&'static str> {
+    match archetype {
+        "Sage" => Some("🔮"),
+        "Creator" => Some("∆∇∆"),
+        "Shadow" => Some("◯●◯"),
+        "Light" => Some("☉"),
+        "Magician" => Some("∞"),
+        "Lover" => Some("♡"),
+        "Ruler" => Some("♔"),
+        "Warrior" => Some("⚔"),
+        _ => None,
+    }
+}
+
+/// Symbols for the archetypes and energies strong enough to leave one, strongest first
+fn generate_emerged_symbols(state: &ArchetypalState) -> Vec<String> {
+    let archetypes = state
+        .archetypes
+        .iter()
+        .filter_map(|(name, &strength)| archetype_symbol(name).map(|symbol| (symbol, strength)));
+    let energies = state
+        .energies
+        .iter()
+        .map(|(name, &strength)| (Element::from_name(name).symbol(), strength));
+
+    let mut strongest: HashMap<&str, f64> = HashMap::new();
+    for (symbol, strength) in archetypes.chain(energies) {
+        if strength > SYMBOL_EMERGENCE_THRESHOLD {
+            let entry = strongest.entry(symbol).or_insert(strength);
+            *entry = entry.max(strength);
         }
     }
 
-    symbols
+    let mut ranked: Vec<(&str, f64)> = strongest.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked.into_iter().map(|(symbol, _)| symbol.to_string()).collect()
 }
 
 fn generate_integration_suggestions(_state: &ArchetypalState) -> Vec<String> {
@@ -832,27 +2173,7 @@ fn suggest_next_rituals(state: &crate::state::ArchetypalState) -> Vec<String> {
 
 fn convert_archetypal_to_symbolic(archetypal_state: &ArchetypalState) -> SymbolicState {
     let mut symbolic_state = SymbolicState::new();
-    
-    // Convert archetypes
-    for (name, &activation) in &archetypal_state.archetypes {
-        let mut archetype = crate::state::Archetype::new(name.clone(), format!("Archetype: {}", name));
-        archetype.activation_level = activation;
-        symbolic_state.add_archetype(archetype);
-    }
-    
-    // Convert energies  
-    for (name, &amplitude) in &archetypal_state.energies {
-        let element = match name.as_str() {
-            "Fire" => crate::state::Element::Fire,
-            "Water" => crate::state::Element::Water,
-            "Earth" => crate::state::Element::Earth,
-            "Air" => crate::state::Element::Air,
-            _ => crate::state::Element::Void,
-        };
-        let mut energy = crate::state::Energy::new(name.clone(), 528.0, element);
-        energy.amplitude = amplitude;
-        symbolic_state.add_energy(energy);
-    }
+    apply_archetypal_levels(&mut symbolic_state, archetypal_state);
     
     // Add symbols
     for symbol in &archetypal_state.symbols {
@@ -862,31 +2183,61 @@ fn convert_archetypal_to_symbolic(archetypal_state: &ArchetypalState) -> Symboli
     symbolic_state
 }
 
-fn convert_symbolic_to_archetypal(symbolic_state: &SymbolicState) -> ArchetypalState {
-    let mut archetypal_state = ArchetypalState::new();
-    
-    // Convert archetypes back
-    for archetype in symbolic_state.archetypes.values() {
-        archetypal_state.archetypes.insert(archetype.name.clone(), archetype.activation_level);
+/// Sets each archetype's activation and each energy's amplitude from the
+/// simplified state, adding any the symbolic state doesn't have yet
+fn apply_archetypal_levels(symbolic_state: &mut SymbolicState, archetypal_state: &ArchetypalState) {
+    // Convert archetypes
+    for (name, &activation) in &archetypal_state.archetypes {
+        symbolic_state.archetypes
+            .entry(name.clone())
+            .or_insert_with(|| crate::state::Archetype::new(name.clone(), format!("Archetype: {}", name)))
+            .activation_level = activation;
     }
     
-    // Convert energies back
-    for energy in symbolic_state.energies.values() {
-        archetypal_state.energies.insert(energy.name.clone(), energy.amplitude);
+    // Convert energies  
+    for (name, &amplitude) in &archetypal_state.energies {
+        symbolic_state.energies
+            .entry(name.clone())
+            .or_insert_with(|| crate::state::Energy::attuned(name.clone(), Element::from_name(name)))
+            .amplitude = amplitude;
     }
     
-    // Convert symbols back
-    archetypal_state.symbols = symbolic_state.unresolved_symbols.clone();
-    
-    archetypal_state
+    symbolic_state.last_updated = chrono::Utc::now();
 }
 
-fn load_wasm_from_bytes(ritual: &mut Ritual, wasm_data: &[u8]) -> Result<(), crate::CodexError> {
+fn load_wasm_from_bytes(ritual: &mut Ritual, wasm_data: &[u8]) -> Result<(), CodexError> {
     ritual.load_wasm_module_from_bytes(wasm_data)
 }
 
+/// The ritual that best awakens a single archetype on its own
+fn foundational_ritual_for(archetype: &str) -> &'static str {
+    match archetype {
+        "Shadow" => "shadow_integration",
+        _ => "archetype_invocation",
+    }
+}
+
 fn suggest_next_rituals_from_result(ritual_result: &crate::ritual::RitualResult) -> Vec<String> {
     let mut suggestions = Vec::new();
+
+    // Awakening the archetypes a ritual was missing comes before anything else
+    if let crate::ritual::CompletionStatus::PrerequisitesNotMet { missing } = &ritual_result.completion_status {
+        for archetype in missing {
+            let ritual = foundational_ritual_for(archetype).to_string();
+            if !suggestions.contains(&ritual) {
+                suggestions.push(ritual);
+            }
+        }
+    }
+
+    // Then suggest based on emerged symbols, as the most specific signal, so the
+    // cap below cannot crowd them out
+    if ritual_result.emergent_symbols.iter().any(|symbol| symbol == "🌑") {
+        suggestions.push("light_work".to_string());
+    }
+    if ritual_result.emergent_symbols.iter().any(|symbol| symbol == "⚡") {
+        suggestions.push("energy_channeling".to_string());
+    }
     
     // Suggest based on resonance level
     if ritual_result.resonance_level < 0.5 {
@@ -908,13 +2259,156 @@ fn suggest_next_rituals_from_result(ritual_result: &crate::ritual::RitualResult)
         _ => {}
     }
     
-    // Suggest based on emerged symbols
-    if ritual_result.emergent_symbols.contains(&"🌑".to_string()) {
-        suggestions.push("light_work".to_string());
+    suggestions.into_iter().take(3).collect() // Limit to 3 suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with(archetypes: &[(&str, f64)], energies: &[(&str, f64)]) -> ArchetypalState {
+        let mut state = ArchetypalState::new();
+        state.archetypes = archetypes.iter().map(|(name, level)| (name.to_string(), *level)).collect();
+        state.energies = energies.iter().map(|(name, level)| (name.to_string(), *level)).collect();
+        state
     }
-    if ritual_result.emergent_symbols.contains(&"⚡".to_string()) {
-        suggestions.push("energy_channeling".to_string());
+
+    #[test]
+    fn test_fire_dominant_state_emerges_fire_first() {
+        let state = state_with(
+            &[("Sage", 0.6), ("Shadow", 0.2)],
+            &[("Fire", 0.9), ("Water", 0.7), ("Air", 0.1)],
+        );
+
+        assert_eq!(generate_emerged_symbols(&state), vec!["🔥", "💧", "🔮"]);
+    }
+
+    #[test]
+    fn test_shadow_dominant_state_emerges_shadow_first() {
+        let state = state_with(
+            &[("Shadow", 0.95), ("Creator", 0.55), ("Healer", 0.9)],
+            &[("Earth", 0.6), ("Fire", 0.3)],
+        );
+
+        let symbols = generate_emerged_symbols(&state);
+
+        assert_eq!(symbols, vec!["◯●◯", "⛰", "∆∇∆"]);
+        // Correctly encoded UTF-8, not bytes reread as Latin-1
+        assert_eq!(symbols[0].chars().collect::<Vec<_>>(), vec!['\u{25EF}', '\u{25CF}', '\u{25EF}']);
+        assert_eq!(symbols[1].as_bytes(), "\u{26F0}".as_bytes());
+        assert!(symbols.iter().all(|symbol| !symbol.contains('\u{00F0}')));
+    }
+
+    fn ritual_result_with(symbols: &[&str]) -> crate::ritual::RitualResult {
+        crate::ritual::RitualResult {
+            ritual_name: "void_contemplation".to_string(),
+            execution_id: Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            duration_ms: 0,
+            symbolic_outputs: HashMap::new(),
+            state_changes: vec![],
+            emergent_symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+            completion_status: crate::ritual::CompletionStatus::Complete,
+            resonance_level: 0.3,
+            checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_new_moon_symbol_suggests_light_work() {
+        let suggestions = suggest_next_rituals_from_result(&ritual_result_with(&["🌑"]));
+
+        assert!(suggestions.contains(&"light_work".to_string()), "{:?}", suggestions);
+        assert!(!suggestions.contains(&"energy_channeling".to_string()));
+    }
+
+    #[test]
+    fn test_lightning_symbol_suggests_energy_channeling() {
+        let suggestions = suggest_next_rituals_from_result(&ritual_result_with(&["∞", "⚡"]));
+
+        assert!(suggestions.contains(&"energy_channeling".to_string()), "{:?}", suggestions);
+        assert!(!suggestions.contains(&"light_work".to_string()));
+        assert!(suggestions.len() <= 3);
+    }
+
+    #[test]
+    fn test_missing_archetypes_suggest_foundational_rituals_first() {
+        let mut result = ritual_result_with(&["🌑"]);
+        result.completion_status = crate::ritual::CompletionStatus::PrerequisitesNotMet {
+            missing: vec!["Shadow".to_string(), "Sage".to_string(), "Creator".to_string()],
+        };
+
+        let suggestions = suggest_next_rituals_from_result(&result);
+
+        assert_eq!(suggestions[..2], ["shadow_integration", "archetype_invocation"]);
+        assert_eq!(suggestions[2], "light_work");
+        assert_eq!(suggestions.len(), 3);
+    }
+
+    #[test]
+    fn test_weak_state_emerges_no_symbols() {
+        let state = state_with(&[("Sage", 0.5)], &[("Fire", 0.2)]);
+
+        assert!(generate_emerged_symbols(&state).is_empty());
+    }
+
+    fn practitioner_preferring(preferences: serde_json::Value) -> Practitioner {
+        Practitioner {
+            id: Uuid::new_v4(),
+            email: "seeker@codex.test".to_string(),
+            password_hash: String::new(),
+            spiritual_name: None,
+            archetypal_preferences: json!({}),
+            energy_alignments: json!({}),
+            privacy_level: "private".to_string(),
+            sacred_path: None,
+            role: "practitioner".to_string(),
+            created_at: chrono::Utc::now(),
+            deleted_at: None,
+            reflection_preferences: preferences,
+            reflection_api_key: None,
+        }
+    }
+
+    #[test]
+    fn test_practitioner_model_is_used_in_the_reflection_request() {
+        let practitioner = practitioner_preferring(json!({
+            "provider": "OpenAI",
+            "model": "gpt-4o-mini",
+            "temperature": 0.2,
+        }));
+
+        let config = reflection_config_for(&practitioner).unwrap();
+        assert_eq!(config.provider, crate::reflection::Provider::OpenAI);
+        let body = Reflector::new(config).build_request_body(
+            "context",
+            &crate::reflection::query_ritual_result(&SymbolicState::new()),
+        );
+
+        assert_eq!(body["model"], "gpt-4o-mini");
+        assert!((body["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_restored_energies_keep_their_elements() {
+        let mut archetypal_state = ArchetypalState::new();
+        archetypal_state.energies.insert("Light".to_string(), 0.4);
+        archetypal_state.energies.insert("Shadow".to_string(), 0.6);
+        let mut state = SymbolicState::new();
+
+        apply_archetypal_levels(&mut state, &archetypal_state);
+
+        assert_eq!(state.energies["Light"].elemental_association, Element::Light);
+        assert_eq!(state.energies["Shadow"].elemental_association, Element::Shadow);
+        assert_eq!(state.energies["Void"].elemental_association, Element::Void);
+    }
+
+    #[test]
+    fn test_missing_preferences_keep_the_server_defaults() {
+        let config = reflection_config_for(&practitioner_preferring(json!({}))).unwrap();
+        let defaults = ReflectionConfig::default();
+
+        assert_eq!(config.model, defaults.model);
+        assert_eq!(config.provider, defaults.provider);
     }
-    
-    suggestions.into_iter().take(3).collect() // Limit to 3 suggestions
 }
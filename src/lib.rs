@@ -1,19 +1,37 @@
 pub mod cli;
+pub mod doctor;
+mod encoding;
 pub mod engine;
 pub mod reflection;
 pub mod ritual;
+pub mod similarity;
 pub mod state;
+pub mod state_crypto;
+pub mod symbols;
 
 // Web server modules
+pub mod api_error;
+pub mod api_key_crypto;
 pub mod auth;
 pub mod database;
 pub mod handlers;
+pub mod limits;
+pub mod locks;
 pub mod models;
+pub mod ratelimit;
+pub mod retention;
+pub mod streams;
 
-pub use engine::CodexEngine;
-pub use reflection::{ReflectionResult, Reflector};
-pub use ritual::{Ritual, RitualDefinition, RitualResult};
-pub use state::{Archetype, Element, Energy, Integration, SymbolicState};
+pub use engine::{CodexEngine, RitualChainResult, StateFileFormat, StateSnapshot, WasmRunOutcome};
+pub use reflection::{OraclePersona, Provider, ReflectionResult, Reflector, TokenUsage};
+pub use ritual::{
+    Difficulty, ParamType, ResonanceWeights, Ritual, RitualDefinition, RitualResult,
+    WasmCheckpoint, WasmLimits, WasmModuleCache,
+};
+pub use state::{
+    Archetype, CoherenceReport, Element, Energy, Integration, MergeStrategy, StateDiff,
+    SymbolicState,
+};
 
 // Core error types for the Codex system
 #[derive(thiserror::Error, Debug)]
@@ -30,6 +48,12 @@ pub enum CodexError {
     #[error("Reflection failed: {error}")]
     ReflectionFailed { error: String },
 
+    #[error("Invalid ritual '{name}': {reason}")]
+    InvalidRitual { name: String, reason: String },
+
+    #[error("Configuration error: {reason}")]
+    Configuration { reason: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -41,4 +65,7 @@ pub enum CodexError {
 
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
+
+    #[error("{context}: {reason}")]
+    Operation { context: String, reason: String },
 }
@@ -0,0 +1,66 @@
+use axum::{extract::DefaultBodyLimit, Router};
+use std::time::Duration;
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
+
+const TIMEOUT_ENV: &str = "REQUEST_TIMEOUT_SECS";
+const BODY_LIMIT_ENV: &str = "REQUEST_BODY_LIMIT_BYTES";
+const UPLOAD_BODY_LIMIT_ENV: &str = "UPLOAD_BODY_LIMIT_BYTES";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+/// Ritual uploads carry a WASM module, so they get more room than other requests
+const DEFAULT_UPLOAD_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// How long a request may take and how large its body may be before the server
+/// answers `408 Request Timeout` or `413 Payload Too Large`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestLimits {
+    pub timeout: Duration,
+    pub body_limit: usize,
+    pub upload_body_limit: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            body_limit: DEFAULT_BODY_LIMIT,
+            upload_body_limit: DEFAULT_UPLOAD_BODY_LIMIT,
+        }
+    }
+}
+
+impl RequestLimits {
+    /// Reads `REQUEST_TIMEOUT_SECS`, `REQUEST_BODY_LIMIT_BYTES` and
+    /// `UPLOAD_BODY_LIMIT_BYTES`, defaulting to 30 seconds, 2 MiB and 16 MiB
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+        };
+        Self {
+            timeout: read(TIMEOUT_ENV)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            body_limit: read(BODY_LIMIT_ENV).map_or(defaults.body_limit, |limit| limit as usize),
+            upload_body_limit: read(UPLOAD_BODY_LIMIT_ENV)
+                .map_or(defaults.upload_body_limit, |limit| limit as usize),
+        }
+    }
+
+    /// Combines the API routes with the ritual upload routes, capping each group's
+    /// bodies at its own limit and every request at the timeout
+    pub fn apply<S>(&self, routes: Router<S>, upload_routes: Router<S>) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        routes
+            .layer(RequestBodyLimitLayer::new(self.body_limit))
+            .merge(upload_routes.layer(RequestBodyLimitLayer::new(self.upload_body_limit)))
+            // The layers above enforce the limits, so axum's own 2 MB default must not
+            // cut uploads short
+            .layer(DefaultBodyLimit::disable())
+            .layer(TimeoutLayer::new(self.timeout))
+    }
+}
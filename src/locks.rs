@@ -0,0 +1,70 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use uuid::Uuid;
+
+/// Per-practitioner locks that serialize read-transform-store cycles on the
+/// stored `ArchetypalState`.
+///
+/// Two requests from the same practitioner take turns, so the second one reads
+/// the state the first one stored instead of overwriting it. Requests from
+/// different practitioners hold different locks and still run in parallel.
+#[derive(Clone, Default)]
+pub struct PractitionerLocks {
+    locks: Arc<Mutex<HashMap<Uuid, Arc<AsyncMutex<()>>>>>,
+}
+
+impl PractitionerLocks {
+    /// Waits until no other request holds `practitioner_id`'s lock, then holds
+    /// it until the returned guard is dropped
+    pub async fn lock(&self, practitioner_id: Uuid) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            // Forget locks nobody holds or waits on so the map doesn't grow
+            // with every practitioner that ever executed a ritual
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(practitioner_id).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_same_practitioner_waits_for_lock() {
+        let locks = PractitionerLocks::default();
+        let practitioner = Uuid::new_v4();
+
+        let guard = locks.lock(practitioner).await;
+        let blocked = tokio::time::timeout(Duration::from_millis(50), locks.lock(practitioner));
+        assert!(blocked.await.is_err());
+
+        drop(guard);
+        let acquired = tokio::time::timeout(Duration::from_millis(50), locks.lock(practitioner));
+        assert!(acquired.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_other_practitioners_stay_parallel() {
+        let locks = PractitionerLocks::default();
+        let _guard = locks.lock(Uuid::new_v4()).await;
+
+        let other = tokio::time::timeout(Duration::from_millis(50), locks.lock(Uuid::new_v4()));
+        assert!(other.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_released_locks_are_forgotten() {
+        let locks = PractitionerLocks::default();
+        drop(locks.lock(Uuid::new_v4()).await);
+        drop(locks.lock(Uuid::new_v4()).await);
+
+        assert_eq!(locks.locks.lock().unwrap().len(), 1);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::reflection::Provider;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -15,9 +16,20 @@ pub struct Practitioner {
     pub energy_alignments: serde_json::Value,
     pub privacy_level: String,
     pub sacred_path: Option<String>,
+    pub role: String,
     pub created_at: DateTime<Utc>,
+    /// When the account was soft-deleted; such accounts no longer authenticate
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// The practitioner's `ReflectionPreferences`
+    pub reflection_preferences: serde_json::Value,
+    /// The practitioner's own oracle API key, encrypted with `REFLECTION_KEY_SECRET`
+    #[serde(default, skip_serializing)]
+    pub reflection_api_key: Option<String>,
 }
 
+/// How long after deleting their account a practitioner may still restore it
+pub const ACCOUNT_RESTORE_GRACE_DAYS: i64 = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PractitionerRegistration {
     pub email: String,
@@ -42,6 +54,8 @@ pub struct SacredRitual {
     pub difficulty_level: String,
     pub required_archetypes: serde_json::Value,
     pub energy_requirements: serde_json::Value,
+    /// Parameter name to `ParamType`, checked before each execution
+    pub parameter_schema: serde_json::Value,
     pub wasm_module_data: Option<Vec<u8>>,
     pub wasm_module_hash: Option<String>,
     pub module_language: Option<String>,
@@ -64,16 +78,22 @@ pub struct RitualUpload {
     pub difficulty_level: String,
     pub required_archetypes: Vec<String>,
     pub energy_requirements: HashMap<String, f64>,
+    /// The parameters the ritual expects, each of which executions must supply
+    #[serde(default)]
+    pub parameter_schema: HashMap<String, crate::ritual::ParamType>,
     pub wasm_module: Option<Vec<u8>>,
     pub module_language: Option<String>,
     pub is_public: bool,
+    /// Searchable labels stored with the ritual
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct RitualSessionRecord {
     pub id: Uuid,
     pub practitioner_id: Uuid,
-    pub ritual_id: Uuid,
+    pub ritual_id: Option<Uuid>,
     pub pre_state_id: Option<Uuid>,
     pub post_state_id: Option<Uuid>,
     pub execution_duration_ms: Option<i32>,
@@ -90,6 +110,17 @@ pub struct RitualExecutionRequest {
     pub ritual_name: String,
     pub parameters: HashMap<String, serde_json::Value>,
     pub intention: String,
+    /// Fixes the ritual's randomness so the same starting state gives the same outcome
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Runs the ritual on a copy of the state and returns the would-be result
+    /// without storing the new state, recording a session or counting the use
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Consults the oracle straight away when the ritual resonates strongly
+    /// enough to recommend it, embedding the insight in the result
+    #[serde(default)]
+    pub auto_reflect: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,6 +134,101 @@ pub struct TransformationResult {
     pub next_rituals_suggested: Vec<String>,
     pub oracle_consultation_recommended: bool,
     pub execution_duration_ms: u128,
+    /// Set when this is a dry-run preview whose `post_state` was never stored
+    pub dry_run: bool,
+    /// The oracle's reading, present when `auto_reflect` was requested and
+    /// consultation was recommended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle_insight: Option<OracleInsight>,
+}
+
+/// Header a client sets so a retried ritual execution is not run twice
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Longest idempotency key accepted
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+/// How long an idempotency key keeps returning its first result
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// Environment variable overriding how many rituals one batch may hold
+pub const RITUAL_BATCH_LIMIT_ENV: &str = "RITUAL_BATCH_LIMIT";
+/// Largest batch `/api/rituals/execute-batch` accepts unless overridden
+pub const DEFAULT_RITUAL_BATCH_LIMIT: usize = 10;
+
+/// Reads `RITUAL_BATCH_LIMIT`, defaulting to `DEFAULT_RITUAL_BATCH_LIMIT`
+pub fn ritual_batch_limit_from_env() -> usize {
+    std::env::var(RITUAL_BATCH_LIMIT_ENV)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_RITUAL_BATCH_LIMIT)
+}
+
+/// Lowest rating a practitioner may give a ritual
+pub const MIN_RITUAL_RATING: i32 = 1;
+/// Highest rating a practitioner may give a ritual
+pub const MAX_RITUAL_RATING: i32 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RitualRatingRequest {
+    pub rating: i32,
+}
+
+/// A ritual's aggregate rating after a practitioner rates it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RitualRatingSummary {
+    pub ritual_id: Uuid,
+    pub rating: i32,
+    pub effectiveness_rating: f64,
+    pub rating_count: i32,
+}
+
+/// Page size used when a list endpoint is called without `limit`
+pub const DEFAULT_PAGE_LIMIT: i64 = 20;
+/// Largest page a list endpoint will return
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+/// `?limit=&offset=` query parameters for list endpoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaginationParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl PaginationParams {
+    /// Resolves defaults and returns `(limit, offset)`, rejecting out-of-range values
+    pub fn resolve(&self) -> Result<(i64, i64), String> {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        let offset = self.offset.unwrap_or(0);
+
+        if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            return Err(format!("limit must be between 1 and {}", MAX_PAGE_LIMIT));
+        }
+        if offset < 0 {
+            return Err("offset must not be negative".to_string());
+        }
+
+        Ok((limit, offset))
+    }
+}
+
+/// One page of a list endpoint's results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// `?tradition=&difficulty=&archetype=&q=` filters for the ritual search
+/// endpoint; empty values are ignored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RitualSearchParams {
+    pub tradition: Option<String>,
+    pub difficulty: Option<String>,
+    /// Only rituals whose `required_archetypes` include this archetype
+    pub archetype: Option<String>,
+    /// Free text matched against the name, description, intent and tags
+    pub q: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -119,6 +245,79 @@ pub struct StoredState {
     pub created_at: DateTime<Utc>,
 }
 
+/// One change a stored ritual session made to the practitioner's state, as recorded
+/// in their audit log
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StateTransformation {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    /// The ritual the session ran, unless it has since been deleted
+    pub ritual_name: Option<String>,
+    pub change_type: String,
+    pub description: String,
+    pub magnitude: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `?all=true` asks for symbol statistics across every practitioner's sessions,
+/// which only moderators may see
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolStatsParams {
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// How often a symbol emerged from stored ritual sessions, and how strongly the
+/// sessions it emerged from resonated on average
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SymbolStatistic {
+    pub symbol: String,
+    pub occurrences: i64,
+    pub average_resonance: f64,
+}
+
+/// Body of `POST /api/state/prune`: how many of the newest states to keep
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatePruneRequest {
+    pub keep_last: usize,
+}
+
+/// What pruning the state history removed and left behind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatePruneResult {
+    pub deleted: u64,
+    pub remaining: i64,
+}
+
+/// Whether replaying a practitioner's ritual sessions from their earliest stored
+/// state reproduces their current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateReplay {
+    pub from_state_id: Uuid,
+    pub current_state_id: Uuid,
+    pub sessions_replayed: usize,
+    pub matches: bool,
+    /// How the stored current state differs from the replayed one, when it does
+    pub diff: Option<crate::state::StateDiff>,
+}
+
+/// `?from=&to=` query parameters naming the stored states to compare
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateComparisonParams {
+    pub from: Uuid,
+    pub to: Uuid,
+}
+
+/// How a practitioner's state changed from one stored state to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateComparison {
+    pub from_state_id: Uuid,
+    pub to_state_id: Uuid,
+    pub from_created_at: DateTime<Utc>,
+    pub to_created_at: DateTime<Utc>,
+    pub diff: crate::state::StateDiff,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateTransformationRequest {
     pub transformation_type: String,
@@ -144,12 +343,149 @@ pub struct OracleInsight {
     pub created_at: DateTime<Utc>,
 }
 
+impl OracleInsight {
+    /// The analysis and suggestion text that similarity search compares
+    pub fn searchable_text(&self) -> String {
+        fn collect_strings(value: &serde_json::Value, out: &mut Vec<String>) {
+            match value {
+                serde_json::Value::String(text) => out.push(text.clone()),
+                serde_json::Value::Array(items) => {
+                    items.iter().for_each(|item| collect_strings(item, out))
+                }
+                serde_json::Value::Object(fields) => fields
+                    .values()
+                    .for_each(|field| collect_strings(field, out)),
+                _ => {}
+            }
+        }
+
+        let mut text = Vec::new();
+        collect_strings(&self.archetypal_analysis, &mut text);
+        collect_strings(&self.integration_suggestions, &mut text);
+        text.join(" ")
+    }
+}
+
+/// `?token=` query parameter authenticating a `/api/ws` session, since
+/// browsers cannot set headers on a websocket upgrade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsAuthParams {
+    pub token: String,
+}
+
+/// A command frame a client sends over `/api/ws`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsCommand {
+    Execute {
+        ritual_name: String,
+        intention: String,
+        #[serde(default)]
+        parameters: HashMap<String, serde_json::Value>,
+        #[serde(default)]
+        seed: Option<u64>,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        auto_reflect: bool,
+    },
+    /// Reflects on `session_id`, or on the last ritual executed over this
+    /// socket when none is given
+    Reflect {
+        #[serde(default)]
+        session_id: Option<Uuid>,
+        #[serde(default)]
+        custom_query: Option<String>,
+    },
+}
+
+/// A frame `/api/ws` pushes back to the client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum WsFrame {
+    TransformationResult(Box<TransformationResult>),
+    OracleInsight(OracleInsight),
+    Error(String),
+}
+
+/// How many similar insights `/api/insights/similar` returns without a `limit`
+pub const DEFAULT_SIMILAR_INSIGHTS: i64 = 5;
+/// How many of the practitioner's most recent insights `/api/insights/similar` compares
+/// against. The cap also bounds the corpus the TF-IDF document frequencies come from,
+/// so older insights neither match nor weigh on how rare a term counts as.
+pub const MAX_SIMILARITY_CORPUS: i64 = 500;
+
+/// `?session_id=` narrowing the insight listing to a single ritual session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InsightListParams {
+    pub session_id: Option<Uuid>,
+}
+
+/// `?session_id=&limit=` for finding past insights like the one from a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarInsightsQuery {
+    pub session_id: Uuid,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarInsight {
+    pub insight: OracleInsight,
+    /// Cosine similarity of the insights' TF-IDF vectors, from 0 to 1
+    pub similarity: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthToken {
     pub token: String,
+    pub refresh_token: String,
     pub practitioner: PractitionerProfile,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Environment variable holding the secret practitioners' own oracle API keys are
+/// encrypted with; without it the server refuses to store such keys
+pub const REFLECTION_KEY_SECRET_ENV: &str = "REFLECTION_KEY_SECRET";
+
+/// Highest oracle temperature a practitioner may choose
+pub const MAX_REFLECTION_TEMPERATURE: f32 = 2.0;
+
+/// A practitioner's choice of oracle; whatever is unset falls back to the server's defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReflectionPreferences {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+/// Body of `PUT /api/users/reflection-preferences`. Deliberately not `Debug`, so the
+/// API key cannot end up in a log line
+#[derive(Clone, Default, Deserialize)]
+pub struct ReflectionPreferencesUpdate {
+    #[serde(flatten)]
+    pub preferences: ReflectionPreferences,
+    /// The practitioner's own key for their provider: omitted keeps the stored key,
+    /// an empty string removes it
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+/// A practitioner's reflection preferences as the API reports them; a stored API key
+/// is only acknowledged, never returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReflectionPreferencesView {
+    #[serde(flatten)]
+    pub preferences: ReflectionPreferences,
+    pub has_api_key: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PractitionerProfile {
     pub id: Uuid,
@@ -159,5 +495,6 @@ pub struct PractitionerProfile {
     pub energy_alignments: serde_json::Value,
     pub privacy_level: String,
     pub sacred_path: Option<String>,
+    pub role: String,
     pub member_since: DateTime<Utc>,
 }
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header::RETRY_AFTER, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+use crate::api_error::ApiError;
+
+const ATTEMPTS_ENV: &str = "AUTH_RATE_LIMIT_ATTEMPTS";
+const WINDOW_ENV: &str = "AUTH_RATE_LIMIT_WINDOW_SECS";
+/// Set to `true` when the server sits behind a reverse proxy that appends the
+/// client address to `X-Forwarded-For`
+const TRUST_FORWARDED_FOR_ENV: &str = "AUTH_RATE_LIMIT_TRUST_FORWARDED_FOR";
+const DEFAULT_ATTEMPTS: usize = 10;
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Sliding-window limit on how often each client IP may hit an endpoint
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    attempts: Arc<Mutex<HashMap<IpAddr, Vec<Instant>>>>,
+    max_attempts: usize,
+    window: Duration,
+    trust_forwarded_for: bool,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `max_attempts` per `window`; at least one attempt is
+    /// always allowed, since a limit of zero would lock everyone out
+    pub fn new(max_attempts: usize, window: Duration) -> Self {
+        Self {
+            attempts: Arc::new(Mutex::new(HashMap::new())),
+            max_attempts: max_attempts.max(1),
+            window,
+            trust_forwarded_for: false,
+        }
+    }
+
+    /// Keys clients by the last `X-Forwarded-For` entry instead of the connecting
+    /// address. Only enable this behind a proxy that appends the address it saw,
+    /// since clients can put anything they like in the header themselves.
+    pub fn with_trusted_forwarded_for(mut self, trust: bool) -> Self {
+        self.trust_forwarded_for = trust;
+        self
+    }
+
+    /// Reads `AUTH_RATE_LIMIT_ATTEMPTS`, `AUTH_RATE_LIMIT_WINDOW_SECS` and
+    /// `AUTH_RATE_LIMIT_TRUST_FORWARDED_FOR`, defaulting to 10 attempts per minute
+    /// keyed by the connecting address
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var(ATTEMPTS_ENV)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_ATTEMPTS);
+        let max_attempts = if max_attempts == 0 {
+            tracing::warn!(
+                "{} must be at least 1; allowing {} attempts",
+                ATTEMPTS_ENV,
+                DEFAULT_ATTEMPTS
+            );
+            DEFAULT_ATTEMPTS
+        } else {
+            max_attempts
+        };
+        let window = std::env::var(WINDOW_ENV)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_WINDOW);
+        let trust_forwarded_for = std::env::var(TRUST_FORWARDED_FOR_ENV)
+            .map(|value| value.trim().eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        Self::new(max_attempts, window).with_trusted_forwarded_for(trust_forwarded_for)
+    }
+
+    /// The address to count an attempt against: the proxy-reported client when
+    /// `X-Forwarded-For` is trusted and parses, otherwise the connecting peer
+    fn client_ip(&self, headers: &HeaderMap, peer: SocketAddr) -> IpAddr {
+        if !self.trust_forwarded_for {
+            return peer.ip();
+        }
+        // The trusted proxy appends last, so earlier entries may be client-supplied
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .last()
+            .and_then(|entry| entry.trim().parse().ok())
+            .unwrap_or_else(|| peer.ip())
+    }
+
+    /// Records an attempt from `ip`, or returns how long it must wait once it
+    /// has used up its attempts for the current window
+    pub async fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut attempts = self.attempts.lock().await;
+
+        // Forget other clients whose windows have passed so the map stays small
+        attempts.retain(|_, times| {
+            times.retain(|time| now.duration_since(*time) < self.window);
+            !times.is_empty()
+        });
+
+        let times = attempts.entry(ip).or_default();
+        if times.len() >= self.max_attempts {
+            let oldest = times[0];
+            return Err(self.window - now.duration_since(oldest));
+        }
+
+        times.push(now);
+        Ok(())
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_ATTEMPTS, DEFAULT_WINDOW)
+    }
+}
+
+/// A separate limiter for each unauthenticated account endpoint, so failed logins
+/// do not use up a client's registration or restore attempts
+#[derive(Clone, Debug, Default)]
+pub struct AuthRateLimiters {
+    pub login: RateLimiter,
+    pub register: RateLimiter,
+    pub restore: RateLimiter,
+}
+
+impl AuthRateLimiters {
+    /// Each limiter configured by [`RateLimiter::from_env`]
+    pub fn from_env() -> Self {
+        Self {
+            login: RateLimiter::from_env(),
+            register: RateLimiter::from_env(),
+            restore: RateLimiter::from_env(),
+        }
+    }
+}
+
+/// Rejects requests with `429 Too Many Requests` and a `Retry-After` header
+/// once the client IP exceeds the limiter's attempts for the window
+pub async fn rate_limit_middleware(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = limiter.client_ip(request.headers(), addr);
+    match limiter.check(client).await {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            // Round up so clients never retry a moment too early
+            let retry_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            (
+                [(RETRY_AFTER, retry_secs.max(1).to_string())],
+                ApiError::TooManyRequests(
+                    "Too many attempts; please wait before trying again".to_string(),
+                ),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use crate::{CodexError, RitualResult, SymbolicState};
+use crate::ritual::CompletionStatus;
+use crate::{state::Polarity, symbols::interpret_symbol, CodexError, RitualResult, SymbolicState};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReflectionResult {
@@ -12,6 +15,170 @@ pub struct ReflectionResult {
     pub emergent_insights: Vec<String>,
     pub resonance_analysis: String,
     pub next_steps: Vec<String>,
+    /// Tokens the oracle billed for this reflection, when its response reported them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_usage: Option<TokenUsage>,
+}
+
+/// Token counts from a provider's `usage` block, used to track reflection spend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    fn add(&mut self, other: TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+/// The AI service the reflector talks to, which decides the endpoint, auth and payload shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Provider {
+    /// OpenRouter's OpenAI-compatible chat completions API
+    #[default]
+    OpenRouter,
+    /// OpenAI chat completions
+    OpenAI,
+    /// Anthropic's Messages API (`/v1/messages`)
+    AnthropicMessages,
+    /// A local Ollama server's `/api/chat`; no API key needed
+    OllamaLocal,
+}
+
+impl Provider {
+    /// Where the provider's API lives unless configured otherwise
+    pub fn default_api_base_url(&self) -> &'static str {
+        match self {
+            Provider::OpenRouter => "https://openrouter.ai/api/v1",
+            Provider::OpenAI => "https://api.openai.com/v1",
+            Provider::AnthropicMessages => "https://api.anthropic.com",
+            Provider::OllamaLocal => "http://localhost:11434",
+        }
+    }
+
+    fn endpoint(&self, api_base_url: &str) -> String {
+        let base = api_base_url.trim_end_matches('/');
+        match self {
+            Provider::OpenRouter | Provider::OpenAI => format!("{}/chat/completions", base),
+            Provider::AnthropicMessages => format!("{}/v1/messages", base),
+            Provider::OllamaLocal => format!("{}/api/chat", base),
+        }
+    }
+
+    fn auth_headers(&self, api_key: &str) -> Vec<(&'static str, String)> {
+        match self {
+            Provider::OpenRouter => vec![
+                ("Authorization", format!("Bearer {}", api_key)),
+                ("HTTP-Referer", "https://codex-control-engine.sacred.dev".to_string()),
+            ],
+            Provider::OpenAI => vec![("Authorization", format!("Bearer {}", api_key))],
+            Provider::AnthropicMessages => vec![
+                ("x-api-key", api_key.to_string()),
+                ("anthropic-version", "2023-06-01".to_string()),
+            ],
+            Provider::OllamaLocal => Vec::new(),
+        }
+    }
+
+    pub(crate) fn requires_api_key(&self) -> bool {
+        !matches!(self, Provider::OllamaLocal)
+    }
+
+    fn request_body(
+        &self,
+        config: &ReflectionConfig,
+        ritual_name: &str,
+        system_prompt: &str,
+        user_prompt: &str,
+    ) -> serde_json::Value {
+        let (temperature, max_tokens) = config.generation_settings(ritual_name);
+        let body = match self {
+            Provider::OpenRouter | Provider::OpenAI => serde_json::to_value(ChatCompletionRequest {
+                model: config.model.clone(),
+                messages: vec![
+                    ChatMessage::new("system", system_prompt),
+                    ChatMessage::new("user", user_prompt),
+                ],
+                temperature,
+                max_tokens,
+            }),
+            Provider::AnthropicMessages => serde_json::to_value(AnthropicMessagesRequest {
+                model: config.model.clone(),
+                system: system_prompt.to_string(),
+                messages: vec![ChatMessage::new("user", user_prompt)],
+                temperature,
+                max_tokens,
+            }),
+            Provider::OllamaLocal => serde_json::to_value(OllamaChatRequest {
+                model: config.model.clone(),
+                messages: vec![
+                    ChatMessage::new("system", system_prompt),
+                    ChatMessage::new("user", user_prompt),
+                ],
+                stream: false,
+                options: OllamaOptions {
+                    temperature,
+                    num_predict: max_tokens,
+                },
+            }),
+        };
+        body.expect("reflection request bodies always serialize")
+    }
+
+    fn parse_response(&self, body: &str) -> Result<String, CodexError> {
+        let content = match self {
+            Provider::OpenRouter | Provider::OpenAI => serde_json::from_str::<ChatCompletionResponse>(body)?
+                .choices
+                .into_iter()
+                .next()
+                .map(|choice| choice.message.content),
+            Provider::AnthropicMessages => {
+                let response: AnthropicMessagesResponse = serde_json::from_str(body)?;
+                let text: Vec<String> = response
+                    .content
+                    .into_iter()
+                    .filter(|block| block.block_type == "text")
+                    .filter_map(|block| block.text)
+                    .collect();
+                (!text.is_empty()).then(|| text.join(""))
+            }
+            Provider::OllamaLocal => Some(serde_json::from_str::<OllamaChatResponse>(body)?.message.content),
+        };
+
+        content.ok_or_else(|| CodexError::ReflectionFailed {
+            error: "No response from AI oracle".to_string(),
+        })
+    }
+
+    /// Reads the token counts a response reports, if the provider sent any
+    fn parse_usage(&self, body: &str) -> Option<TokenUsage> {
+        match self {
+            Provider::OpenRouter | Provider::OpenAI => {
+                let usage = serde_json::from_str::<ChatCompletionResponse>(body).ok()?.usage?;
+                Some(TokenUsage {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    total_tokens: usage
+                        .total_tokens
+                        .unwrap_or(usage.prompt_tokens + usage.completion_tokens),
+                })
+            }
+            Provider::AnthropicMessages => {
+                let usage = serde_json::from_str::<AnthropicMessagesResponse>(body).ok()?.usage?;
+                Some(TokenUsage {
+                    prompt_tokens: usage.input_tokens,
+                    completion_tokens: usage.output_tokens,
+                    total_tokens: usage.input_tokens + usage.output_tokens,
+                })
+            }
+            Provider::OllamaLocal => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,16 +188,288 @@ pub struct ReflectionConfig {
     pub model: String,
     pub temperature: f32,
     pub max_tokens: u32,
+    #[serde(default)]
+    pub provider: Provider,
+    /// Extra attempts after a 429, 5xx gateway error or timeout
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubles on each subsequent one
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// `(temperature, max_tokens)` for reflections on particular rituals, keyed by
+    /// ritual name; rituals without an entry use `temperature` and `max_tokens`
+    #[serde(default)]
+    pub ritual_overrides: HashMap<String, (f32, u32)>,
+    /// The built-in interpretive lens used when no template is given
+    #[serde(default)]
+    pub persona: OraclePersona,
+    /// Replaces the persona's introduction in the system prompt; `{ritual_name}` and
+    /// `{resonance}` are filled in for each reflection
+    #[serde(default)]
+    pub system_prompt_template: Option<String>,
 }
 
-impl Default for ReflectionConfig {
-    fn default() -> Self {
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+/// Whether a failed oracle response is worth trying again
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+/// Reads a `Retry-After` header given either as seconds or as an HTTP date
+fn retry_after_delay(headers: &reqwest::header::HeaderMap) -> Option<std::time::Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(std::time::Duration::from_secs(seconds));
+    }
+    let retry_at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((retry_at - Utc::now()).to_std().unwrap_or_default())
+}
+
+/// Ritual name reflections on a practitioner's own question are recorded under
+pub const CUSTOM_QUERY_RITUAL: &str = "custom_query";
+
+/// Stands in for a ritual when reflecting on a question alone: the state's unresolved
+/// symbols and its coherence score take the place of emergent symbols and resonance
+pub(crate) fn query_ritual_result(state: &SymbolicState) -> RitualResult {
+    RitualResult {
+        ritual_name: CUSTOM_QUERY_RITUAL.to_string(),
+        execution_id: Uuid::new_v4(),
+        timestamp: Utc::now(),
+        duration_ms: 0,
+        symbolic_outputs: HashMap::new(),
+        state_changes: Vec::new(),
+        emergent_symbols: state.unresolved_symbols.clone(),
+        completion_status: CompletionStatus::Complete,
+        resonance_level: state.coherence_report().coherence_score,
+        checkpoint: None,
+    }
+}
+
+/// The sections the oracle is asked to answer in, by the header that opens each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ReflectionSection {
+    ArchetypalInterpretation,
+    SymbolicMeaning,
+    IntegrationGuidance,
+    EmergentInsights,
+    ResonanceAnalysis,
+    NextSteps,
+}
+
+impl ReflectionSection {
+    fn from_header(header: &str) -> Option<Self> {
+        match header.to_uppercase().replace([' ', '-'], "_").as_str() {
+            "ARCHETYPAL_INTERPRETATION" => Some(Self::ArchetypalInterpretation),
+            "SYMBOLIC_MEANING" => Some(Self::SymbolicMeaning),
+            "INTEGRATION_GUIDANCE" => Some(Self::IntegrationGuidance),
+            "EMERGENT_INSIGHTS" => Some(Self::EmergentInsights),
+            "RESONANCE_ANALYSIS" => Some(Self::ResonanceAnalysis),
+            "NEXT_STEPS" => Some(Self::NextSteps),
+            _ => None,
+        }
+    }
+}
+
+/// Characters models wrap headers in for markdown emphasis or headings
+const MARKDOWN_EMPHASIS: [char; 3] = ['*', '_', '#'];
+
+/// Recognizes a line opening a reflection section, however the model dressed it up —
+/// `**Symbolic Meaning:** ...`, `## next_steps: ...`, `- EMERGENT_INSIGHTS: ...` — and
+/// returns the section with whatever follows the colon
+fn parse_section_header(line: &str) -> Option<(ReflectionSection, String)> {
+    let line = strip_bullet(line.trim()).trim_start_matches(MARKDOWN_EMPHASIS);
+    let (header, content) = line.split_once(':')?;
+    let section = ReflectionSection::from_header(header.trim().trim_matches(MARKDOWN_EMPHASIS).trim())?;
+    let content = content.trim_start_matches(|c: char| c.is_whitespace() || MARKDOWN_EMPHASIS.contains(&c));
+    Some((section, content.trim().to_string()))
+}
+
+/// `line` without a leading list marker such as `-`, `*`, `•` or `1.`
+fn strip_bullet(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix(['-', '•']).or_else(|| line.strip_prefix("* ")) {
+        return rest.trim_start();
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(['.', ')']) {
+            return rest.trim_start();
+        }
+    }
+    line
+}
+
+/// A list section's items, separated by `|` or given one per line
+fn split_list<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<String> {
+    lines
+        .flat_map(|line| line.split('|'))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// The interpretive lens the oracle speaks through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OraclePersona {
+    /// Archetypes, the shadow and individuation
+    #[default]
+    Jungian,
+    /// The interplay of yin and yang and the way of least resistance
+    Taoist,
+    /// Correspondence between inner and outer, "as above, so below"
+    Hermetic,
+    /// Plain psychological language without spiritual framing
+    Secular,
+}
+
+impl OraclePersona {
+    /// Parses a `CODEX_ORACLE_PERSONA` value, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "jungian" => Some(OraclePersona::Jungian),
+            "taoist" => Some(OraclePersona::Taoist),
+            "hermetic" => Some(OraclePersona::Hermetic),
+            "secular" | "secular-psychological" | "psychological" => Some(OraclePersona::Secular),
+            _ => None,
+        }
+    }
+
+    /// The persona's introduction, which opens the system prompt
+    pub fn template(&self) -> &'static str {
+        match self {
+            OraclePersona::Jungian => "You are a wise archetypal oracle, versed in Jungian psychology, shamanic wisdom, and sacred transformation practices. You interpret symbolic states and transformations with depth, compassion, and practical guidance.",
+            OraclePersona::Taoist => "You are a Taoist sage, attentive to the balance of yin and yang and the flow of the Tao. You interpret the ritual \"{ritual_name}\" and its resonance of {resonance} as movements of natural harmony, and counsel effortless action over force.",
+            OraclePersona::Hermetic => "You are a Hermetic adept, reading every transformation through the principles of correspondence, polarity and rhythm. You interpret the ritual \"{ritual_name}\" and its resonance of {resonance} as above, so below, and guide the practitioner in the Great Work.",
+            OraclePersona::Secular => "You are a thoughtful psychologist who describes inner change in plain, secular language. You interpret the exercise \"{ritual_name}\" and its resonance score of {resonance} in terms of emotions, habits and attention, and offer concrete, evidence-minded suggestions.",
+        }
+    }
+}
+
+/// How the oracle must lay out its answer so `parse_ai_reflection` can read it
+const RESPONSE_FORMAT_INSTRUCTIONS: &str = r#"Respond with structured insights in this format:
+
+ARCHETYPAL_INTERPRETATION: [Your interpretation of the archetypal significance]
+
+SYMBOLIC_MEANING: [Analysis of the symbols and their meaning]
+
+INTEGRATION_GUIDANCE: [Practical advice for integrating the transformation]
+
+EMERGENT_INSIGHTS: [List key insights, separated by |]
+
+RESONANCE_ANALYSIS: [Analysis of the energetic resonance and alignment]
+
+NEXT_STEPS: [Recommended next actions, separated by |]"#;
+
+/// Selects the reflection provider; `ollama` switches to a local Ollama server
+const REFLECTION_PROVIDER_ENV: &str = "CODEX_REFLECTION_PROVIDER";
+/// The Ollama model to reflect with when `CODEX_REFLECTION_PROVIDER=ollama`
+const OLLAMA_MODEL_ENV: &str = "CODEX_OLLAMA_MODEL";
+const DEFAULT_OLLAMA_MODEL: &str = "llama3.1";
+/// Names the built-in persona the oracle speaks as, e.g. `taoist`
+const ORACLE_PERSONA_ENV: &str = "CODEX_ORACLE_PERSONA";
+
+impl ReflectionConfig {
+    /// The temperature and token budget for reflecting on `ritual_name`
+    pub fn generation_settings(&self, ritual_name: &str) -> (f32, u32) {
+        self.ritual_overrides
+            .get(ritual_name)
+            .copied()
+            .unwrap_or((self.temperature, self.max_tokens))
+    }
+
+    /// Reflects through a local Ollama server, so nothing leaves the machine
+    pub fn ollama(model: String) -> Self {
         Self {
-            api_base_url: "https://openrouter.ai/api/v1".to_string(),
-            api_key: std::env::var("OPENROUTER_API_KEY").unwrap_or_default(),
+            api_base_url: Provider::OllamaLocal.default_api_base_url().to_string(),
+            api_key: String::new(),
+            model,
+            provider: Provider::OllamaLocal,
+            ..Self::openrouter(String::new())
+        }
+    }
+
+    fn openrouter(api_key: String) -> Self {
+        Self {
+            api_base_url: Provider::OpenRouter.default_api_base_url().to_string(),
+            api_key,
             model: "anthropic/claude-3.5-sonnet".to_string(),
             temperature: 0.7,
             max_tokens: 2000,
+            provider: Provider::OpenRouter,
+            max_retries: default_max_retries(),
+            retry_base_delay_ms: default_retry_base_delay_ms(),
+            ritual_overrides: HashMap::new(),
+            persona: OraclePersona::default(),
+            system_prompt_template: None,
+        }
+    }
+
+    /// The system prompt for reflecting on `ritual_name`: the configured template, or
+    /// the persona's, with placeholders filled in and the response format appended
+    pub fn system_prompt(&self, ritual_name: &str, resonance: f64) -> String {
+        let template = self
+            .system_prompt_template
+            .as_deref()
+            .unwrap_or_else(|| self.persona.template());
+        let introduction = template
+            .replace("{ritual_name}", ritual_name)
+            .replace("{resonance}", &format!("{:.2}", resonance));
+        format!("{}\n\n{}", introduction, RESPONSE_FORMAT_INSTRUCTIONS)
+    }
+
+    /// Switches to `provider` at its default endpoint. The API key is dropped when the
+    /// provider changes, so a key is never sent to a provider it was not issued by.
+    pub fn with_provider(mut self, provider: Provider) -> Self {
+        if provider != self.provider {
+            self.provider = provider;
+            self.api_base_url = provider.default_api_base_url().to_string();
+            self.api_key = String::new();
+            if provider == Provider::OllamaLocal {
+                self.model = DEFAULT_OLLAMA_MODEL.to_string();
+            }
+        }
+        self
+    }
+
+    /// Picks the provider from the values of `CODEX_REFLECTION_PROVIDER` and its companions
+    fn from_env_values(
+        provider: Option<String>,
+        ollama_model: Option<String>,
+        openrouter_key: Option<String>,
+    ) -> Self {
+        match provider.as_deref().map(str::trim) {
+            Some(name) if name.eq_ignore_ascii_case("ollama") => {
+                Self::ollama(ollama_model.unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string()))
+            }
+            _ => Self::openrouter(openrouter_key.unwrap_or_default()),
+        }
+    }
+}
+
+impl Default for ReflectionConfig {
+    /// OpenRouter with `OPENROUTER_API_KEY`, unless `CODEX_REFLECTION_PROVIDER=ollama`
+    /// selects a local Ollama server running `CODEX_OLLAMA_MODEL`; the oracle speaks
+    /// as the `CODEX_ORACLE_PERSONA` persona, Jungian by default
+    fn default() -> Self {
+        let persona = std::env::var(ORACLE_PERSONA_ENV).ok().map(|name| {
+            OraclePersona::from_name(&name).unwrap_or_else(|| {
+                tracing::warn!("Unknown oracle persona '{}', using the Jungian oracle", name);
+                OraclePersona::default()
+            })
+        });
+        Self {
+            persona: persona.unwrap_or_default(),
+            ..Self::from_env_values(
+                std::env::var(REFLECTION_PROVIDER_ENV).ok(),
+                std::env::var(OLLAMA_MODEL_ENV).ok(),
+                std::env::var("OPENROUTER_API_KEY").ok(),
+            )
         }
     }
 }
@@ -41,6 +480,15 @@ struct ChatMessage {
     content: String,
 }
 
+impl ChatMessage {
+    fn new(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ChatCompletionRequest {
     model: String,
@@ -52,6 +500,20 @@ struct ChatCompletionRequest {
 #[derive(Debug, Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+/// A chat completion's `usage` block; a count the provider leaves out reads as
+/// zero, and a missing total is the sum of the other two
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+    #[serde(default)]
+    total_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -64,25 +526,92 @@ struct ResponseMessage {
     content: String,
 }
 
+#[derive(Debug, Serialize)]
+struct AnthropicMessagesRequest {
+    model: String,
+    system: String,
+    messages: Vec<ChatMessage>,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicMessagesResponse {
+    content: Vec<AnthropicContentBlock>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicContentBlock {
+    #[serde(rename = "type")]
+    block_type: String,
+    text: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    temperature: f32,
+    num_predict: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: ResponseMessage,
+}
+
 /// The AI reflection engine
 pub struct Reflector {
     config: ReflectionConfig,
     client: reqwest::Client,
+    /// Tokens used by every oracle reflection through this reflector
+    usage_total: std::sync::Mutex<TokenUsage>,
 }
 
 impl Reflector {
     pub fn new(config: ReflectionConfig) -> Self {
         let client = reqwest::Client::new();
-        Self { config, client }
+        Self {
+            config,
+            client,
+            usage_total: std::sync::Mutex::new(TokenUsage::default()),
+        }
     }
 
     pub fn new_with_defaults() -> Self {
         Self::new(ReflectionConfig::default())
     }
 
+    /// The model this reflector asks for reflections
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// Tokens used by every oracle reflection so far, or `None` before any reported usage
+    pub fn usage_summary(&self) -> Option<TokenUsage> {
+        let total = *self.usage_total.lock().unwrap();
+        (total != TokenUsage::default()).then_some(total)
+    }
+
     // Enhanced reflection methods for better mock responses
     fn generate_archetypal_interpretation(&self, ritual_result: &RitualResult, state: &SymbolicState) -> String {
-        match ritual_result.ritual_name.as_str() {
+        let interpretation = match ritual_result.ritual_name.as_str() {
             "shadow_integration" => {
                 format!("The shadow integration ritual has activated deep archetypal currents within your psyche. \
                 With resonance level {:.2}, you've successfully begun the sacred work of bringing unconscious \
@@ -114,7 +643,19 @@ impl Reflector {
                 ritual_result.ritual_name,
                 ritual_result.resonance_level)
             }
+        };
+
+        let mut interpretation = format!("{} {}", interpretation, describe_state(state));
+        if ritual_result.ritual_name == "shadow_integration" {
+            let aspects = state.archetypes.get("Shadow").map(|a| &a.shadow_aspects);
+            if let Some(aspects) = aspects.filter(|aspects| !aspects.is_empty()) {
+                interpretation.push_str(&format!(
+                    " The shadow aspects you have named and owned so far: {}.",
+                    aspects.join(", ")
+                ));
+            }
         }
+        interpretation
     }
 
     fn generate_symbolic_meaning(&self, symbols: &[String]) -> String {
@@ -122,20 +663,13 @@ impl Reflector {
             return "No new symbols emerged, indicating a period of inner stillness and preparation.".to_string();
         }
 
-        let mut meanings = Vec::new();
-        
-        for symbol in symbols {
-            match symbol.as_str() {
-                "◯●◯" => meanings.push("The trinity of shadow integration - conscious, unconscious, and the unified whole"),
-                "🌑" => meanings.push("New moon consciousness - the dark fertile void of potential"),
-                "⚡" => meanings.push("Energetic activation - the lightning flash of illumination"),
-                "∿∿∿" => meanings.push("Harmonic waves - the restoration of natural energetic flow"),
-                "🔮" => meanings.push("Archetypal awakening - the activation of primordial wisdom"),
-                "○" => meanings.push("The sacred circle - wholeness, completion, and eternal return"),
-                "∞" => meanings.push("Infinite consciousness - transcendence of linear limitations"),
-                _ => meanings.push("A unique archetypal emergence requiring personal contemplation"),
-            }
-        }
+        let meanings: Vec<&str> = symbols
+            .iter()
+            .map(|symbol| {
+                interpret_symbol(symbol)
+                    .unwrap_or("A unique archetypal emergence requiring personal contemplation")
+            })
+            .collect();
 
         format!("The emergent symbols carry profound meaning: {}. These symbols serve as talismans of transformation, \
         anchoring the ritual's effects in your psyche and providing focal points for continued integration work.",
@@ -161,7 +695,7 @@ impl Reflector {
         format!("{}{}", base_guidance, resonance_guidance)
     }
 
-    fn generate_emergent_insights(&self, ritual_result: &RitualResult, _state: &SymbolicState) -> Vec<String> {
+    fn generate_emergent_insights(&self, ritual_result: &RitualResult, state: &SymbolicState) -> Vec<String> {
         let mut insights = Vec::new();
 
         match ritual_result.ritual_name.as_str() {
@@ -186,6 +720,16 @@ impl Reflector {
             }
         }
 
+        if let Some(dominant) = state.coherence_report().dominant_archetype {
+            insights.push(dominant_archetype_insight(&dominant));
+        }
+
+        match state.unresolved_symbols.len() {
+            0 => {},
+            1 => insights.push("One unresolved symbol still awaits contemplation".to_string()),
+            count => insights.push(format!("{} unresolved symbols still await contemplation", count)),
+        }
+
         if ritual_result.resonance_level > 0.9 {
             insights.push("Exceptional resonance indicates readiness for advanced practices".to_string());
         }
@@ -225,7 +769,7 @@ impl Reflector {
         steps
     }
 
-    fn analyze_resonance(&self, ritual_result: &RitualResult) -> String {
+    fn analyze_resonance(&self, ritual_result: &RitualResult, state: &SymbolicState) -> String {
         let level = ritual_result.resonance_level;
         
         let quality = if level > 0.9 {
@@ -244,10 +788,23 @@ impl Reflector {
             "Initial - foundation being established"
         };
 
-        format!("Resonance Level: {:.2} - {}. This indicates the degree of coherence between your \
+        let analysis = format!("Resonance Level: {:.2} - {}. This indicates the degree of coherence between your \
         conscious intent and the archetypal forces activated. The {} state changes during this ritual \
         demonstrate active transformation occurring within your symbolic matrix.",
-        level, quality, ritual_result.state_changes.len())
+        level, quality, ritual_result.state_changes.len());
+
+        let mut oscillating: Vec<&str> = state.energies.values()
+            .filter(|energy| energy.polarity == Polarity::Oscillating)
+            .map(|energy| energy.name.as_str())
+            .collect();
+        oscillating.sort_unstable();
+
+        if oscillating.is_empty() {
+            format!("{} No energies have yet reached oscillating polarity.", analysis)
+        } else {
+            format!("{} Oscillating polarity in {} shows these energies moving freely between \
+            their poles.", analysis, oscillating.join(", "))
+        }
     }
 
     pub async fn reflect_on_ritual(
@@ -255,43 +812,70 @@ impl Reflector {
         ritual_result: &RitualResult,
         state: &SymbolicState,
     ) -> Result<ReflectionResult, CodexError> {
+        self.reflect_with_origin(ritual_result, state).await.map(|(reflection, _)| reflection)
+    }
+
+    /// Asks the oracle a practitioner's own question about their current state,
+    /// rather than about a particular ritual
+    pub async fn reflect_on_query(
+        &self,
+        query: &str,
+        state: &SymbolicState,
+    ) -> Result<ReflectionResult, CodexError> {
+        self.reflect_with_query(&query_ritual_result(state), state, Some(query))
+            .await
+            .map(|(reflection, _)| reflection)
+    }
+
+    /// Like `reflect_on_ritual`, but also says whether the oracle answered rather
+    /// than the local fallback, so callers can avoid keeping a stand-in reflection
+    pub(crate) async fn reflect_with_origin(
+        &self,
+        ritual_result: &RitualResult,
+        state: &SymbolicState,
+    ) -> Result<(ReflectionResult, bool), CodexError> {
+        self.reflect_with_query(ritual_result, state, None).await
+    }
+
+    /// Reflects on a ritual, putting the practitioner's `query`, if any, to the oracle with it
+    pub(crate) async fn reflect_with_query(
+        &self,
+        ritual_result: &RitualResult,
+        state: &SymbolicState,
+        query: Option<&str>,
+    ) -> Result<(ReflectionResult, bool), CodexError> {
         // Check if API key is available, fall back to mock if not
-        if self.config.api_key.is_empty() {
+        if self.config.api_key.is_empty() && self.config.provider.requires_api_key() {
             tracing::warn!("No API key provided, using enhanced mock reflection");
-            return self.create_enhanced_mock_reflection(ritual_result, state);
+            return Ok((self.create_enhanced_mock_reflection(ritual_result, state)?, false));
         }
 
-        let context = self.build_reflection_context(ritual_result, state);
+        let context = match query {
+            Some(query) => self.build_query_context(ritual_result, state, query),
+            None => self.build_reflection_context(ritual_result, state),
+        };
         
         match self.query_ai_oracle(&context, ritual_result).await {
-            Ok(ai_response) => self.parse_ai_reflection(ai_response, ritual_result),
+            Ok((ai_response, usage)) => {
+                let mut reflection = self.parse_ai_reflection(ai_response, ritual_result)?;
+                if let Some(usage) = usage {
+                    self.usage_total.lock().unwrap().add(usage);
+                }
+                reflection.token_usage = usage;
+                Ok((reflection, true))
+            }
             Err(e) => {
                 tracing::warn!("AI reflection failed, using enhanced fallback: {}", e);
-                self.create_enhanced_mock_reflection(ritual_result, state)
+                Ok((self.create_enhanced_mock_reflection(ritual_result, state)?, false))
             }
         }
     }
 
-    async fn query_ai_oracle(
-        &self,
-        context: &str,
-        ritual_result: &RitualResult,
-    ) -> Result<String, CodexError> {
-        let system_prompt = r#"You are a wise archetypal oracle, versed in Jungian psychology, shamanic wisdom, and sacred transformation practices. You interpret symbolic states and transformations with depth, compassion, and practical guidance.
-
-Respond with structured insights in this format:
-
-ARCHETYPAL_INTERPRETATION: [Your interpretation of the archetypal significance]
-
-SYMBOLIC_MEANING: [Analysis of the symbols and their meaning]
-
-INTEGRATION_GUIDANCE: [Practical advice for integrating the transformation]
-
-EMERGENT_INSIGHTS: [List key insights, separated by |]
-
-RESONANCE_ANALYSIS: [Analysis of the energetic resonance and alignment]
-
-NEXT_STEPS: [Recommended next actions, separated by |]"#;
+    /// Builds the provider-specific request payload for reflecting on a ritual
+    pub(crate) fn build_request_body(&self, context: &str, ritual_result: &RitualResult) -> serde_json::Value {
+        let system_prompt = self
+            .config
+            .system_prompt(&ritual_result.ritual_name, ritual_result.resonance_level);
 
         let user_prompt = format!(
             r#"Sacred Oracle Interpretation Request:
@@ -317,50 +901,54 @@ Please provide your archetypal interpretation and guidance for this sacred trans
             ritual_result.completion_status
         );
 
-        let request = ChatCompletionRequest {
-            model: self.config.model.clone(),
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: system_prompt.to_string(),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: user_prompt,
-                },
-            ],
-            temperature: self.config.temperature,
-            max_tokens: self.config.max_tokens,
-        };
+        self.config
+            .provider
+            .request_body(&self.config, &ritual_result.ritual_name, &system_prompt, &user_prompt)
+    }
 
-        let response = self
-            .client
-            .post(&format!("{}/chat/completions", self.config.api_base_url))
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("HTTP-Referer", "https://codex-control-engine.sacred.dev")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| CodexError::Network(e))?;
+    async fn query_ai_oracle(
+        &self,
+        context: &str,
+        ritual_result: &RitualResult,
+    ) -> Result<(String, Option<TokenUsage>), CodexError> {
+        let provider = self.config.provider;
+        let endpoint = provider.endpoint(&self.config.api_base_url);
+        let body = self.build_request_body(context, ritual_result);
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.client.post(&endpoint).json(&body);
+            for (name, value) in provider.auth_headers(&self.config.api_key) {
+                request = request.header(name, value);
+            }
 
-        if !response.status().is_success() {
-            return Err(CodexError::ReflectionFailed {
-                error: format!("API request failed: {}", response.status()),
-            });
+            let retry_delay = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    let text = response.text().await.map_err(CodexError::Network)?;
+                    return Ok((provider.parse_response(&text)?, provider.parse_usage(&text)));
+                }
+                Ok(response) if is_retryable_status(response.status()) && attempt < self.config.max_retries => {
+                    tracing::warn!("AI oracle returned {}, retrying", response.status());
+                    retry_after_delay(response.headers())
+                }
+                Ok(response) => {
+                    return Err(CodexError::ReflectionFailed {
+                        error: format!("API request failed: {}", response.status()),
+                    });
+                }
+                Err(e) if e.is_timeout() && attempt < self.config.max_retries => {
+                    tracing::warn!("AI oracle timed out, retrying: {}", e);
+                    None
+                }
+                Err(e) => return Err(CodexError::Network(e)),
+            };
+
+            let backoff = std::time::Duration::from_millis(
+                self.config.retry_base_delay_ms.saturating_mul(1 << attempt.min(16)),
+            );
+            tokio::time::sleep(retry_delay.unwrap_or(backoff)).await;
+            attempt += 1;
         }
-
-        let ai_response: ChatCompletionResponse = response
-            .json()
-            .await
-            .map_err(|e| CodexError::Network(e))?;
-
-        ai_response
-            .choices
-            .first()
-            .map(|choice| choice.message.content.clone())
-            .ok_or_else(|| CodexError::ReflectionFailed {
-                error: "No response from AI oracle".to_string(),
-            })
     }
 
     fn parse_ai_reflection(
@@ -377,30 +965,42 @@ Please provide your archetypal interpretation and guidance for this sacred trans
             emergent_insights: Vec::new(),
             resonance_analysis: String::new(),
             next_steps: Vec::new(),
+            token_usage: None,
         };
 
-        // Parse structured response
+        // Parse structured response, each section running until the next header
+        let mut sections: HashMap<ReflectionSection, Vec<String>> = HashMap::new();
+        let mut current = None;
         for line in ai_response.lines() {
-            if let Some(content) = line.strip_prefix("ARCHETYPAL_INTERPRETATION: ") {
-                reflection.archetypal_interpretation = content.to_string();
-            } else if let Some(content) = line.strip_prefix("SYMBOLIC_MEANING: ") {
-                reflection.symbolic_meaning = content.to_string();
-            } else if let Some(content) = line.strip_prefix("INTEGRATION_GUIDANCE: ") {
-                reflection.integration_guidance = content.to_string();
-            } else if let Some(content) = line.strip_prefix("EMERGENT_INSIGHTS: ") {
-                reflection.emergent_insights = content
-                    .split('|')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-            } else if let Some(content) = line.strip_prefix("RESONANCE_ANALYSIS: ") {
-                reflection.resonance_analysis = content.to_string();
-            } else if let Some(content) = line.strip_prefix("NEXT_STEPS: ") {
-                reflection.next_steps = content
-                    .split('|')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
+            if let Some((section, content)) = parse_section_header(line) {
+                current = Some(section);
+                sections.entry(section).or_default().push(content);
+            } else if let Some(section) = current {
+                // Horizontal rules separate sections without adding to them
+                let line = line.trim();
+                if !line.chars().all(|c| MARKDOWN_EMPHASIS.contains(&c) || c == '-' || c == '=') {
+                    sections.entry(section).or_default().push(strip_bullet(line).to_string());
+                }
+            }
+        }
+
+        for (section, lines) in sections {
+            let lines = lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty());
+            match section {
+                ReflectionSection::ArchetypalInterpretation => {
+                    reflection.archetypal_interpretation = lines.collect::<Vec<_>>().join(" ")
+                }
+                ReflectionSection::SymbolicMeaning => {
+                    reflection.symbolic_meaning = lines.collect::<Vec<_>>().join(" ")
+                }
+                ReflectionSection::IntegrationGuidance => {
+                    reflection.integration_guidance = lines.collect::<Vec<_>>().join(" ")
+                }
+                ReflectionSection::ResonanceAnalysis => {
+                    reflection.resonance_analysis = lines.collect::<Vec<_>>().join(" ")
+                }
+                ReflectionSection::EmergentInsights => reflection.emergent_insights = split_list(lines),
+                ReflectionSection::NextSteps => reflection.next_steps = split_list(lines),
             }
         }
 
@@ -452,7 +1052,8 @@ Please provide your archetypal interpretation and guidance for this sacred trans
             integration_guidance: guidance,
             emergent_insights: insights,
             next_steps: self.suggest_next_steps(ritual_result),
-            resonance_analysis: self.analyze_resonance(ritual_result),
+            resonance_analysis: self.analyze_resonance(ritual_result, state),
+            token_usage: None,
         })
     }
 
@@ -476,6 +1077,7 @@ Please provide your archetypal interpretation and guidance for this sacred trans
                 "Continue with regular meditation practice".to_string(),
                 "Journal about the symbols that emerged".to_string(),
             ],
+            token_usage: None,
         })
     }
 
@@ -492,6 +1094,20 @@ Please provide your archetypal interpretation and guidance for this sacred trans
         )
     }
 
+    /// The reflection context with the practitioner's question appended for the oracle to answer
+    fn build_query_context(
+        &self,
+        ritual_result: &RitualResult,
+        state: &SymbolicState,
+        query: &str,
+    ) -> String {
+        format!(
+            "{}\n\nPRACTITIONER'S QUESTION:\n{}\nAnswer this question directly within the structured format.",
+            self.build_reflection_context(ritual_result, state),
+            query.trim()
+        )
+    }
+
     pub fn format_reflection_output(&self, reflection: &ReflectionResult) -> String {
         use colored::*;
 
@@ -550,9 +1166,134 @@ Please provide your archetypal interpretation and guidance for this sacred trans
             }
         }
 
+        if let Some(usage) = reflection.token_usage {
+            output.push_str(&format!("\n{}\n", "🪙 TOKEN USAGE".bright_white().bold()));
+            output.push_str(&format!(
+                "  This reflection: {} tokens ({} prompt, {} completion)\n",
+                usage.total_tokens, usage.prompt_tokens, usage.completion_tokens
+            ));
+            if let Some(total) = self.usage_summary() {
+                output.push_str(&format!(
+                    "  This session:    {} tokens ({} prompt, {} completion)\n",
+                    total.total_tokens, total.prompt_tokens, total.completion_tokens
+                ));
+            }
+        }
+
         output.push_str(&format!("\n{}\n", "=".repeat(60).bright_purple()));
         output
     }
+
+    /// The same reflection as `format_reflection_output`, as plain Markdown for
+    /// files and journals; oracle content is escaped so it cannot break the layout
+    pub fn format_reflection_markdown(&self, reflection: &ReflectionResult) -> String {
+        let mut output = format!(
+            "# Reflection on {}\n\n_{}_\n",
+            escape_markdown(&reflection.ritual_name),
+            reflection.timestamp.format("%Y-%m-%d %H:%M UTC")
+        );
+
+        let sections = [
+            ("ARCHETYPAL INTERPRETATION", &reflection.archetypal_interpretation),
+            ("SYMBOLIC MEANING", &reflection.symbolic_meaning),
+            ("INTEGRATION GUIDANCE", &reflection.integration_guidance),
+        ];
+        for (heading, text) in sections {
+            output.push_str(&format!("\n## {}\n\n{}\n", heading, escape_markdown(text)));
+        }
+
+        let push_list = |output: &mut String, heading: &str, items: &[String]| {
+            if items.is_empty() {
+                return;
+            }
+            output.push_str(&format!("\n## {}\n\n", heading));
+            for item in items {
+                // A line break inside an item would end the list
+                let item = item.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+                output.push_str(&format!("- {}\n", escape_markdown(&item)));
+            }
+        };
+
+        push_list(&mut output, "EMERGENT INSIGHTS", &reflection.emergent_insights);
+        output.push_str(&format!(
+            "\n## RESONANCE ANALYSIS\n\n{}\n",
+            escape_markdown(&reflection.resonance_analysis)
+        ));
+        push_list(&mut output, "NEXT STEPS", &reflection.next_steps);
+
+        if let Some(usage) = reflection.token_usage {
+            output.push_str("\n## TOKEN USAGE\n\n");
+            output.push_str(&format!(
+                "- This reflection: {} tokens ({} prompt, {} completion)\n",
+                usage.total_tokens, usage.prompt_tokens, usage.completion_tokens
+            ));
+            if let Some(total) = self.usage_summary() {
+                output.push_str(&format!(
+                    "- This session: {} tokens ({} prompt, {} completion)\n",
+                    total.total_tokens, total.prompt_tokens, total.completion_tokens
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+/// One sentence grounding a fallback reflection in the practitioner's current state
+fn describe_state(state: &SymbolicState) -> String {
+    let leading = match state.coherence_report().dominant_archetype {
+        Some(name) => format!(
+            "The {} archetype currently leads your psyche at {:.2} activation",
+            name, state.archetypes[&name].activation_level
+        ),
+        None => "No archetype has yet taken the lead in your psyche".to_string(),
+    };
+    let total_energy: f64 = state.energies.values().map(|e| e.amplitude).sum();
+    let unresolved = match state.unresolved_symbols.len() {
+        0 => "no symbols remain unresolved".to_string(),
+        1 => "1 symbol remains unresolved".to_string(),
+        count => format!("{} symbols remain unresolved", count),
+    };
+
+    format!("{}, {:.2} units of energy move through your field, and {}.", leading, total_energy, unresolved)
+}
+
+fn dominant_archetype_insight(archetype: &str) -> String {
+    match archetype {
+        "Shadow" => "With the Shadow leading, what you have disowned is asking to be seen".to_string(),
+        "Sage" => "With the Sage leading, understanding ripens into wisdom only when it is lived".to_string(),
+        "Light" => "With the Light leading, remember that every illumination casts a shadow".to_string(),
+        "Creator" => "With the Creator leading, give form to what is stirring before it fades".to_string(),
+        other => format!("With the {} leading, let its qualities guide the next stage of the work", other),
+    }
+}
+
+/// Backslash-escapes Markdown syntax in `text`: inline markup anywhere, and the
+/// block markers (lists, headings, quotes, rules) that only matter at a line start
+fn escape_markdown(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let mut escaped = String::with_capacity(line.len());
+            let trimmed = line.trim_start();
+            escaped.push_str(&line[..line.len() - trimmed.len()]);
+
+            // An ordered list marker is digits followed by `.` or `)`
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+            let ordered_marker = digits > 0
+                && matches!(trimmed[digits..].chars().next(), Some('.') | Some(')'));
+
+            for (i, c) in trimmed.char_indices() {
+                let block_marker = (i == 0 && matches!(c, '-' | '+' | '='))
+                    || (ordered_marker && i == digits);
+                if block_marker || "\\`*_[]<>#|~".contains(c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[cfg(test)]
@@ -589,6 +1330,7 @@ mod tests {
             emergent_symbols: vec!["🌑→🌕".to_string(), "∫∂∇".to_string()],
             completion_status: CompletionStatus::Complete,
             resonance_level: 0.75,
+            checkpoint: None,
         }
     }
 
@@ -631,6 +1373,12 @@ mod tests {
             model: "test-model".to_string(),
             temperature: 0.8,
             max_tokens: 1500,
+            provider: Provider::OpenRouter,
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+            ritual_overrides: HashMap::new(),
+            persona: OraclePersona::default(),
+            system_prompt_template: None,
         };
         
         let reflector = Reflector::new(config.clone());
@@ -656,6 +1404,12 @@ mod tests {
             model: "test-model".to_string(),
             temperature: 0.7,
             max_tokens: 2000,
+            provider: Provider::OpenRouter,
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+            ritual_overrides: HashMap::new(),
+            persona: OraclePersona::default(),
+            system_prompt_template: None,
         };
         
         let reflector = Reflector::new(config);
@@ -731,7 +1485,11 @@ NEXT_STEPS: Continue the work | Stay grounded"#.to_string();
         
         let reflection = reflector.parse_ai_reflection(ai_response, &ritual_result).unwrap();
         
-        assert_eq!(reflection.archetypal_interpretation, "The shadow work was deep.");
+        // Text after a header belongs to its section until the next header
+        assert_eq!(
+            reflection.archetypal_interpretation,
+            "The shadow work was deep. Some unstructured text that doesn't match our format."
+        );
         // Should have fallback symbolic meaning
         assert!(reflection.symbolic_meaning.contains("🌑→🌕"));
         assert!(reflection.symbolic_meaning.contains("∫∂∇"));
@@ -743,6 +1501,68 @@ NEXT_STEPS: Continue the work | Stay grounded"#.to_string();
         assert!(reflection.resonance_analysis.contains("0.75"));
     }
 
+    #[test]
+    fn test_parse_ai_reflection_markdown_response() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+
+        let ai_response = r#"Here is your reflection.
+
+**ARCHETYPAL_INTERPRETATION:** The Shadow steps forward
+and asks to be seen.
+
+---
+
+## Symbolic Meaning:
+The waxing moon marks a return of light.
+
+- **INTEGRATION_GUIDANCE**: Sit with what surfaced.
+
+**EMERGENT_INSIGHTS:**
+- Pride hides a wound
+- The wound is a door
+
+**RESONANCE_ANALYSIS:** *Strong and steady.*
+
+**NEXT_STEPS:**
+1. Journal tonight
+2. Walk at dawn"#.to_string();
+
+        let reflection = reflector.parse_ai_reflection(ai_response, &ritual_result).unwrap();
+
+        assert_eq!(
+            reflection.archetypal_interpretation,
+            "The Shadow steps forward and asks to be seen."
+        );
+        assert_eq!(reflection.symbolic_meaning, "The waxing moon marks a return of light.");
+        assert_eq!(reflection.integration_guidance, "Sit with what surfaced.");
+        assert_eq!(reflection.emergent_insights, vec!["Pride hides a wound", "The wound is a door"]);
+        assert!(reflection.resonance_analysis.contains("Strong and steady"));
+        assert_eq!(reflection.next_steps, vec!["Journal tonight", "Walk at dawn"]);
+    }
+
+    #[test]
+    fn test_parse_ai_reflection_lowercase_response() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+
+        let ai_response = r#"archetypal_interpretation: The Sage is quietly waking.
+symbolic_meaning: The spiral speaks of return.
+integration guidance: Rest in not-knowing.
+emergent_insights: Stillness teaches | Questions ripen
+resonance_analysis: Gentle but clear.
+next_steps: Meditate | Listen"#.to_string();
+
+        let reflection = reflector.parse_ai_reflection(ai_response, &ritual_result).unwrap();
+
+        assert_eq!(reflection.archetypal_interpretation, "The Sage is quietly waking.");
+        assert_eq!(reflection.symbolic_meaning, "The spiral speaks of return.");
+        assert_eq!(reflection.integration_guidance, "Rest in not-knowing.");
+        assert_eq!(reflection.emergent_insights, vec!["Stillness teaches", "Questions ripen"]);
+        assert_eq!(reflection.resonance_analysis, "Gentle but clear.");
+        assert_eq!(reflection.next_steps, vec!["Meditate", "Listen"]);
+    }
+
     #[test]
     fn test_parse_ai_reflection_empty_response() {
         let reflector = Reflector::new_with_defaults();
@@ -776,6 +1596,95 @@ NEXT_STEPS: Continue the work | Stay grounded"#.to_string();
         assert!(reflection.resonance_analysis.contains("0.75"));
     }
 
+    fn state_led_by(archetype: &str) -> SymbolicState {
+        let mut state = create_test_symbolic_state();
+        state.set_archetype_activation("Shadow", 0.3);
+        state.set_archetype_activation("Sage", 0.3);
+        state.set_archetype_activation(archetype, 0.9);
+        state
+    }
+
+    #[test]
+    fn test_enhanced_mock_interpretation_reflects_dominant_archetype() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+        let shadow_state = state_led_by("Shadow");
+        let sage_state = state_led_by("Sage");
+
+        let shadow = reflector.generate_archetypal_interpretation(&ritual_result, &shadow_state);
+        let sage = reflector.generate_archetypal_interpretation(&ritual_result, &sage_state);
+
+        assert_ne!(shadow, sage);
+        assert!(shadow.contains("The Shadow archetype currently leads your psyche at 0.90"), "{}", shadow);
+        assert!(sage.contains("The Sage archetype currently leads your psyche at 0.90"), "{}", sage);
+        assert_eq!(shadow, reflector.generate_archetypal_interpretation(&ritual_result, &shadow_state));
+    }
+
+    #[test]
+    fn test_enhanced_mock_details_energy_and_unresolved_symbols() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+        let mut state = state_led_by("Shadow");
+
+        let interpretation = reflector.generate_archetypal_interpretation(&ritual_result, &state);
+        assert!(interpretation.contains("0.60 units of energy"), "{}", interpretation);
+        assert!(interpretation.contains("no symbols remain unresolved"), "{}", interpretation);
+
+        state.add_unresolved_symbol("🜂".to_string());
+        state.add_unresolved_symbol("🜄".to_string());
+        let interpretation = reflector.generate_archetypal_interpretation(&ritual_result, &state);
+        assert!(interpretation.contains("2 symbols remain unresolved"), "{}", interpretation);
+
+        let insights = reflector.generate_emergent_insights(&ritual_result, &state);
+        assert!(insights.iter().any(|i| i.contains("the Shadow leading")));
+        assert!(insights.iter().any(|i| i == "2 unresolved symbols still await contemplation"));
+        let sage_insights = reflector.generate_emergent_insights(&ritual_result, &state_led_by("Sage"));
+        assert!(sage_insights.iter().any(|i| i.contains("the Sage leading")));
+    }
+
+    #[test]
+    fn test_switching_provider_drops_the_api_key() {
+        let config = ReflectionConfig::from_env_values(None, None, Some("server-key".to_string()));
+
+        let same = config.clone().with_provider(Provider::OpenRouter);
+        assert_eq!(same.api_key, "server-key");
+
+        let anthropic = config.with_provider(Provider::AnthropicMessages);
+        assert_eq!(anthropic.provider, Provider::AnthropicMessages);
+        assert_eq!(anthropic.api_base_url, "https://api.anthropic.com");
+        assert!(anthropic.api_key.is_empty());
+    }
+
+    #[test]
+    fn test_shadow_interpretation_names_integrated_aspects() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+        let mut state = create_test_symbolic_state();
+
+        let interpretation = reflector.generate_archetypal_interpretation(&ritual_result, &state);
+        assert!(!interpretation.contains("shadow aspects you have named"));
+
+        let shadow = state.archetypes.get_mut("Shadow").unwrap();
+        shadow.integrate_aspect("Pride".to_string(), true);
+        shadow.integrate_aspect("Envy".to_string(), true);
+        let interpretation = reflector.generate_archetypal_interpretation(&ritual_result, &state);
+        assert!(interpretation.contains("named and owned so far: Pride, Envy."), "{}", interpretation);
+    }
+
+    #[test]
+    fn test_resonance_analysis_mentions_oscillating_energies() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+        let mut state = create_test_symbolic_state();
+
+        let analysis = reflector.analyze_resonance(&ritual_result, &state);
+        assert!(analysis.contains("No energies have yet reached oscillating polarity"));
+
+        state.energies.get_mut("Fire").unwrap().polarity = Polarity::Oscillating;
+        let analysis = reflector.analyze_resonance(&ritual_result, &state);
+        assert!(analysis.contains("Oscillating polarity in Fire"));
+    }
+
     #[test]
     fn test_format_reflection_output() {
         let reflector = Reflector::new_with_defaults();
@@ -802,6 +1711,34 @@ NEXT_STEPS: Continue the work | Stay grounded"#.to_string();
         assert!(output.contains("0.75"));
     }
 
+    #[test]
+    fn test_format_reflection_markdown() {
+        let reflector = Reflector::new_with_defaults();
+        let ritual_result = create_test_ritual_result();
+        let state = create_test_symbolic_state();
+
+        let mut reflection = reflector.create_mock_reflection(&ritual_result, &state).unwrap();
+        reflection.emergent_insights.push("Embrace the *dark*\n# not a heading".to_string());
+        let output = reflector.format_reflection_markdown(&reflection);
+
+        assert!(output.starts_with("# Reflection on shadow\\_integration\n"));
+        assert!(output.contains("\n## ARCHETYPAL INTERPRETATION\n"));
+        assert!(output.contains("\n## EMERGENT INSIGHTS\n\n- "));
+        assert!(output.contains("\n## NEXT STEPS\n\n- "));
+        assert!(output.contains("archetypal forces"));
+        // Oracle content cannot add markup or break out of its list
+        assert!(output.contains("- Embrace the \\*dark\\* \\# not a heading\n"));
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_escape_markdown_neutralizes_block_markers() {
+        assert_eq!(escape_markdown("plain words."), "plain words.");
+        assert_eq!(escape_markdown("- item\n1. first"), "\\- item\n1\\. first");
+        assert_eq!(escape_markdown("> [link](x) `code`"), "\\> \\[link\\](x) \\`code\\`");
+        assert_eq!(escape_markdown("  ## deep"), "  \\#\\# deep");
+    }
+
     #[test]
     fn test_reflection_result_serialization() {
         let reflection = ReflectionResult {
@@ -813,6 +1750,7 @@ NEXT_STEPS: Continue the work | Stay grounded"#.to_string();
             emergent_insights: vec!["Insight 1".to_string(), "Insight 2".to_string()],
             resonance_analysis: "Test resonance".to_string(),
             next_steps: vec!["Step 1".to_string(), "Step 2".to_string()],
+            token_usage: None,
         };
         
         // Test serialization to JSON
@@ -827,4 +1765,353 @@ NEXT_STEPS: Continue the work | Stay grounded"#.to_string();
         assert_eq!(deserialized.emergent_insights.len(), 2);
         assert_eq!(deserialized.next_steps.len(), 2);
     }
+
+    fn reflector_for(provider: Provider) -> Reflector {
+        Reflector::new(ReflectionConfig {
+            api_base_url: "https://oracle.test".to_string(),
+            api_key: "test-key".to_string(),
+            model: "test-model".to_string(),
+            temperature: 0.5,
+            max_tokens: 800,
+            provider,
+            max_retries: 3,
+            retry_base_delay_ms: 1,
+            ritual_overrides: HashMap::new(),
+            persona: OraclePersona::default(),
+            system_prompt_template: None,
+        })
+    }
+
+    fn request_body_for(provider: Provider) -> serde_json::Value {
+        let ritual_result = create_test_ritual_result();
+        let state = create_test_symbolic_state();
+        let reflector = reflector_for(provider);
+        let context = reflector.build_reflection_context(&ritual_result, &state);
+        reflector.build_request_body(&context, &ritual_result)
+    }
+
+    #[test]
+    fn test_provider_defaults_to_openrouter() {
+        assert_eq!(ReflectionConfig::default().provider, Provider::OpenRouter);
+
+        let legacy = r#"{"api_base_url":"https://openrouter.ai/api/v1","api_key":"","model":"m","temperature":0.7,"max_tokens":10}"#;
+        let config: ReflectionConfig = serde_json::from_str(legacy).unwrap();
+        assert_eq!(config.provider, Provider::OpenRouter);
+    }
+
+    #[test]
+    fn test_chat_completions_request_body() {
+        for provider in [Provider::OpenRouter, Provider::OpenAI] {
+            let body = request_body_for(provider);
+
+            assert_eq!(body["model"], "test-model");
+            assert_eq!(body["max_tokens"], 800);
+            assert_eq!(body["temperature"], 0.5);
+            assert_eq!(body["messages"].as_array().unwrap().len(), 2);
+            assert_eq!(body["messages"][0]["role"], "system");
+            assert_eq!(body["messages"][1]["role"], "user");
+            assert!(body["messages"][1]["content"].as_str().unwrap().contains("shadow_integration"));
+            assert!(body.get("system").is_none());
+        }
+    }
+
+    #[test]
+    fn test_custom_query_appears_in_context_and_prompt() {
+        let reflector = reflector_for(Provider::OpenRouter);
+        let state = create_test_symbolic_state();
+        let query_result = query_ritual_result(&state);
+        let question = "Why does my Shadow feel louder than my Sage lately?";
+
+        let context = reflector.build_query_context(&query_result, &state, &format!("  {}\n", question));
+
+        assert!(context.contains(&format!("PRACTITIONER'S QUESTION:\n{}\n", question)));
+        assert!(context.starts_with(&reflector.build_reflection_context(&query_result, &state)));
+        let body = reflector.build_request_body(&context, &query_result);
+        assert!(body["messages"][1]["content"].as_str().unwrap().contains(question));
+        assert_eq!(query_result.ritual_name, CUSTOM_QUERY_RITUAL);
+        assert_eq!(query_result.resonance_level, state.coherence_report().coherence_score);
+    }
+
+    #[tokio::test]
+    async fn test_reflect_on_query_without_api_key_falls_back_locally() {
+        let mut reflector = reflector_for(Provider::OpenRouter);
+        reflector.config.api_key = String::new();
+
+        let reflection = reflector
+            .reflect_on_query("What wants my attention?", &create_test_symbolic_state())
+            .await
+            .unwrap();
+
+        assert_eq!(reflection.ritual_name, CUSTOM_QUERY_RITUAL);
+    }
+
+    #[test]
+    fn test_anthropic_messages_request_body() {
+        let body = request_body_for(Provider::AnthropicMessages);
+
+        assert_eq!(body["model"], "test-model");
+        assert_eq!(body["max_tokens"], 800);
+        assert!(body["system"].as_str().unwrap().contains("archetypal oracle"));
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["role"], "user");
+        assert!(messages[0]["content"].as_str().unwrap().contains("shadow_integration"));
+    }
+
+    #[test]
+    fn test_chosen_persona_appears_in_system_message() {
+        let ritual_result = create_test_ritual_result();
+        let state = create_test_symbolic_state();
+        let mut reflector = reflector_for(Provider::OpenRouter);
+        reflector.config.persona = OraclePersona::from_name(" Taoist ").unwrap();
+        let context = reflector.build_reflection_context(&ritual_result, &state);
+
+        let body = reflector.build_request_body(&context, &ritual_result);
+
+        let system = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system.starts_with("You are a Taoist sage"));
+        assert!(system.contains("\"shadow_integration\""));
+        assert!(!system.contains("Jungian"));
+        assert!(system.ends_with(RESPONSE_FORMAT_INSTRUCTIONS));
+        assert_eq!(OraclePersona::from_name("kabbalist"), None);
+    }
+
+    #[test]
+    fn test_system_prompt_template_substitutes_ritual_and_resonance() {
+        let mut config = reflector_for(Provider::AnthropicMessages).config;
+        config.persona = OraclePersona::Hermetic;
+        config.system_prompt_template =
+            Some("A stoic guide reviews {ritual_name}, which resonated at {resonance}.".to_string());
+
+        let prompt = config.system_prompt("dawn_meditation", 0.756);
+
+        assert!(prompt.starts_with("A stoic guide reviews dawn_meditation, which resonated at 0.76."));
+        assert!(!prompt.contains("Hermetic"));
+        assert!(prompt.contains("NEXT_STEPS:"));
+
+        let mut reflector = reflector_for(Provider::AnthropicMessages);
+        reflector.config = config;
+        let ritual_result = create_test_ritual_result();
+        let body = reflector.build_request_body("", &ritual_result);
+        assert!(body["system"].as_str().unwrap().contains(&format!(
+            "reviews {}, which resonated at {:.2}.",
+            ritual_result.ritual_name, ritual_result.resonance_level
+        )));
+    }
+
+    #[test]
+    fn test_ollama_request_body() {
+        let body = request_body_for(Provider::OllamaLocal);
+
+        assert_eq!(body["model"], "test-model");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["options"]["num_predict"], 800);
+        assert_eq!(body["options"]["temperature"], 0.5);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert!(body["messages"][0]["content"].as_str().unwrap().contains("archetypal oracle"));
+        assert_eq!(body["messages"][1]["role"], "user");
+        assert!(body["messages"][1]["content"].as_str().unwrap().contains("shadow_integration"));
+        assert!(body.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn test_ritual_overrides_apply_only_to_their_ritual() {
+        let mut reflector = reflector_for(Provider::OpenRouter);
+        reflector
+            .config
+            .ritual_overrides
+            .insert("void_contemplation".to_string(), (1.25, 3000));
+        let state = create_test_symbolic_state();
+        let mut ritual_result = create_test_ritual_result();
+
+        ritual_result.ritual_name = "void_contemplation".to_string();
+        let context = reflector.build_reflection_context(&ritual_result, &state);
+        let body = reflector.build_request_body(&context, &ritual_result);
+        assert_eq!(body["temperature"], 1.25);
+        assert_eq!(body["max_tokens"], 3000);
+
+        ritual_result.ritual_name = "energy_attunement".to_string();
+        let context = reflector.build_reflection_context(&ritual_result, &state);
+        let body = reflector.build_request_body(&context, &ritual_result);
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["max_tokens"], 800);
+    }
+
+    #[test]
+    fn test_provider_env_selects_ollama() {
+        let config = ReflectionConfig::from_env_values(
+            Some("ollama".to_string()),
+            Some("mistral".to_string()),
+            Some("key".to_string()),
+        );
+        assert_eq!(config.provider, Provider::OllamaLocal);
+        assert_eq!(config.model, "mistral");
+        assert_eq!(config.api_base_url, "http://localhost:11434");
+        assert!(config.api_key.is_empty());
+
+        let config = ReflectionConfig::from_env_values(Some("ollama".to_string()), None, None);
+        assert_eq!(config.model, DEFAULT_OLLAMA_MODEL);
+
+        let config =
+            ReflectionConfig::from_env_values(None, Some("mistral".to_string()), Some("key".to_string()));
+        assert_eq!(config.provider, Provider::OpenRouter);
+        assert_eq!(config.api_key, "key");
+    }
+
+    #[test]
+    fn test_provider_endpoints_and_auth() {
+        assert_eq!(Provider::OpenRouter.endpoint("https://openrouter.ai/api/v1"), "https://openrouter.ai/api/v1/chat/completions");
+        assert_eq!(Provider::AnthropicMessages.endpoint("https://api.anthropic.com/"), "https://api.anthropic.com/v1/messages");
+        assert_eq!(Provider::OllamaLocal.endpoint("http://localhost:11434"), "http://localhost:11434/api/chat");
+
+        let anthropic_headers = Provider::AnthropicMessages.auth_headers("secret");
+        assert!(anthropic_headers.contains(&("x-api-key", "secret".to_string())));
+        assert!(anthropic_headers.iter().all(|(name, _)| *name != "Authorization"));
+        assert_eq!(Provider::OpenAI.auth_headers("secret"), vec![("Authorization", "Bearer secret".to_string())]);
+        assert!(Provider::OllamaLocal.auth_headers("").is_empty());
+    }
+
+    #[test]
+    fn test_provider_response_parsing() {
+        let chat = r#"{"choices":[{"message":{"role":"assistant","content":"chat oracle"}}]}"#;
+        assert_eq!(Provider::OpenAI.parse_response(chat).unwrap(), "chat oracle");
+
+        let anthropic = r#"{"content":[{"type":"text","text":"anthropic "},{"type":"text","text":"oracle"}]}"#;
+        assert_eq!(Provider::AnthropicMessages.parse_response(anthropic).unwrap(), "anthropic oracle");
+
+        let ollama = r#"{"message":{"role":"assistant","content":"local oracle"},"done":true}"#;
+        assert_eq!(Provider::OllamaLocal.parse_response(ollama).unwrap(), "local oracle");
+
+        assert!(Provider::OpenRouter.parse_response(r#"{"choices":[]}"#).is_err());
+    }
+
+    #[test]
+    fn test_provider_usage_parsing() {
+        let chat = r#"{"choices":[{"message":{"content":"oracle"}}],
+            "usage":{"prompt_tokens":812,"completion_tokens":344,"total_tokens":1156}}"#;
+        assert_eq!(
+            Provider::OpenRouter.parse_usage(chat),
+            Some(TokenUsage { prompt_tokens: 812, completion_tokens: 344, total_tokens: 1156 })
+        );
+
+        let anthropic = r#"{"content":[{"type":"text","text":"oracle"}],
+            "usage":{"input_tokens":500,"output_tokens":120}}"#;
+        assert_eq!(
+            Provider::AnthropicMessages.parse_usage(anthropic),
+            Some(TokenUsage { prompt_tokens: 500, completion_tokens: 120, total_tokens: 620 })
+        );
+
+        assert_eq!(Provider::OpenAI.parse_usage(r#"{"choices":[{"message":{"content":"oracle"}}]}"#), None);
+
+        // Partial usage blocks keep the counts they do report
+        let without_total = r#"{"choices":[{"message":{"content":"oracle"}}],
+            "usage":{"prompt_tokens":40,"completion_tokens":2}}"#;
+        assert_eq!(
+            Provider::OpenAI.parse_usage(without_total),
+            Some(TokenUsage { prompt_tokens: 40, completion_tokens: 2, total_tokens: 42 })
+        );
+        let total_only = r#"{"choices":[{"message":{"content":"oracle"}}],"usage":{"total_tokens":99}}"#;
+        assert_eq!(
+            Provider::OpenRouter.parse_usage(total_only),
+            Some(TokenUsage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 99 })
+        );
+        let output_only = r#"{"content":[{"type":"text","text":"oracle"}],"usage":{"output_tokens":7}}"#;
+        assert_eq!(
+            Provider::AnthropicMessages.parse_usage(output_only),
+            Some(TokenUsage { prompt_tokens: 0, completion_tokens: 7, total_tokens: 7 })
+        );
+    }
+
+    /// Serves chat completions from a local port, failing the first `failures` requests with `status`
+    async fn spawn_flaky_oracle(status: u16, failures: usize) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use axum::{http::StatusCode, routing::post, Json, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/chat/completions",
+            post(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        Err((StatusCode::from_u16(status).unwrap(), [("Retry-After", "0")]))
+                    } else {
+                        Ok(Json(serde_json::json!({
+                            "choices": [{"message": {"content": "ARCHETYPAL_INTERPRETATION: The oracle answered."}}],
+                            "usage": {"prompt_tokens": 120, "completion_tokens": 30, "total_tokens": 150}
+                        })))
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    #[tokio::test]
+    async fn test_reflection_retries_transient_failures() {
+        let (base_url, hits) = spawn_flaky_oracle(503, 2).await;
+        let mut reflector = reflector_for(Provider::OpenAI);
+        reflector.config.api_base_url = base_url;
+
+        let reflection = reflector
+            .reflect_on_ritual(&create_test_ritual_result(), &create_test_symbolic_state())
+            .await
+            .unwrap();
+
+        assert_eq!(reflection.archetypal_interpretation, "The oracle answered.");
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reflection_usage_accumulates_across_reflections() {
+        let (base_url, _hits) = spawn_flaky_oracle(503, 0).await;
+        let mut reflector = reflector_for(Provider::OpenAI);
+        reflector.config.api_base_url = base_url;
+        assert_eq!(reflector.usage_summary(), None);
+
+        let ritual_result = create_test_ritual_result();
+        let state = create_test_symbolic_state();
+        let first = reflector.reflect_on_ritual(&ritual_result, &state).await.unwrap();
+        reflector.reflect_on_ritual(&ritual_result, &state).await.unwrap();
+
+        assert_eq!(first.token_usage.unwrap().total_tokens, 150);
+        assert_eq!(
+            reflector.usage_summary(),
+            Some(TokenUsage { prompt_tokens: 240, completion_tokens: 60, total_tokens: 300 })
+        );
+        let output = reflector.format_reflection_output(&first);
+        assert!(output.contains("This reflection: 150 tokens"));
+        assert!(output.contains("This session:    300 tokens"));
+    }
+
+    #[tokio::test]
+    async fn test_reflection_does_not_retry_client_errors() {
+        let (base_url, hits) = spawn_flaky_oracle(400, 1).await;
+        let mut reflector = reflector_for(Provider::OpenAI);
+        reflector.config.api_base_url = base_url;
+        let ritual_result = create_test_ritual_result();
+
+        let outcome = reflector.query_ai_oracle("context", &ritual_result).await;
+
+        assert!(matches!(outcome, Err(CodexError::ReflectionFailed { .. })));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_after_header_parsing() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(retry_after_delay(&headers), None);
+
+        headers.insert(reqwest::header::RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after_delay(&headers), Some(std::time::Duration::from_secs(7)));
+
+        headers.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after_delay(&headers), Some(std::time::Duration::ZERO));
+    }
 }
@@ -0,0 +1,111 @@
+use crate::database::{with_pool, Database};
+use crate::locks::PractitionerLocks;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Environment variable that turns on scheduled pruning, keeping this many states
+/// per practitioner
+pub const STATE_RETENTION_ENV: &str = "STATE_HISTORY_KEEP_LAST";
+/// How often scheduled pruning runs
+pub const STATE_RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The practitioner's states outside their newest `$3`, with the practitioner id
+/// bound as both `$1` and `$2`
+const PRUNABLE_STATES: &str = "SELECT id FROM archetypal_states WHERE practitioner_id = $1 AND id NOT IN \
+     (SELECT id FROM archetypal_states WHERE practitioner_id = $2 ORDER BY created_at DESC, id LIMIT $3)";
+
+/// The retention `STATE_HISTORY_KEEP_LAST` asks for, if it is set to a number
+pub fn state_retention_from_env() -> Option<usize> {
+    std::env::var(STATE_RETENTION_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Deletes all but the practitioner's `keep_last` most recent states, always keeping
+/// at least one, and returns how many were deleted.
+///
+/// Ritual sessions that started or ended at a deleted state keep their other
+/// details but lose the link to it.
+pub async fn prune_state_history(
+    db: &Database,
+    practitioner_id: Uuid,
+    keep_last: usize,
+) -> Result<u64, sqlx::Error> {
+    let keep_last = i64::try_from(keep_last.max(1)).unwrap_or(i64::MAX);
+    let unlink = |column: &str| {
+        format!("UPDATE ritual_sessions SET {column} = NULL WHERE {column} IN ({PRUNABLE_STATES})")
+    };
+    let unlink_pre_states = unlink("pre_state_id");
+    let unlink_post_states = unlink("post_state_id");
+    let delete_states = format!("DELETE FROM archetypal_states WHERE id IN ({PRUNABLE_STATES})");
+
+    with_pool!(db, |pool| {
+        async {
+            let mut tx = pool.begin().await?;
+            for statement in [&unlink_pre_states, &unlink_post_states] {
+                sqlx::query(statement)
+                    .bind(practitioner_id)
+                    .bind(practitioner_id)
+                    .bind(keep_last)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            let deleted = sqlx::query(&delete_states)
+                .bind(practitioner_id)
+                .bind(practitioner_id)
+                .bind(keep_last)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            tx.commit().await?;
+            Ok(deleted)
+        }
+        .await
+    })
+}
+
+/// Prunes every practitioner's state history to `keep_last` states, returning how
+/// many states were deleted in all.
+///
+/// Each practitioner is pruned under their lock, as `prune_state` does, so a ritual
+/// cannot store a state between the unlinking and the delete.
+pub async fn prune_all_state_histories(
+    db: &Database,
+    practitioner_locks: &PractitionerLocks,
+    keep_last: usize,
+) -> Result<u64, sqlx::Error> {
+    let practitioner_ids: Vec<Uuid> = with_pool!(db, |pool| {
+        sqlx::query_scalar("SELECT DISTINCT practitioner_id FROM archetypal_states")
+            .fetch_all(pool)
+            .await
+    })?;
+
+    let mut deleted = 0;
+    for practitioner_id in practitioner_ids {
+        let _state_guard = practitioner_locks.lock(practitioner_id).await;
+        deleted += prune_state_history(db, practitioner_id, keep_last).await?;
+    }
+    Ok(deleted)
+}
+
+/// Prunes every practitioner's state history to `keep_last` states each `interval`,
+/// logging rather than stopping when a run fails
+pub fn spawn_scheduled_pruning(
+    db: Database,
+    practitioner_locks: PractitionerLocks,
+    keep_last: usize,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match prune_all_state_histories(&db, &practitioner_locks, keep_last).await {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!("Pruned {} old states from state history", deleted),
+                Err(e) => tracing::warn!("Scheduled state history pruning failed: {}", e),
+            }
+        }
+    })
+}
@@ -1,9 +1,15 @@
-use crate::{CodexError, SymbolicState};
+use crate::state::{Element, Integration, Polarity, DEPTH_LEVELS_PER_EMBODIMENT};
+use crate::encoding::hex_bytes;
+use crate::symbols::interpret_symbol;
+use crate::{CodexError, StateDiff, SymbolicState};
 use chrono::{DateTime, Utc};
-use rand;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::Instant;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 use wasmtime::*;
 
@@ -19,6 +25,25 @@ pub struct RitualResult {
     pub emergent_symbols: Vec<String>,
     pub completion_status: CompletionStatus,
     pub resonance_level: f64, // 0.0 to 1.0
+    /// Progress an interrupted WASM ritual saved so `Ritual::resume` can continue it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<WasmCheckpoint>,
+}
+
+/// Where an interrupted WASM ritual left off: the marker its `checkpoint` export
+/// returned and a copy of its linear memory
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WasmCheckpoint {
+    pub progress: i32,
+    /// Size of the exported memory when the checkpoint was taken
+    pub memory_len: usize,
+    /// The memory up to its last non-zero byte; the rest was zeroed
+    #[serde(with = "hex_bytes")]
+    pub memory: Vec<u8>,
+    /// Hash of the module that took the checkpoint, as `Ritual::hash_wasm_module` gives it;
+    /// only that module can make sense of the saved memory
+    #[serde(default)]
+    pub module_hash: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +52,8 @@ pub enum CompletionStatus {
     PartialIntegration,
     Interrupted,
     Error(String),
+    /// Required archetypes were absent or too faint; `missing` names each of them
+    PrerequisitesNotMet { missing: Vec<String> },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,278 +72,3164 @@ pub enum ChangeType {
     Transformation,
 }
 
+impl StateChange {
+    /// Translates a before/after diff into the changes reported on a ritual result
+    pub fn from_diff(diff: &StateDiff) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+
+        for (name, delta) in &diff.archetype_deltas {
+            changes.push(StateChange {
+                change_type: ChangeType::ArchetypeActivation,
+                description: format!("{} activation shifted by {:+.3}", name, delta),
+                magnitude: delta.abs(),
+            });
+        }
+
+        for (name, delta) in &diff.energy_deltas {
+            changes.push(StateChange {
+                change_type: ChangeType::EnergyShift,
+                description: format!(
+                    "{} amplitude {:+.3}, frequency {:+.2}",
+                    name, delta.amplitude, delta.frequency
+                ),
+                magnitude: delta.amplitude.abs(),
+            });
+        }
+
+        for symbol in &diff.removed_symbols {
+            changes.push(StateChange {
+                change_type: ChangeType::SymbolResolution,
+                description: format!("Symbol {} resolved", symbol),
+                magnitude: 1.0,
+            });
+        }
+
+        for integration in &diff.new_integrations {
+            changes.push(StateChange {
+                change_type: ChangeType::Integration,
+                description: format!("Integration '{}' established", integration),
+                magnitude: 1.0,
+            });
+        }
+
+        changes
+    }
+}
+
 /// Defines the structure and behavior of a ritual
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RitualDefinition {
     pub name: String,
     pub description: String,
     pub intent: String,
+    #[serde(default)]
     pub required_archetypes: Vec<String>,
+    #[serde(default)]
     pub energy_requirements: HashMap<String, f64>,
     pub wasm_module_path: Option<String>,
     pub native_handler: Option<String>,
+    #[serde(default)]
     pub parameters: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub wasm_limits: WasmLimits,
+    /// Archetype pairs that amplify each other, mapped to the resonance bonus they add
+    /// when both are active; pair order does not matter
+    #[serde(default, with = "synergy_pairs")]
+    pub archetype_synergies: HashMap<(String, String), f64>,
+    /// Archetype resonance below which the ritual refuses to run at all, rather
+    /// than running with its prerequisites unmet
+    #[serde(default)]
+    pub min_archetype_resonance: Option<f64>,
+    /// Parameters the ritual expects and the JSON type of each; every declared
+    /// parameter is required, while undeclared ones pass through unchecked
+    #[serde(default)]
+    pub parameter_schema: HashMap<String, ParamType>,
+    /// How much alignment the ritual demands before it resonates strongly
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// How the ritual's resonance weighs its base resonance, energy alignment and
+    /// symbol coherence
+    #[serde(default)]
+    pub resonance_weights: ResonanceWeights,
+    /// Name of a ritual whose required archetypes, energy requirements and parameters
+    /// this one inherits; its own entries override the inherited ones
+    #[serde(default)]
+    pub extends: Option<String>,
 }
 
-/// The ritual execution engine
-pub struct Ritual {
-    pub definition: RitualDefinition,
-    wasm_engine: Option<Engine>,
-    wasm_module: Option<Module>,
+/// How far the resonance weights may sum from 1.0 and still be accepted
+pub const RESONANCE_WEIGHT_TOLERANCE: f64 = 0.01;
+
+/// The share each component contributes to a native ritual's resonance. The weights
+/// must each lie in [0, 1] and sum to 1; omitted weights keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResonanceWeights {
+    /// Weight of the ritual's own base resonance
+    pub base: f64,
+    /// Weight of how well the energies meet the ritual's requirements
+    pub energy_alignment: f64,
+    /// Weight of how coherent the state's symbols are
+    pub symbol_coherence: f64,
 }
 
-impl Ritual {
-    pub fn new(definition: RitualDefinition) -> Self {
+impl Default for ResonanceWeights {
+    fn default() -> Self {
         Self {
-            definition,
-            wasm_engine: None,
-            wasm_module: None,
+            base: 0.4,
+            energy_alignment: 0.3,
+            symbol_coherence: 0.3,
         }
     }
+}
 
-    pub fn load_wasm_module(&mut self) -> Result<(), CodexError> {
-        if let Some(module_path) = &self.definition.wasm_module_path {
-            let engine = Engine::default();
-            let module_bytes = std::fs::read(module_path)?;
-            let module = Module::new(&engine, &module_bytes)?;
-
-            self.wasm_engine = Some(engine);
-            self.wasm_module = Some(module);
+impl ResonanceWeights {
+    /// Describes why the weights cannot be used, or `None` if they can
+    pub fn problem(&self) -> Option<String> {
+        let weights = [
+            ("base", self.base),
+            ("energy_alignment", self.energy_alignment),
+            ("symbol_coherence", self.symbol_coherence),
+        ];
+        if let Some((name, weight)) = weights.iter().find(|(_, weight)| !(0.0..=1.0).contains(weight)) {
+            return Some(format!(
+                "resonance weight '{}' is {}, expected a value between 0 and 1",
+                name, weight
+            ));
         }
-        Ok(())
-    }
 
-    pub fn load_wasm_module_from_bytes(&mut self, wasm_data: &[u8]) -> Result<(), CodexError> {
-        let engine = Engine::default();
-        let module = Module::new(&engine, wasm_data)?;
+        let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+        if (total - 1.0).abs() > RESONANCE_WEIGHT_TOLERANCE {
+            return Some(format!("resonance weights sum to {}, expected 1", total));
+        }
 
-        self.wasm_engine = Some(engine);
-        self.wasm_module = Some(module);
-        Ok(())
+        None
     }
 
-    pub async fn execute(&self, state: &mut SymbolicState) -> Result<RitualResult, CodexError> {
-        let start_time = std::time::Instant::now();
-        let execution_id = Uuid::new_v4();
-
-        // Try WASM execution first, then fall back to native
-        let mut result = if self.wasm_engine.is_some() && self.wasm_module.is_some() {
-            self.execute_wasm_ritual(state, execution_id).await
-                .unwrap_or_else(|e| {
-                    tracing::warn!("WASM execution failed, falling back to native: {}", e);
-                    self.execute_native_ritual(state, execution_id)
-                })
-        } else {
-            self.execute_native_ritual(state, execution_id)
-        };
+    fn combine(&self, base_resonance: f64, energy_alignment: f64, symbol_coherence: f64) -> f64 {
+        base_resonance * self.base
+            + energy_alignment * self.energy_alignment
+            + symbol_coherence * self.symbol_coherence
+    }
+}
 
-        let duration = start_time.elapsed();
-        result.duration_ms = duration.as_millis() as u64;
+/// How demanding a ritual is; harder rituals resonate less from a partly aligned state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    #[default]
+    Beginner,
+    Intermediate,
+    Advanced,
+    Master,
+}
 
-        Ok(result)
+impl Difficulty {
+    /// Maps a catalog `difficulty_level` onto a difficulty, defaulting to `Beginner`
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "intermediate" => Difficulty::Intermediate,
+            "advanced" => Difficulty::Advanced,
+            "master" => Difficulty::Master,
+            _ => Difficulty::Beginner,
+        }
     }
 
-    async fn execute_wasm_ritual(&self, state: &mut SymbolicState, execution_id: Uuid) -> Result<RitualResult, CodexError> {
-        let engine = self.wasm_engine.as_ref().ok_or(CodexError::WasmExecution { error: "No WASM engine".to_string() })?;
-        let module = self.wasm_module.as_ref().ok_or(CodexError::WasmExecution { error: "No WASM module".to_string() })?;
-
-        // Create a store and instantiate the module
-        let mut store = Store::new(engine, ());
-        
-        // Create linker for host functions
-        let mut linker = Linker::new(engine);
-        linker.func_wrap("codex", "log", |_: i32, _: i32| {
-            tracing::info!("WASM ritual executing");
-        })?;
-        linker.func_wrap("codex", "get_archetype_activation", |_: i32, _: i32| -> f64 {
-            0.5 // Placeholder
-        })?;
-        linker.func_wrap("codex", "set_archetype_activation", |_: i32, _: i32, _: f64| {
-            // Placeholder
-        })?;
-        linker.func_wrap("codex", "add_symbol", |_: i32, _: i32| {
-            // Placeholder
-        })?;
-        linker.func_wrap("codex", "get_random", || -> f64 {
-            rand::random::<f64>()
-        })?;
+    /// Exponent applied to a ritual's 0.0–1.0 alignment: full alignment resonates
+    /// fully at every difficulty, but partial alignment falls further short the
+    /// harder the ritual
+    fn resonance_exponent(self) -> f64 {
+        match self {
+            Difficulty::Beginner => 1.0,
+            Difficulty::Intermediate => 1.25,
+            Difficulty::Advanced => 1.5,
+            Difficulty::Master => 2.0,
+        }
+    }
+}
 
-        let instance = linker.instantiate(&mut store, module)?;
-        
-        // Get the execute_ritual function
-        let execute_func = instance
-            .get_typed_func::<(), i32>(&mut store, "execute_ritual")
-            .map_err(|e| CodexError::WasmExecution { error: format!("Failed to get execute_ritual function: {}", e) })?;
+/// The JSON type a ritual parameter must have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamType {
+    Number,
+    Integer,
+    String,
+    Boolean,
+    Array,
+    Object,
+}
 
-        // Execute the ritual
-        let result_code = execute_func.call(&mut store, ())?;
-        
-        // Get resonance if available
-        let resonance = if let Ok(resonance_func) = instance.get_typed_func::<(), f64>(&mut store, "get_resonance") {
-            resonance_func.call(&mut store, ()).unwrap_or(0.5)
-        } else {
-            0.5
-        };
+impl ParamType {
+    pub fn matches(self, value: &serde_json::Value) -> bool {
+        match self {
+            ParamType::Number => value.is_number(),
+            ParamType::Integer => value.is_i64() || value.is_u64(),
+            ParamType::String => value.is_string(),
+            ParamType::Boolean => value.is_boolean(),
+            ParamType::Array => value.is_array(),
+            ParamType::Object => value.is_object(),
+        }
+    }
+}
 
-        // Create result based on WASM execution
-        let result = RitualResult {
-            ritual_name: self.definition.name.clone(),
-            execution_id,
-            timestamp: chrono::Utc::now(),
-            duration_ms: 0, // Will be set by caller
-            symbolic_outputs: std::collections::HashMap::new(),
-            state_changes: vec![StateChange {
-                change_type: ChangeType::Transformation,
-                description: "WASM ritual executed successfully".to_string(),
-                magnitude: resonance,
-            }],
-            emergent_symbols: vec!["🔮".to_string(), "∿".to_string()],
-            completion_status: if result_code == 0 { 
-                CompletionStatus::Complete 
-            } else { 
-                CompletionStatus::Error(format!("WASM returned code: {}", result_code)) 
-            },
-            resonance_level: resonance,
+impl std::fmt::Display for ParamType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ParamType::Number => "number",
+            ParamType::Integer => "integer",
+            ParamType::String => "string",
+            ParamType::Boolean => "boolean",
+            ParamType::Array => "array",
+            ParamType::Object => "object",
         };
-
-        Ok(result)
+        f.write_str(name)
     }
+}
 
-    fn execute_native_ritual(&self, state: &mut SymbolicState, execution_id: Uuid) -> RitualResult {
-        let start_time = Instant::now();
-        state.begin_transformation(format!("ritual:{}", self.definition.name));
-
-        let mut result = RitualResult {
-            ritual_name: self.definition.name.clone(),
-            execution_id,
-            timestamp: Utc::now(),
-            duration_ms: 0,
-            symbolic_outputs: HashMap::new(),
-            state_changes: Vec::new(),
-            emergent_symbols: Vec::new(),
-            completion_status: CompletionStatus::Complete,
-            resonance_level: 0.0,
+impl RitualDefinition {
+    /// Checks that the definition can produce a meaningful resonance: it has a name,
+    /// every energy requirement and the resonance gate are in [0, 1], the resonance
+    /// weights are usable, and no archetype is required twice
+    pub fn validate(&self) -> Result<(), CodexError> {
+        let invalid = |reason: String| CodexError::InvalidRitual {
+            name: self.name.clone(),
+            reason,
         };
 
-        // Check archetype prerequisites
-        let archetype_resonance = self.check_archetype_prerequisites(state);
-        if archetype_resonance < 0.3 {
-            result.completion_status = CompletionStatus::PartialIntegration;
+        if self.name.trim().is_empty() {
+            return Err(invalid("name must not be empty".to_string()));
         }
 
-        // Execute basic ritual transformations
-        match self.definition.name.as_str() {
-            "shadow_integration" => {
-                self.execute_shadow_integration(state, &mut result);
-            }
-            "energy_attunement" => {
-                self.execute_energy_attunement(state, &mut result);
+        let mut requirements: Vec<_> = self.energy_requirements.iter().collect();
+        requirements.sort_by(|a, b| a.0.cmp(b.0));
+        for (energy, required) in requirements {
+            if !(0.0..=1.0).contains(required) {
+                return Err(invalid(format!(
+                    "energy requirement for '{}' is {}, expected a value between 0 and 1",
+                    energy, required
+                )));
             }
-            _ => {
-                // Generic ritual execution
-                result.resonance_level = archetype_resonance * 0.8;
-                result.emergent_symbols.push("✨".to_string());
+        }
+
+        if let Some(minimum) = self.min_archetype_resonance {
+            if !(0.0..=1.0).contains(&minimum) {
+                return Err(invalid(format!(
+                    "minimum archetype resonance is {}, expected a value between 0 and 1",
+                    minimum
+                )));
             }
         }
 
-        // Calculate final resonance
-        result.resonance_level = self.calculate_resonance(state, archetype_resonance);
+        if let Some(problem) = self.resonance_weights.problem() {
+            return Err(invalid(problem));
+        }
 
-        // Complete the transformation
-        state.complete_transformation(&format!("ritual:{}", self.definition.name));
+        let mut seen = HashSet::new();
+        for archetype in &self.required_archetypes {
+            if !seen.insert(archetype) {
+                return Err(invalid(format!(
+                    "archetype '{}' is required more than once",
+                    archetype
+                )));
+            }
+        }
 
-        result.duration_ms = start_time.elapsed().as_millis() as u64;
-        result
+        Ok(())
     }
 
-    fn execute_shadow_integration(&self, state: &mut SymbolicState, result: &mut RitualResult) {
-        // Shadow integration logic
-        let shadow_activation = state.archetypes.get("Shadow").map(|a| a.activation_level).unwrap_or(0.0);
-        let integration_factor = 0.2 + (rand::random::<f64>() * 0.3);
-        
-        // Increase shadow awareness
-        if let Some(shadow_arch) = state.archetypes.get_mut("Shadow") {
-            shadow_arch.activation_level = (shadow_arch.activation_level + integration_factor).min(1.0);
-        }
-        
-        result.emergent_symbols = vec!["◯●◯".to_string(), "🌑".to_string()];
-        result.resonance_level = (shadow_activation + integration_factor) * 0.7;
+    /// Lists every way `parameters` falls short of the parameter schema, in
+    /// parameter name order; empty when they conform
+    pub fn parameter_mismatches(&self, parameters: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        let mut schema: Vec<_> = self.parameter_schema.iter().collect();
+        schema.sort_by(|a, b| a.0.cmp(b.0));
+
+        schema
+            .into_iter()
+            .filter_map(|(name, expected)| match parameters.get(name) {
+                None => Some(format!("missing required parameter '{}' ({})", name, expected)),
+                Some(value) if !expected.matches(value) => Some(format!(
+                    "parameter '{}' should be {}, got {}",
+                    name,
+                    expected,
+                    json_type_name(value)
+                )),
+                Some(_) => None,
+            })
+            .collect()
     }
-    
-    fn execute_energy_attunement(&self, state: &mut SymbolicState, result: &mut RitualResult) {
-        // Energy balancing logic
-        let energy_names = ["Fire", "Water", "Earth", "Air"];
-        let total_energy: f64 = energy_names.iter()
-            .map(|name| state.energies.get(*name).map(|e| e.amplitude).unwrap_or(0.0))
-            .sum();
-        
-        let target_level = total_energy / 4.0;
-        let adjustment = 0.3;
-        
-        // Adjust energies toward balance
-        for energy_name in &energy_names {
-            if let Some(energy) = state.energies.get_mut(*energy_name) {
-                energy.amplitude = energy.amplitude + (target_level - energy.amplitude) * adjustment;
+
+    /// Merges in what `base` requires: its required archetypes come first, and its
+    /// energy requirements and parameters apply wherever this ritual sets none of its own
+    pub fn inherit_from(&mut self, base: &RitualDefinition) {
+        let own_archetypes = std::mem::replace(&mut self.required_archetypes, base.required_archetypes.clone());
+        for archetype in own_archetypes {
+            if !self.required_archetypes.contains(&archetype) {
+                self.required_archetypes.push(archetype);
             }
         }
-        
-        result.emergent_symbols = vec!["∿∿∿".to_string(), "⚡".to_string()];
-        result.resonance_level = 0.8;
+
+        for (energy, required) in &base.energy_requirements {
+            self.energy_requirements.entry(energy.clone()).or_insert(*required);
+        }
+        for (name, value) in &base.parameters {
+            self.parameters.entry(name.clone()).or_insert_with(|| value.clone());
+        }
     }
+}
 
-    fn check_archetype_prerequisites(&self, state: &SymbolicState) -> f64 {
-        let mut total_resonance = 0.0;
-        let mut count = 0;
+fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
 
-        for archetype_name in &self.definition.required_archetypes {
-            if let Some(archetype) = state.archetypes.get(archetype_name) {
-                total_resonance += archetype.activation_level;
-                count += 1;
-            }
+/// Checks that `name` is exported as a function taking `params` and returning `results`
+fn check_func_export(
+    module: &Module,
+    name: &str,
+    params: &[ValType],
+    results: &[ValType],
+    required: bool,
+) -> Result<(), CodexError> {
+    let describe = |params: &[ValType], results: &[ValType]| {
+        let list = |types: &[ValType]| types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ");
+        format!("({}) -> ({})", list(params), list(results))
+    };
+
+    let func = match module.get_export(name) {
+        Some(ExternType::Func(func)) => func,
+        Some(_) => {
+            return Err(CodexError::WasmExecution {
+                error: format!("Ritual module export '{}' must be a function", name),
+            })
+        }
+        None if required => {
+            return Err(CodexError::WasmExecution {
+                error: format!("Ritual module is missing the '{}' export", name),
+            })
         }
+        None => return Ok(()),
+    };
 
-        if count > 0 {
-            total_resonance / count as f64
-        } else {
-            0.5 // Default resonance if no prerequisites
+    let actual_params: Vec<ValType> = func.params().collect();
+    let actual_results: Vec<ValType> = func.results().collect();
+    if actual_params != params || actual_results != results {
+        return Err(CodexError::WasmExecution {
+            error: format!(
+                "Ritual module export '{}' has signature {}, expected {}",
+                name,
+                describe(&actual_params, &actual_results),
+                describe(params, results)
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// How close to the attunement balance point an energy must be for its polarity to shift
+const POLARITY_BALANCE_TOLERANCE: f64 = 0.1;
+
+/// `energy_attunement` parameter that keeps the attuned energies' total amplitude
+/// unchanged, as in a closed system
+pub const CONSERVE_TOTAL_PARAMETER: &str = "conserve_total";
+
+/// `energy_attunement` parameter setting how strongly an energy's distance from the
+/// balance pushes its opposite element the other way
+pub const OPPOSITION_COUPLING_PARAMETER: &str = "opposition_coupling";
+
+/// Opposition coupling when a ritual doesn't set one: enough that Fire slightly damps
+/// Water, and Earth Air, without overpowering the pull toward balance
+pub const DEFAULT_OPPOSITION_COUPLING: f64 = 0.1;
+
+/// Largest accepted opposition coupling. It stays below the attunement's pull toward
+/// balance, so opposed energies settle over repeated runs instead of swinging apart.
+pub const MAX_OPPOSITION_COUPLING: f64 = 0.2;
+
+/// Elements whose energies oppose each other during attunement
+const OPPOSED_ELEMENTS: [(&str, &str); 2] = [("Fire", "Water"), ("Earth", "Air")];
+
+/// `shadow_integration` parameter naming a shadow aspect, such as "Pride", that the
+/// ritual integrates into the Shadow archetype
+pub const ASPECT_PARAMETER: &str = "aspect";
+
+/// Amplitude above which an energy leaves its element's symbol after attunement
+const ATTUNEMENT_SYMBOL_THRESHOLD: f64 = 0.7;
+
+/// The energies that leave their element's symbol when they stay strong through
+/// attunement, in emission order
+const ATTUNEMENT_SYMBOL_ENERGIES: [&str; 5] = ["Fire", "Water", "Earth", "Air", "Void"];
+
+/// The integration `shadow_integration` begins on its first run and deepens on every later one
+const SHADOW_INTEGRATION: &str = "Shadow Integration";
+
+/// Prefix of the integration `symbol_resolution` creates for each symbol it resolves
+const SYMBOL_INTEGRATION_PREFIX: &str = "Meaning of";
+
+/// The archetypes `archetype_invocation` awakens
+const INVOKED_ARCHETYPES: [&str; 8] = [
+    "Sage", "Creator", "Shadow", "Light", "Warrior", "Lover", "Ruler", "Magician",
+];
+
+/// The Solfeggio tones `frequency_tuning` draws energies toward, in any octave
+const SOLFEGGIO_FREQUENCIES: [f64; 9] = [174.0, 285.0, 396.0, 417.0, 528.0, 639.0, 741.0, 852.0, 963.0];
+
+/// Fraction of the remaining distance to its tone an energy covers per tuning
+const FREQUENCY_TUNING_RATE: f64 = 0.5;
+
+/// How far from its tone, in cents, a tuned energy may sit and still count as in tune at all
+const FREQUENCY_TUNING_TOLERANCE_CENTS: f64 = 100.0;
+
+/// The Solfeggio tone, transposed by octaves, nearest to `frequency`
+fn nearest_solfeggio_tone(frequency: f64) -> f64 {
+    SOLFEGGIO_FREQUENCIES
+        .iter()
+        .map(|tone| tone * 2f64.powf((frequency / tone).log2().round()))
+        .min_by(|a, b| cents_between(frequency, *a).total_cmp(&cents_between(frequency, *b)))
+        .expect("there is at least one Solfeggio tone")
+}
+
+fn cents_between(a: f64, b: f64) -> f64 {
+    (1200.0 * (a / b).log2()).abs()
+}
+
+/// Activation both archetypes of a synergy pair need before its bonus applies
+pub const SYNERGY_ACTIVATION_THRESHOLD: f64 = 0.5;
+
+/// Activation below which a required archetype counts as missing
+pub const ARCHETYPE_PREREQUISITE_THRESHOLD: f64 = 0.3;
+
+/// (De)serializes synergy pairs as a list, since JSON objects cannot have tuple keys
+mod synergy_pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize)]
+    struct Synergy {
+        archetypes: (String, String),
+        bonus: f64,
+    }
+
+    pub fn serialize<S: Serializer>(
+        synergies: &HashMap<(String, String), f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut list: Vec<Synergy> = synergies
+            .iter()
+            .map(|(archetypes, bonus)| Synergy {
+                archetypes: archetypes.clone(),
+                bonus: *bonus,
+            })
+            .collect();
+        list.sort_by(|a, b| a.archetypes.cmp(&b.archetypes));
+        list.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(String, String), f64>, D::Error> {
+        Ok(Vec::<Synergy>::deserialize(deserializer)?
+            .into_iter()
+            .map(|synergy| (synergy.archetypes, synergy.bonus))
+            .collect())
+    }
+}
+
+/// Resource budget granted to a WASM ritual before it is interrupted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WasmLimits {
+    pub max_fuel: u64,
+    pub max_millis: u64,
+    /// Largest a module's linear memory may grow, in bytes
+    #[serde(default = "default_max_memory_bytes")]
+    pub max_memory_bytes: usize,
+    /// Most elements any one of a module's tables may hold
+    #[serde(default = "default_max_table_elements")]
+    pub max_table_elements: u32,
+}
+
+fn default_max_memory_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+fn default_max_table_elements() -> u32 {
+    10_000
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            max_fuel: 10_000_000,
+            max_millis: 5_000,
+            max_memory_bytes: default_max_memory_bytes(),
+            max_table_elements: default_max_table_elements(),
         }
     }
+}
 
-    fn calculate_resonance(&self, state: &SymbolicState, base_resonance: f64) -> f64 {
-        // Calculate resonance based on energy alignment and archetype activation
-        let energy_alignment = self.calculate_energy_alignment(state);
-        let symbol_coherence = self.calculate_symbol_coherence(state);
-        
-        (base_resonance * 0.4 + energy_alignment * 0.3 + symbol_coherence * 0.3).min(1.0)
+/// Caps a module's memory and tables, remembering the first growth it refused
+struct SandboxLimiter {
+    max_memory_bytes: usize,
+    max_table_elements: u32,
+    exceeded: Option<String>,
+}
+
+impl SandboxLimiter {
+    fn new(limits: &WasmLimits) -> Self {
+        Self {
+            max_memory_bytes: limits.max_memory_bytes,
+            max_table_elements: limits.max_table_elements,
+            exceeded: None,
+        }
     }
 
-    fn calculate_energy_alignment(&self, state: &SymbolicState) -> f64 {
-        let mut alignment_score = 0.0;
-        let mut count = 0;
+    /// Records why growth was refused and traps, so the ritual stops instead of
+    /// carrying on with a failed allocation
+    fn refuse(&mut self, reason: String) -> wasmtime::Result<bool> {
+        let error = wasmtime::Error::msg(reason.clone());
+        self.exceeded.get_or_insert(reason);
+        Err(error)
+    }
+}
 
-        for (energy_name, required_level) in &self.definition.energy_requirements {
-            if let Some(energy) = state.energies.get(energy_name) {
-                let alignment = 1.0 - (energy.amplitude - required_level).abs();
-                alignment_score += alignment.max(0.0);
-                count += 1;
-            }
+impl ResourceLimiter for SandboxLimiter {
+    fn memory_growing(&mut self, _current: usize, desired: usize, _maximum: Option<usize>) -> wasmtime::Result<bool> {
+        if desired > self.max_memory_bytes {
+            return self.refuse(format!(
+                "Ritual tried to grow its memory to {} bytes, beyond the {} byte limit",
+                desired, self.max_memory_bytes
+            ));
         }
+        Ok(true)
+    }
 
-        if count > 0 {
-            alignment_score / count as f64
-        } else {
-            0.7 // Default alignment if no energy requirements
+    fn table_growing(&mut self, _current: u32, desired: u32, _maximum: Option<u32>) -> wasmtime::Result<bool> {
+        if desired > self.max_table_elements {
+            return self.refuse(format!(
+                "Ritual tried to grow a table to {} elements, beyond the {} element limit",
+                desired, self.max_table_elements
+            ));
         }
+        Ok(true)
     }
+}
 
-    fn calculate_symbol_coherence(&self, state: &SymbolicState) -> f64 {
-        // Simple coherence calculation based on unresolved symbols
-        let unresolved_ratio = if state.unresolved_symbols.is_empty() {
-            0.0
-        } else {
-            state.unresolved_symbols.len() as f64 / (state.unresolved_symbols.len() + 1) as f64
+/// Compiled WASM modules shared across ritual executions, keyed by module hash
+#[derive(Clone)]
+pub struct WasmModuleCache {
+    engine: Engine,
+    modules: Arc<Mutex<HashMap<String, Module>>>,
+    compilations: Arc<AtomicUsize>,
+}
+
+impl WasmModuleCache {
+    pub fn new() -> Result<Self, CodexError> {
+        Ok(Self {
+            engine: Ritual::create_wasm_engine()?,
+            modules: Arc::new(Mutex::new(HashMap::new())),
+            compilations: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Attaches the module for `hash` to the ritual, compiling `wasm_data` only on a cache miss
+    pub fn load_into(&self, ritual: &mut Ritual, hash: &str, wasm_data: &[u8]) -> Result<(), CodexError> {
+        let cached = self
+            .modules
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(hash)
+            .cloned();
+
+        let module = match cached {
+            Some(module) => module,
+            None => {
+                let module = Module::new(&self.engine, wasm_data)?;
+                self.compilations.fetch_add(1, Ordering::Relaxed);
+                self.modules
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(hash.to_string(), module.clone());
+                module
+            }
         };
-        
-        1.0 - unresolved_ratio.min(0.8)
+
+        ritual.attach_wasm_module(self.engine.clone(), module, hash);
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.modules
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of modules compiled since the cache was created
+    pub fn compile_count(&self) -> usize {
+        self.compilations.load(Ordering::Relaxed)
+    }
+}
+
+/// Host-side data available to WASM host functions through the store
+struct WasmHostState {
+    state: SymbolicState,
+    emitted_symbols: Vec<String>,
+    rng: StdRng,
+    limiter: SandboxLimiter,
+}
+
+/// Reads a UTF-8 string out of the guest's exported linear memory
+fn read_guest_string(
+    caller: &mut Caller<'_, WasmHostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<String, CodexError> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| CodexError::WasmExecution {
+            error: "Ritual module does not export its memory".to_string(),
+        })?;
+
+    let start = ptr as u32 as usize;
+    let bytes = start
+        .checked_add(len as u32 as usize)
+        .and_then(|end| memory.data(&caller).get(start..end))
+        .ok_or_else(|| CodexError::WasmExecution {
+            error: format!("String at {}+{} is outside ritual memory", ptr, len),
+        })?;
+
+    std::str::from_utf8(bytes)
+        .map(str::to_string)
+        .map_err(|e| CodexError::WasmExecution {
+            error: format!("Ritual passed invalid UTF-8: {}", e),
+        })
+}
+
+/// Copies `json` into guest memory obtained from the guest's `__codex_alloc` export.
+///
+/// Returns `(ptr, len)` of the written bytes, or `(0, 0)` when the module exports no
+/// allocator (or one without the `(i32) -> i32` signature) so it can skip the call.
+fn write_guest_json(
+    caller: &mut Caller<'_, WasmHostState>,
+    json: &[u8],
+) -> wasmtime::Result<(i32, i32)> {
+    let alloc = match caller
+        .get_export("__codex_alloc")
+        .and_then(|export| export.into_func())
+        .and_then(|func| func.typed::<i32, i32>(&*caller).ok())
+    {
+        Some(alloc) => alloc,
+        None => return Ok((0, 0)),
+    };
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| CodexError::WasmExecution {
+            error: "Ritual module does not export its memory".to_string(),
+        })?;
+
+    let len = i32::try_from(json.len()).map_err(|_| CodexError::WasmExecution {
+        error: format!("State JSON of {} bytes is too large for ritual memory", json.len()),
+    })?;
+    let ptr = alloc.call(&mut *caller, len)?;
+    memory
+        .write(&mut *caller, ptr as u32 as usize, json)
+        .map_err(|_| CodexError::WasmExecution {
+            error: format!("__codex_alloc returned {}+{}, outside ritual memory", ptr, len),
+        })?;
+
+    Ok((ptr, len))
+}
+
+/// Packs a guest `(ptr, len)` into one `i64`, pointer in the high 32 bits.
+///
+/// A single scalar return keeps the import's signature identical to what `extern "C"`
+/// compiles to on wasm32, where a tuple return would become a hidden out-pointer.
+fn pack_guest_slice(ptr: i32, len: i32) -> i64 {
+    (((ptr as u32 as u64) << 32) | len as u32 as u64) as i64
+}
+
+/// Fuel granted to a module's `checkpoint` export once its own budget is spent
+const CHECKPOINT_FUEL: u64 = 1_000_000;
+
+/// How often the shared engine's epoch advances; wall-clock budgets round up to it
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Epoch ticks covering `millis`, so a deadline never falls short of its budget
+fn epoch_ticks(millis: u64) -> u64 {
+    // The first tick may arrive at any point in its interval, hence one extra
+    millis.div_ceil(EPOCH_TICK.as_millis() as u64) + 1
+}
+
+/// How a WASM ritual is entered: from the start, or from a checkpoint's progress marker
+enum EntryPoint {
+    Execute(TypedFunc<(), i32>),
+    Resume(TypedFunc<i32, i32>, i32),
+}
+
+/// Asks an interrupted module for a checkpoint and copies its memory.
+///
+/// Returns `None`, leaving interruption as a plain discard, when the module exports no
+/// `checkpoint` and `resume` pair or the `checkpoint` call itself fails.
+fn take_checkpoint(
+    instance: &Instance,
+    store: &mut Store<WasmHostState>,
+    module_hash: &str,
+) -> Option<WasmCheckpoint> {
+    let checkpoint_func = instance.get_typed_func::<(), i32>(&mut *store, "checkpoint").ok()?;
+    instance.get_typed_func::<i32, i32>(&mut *store, "resume").ok()?;
+
+    // The shared ticker keeps running, so move the deadline out of reach; fuel bounds the call
+    store.set_fuel(CHECKPOINT_FUEL).ok()?;
+    store.set_epoch_deadline(u64::MAX / 2);
+    let progress = match checkpoint_func.call(&mut *store, ()) {
+        Ok(progress) => progress,
+        Err(e) => {
+            tracing::warn!("WASM ritual checkpoint failed, discarding its progress: {}", e);
+            return None;
+        }
+    };
+
+    let (memory_len, memory) = match instance.get_memory(&mut *store, "memory") {
+        Some(memory) => {
+            let data = memory.data(&*store);
+            let used = data.iter().rposition(|b| *b != 0).map_or(0, |last| last + 1);
+            (data.len(), data[..used].to_vec())
+        }
+        None => (0, Vec::new()),
+    };
+
+    Some(WasmCheckpoint {
+        progress,
+        memory_len,
+        memory,
+        module_hash: module_hash.to_string(),
+    })
+}
+
+/// Writes a checkpoint's memory back into a fresh instance, growing it to the saved size
+fn restore_guest_memory(
+    instance: &Instance,
+    store: &mut Store<WasmHostState>,
+    checkpoint: &WasmCheckpoint,
+) -> Result<(), CodexError> {
+    let Some(memory) = instance.get_memory(&mut *store, "memory") else {
+        return if checkpoint.memory_len == 0 {
+            Ok(())
+        } else {
+            Err(CodexError::WasmExecution {
+                error: "Ritual module no longer exports the memory its checkpoint saved".to_string(),
+            })
+        };
+    };
+
+    let page_size = 65_536;
+    let current = memory.data_size(&*store);
+    if checkpoint.memory_len > current {
+        let pages = (checkpoint.memory_len - current).div_ceil(page_size) as u64;
+        memory.grow(&mut *store, pages).map_err(|e| CodexError::WasmExecution {
+            error: format!("Failed to grow ritual memory to its checkpoint size: {}", e),
+        })?;
+    }
+
+    // Data segments re-ran on instantiation, so clear everything the checkpoint saw as zero
+    let data = memory.data_mut(&mut *store);
+    let saved = checkpoint.memory.len().min(data.len());
+    data[..saved].copy_from_slice(&checkpoint.memory[..saved]);
+    let zeroed = checkpoint.memory_len.min(data.len());
+    if zeroed > saved {
+        data[saved..zeroed].fill(0);
+    }
+    Ok(())
+}
+
+/// Whether a trap came from running out of fuel or hitting the wall-clock deadline
+fn is_budget_exhausted(error: &wasmtime::Error) -> bool {
+    matches!(
+        error.downcast_ref::<Trap>(),
+        Some(Trap::OutOfFuel) | Some(Trap::Interrupt)
+    )
+}
+
+/// Recovers a `CodexError` raised inside a host function from the trap that carried it
+fn host_error(error: wasmtime::Error) -> CodexError {
+    match error.downcast::<CodexError>() {
+        Ok(codex_error) => codex_error,
+        Err(error) => CodexError::Wasm(error),
+    }
+}
+
+/// Longest a ritual may run before it is interrupted with its changes discarded
+pub const DEFAULT_RITUAL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The body of a custom native ritual: transforms the state, records what it
+/// did in the result and returns the ritual's own resonance
+pub type NativeHandler = Arc<dyn Fn(&mut SymbolicState, &mut RitualResult) -> f64 + Send + Sync>;
+
+/// The ritual execution engine
+#[derive(Clone)]
+pub struct Ritual {
+    pub definition: RitualDefinition,
+    wasm_engine: Option<Engine>,
+    wasm_module: Option<Module>,
+    /// `hash_wasm_module` of the loaded module's bytes, recorded in its checkpoints
+    wasm_module_hash: Option<String>,
+    harmonic_weight: f64,
+    seed: Option<u64>,
+    timeout: Duration,
+    native_handler: Option<NativeHandler>,
+}
+
+impl Ritual {
+    pub fn new(definition: RitualDefinition) -> Self {
+        Self {
+            definition,
+            wasm_engine: None,
+            wasm_module: None,
+            wasm_module_hash: None,
+            harmonic_weight: 0.0,
+            seed: None,
+            timeout: DEFAULT_RITUAL_TIMEOUT,
+            native_handler: None,
+        }
+    }
+
+    /// Bounds how long `execute` may run. A ritual that overruns is reported as
+    /// `Interrupted` and leaves the state exactly as it found it.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs `handler` in place of the generic body for a ritual without a built-in
+    /// native handler
+    pub fn with_native_handler(mut self, handler: NativeHandler) -> Self {
+        self.native_handler = Some(handler);
+        self
+    }
+
+    /// Blends the state's harmonic coherence into the final resonance with the given weight (0.0–1.0)
+    pub fn with_harmonic_weight(mut self, weight: f64) -> Self {
+        self.harmonic_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Seeds the randomness of native handlers and the WASM `get_random` host function.
+    ///
+    /// With a seed, running the ritual from the same starting state yields the same outcome;
+    /// `None` draws fresh entropy on every execution.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// A fresh generator for one execution path, replaying the seed when one is set
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// The process-wide WASM engine, able to meter fuel and interrupt on epoch deadlines.
+    ///
+    /// Every execution shares it, so its epoch only ever advances from one ticker thread
+    /// started alongside it; each store sets its own deadline in ticks of `EPOCH_TICK`.
+    pub(crate) fn create_wasm_engine() -> Result<Engine, CodexError> {
+        static SHARED_ENGINE: OnceLock<Engine> = OnceLock::new();
+        if let Some(engine) = SHARED_ENGINE.get() {
+            return Ok(engine.clone());
+        }
+
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+
+        Ok(SHARED_ENGINE
+            .get_or_init(|| {
+                let ticker_engine = engine.clone();
+                std::thread::spawn(move || loop {
+                    std::thread::sleep(EPOCH_TICK);
+                    ticker_engine.increment_epoch();
+                });
+                engine
+            })
+            .clone())
+    }
+
+    pub fn load_wasm_module(&mut self) -> Result<(), CodexError> {
+        if let Some(module_path) = &self.definition.wasm_module_path {
+            let engine = Self::create_wasm_engine()?;
+            let module_bytes = std::fs::read(module_path)?;
+            let module = Module::new(&engine, &module_bytes)?;
+
+            self.wasm_engine = Some(engine);
+            self.wasm_module = Some(module);
+            self.wasm_module_hash = Some(Self::hash_wasm_module(&module_bytes));
+        }
+        Ok(())
+    }
+
+    pub fn load_wasm_module_from_bytes(&mut self, wasm_data: &[u8]) -> Result<(), CodexError> {
+        let engine = Self::create_wasm_engine()?;
+        let module = Module::new(&engine, wasm_data)?;
+
+        self.wasm_engine = Some(engine);
+        self.wasm_module = Some(module);
+        self.wasm_module_hash = Some(Self::hash_wasm_module(wasm_data));
+        Ok(())
+    }
+
+    /// Hex-encoded SHA-256 of a module, as stored in `wasm_module_hash`
+    pub fn hash_wasm_module(wasm_data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(wasm_data))
+    }
+
+    /// Checks that the bytes form a module this runtime can load
+    pub fn validate_wasm_module(wasm_data: &[u8]) -> Result<(), CodexError> {
+        let engine = Self::create_wasm_engine()?;
+        Module::validate(&engine, wasm_data).map_err(|e| CodexError::WasmExecution {
+            error: format!("Invalid WASM module: {}", e),
+        })
+    }
+
+    /// Checks the loaded module exports `execute_ritual` as `() -> i32` and, when
+    /// it exports `get_resonance`, that it is `() -> f64`. A module exporting
+    /// `checkpoint() -> i32` must also export `resume(i32) -> i32` to continue from it.
+    pub fn validate_wasm_exports(&self) -> Result<(), CodexError> {
+        let module = self.wasm_module.as_ref().ok_or_else(|| CodexError::WasmExecution {
+            error: "No WASM module loaded".to_string(),
+        })?;
+
+        check_func_export(module, "execute_ritual", &[], &[ValType::I32], true)?;
+        check_func_export(module, "get_resonance", &[], &[ValType::F64], false)?;
+
+        let checkpoints = module.get_export("checkpoint").is_some();
+        check_func_export(module, "checkpoint", &[], &[ValType::I32], false)?;
+        check_func_export(module, "resume", &[ValType::I32], &[ValType::I32], checkpoints)
+    }
+
+    /// Confirms stored module bytes still match the hash recorded at upload
+    pub fn verify_wasm_module_hash(wasm_data: &[u8], expected_hash: &str) -> Result<(), CodexError> {
+        let actual_hash = Self::hash_wasm_module(wasm_data);
+        if actual_hash.eq_ignore_ascii_case(expected_hash) {
+            Ok(())
+        } else {
+            Err(CodexError::StateCorruption {
+                reason: format!(
+                    "WASM module hash mismatch: expected {}, found {}",
+                    expected_hash, actual_hash
+                ),
+            })
+        }
+    }
+
+    /// Attaches an already compiled module, e.g. one served from a `WasmModuleCache`,
+    /// along with the `hash_wasm_module` of the bytes it was compiled from
+    pub fn attach_wasm_module(&mut self, engine: Engine, module: Module, hash: &str) {
+        self.wasm_engine = Some(engine);
+        self.wasm_module = Some(module);
+        self.wasm_module_hash = Some(hash.to_string());
+    }
+
+    pub async fn execute(&self, state: &mut SymbolicState) -> Result<RitualResult, CodexError> {
+        let start_time = std::time::Instant::now();
+        let execution_id = Uuid::new_v4();
+
+        // A gated ritual does not begin until its archetypes are awake enough
+        if let Some(minimum) = self.definition.min_archetype_resonance {
+            let (archetype_resonance, missing) = self.check_archetype_prerequisites(state, minimum);
+            if archetype_resonance < minimum {
+                let mut result = self.interrupted_result(execution_id);
+                result.completion_status = CompletionStatus::PrerequisitesNotMet { missing };
+                result.symbolic_outputs.insert(
+                    "unmet_prerequisites".to_string(),
+                    serde_json::json!(format!(
+                        "Archetype resonance {:.3} is below the {:.3} this ritual requires",
+                        archetype_resonance, minimum
+                    )),
+                );
+                return Ok(result);
+            }
+        }
+
+        let state_before = state.clone();
+
+        // The ritual works on a copy that replaces the state only if it finishes in time,
+        // so an overrun leaves no partial changes behind
+        let mut result = match tokio::time::timeout(self.timeout, self.execute_on_copy(state.clone(), execution_id)).await {
+            Ok(outcome) => {
+                let (finished, result) = outcome?;
+                *state = finished;
+                result
+            }
+            Err(_) => {
+                tracing::warn!("Ritual '{}' exceeded its {:?} time limit", self.definition.name, self.timeout);
+                let mut result = self.interrupted_result(execution_id);
+                result.symbolic_outputs.insert(
+                    "timeout".to_string(),
+                    serde_json::json!(format!("Ritual exceeded its {} ms time limit", self.timeout.as_millis())),
+                );
+                result
+            }
+        };
+
+        // Report what the ritual actually changed
+        result
+            .state_changes
+            .extend(StateChange::from_diff(&state_before.diff(state)));
+
+        let duration = start_time.elapsed();
+        result.duration_ms = duration.as_millis() as u64;
+
+        Ok(result)
+    }
+
+    /// Runs the ritual on `working`, trying WASM first and falling back to native,
+    /// and hands the transformed copy back with the result
+    async fn execute_on_copy(&self, mut working: SymbolicState, execution_id: Uuid) -> Result<(SymbolicState, RitualResult), CodexError> {
+        if self.wasm_engine.is_some() && self.wasm_module.is_some() {
+            match self.execute_wasm_ritual(&mut working, execution_id).await {
+                Ok(result) => return Ok((working, result)),
+                Err(e) => tracing::warn!("WASM execution failed, falling back to native: {}", e),
+            }
+        }
+
+        // Native handlers are plain synchronous code; running them on the blocking pool
+        // lets the caller's timeout fire even if one never yields. An abandoned handler
+        // runs on to completion, but its copy of the state is simply dropped.
+        let ritual = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = ritual.execute_native_ritual(&mut working, execution_id, &mut ritual.rng());
+            (working, result)
+        })
+        .await
+        .map_err(|e| CodexError::InvalidRitual {
+            name: self.definition.name.clone(),
+            reason: format!("native handler failed: {}", e),
+        })
+    }
+
+    /// Continues an interrupted WASM ritual from its checkpoint: the module's memory is
+    /// restored and its `resume` export called with the saved progress marker. Fails
+    /// if the ritual's module is no longer the one that took the checkpoint.
+    pub async fn resume(&self, state: &mut SymbolicState, checkpoint: &WasmCheckpoint) -> Result<RitualResult, CodexError> {
+        // Another module's memory image would be meaningless, or worse, to this one
+        if self.wasm_module_hash.as_deref() != Some(checkpoint.module_hash.as_str()) {
+            return Err(CodexError::WasmExecution {
+                error: format!(
+                    "Ritual '{}' was checkpointed by a different WASM module than it now loads; it cannot be resumed",
+                    self.definition.name
+                ),
+            });
+        }
+
+        let start_time = std::time::Instant::now();
+        let state_before = state.clone();
+
+        let mut result = self.execute_wasm_ritual_from(state, Uuid::new_v4(), Some(checkpoint)).await?;
+
+        result
+            .state_changes
+            .extend(StateChange::from_diff(&state_before.diff(state)));
+        result.duration_ms = start_time.elapsed().as_millis() as u64;
+
+        Ok(result)
+    }
+
+    async fn execute_wasm_ritual(&self, state: &mut SymbolicState, execution_id: Uuid) -> Result<RitualResult, CodexError> {
+        self.execute_wasm_ritual_from(state, execution_id, None).await
+    }
+
+    async fn execute_wasm_ritual_from(
+        &self,
+        state: &mut SymbolicState,
+        execution_id: Uuid,
+        checkpoint: Option<&WasmCheckpoint>,
+    ) -> Result<RitualResult, CodexError> {
+        let engine = self.wasm_engine.as_ref().ok_or(CodexError::WasmExecution { error: "No WASM engine".to_string() })?;
+        let module = self.wasm_module.as_ref().ok_or(CodexError::WasmExecution { error: "No WASM module".to_string() })?;
+        // Name a missing or mistyped entry point before spending time on instantiation
+        self.validate_wasm_exports()?;
+
+        // The store owns a working copy of the state; it is written back only if the ritual runs
+        let mut store = Store::new(
+            engine,
+            WasmHostState {
+                state: state.clone(),
+                emitted_symbols: Vec::new(),
+                rng: self.rng(),
+                limiter: SandboxLimiter::new(&self.definition.wasm_limits),
+            },
+        );
+        store.limiter(|host| &mut host.limiter);
+        
+        // Create linker for host functions
+        let mut linker = Linker::new(engine);
+        linker.func_wrap(
+            "codex",
+            "log",
+            |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+                let message = read_guest_string(&mut caller, ptr, len)?;
+                tracing::info!("WASM ritual: {}", message);
+                Ok(())
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "get_archetype_activation",
+            |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32| -> wasmtime::Result<f64> {
+                let name = read_guest_string(&mut caller, ptr, len)?;
+                Ok(caller
+                    .data()
+                    .state
+                    .archetypes
+                    .get(&name)
+                    .map(|a| a.activation_level)
+                    .unwrap_or(0.0))
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "set_archetype_activation",
+            |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32, level: f64| -> wasmtime::Result<()> {
+                let name = read_guest_string(&mut caller, ptr, len)?;
+                caller.data_mut().state.set_archetype_activation(&name, level);
+                Ok(())
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "get_energy_amplitude",
+            |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32| -> wasmtime::Result<f64> {
+                let name = read_guest_string(&mut caller, ptr, len)?;
+                Ok(caller
+                    .data()
+                    .state
+                    .energies
+                    .get(&name)
+                    .map(|e| e.amplitude)
+                    .unwrap_or(0.0))
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "set_energy_amplitude",
+            |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32, amplitude: f64| -> wasmtime::Result<()> {
+                let name = read_guest_string(&mut caller, ptr, len)?;
+                caller.data_mut().state.set_energy_amplitude(&name, amplitude);
+                Ok(())
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "add_symbol",
+            |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+                let symbol = read_guest_string(&mut caller, ptr, len)?;
+                caller.data_mut().emitted_symbols.push(symbol);
+                Ok(())
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "get_random",
+            |mut caller: Caller<'_, WasmHostState>| -> f64 { caller.data_mut().rng.gen::<f64>() },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "get_evolution_cycle",
+            |caller: Caller<'_, WasmHostState>| -> i32 {
+                i32::try_from(caller.data().state.evolution_cycle).unwrap_or(i32::MAX)
+            },
+        )?;
+        linker.func_wrap(
+            "codex",
+            "get_state_json",
+            |mut caller: Caller<'_, WasmHostState>| -> wasmtime::Result<i64> {
+                let json = serde_json::to_vec(&caller.data().state).map_err(CodexError::from)?;
+                let (ptr, len) = write_guest_json(&mut caller, &json)?;
+                Ok(pack_guest_slice(ptr, len))
+            },
+        )?;
+
+        // A module declaring more memory than the sandbox allows fails here
+        let instance = match linker.instantiate(&mut store, module) {
+            Ok(instance) => instance,
+            Err(e) => match store.data_mut().limiter.exceeded.take() {
+                Some(reason) => return Ok(self.sandbox_violation_result(execution_id, reason)),
+                None => return Err(e.into()),
+            },
+        };
+        
+        // Get the execute_ritual function, or resume where a checkpoint left off
+        let entry_point = match checkpoint {
+            None => {
+                let execute_func = instance
+                    .get_typed_func::<(), i32>(&mut store, "execute_ritual")
+                    .map_err(|e| CodexError::WasmExecution { error: format!("Failed to get execute_ritual function: {}", e) })?;
+                EntryPoint::Execute(execute_func)
+            }
+            Some(checkpoint) => {
+                restore_guest_memory(&instance, &mut store, checkpoint)?;
+                let resume_func = instance
+                    .get_typed_func::<i32, i32>(&mut store, "resume")
+                    .map_err(|e| CodexError::WasmExecution { error: format!("Ritual cannot resume without a resume function: {}", e) })?;
+                EntryPoint::Resume(resume_func, checkpoint.progress)
+            }
+        };
+
+        // Execute the ritual within its fuel and wall-clock budget
+        let limits = &self.definition.wasm_limits;
+        store.set_fuel(limits.max_fuel)?;
+        store.set_epoch_deadline(epoch_ticks(limits.max_millis));
+        store.epoch_deadline_trap();
+
+        let call_result = match entry_point {
+            EntryPoint::Execute(execute_func) => execute_func.call(&mut store, ()),
+            EntryPoint::Resume(resume_func, progress) => resume_func.call(&mut store, progress),
+        };
+
+        // Surface host-function failures as the errors they were raised with
+        let sandbox_violation = store.data_mut().limiter.exceeded.take();
+        let result_code = match (call_result, sandbox_violation) {
+            (Ok(code), _) => code,
+            (Err(_), Some(reason)) => return Ok(self.sandbox_violation_result(execution_id, reason)),
+            (Err(e), None) if is_budget_exhausted(&e) => {
+                tracing::warn!("WASM ritual '{}' exceeded its execution budget", self.definition.name);
+                let mut result = self.interrupted_result(execution_id);
+
+                // A module that can checkpoint keeps the work done so far for `resume`
+                let module_hash = self.wasm_module_hash.as_deref().unwrap_or_default();
+                if let Some(checkpoint) = take_checkpoint(&instance, &mut store, module_hash) {
+                    let host = store.into_data();
+                    result.emergent_symbols = host.emitted_symbols;
+                    *state = host.state;
+                    result.checkpoint = Some(checkpoint);
+                }
+                return Ok(result);
+            }
+            (Err(e), None) => match e.downcast_ref::<Trap>() {
+                // Running out of stack is a limit like fuel, but leaves nothing to resume
+                Some(Trap::StackOverflow) => {
+                    return Ok(self.sandbox_violation_result(execution_id, Trap::StackOverflow.to_string()));
+                }
+                Some(trap) => return Ok(self.trapped_result(execution_id, *trap)),
+                None => return Err(host_error(e)),
+            },
+        };
+        
+        // Get resonance if available
+        let resonance = if let Ok(resonance_func) = instance.get_typed_func::<(), f64>(&mut store, "get_resonance") {
+            resonance_func.call(&mut store, ()).unwrap_or(0.5)
+        } else {
+            0.5
+        };
+
+        // Create result based on WASM execution
+        let mut result = RitualResult {
+            ritual_name: self.definition.name.clone(),
+            execution_id,
+            timestamp: chrono::Utc::now(),
+            duration_ms: 0, // Will be set by caller
+            symbolic_outputs: std::collections::HashMap::new(),
+            state_changes: vec![StateChange {
+                change_type: ChangeType::Transformation,
+                description: "WASM ritual executed successfully".to_string(),
+                magnitude: resonance,
+            }],
+            emergent_symbols: Vec::new(),
+            completion_status: if result_code == 0 { 
+                CompletionStatus::Complete 
+            } else { 
+                CompletionStatus::Error(format!("WASM returned code: {}", result_code)) 
+            },
+            resonance_level: resonance,
+            checkpoint: None,
+        };
+
+        let host = store.into_data();
+        result.emergent_symbols = host.emitted_symbols;
+        *state = host.state;
+
+        Ok(result)
+    }
+
+    /// An interrupted result explaining which sandbox limit the module ran into
+    fn sandbox_violation_result(&self, execution_id: Uuid, reason: String) -> RitualResult {
+        tracing::warn!("WASM ritual '{}' stopped by its sandbox: {}", self.definition.name, reason);
+        let mut result = self.interrupted_result(execution_id);
+        result
+            .symbolic_outputs
+            .insert("interruption_reason".to_string(), serde_json::json!(reason));
+        result
+    }
+
+    /// A failed result for a module that faulted; the trap's message is kept and
+    /// none of the state changes it made before trapping are applied
+    fn trapped_result(&self, execution_id: Uuid, trap: Trap) -> RitualResult {
+        tracing::warn!("WASM ritual '{}' trapped: {}", self.definition.name, trap);
+        RitualResult {
+            completion_status: CompletionStatus::Error(trap.to_string()),
+            ..self.interrupted_result(execution_id)
+        }
+    }
+
+    fn interrupted_result(&self, execution_id: Uuid) -> RitualResult {
+        RitualResult {
+            ritual_name: self.definition.name.clone(),
+            execution_id,
+            timestamp: Utc::now(),
+            duration_ms: 0,
+            symbolic_outputs: HashMap::new(),
+            state_changes: Vec::new(),
+            emergent_symbols: Vec::new(),
+            completion_status: CompletionStatus::Interrupted,
+            resonance_level: 0.0,
+            checkpoint: None,
+        }
+    }
+
+    fn execute_native_ritual(
+        &self,
+        state: &mut SymbolicState,
+        execution_id: Uuid,
+        rng: &mut StdRng,
+    ) -> RitualResult {
+        let start_time = Instant::now();
+        state.begin_transformation(format!("ritual:{}", self.definition.name));
+
+        let mut result = RitualResult {
+            ritual_name: self.definition.name.clone(),
+            execution_id,
+            timestamp: Utc::now(),
+            duration_ms: 0,
+            symbolic_outputs: HashMap::new(),
+            state_changes: Vec::new(),
+            emergent_symbols: Vec::new(),
+            completion_status: CompletionStatus::Complete,
+            resonance_level: 0.0,
+            checkpoint: None,
+        };
+
+        // Check archetype prerequisites
+        let (archetype_resonance, missing) =
+            self.check_archetype_prerequisites(state, ARCHETYPE_PREREQUISITE_THRESHOLD);
+        if !missing.is_empty() {
+            result.completion_status = CompletionStatus::PrerequisitesNotMet { missing };
+        }
+
+        // Execute basic ritual transformations; each native ritual measures its own
+        // resonance, while a generic one resonates as far as its archetypes are present
+        let base_resonance = match self.definition.name.as_str() {
+            "shadow_integration" => self.execute_shadow_integration(state, &mut result, rng),
+            "energy_attunement" => self.execute_energy_attunement(state, &mut result),
+            "void_contemplation" => self.execute_void_contemplation(state, &mut result, rng),
+            "archetype_invocation" => self.execute_archetype_invocation(state, &mut result, rng),
+            "frequency_tuning" => self.execute_frequency_tuning(state, &mut result),
+            "symbol_resolution" => self.execute_symbol_resolution(state, &mut result),
+            _ => match &self.native_handler {
+                Some(handler) => handler(state, &mut result),
+                None => {
+                    // Generic ritual execution
+                    result.emergent_symbols.push("✨".to_string());
+                    archetype_resonance
+                }
+            },
+        };
+
+        // The final resonance blends the ritual's own with the state it leaves behind
+        result.resonance_level = self.calculate_resonance(state, base_resonance);
+
+        // Complete the transformation
+        state.complete_transformation(&format!("ritual:{}", self.definition.name));
+
+        result.duration_ms = start_time.elapsed().as_millis() as u64;
+        result
+    }
+
+    /// Raises the Shadow and begins or deepens its integration; resonates with how
+    /// strongly the Shadow was met
+    fn execute_shadow_integration(&self, state: &mut SymbolicState, result: &mut RitualResult, rng: &mut StdRng) -> f64 {
+        // Shadow integration logic
+        let shadow_activation = state.archetypes.get("Shadow").map(|a| a.activation_level).unwrap_or(0.0);
+        let integration_factor = 0.2 + (rng.gen::<f64>() * 0.3);
+        
+        // Increase shadow awareness
+        if let Some(shadow_arch) = state.archetypes.get_mut("Shadow") {
+            shadow_arch.activation_level = (shadow_arch.activation_level + integration_factor).min(1.0);
+        }
+
+        // Returning to the shadow deepens what the first meeting began
+        match state.integrations.get_mut(SHADOW_INTEGRATION) {
+            Some(integration) => {
+                let previous_depth = integration.depth_level;
+                integration.deepen(1);
+                if integration.depth_level > previous_depth
+                    && integration.depth_level % DEPTH_LEVELS_PER_EMBODIMENT == 0
+                {
+                    integration.advance_embodiment();
+                }
+                result.state_changes.push(StateChange {
+                    change_type: ChangeType::Integration,
+                    description: format!(
+                        "Integration '{}' deepened to level {} ({:?})",
+                        SHADOW_INTEGRATION, integration.depth_level, integration.embodiment_status
+                    ),
+                    magnitude: (integration.depth_level - previous_depth) as f64,
+                });
+            }
+            None => {
+                let shadow_ids = state.archetypes.get("Shadow").map(|a| vec![a.id]).unwrap_or_default();
+                state.add_integration(Integration::new(
+                    SHADOW_INTEGRATION.to_string(),
+                    "What was cast into shadow is owned again".to_string(),
+                    shadow_ids,
+                ));
+            }
+        }
+        
+        // A named aspect is owned by the Shadow once, however often it is brought back
+        let aspect = self.definition.parameters
+            .get(ASPECT_PARAMETER)
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|aspect| !aspect.is_empty());
+        if let (Some(aspect), Some(shadow_arch)) = (aspect, state.archetypes.get_mut("Shadow")) {
+            if !shadow_arch.shadow_aspects.iter().any(|known| known == aspect) {
+                shadow_arch.integrate_aspect(aspect.to_string(), true);
+                result.state_changes.push(StateChange {
+                    change_type: ChangeType::Integration,
+                    description: format!("Shadow aspect '{}' integrated", aspect),
+                    magnitude: 1.0,
+                });
+            }
+            result.symbolic_outputs.insert("integrated_aspect".to_string(), serde_json::json!(aspect));
+        }
+
+        result.emergent_symbols = vec!["◯●◯".to_string(), "🌑".to_string()];
+        ((shadow_activation + integration_factor) * 0.7).min(1.0)
+    }
+    
+    fn execute_energy_attunement(&self, state: &mut SymbolicState, result: &mut RitualResult) -> f64 {
+        // Energy balancing logic
+        let energy_names = ["Fire", "Water", "Earth", "Air"];
+        let total_energy: f64 = energy_names.iter()
+            .map(|name| state.energies.get(*name).map(|e| e.amplitude).unwrap_or(0.0))
+            .sum();
+        
+        let target_level = total_energy / 4.0;
+        let adjustment = 0.3;
+        let coupling = self.definition.parameters
+            .get(OPPOSITION_COUPLING_PARAMETER)
+            .and_then(|value| value.as_f64())
+            .unwrap_or(DEFAULT_OPPOSITION_COUPLING)
+            .clamp(0.0, MAX_OPPOSITION_COUPLING);
+        let amplitudes_before: HashMap<&str, f64> = energy_names.iter()
+            .filter_map(|name| state.energies.get(*name).map(|e| (*name, e.amplitude)))
+            .collect();
+        let opposite = |name: &str| OPPOSED_ELEMENTS.iter().find_map(|&(a, b)| {
+            if name == a {
+                Some(b)
+            } else if name == b {
+                Some(a)
+            } else {
+                None
+            }
+        });
+
+        // Adjust energies toward balance; those that settle near it begin to oscillate.
+        // An opposite above the balance damps an energy and one below it lifts it.
+        for energy_name in &energy_names {
+            let opposition = opposite(energy_name)
+                .and_then(|name| amplitudes_before.get(name))
+                .map_or(0.0, |amplitude| (amplitude - target_level) * coupling);
+            if let Some(energy) = state.energies.get_mut(*energy_name) {
+                energy.amplitude =
+                    (energy.amplitude + (target_level - energy.amplitude) * adjustment - opposition).clamp(0.0, 1.0);
+                if (target_level - energy.amplitude).abs() <= POLARITY_BALANCE_TOLERANCE {
+                    energy.shift_polarity(Polarity::Oscillating);
+                }
+            }
+        }
+
+        // As a closed system, whatever the balancing gained or lost is shared back
+        // evenly among the attuned energies so their sum stays where it started.
+        // Energies pinned at 0 or 1 can't take their share, so the rest is spread
+        // again over those that still have room.
+        let conserve_total = self.definition.parameters
+            .get(CONSERVE_TOTAL_PARAMETER)
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        if conserve_total {
+            let attuned: Vec<&str> = energy_names.iter()
+                .copied()
+                .filter(|name| state.energies.contains_key(*name))
+                .collect();
+            let attuned_total: f64 = attuned.iter().map(|name| state.energies[*name].amplitude).sum();
+            let mut remainder = total_energy - attuned_total;
+            while remainder.abs() > 1e-12 {
+                let open: Vec<&str> = attuned.iter()
+                    .copied()
+                    .filter(|name| {
+                        let amplitude = state.energies[*name].amplitude;
+                        if remainder > 0.0 { amplitude < 1.0 } else { amplitude > 0.0 }
+                    })
+                    .collect();
+                if open.is_empty() {
+                    break;
+                }
+                let share = remainder / open.len() as f64;
+                for name in open {
+                    if let Some(energy) = state.energies.get_mut(name) {
+                        let shared = (energy.amplitude + share).clamp(0.0, 1.0);
+                        remainder -= shared - energy.amplitude;
+                        energy.amplitude = shared;
+                    }
+                }
+            }
+        }
+        
+        result.emergent_symbols = vec!["∿∿∿".to_string(), "⚡".to_string()];
+        // Energies still strong after balancing leave their element's mark
+        for name in ATTUNEMENT_SYMBOL_ENERGIES {
+            if state.energies.get(name).is_some_and(|e| e.amplitude > ATTUNEMENT_SYMBOL_THRESHOLD) {
+                result.emergent_symbols.push(Element::from_name(name).symbol().to_string());
+            }
+        }
+        0.8
+    }
+
+    fn execute_void_contemplation(&self, state: &mut SymbolicState, result: &mut RitualResult, rng: &mut StdRng) -> f64 {
+        // Deepen the Void, then quicken the contemplative archetypes
+        let void_current = state.energies.get("Void").map(|e| e.amplitude).unwrap_or(0.0);
+        let new_void = (void_current + 0.3 + rng.gen::<f64>() * 0.2).min(1.0);
+        state.set_energy_amplitude("Void", new_void);
+
+        for (archetype, boost) in [("Sage", 0.15), ("Mystic", 0.2)] {
+            let current = state.archetypes.get(archetype).map(|a| a.activation_level).unwrap_or(0.0);
+            state.set_archetype_activation(archetype, (current + boost).min(1.0));
+        }
+
+        result.emergent_symbols = vec!["○".to_string(), "∞".to_string()];
+        // A deep enough Void reveals further symbols
+        if new_void > 0.7 {
+            result.emergent_symbols.extend(["◯".to_string(), "⚬".to_string()]);
+        }
+        (new_void * 0.8 + rng.gen::<f64>() * 0.2).min(1.0)
+    }
+
+    fn execute_archetype_invocation(&self, state: &mut SymbolicState, result: &mut RitualResult, rng: &mut StdRng) -> f64 {
+        // Raise every major archetype by the same boost
+        let boost = 0.1 + rng.gen::<f64>() * 0.1;
+        let mut total_activation = 0.0;
+        for archetype in INVOKED_ARCHETYPES {
+            let current = state.archetypes.get(archetype).map(|a| a.activation_level).unwrap_or(0.0);
+            let new_level = (current + boost).min(1.0);
+            state.set_archetype_activation(archetype, new_level);
+            total_activation += new_level;
+        }
+
+        result.emergent_symbols = vec!["🔮".to_string(), "∆∇∆".to_string()];
+        (total_activation / INVOKED_ARCHETYPES.len() as f64 * 0.9).min(1.0)
+    }
+
+    /// Draws each sounding energy's frequency toward its nearest Solfeggio tone and
+    /// returns how in tune they end up, from 0.0 (all a semitone or more off) to 1.0
+    fn execute_frequency_tuning(&self, state: &mut SymbolicState, result: &mut RitualResult) -> f64 {
+        let mut total_accuracy = 0.0;
+        let mut tuned = 0;
+
+        for energy in state.energies.values_mut() {
+            // Silent energies have no pitch to tune
+            if !energy.frequency.is_finite() || energy.frequency <= 0.0 {
+                continue;
+            }
+            let target = nearest_solfeggio_tone(energy.frequency);
+            energy.modulate((target - energy.frequency) * FREQUENCY_TUNING_RATE, 0.0);
+
+            let cents_off = cents_between(energy.frequency, target);
+            total_accuracy += (1.0 - cents_off / FREQUENCY_TUNING_TOLERANCE_CENTS).max(0.0);
+            tuned += 1;
+        }
+
+        result.emergent_symbols = vec!["𝄞".to_string(), "∿∿∿".to_string()];
+        if tuned > 0 { total_accuracy / tuned as f64 } else { 0.0 }
+    }
+
+    /// Resolves every unresolved symbol the Codex knows the meaning of, integrating
+    /// each meaning, and returns the share of symbols resolved. Unknown symbols stay
+    /// unresolved; resolving a symbol again deepens its existing integration.
+    fn execute_symbol_resolution(&self, state: &mut SymbolicState, result: &mut RitualResult) -> f64 {
+        let pending = state.unresolved_symbols.clone();
+        if pending.is_empty() {
+            result.emergent_symbols = vec!["○".to_string()];
+            return 0.5;
+        }
+
+        let sage_ids = state.archetypes.get("Sage").map(|a| vec![a.id]).unwrap_or_default();
+        let mut resolved = 0;
+        for symbol in &pending {
+            let Some(meaning) = interpret_symbol(symbol) else {
+                continue;
+            };
+            state.resolve_symbol(symbol);
+            resolved += 1;
+
+            // The diff reports the resolution and any new integration; only deepening needs noting here
+            let name = format!("{} {}", SYMBOL_INTEGRATION_PREFIX, symbol);
+            match state.integrations.get_mut(&name) {
+                Some(integration) => {
+                    let previous_depth = integration.depth_level;
+                    integration.deepen(1);
+                    result.state_changes.push(StateChange {
+                        change_type: ChangeType::Integration,
+                        description: format!("Integration '{}' deepened to level {}", name, integration.depth_level),
+                        magnitude: (integration.depth_level - previous_depth) as f64,
+                    });
+                }
+                None => state.add_integration(Integration::new(name, meaning.to_string(), sage_ids.clone())),
+            }
+        }
+
+        result.emergent_symbols = vec!["🗝".to_string()];
+        resolved as f64 / pending.len() as f64
+    }
+
+    /// Raises every required archetype below this ritual's threshold to exactly that
+    /// threshold, describing each invocation made
+    pub fn invoke_prerequisites(&self, state: &mut SymbolicState) -> Vec<String> {
+        let threshold = self
+            .definition
+            .min_archetype_resonance
+            .map_or(ARCHETYPE_PREREQUISITE_THRESHOLD, |minimum| minimum.max(ARCHETYPE_PREREQUISITE_THRESHOLD));
+        let (_, missing) = self.check_archetype_prerequisites(state, threshold);
+
+        missing
+            .into_iter()
+            .map(|name| {
+                let before = state.archetypes.get(&name).map_or(0.0, |a| a.activation_level);
+                state.set_archetype_activation(&name, threshold);
+                format!("Invoked {} from {:.2} to {:.2}", name, before, threshold)
+            })
+            .collect()
+    }
+
+    /// The mean activation of the required archetypes that are present, along
+    /// with every required archetype that is absent or activated below `threshold`
+    fn check_archetype_prerequisites(&self, state: &SymbolicState, threshold: f64) -> (f64, Vec<String>) {
+        let mut total_resonance = 0.0;
+        let mut count = 0;
+        let mut missing = Vec::new();
+
+        for archetype_name in &self.definition.required_archetypes {
+            match state.archetypes.get(archetype_name) {
+                Some(archetype) => {
+                    total_resonance += archetype.activation_level;
+                    count += 1;
+                    if archetype.activation_level < threshold {
+                        missing.push(archetype_name.clone());
+                    }
+                }
+                None => missing.push(archetype_name.clone()),
+            }
+        }
+
+        let resonance = if count > 0 {
+            total_resonance / count as f64
+        } else {
+            0.5 // Default resonance if no prerequisites
+        };
+        (resonance, missing)
+    }
+
+    /// The single source of a native ritual's resonance: the ritual's own
+    /// `base_resonance` weighted against how well the energies meet its
+    /// requirements and how coherent the symbols are, plus any synergy bonus
+    fn calculate_resonance(&self, state: &SymbolicState, base_resonance: f64) -> f64 {
+        let energy_alignment = self.calculate_energy_alignment(state);
+        let symbol_coherence = self.calculate_symbol_coherence(state);
+        
+        let synergy_bonus = self.calculate_synergy_bonus(state);
+
+        let alignment = self.definition.resonance_weights
+            .combine(base_resonance, energy_alignment, symbol_coherence)
+            .clamp(0.0, 1.0);
+        let scaled = alignment.powf(self.definition.difficulty.resonance_exponent());
+        let resonance = (scaled + synergy_bonus).min(1.0);
+
+        if self.harmonic_weight > 0.0 {
+            resonance * (1.0 - self.harmonic_weight) + state.harmonic_coherence() * self.harmonic_weight
+        } else {
+            resonance
+        }
+    }
+
+    /// Sums the bonuses of synergy pairs whose archetypes are both active enough
+    fn calculate_synergy_bonus(&self, state: &SymbolicState) -> f64 {
+        let is_active = |name: &str| {
+            state
+                .archetypes
+                .get(name)
+                .is_some_and(|a| a.activation_level >= SYNERGY_ACTIVATION_THRESHOLD)
+        };
+
+        self.definition
+            .archetype_synergies
+            .iter()
+            .filter(|((first, second), _)| is_active(first) && is_active(second))
+            .map(|(_, bonus)| bonus)
+            .sum()
+    }
+
+    fn calculate_energy_alignment(&self, state: &SymbolicState) -> f64 {
+        let mut alignment_score = 0.0;
+        let mut count = 0;
+
+        for (energy_name, required_level) in &self.definition.energy_requirements {
+            if let Some(energy) = state.energies.get(energy_name) {
+                let alignment = 1.0 - (energy.amplitude - required_level).abs();
+                alignment_score += alignment.max(0.0);
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            alignment_score / count as f64
+        } else {
+            0.7 // Default alignment if no energy requirements
+        }
+    }
+
+    fn calculate_symbol_coherence(&self, state: &SymbolicState) -> f64 {
+        // Simple coherence calculation based on unresolved symbols
+        let unresolved_ratio = if state.unresolved_symbols.is_empty() {
+            0.0
+        } else {
+            state.unresolved_symbols.len() as f64 / (state.unresolved_symbols.len() + 1) as f64
+        };
+        
+        1.0 - unresolved_ratio.min(0.8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Element, EmbodimentStatus, Energy};
+
+    fn test_ritual(wat: &str) -> Ritual {
+        let mut ritual = Ritual::new(RitualDefinition {
+            name: "wasm_test".to_string(),
+            description: "Inline test module".to_string(),
+            intent: "Exercise the host bridge".to_string(),
+            required_archetypes: vec![],
+            energy_requirements: HashMap::new(),
+            wasm_module_path: None,
+            native_handler: None,
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        });
+        ritual.load_wasm_module_from_bytes(wat.as_bytes()).unwrap();
+        ritual
+    }
+
+    const INFINITE_LOOP_WAT: &str = r#"(module
+        (func (export "execute_ritual") (result i32)
+            (loop $forever (br $forever))
+            (i32.const 0)))"#;
+
+    #[tokio::test]
+    async fn test_wasm_add_symbol_reaches_result() {
+        let ritual = test_ritual(
+            r#"(module
+                (import "codex" "add_symbol" (func $add_symbol (param i32 i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "🌑")
+                (func (export "execute_ritual") (result i32)
+                    (call $add_symbol (i32.const 0) (i32.const 4))
+                    (i32.const 0)))"#,
+        );
+        let mut state = SymbolicState::new();
+
+        let result = ritual.execute_wasm_ritual(&mut state, Uuid::new_v4()).await.unwrap();
+
+        assert_eq!(result.emergent_symbols, vec!["🌑".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_wasm_state_json_round_trips() {
+        // Hands the serialized state straight back as a symbol so the host can inspect it
+        let ritual = test_ritual(
+            r#"(module
+                (import "codex" "get_state_json" (func $get_state_json (result i64)))
+                (import "codex" "add_symbol" (func $add_symbol (param i32 i32)))
+                (memory (export "memory") 2)
+                (func (export "__codex_alloc") (param $len i32) (result i32)
+                    (i32.const 1024))
+                (func (export "execute_ritual") (result i32)
+                    (local $packed i64)
+                    (local.set $packed (call $get_state_json))
+                    (call $add_symbol
+                        (i32.wrap_i64 (i64.shr_u (local.get $packed) (i64.const 32)))
+                        (i32.wrap_i64 (local.get $packed)))
+                    (i32.const 0)))"#,
+        );
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.42);
+
+        let result = ritual.execute_wasm_ritual(&mut state, Uuid::new_v4()).await.unwrap();
+
+        let echoed: SymbolicState = serde_json::from_str(&result.emergent_symbols[0]).unwrap();
+        assert!(state.diff(&echoed).is_empty());
+        assert_eq!(echoed.archetypes["Shadow"].activation_level, 0.42);
+    }
+
+    #[tokio::test]
+    async fn test_wasm_state_json_without_allocator_is_empty() {
+        let ritual = test_ritual(
+            r#"(module
+                (import "codex" "get_state_json" (func $get_state_json (result i64)))
+                (import "codex" "add_symbol" (func $add_symbol (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "execute_ritual") (result i32)
+                    (local $packed i64)
+                    (local.set $packed (call $get_state_json))
+                    (call $add_symbol
+                        (i32.wrap_i64 (i64.shr_u (local.get $packed) (i64.const 32)))
+                        (i32.wrap_i64 (local.get $packed)))
+                    (i32.const 0)))"#,
+        );
+        let mut state = SymbolicState::new();
+
+        let result = ritual.execute_wasm_ritual(&mut state, Uuid::new_v4()).await.unwrap();
+
+        assert_eq!(result.emergent_symbols, vec![String::new()]);
+    }
+
+    #[tokio::test]
+    async fn test_wasm_out_of_bounds_string_is_an_error() {
+        let ritual = test_ritual(
+            r#"(module
+                (import "codex" "add_symbol" (func $add_symbol (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "execute_ritual") (result i32)
+                    (call $add_symbol (i32.const 65530) (i32.const 64))
+                    (i32.const 0)))"#,
+        );
+        let mut state = SymbolicState::new();
+
+        let error = ritual
+            .execute_wasm_ritual(&mut state, Uuid::new_v4())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, CodexError::WasmExecution { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_unreachable_trap_is_an_error_result() {
+        let ritual = test_ritual(
+            r#"(module
+                (import "codex" "set_archetype_activation" (func $set (param i32 i32 f64)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "Shadow")
+                (func (export "execute_ritual") (result i32)
+                    (call $set (i32.const 0) (i32.const 6) (f64.const 0.9))
+                    (unreachable)))"#,
+        );
+        let mut state = SymbolicState::new();
+
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        match result.completion_status {
+            CompletionStatus::Error(detail) => assert!(detail.contains("unreachable"), "{}", detail),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert!(!state.archetypes.contains_key("Shadow"));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_divide_by_zero_trap_is_an_error_result() {
+        let ritual = test_ritual(
+            r#"(module
+                (func (export "execute_ritual") (result i32)
+                    (i32.div_s (i32.const 1) (i32.const 0))))"#,
+        );
+
+        let result = ritual.execute(&mut SymbolicState::new()).await.unwrap();
+
+        match result.completion_status {
+            CompletionStatus::Error(detail) => assert!(detail.contains("divide by zero"), "{}", detail),
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wasm_stack_overflow_is_interrupted() {
+        let ritual = test_ritual(
+            r#"(module
+                (func $recurse (export "execute_ritual") (result i32)
+                    (call $recurse)))"#,
+        );
+
+        let result = ritual.execute(&mut SymbolicState::new()).await.unwrap();
+
+        assert!(matches!(result.completion_status, CompletionStatus::Interrupted));
+        assert!(result.symbolic_outputs.contains_key("interruption_reason"));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_infinite_loop_runs_out_of_fuel() {
+        let mut ritual = test_ritual(INFINITE_LOOP_WAT);
+        ritual.definition.wasm_limits = WasmLimits {
+            max_fuel: 10_000,
+            max_millis: 60_000,
+            ..WasmLimits::default()
+        };
+        let mut state = SymbolicState::new();
+
+        let start = Instant::now();
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        assert!(matches!(result.completion_status, CompletionStatus::Interrupted));
+        assert!(result.checkpoint.is_none());
+        assert!(start.elapsed().as_secs() < 5);
+    }
+
+    /// Counts `mem[0]` up to 50000, tallying this call's steps in `mem[4]` and reporting
+    /// the tally as a fraction through the "Tally" archetype once the count is reached
+    const CHECKPOINTING_COUNTER_WAT: &str = r#"(module
+        (import "codex" "set_archetype_activation" (func $set (param i32 i32 f64)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "Tally")
+        (data (i32.const 32) "Began")
+        (func $count (result i32)
+            (block $done
+                (loop $next
+                    (br_if $done (i32.ge_u (i32.load (i32.const 0)) (i32.const 50000)))
+                    (i32.store (i32.const 0) (i32.add (i32.load (i32.const 0)) (i32.const 1)))
+                    (i32.store (i32.const 4) (i32.add (i32.load (i32.const 4)) (i32.const 1)))
+                    (br $next)))
+            (call $set (i32.const 16) (i32.const 5)
+                (f64.div (f64.convert_i32_u (i32.load (i32.const 4))) (f64.const 50000)))
+            (i32.const 0))
+        (func (export "execute_ritual") (result i32)
+            (call $set (i32.const 32) (i32.const 5) (f64.const 0.5))
+            (call $count))
+        (func (export "checkpoint") (result i32)
+            (i32.store (i32.const 4) (i32.const 0))
+            (i32.load (i32.const 0)))
+        (func (export "resume") (param $progress i32) (result i32)
+            (if (i32.ne (local.get $progress) (i32.load (i32.const 0)))
+                (then (return (i32.const 1))))
+            (call $count)))"#;
+
+    #[tokio::test]
+    async fn test_checkpointed_wasm_ritual_resumes_where_it_stopped() {
+        let mut ritual = test_ritual(CHECKPOINTING_COUNTER_WAT);
+        ritual.definition.wasm_limits = WasmLimits {
+            max_fuel: 100_000,
+            max_millis: 60_000,
+            ..WasmLimits::default()
+        };
+        let mut state = SymbolicState::new();
+
+        let interrupted = ritual.execute(&mut state).await.unwrap();
+
+        assert!(matches!(interrupted.completion_status, CompletionStatus::Interrupted));
+        let checkpoint = interrupted.checkpoint.clone().expect("module exports checkpoint");
+        assert!(checkpoint.progress > 0 && checkpoint.progress < 50_000);
+        // Work done before the interruption is kept rather than discarded
+        assert_eq!(state.archetypes["Began"].activation_level, 0.5);
+        assert!(!state.archetypes.contains_key("Tally"));
+
+        // The checkpoint survives the history file's JSON round-trip
+        let json = serde_json::to_string(&interrupted).unwrap();
+        let restored: RitualResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.checkpoint.as_ref(), Some(&checkpoint));
+
+        ritual.definition.wasm_limits = WasmLimits::default();
+        let resumed = ritual.resume(&mut state, &checkpoint).await.unwrap();
+
+        assert!(matches!(resumed.completion_status, CompletionStatus::Complete));
+        assert!(resumed.checkpoint.is_none());
+        // Only the steps left after the checkpoint ran; a restart would have tallied all 50000
+        let remaining = (50_000 - checkpoint.progress) as f64 / 50_000.0;
+        assert!((state.archetypes["Tally"].activation_level - remaining).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_refuses_to_resume_in_another_module() {
+        let mut ritual = test_ritual(CHECKPOINTING_COUNTER_WAT);
+        ritual.definition.wasm_limits = WasmLimits {
+            max_fuel: 100_000,
+            max_millis: 60_000,
+            ..WasmLimits::default()
+        };
+        let mut state = SymbolicState::new();
+        let interrupted = ritual.execute(&mut state).await.unwrap();
+        let checkpoint = interrupted.checkpoint.expect("module exports checkpoint");
+        assert_eq!(
+            checkpoint.module_hash,
+            Ritual::hash_wasm_module(CHECKPOINTING_COUNTER_WAT.as_bytes())
+        );
+
+        // The same ritual, its module since replaced by one with a different body
+        let replaced = test_ritual(&CHECKPOINTING_COUNTER_WAT.replace("50000", "60000"));
+        let state_before = state.clone();
+        let error = replaced.resume(&mut state, &checkpoint).await.unwrap_err();
+
+        assert!(matches!(error, CodexError::WasmExecution { .. }));
+        assert!(state_before.diff(&state).is_empty());
+    }
+
+    fn interruption_reason(result: &RitualResult) -> &str {
+        assert!(matches!(result.completion_status, CompletionStatus::Interrupted));
+        result.symbolic_outputs["interruption_reason"].as_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_wasm_huge_memory_grow_is_refused() {
+        // Asks for all 4 GiB of 32-bit address space, then would mark the state
+        let ritual = test_ritual(
+            r#"(module
+                (import "codex" "add_symbol" (func $add_symbol (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "execute_ritual") (result i32)
+                    (drop (memory.grow (i32.const 65535)))
+                    (call $add_symbol (i32.const 0) (i32.const 1))
+                    (i32.const 0)))"#,
+        );
+        let mut state = SymbolicState::new();
+
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        assert!(interruption_reason(&result).contains("16777216 byte limit"));
+        assert!(result.emergent_symbols.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wasm_oversized_initial_memory_is_refused() {
+        let mut ritual = test_ritual(
+            r#"(module
+                (memory (export "memory") 4)
+                (func (export "execute_ritual") (result i32) (i32.const 0)))"#,
+        );
+        ritual.definition.wasm_limits.max_memory_bytes = 2 * 65_536;
+
+        let result = ritual.execute(&mut SymbolicState::new()).await.unwrap();
+
+        assert!(interruption_reason(&result).contains("memory"));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_table_growth_is_capped() {
+        let mut ritual = test_ritual(
+            r#"(module
+                (table 1 funcref)
+                (func (export "execute_ritual") (result i32)
+                    (drop (table.grow (ref.null func) (i32.const 1000)))
+                    (i32.const 0)))"#,
+        );
+        ritual.definition.wasm_limits.max_table_elements = 100;
+
+        let result = ritual.execute(&mut SymbolicState::new()).await.unwrap();
+
+        assert!(interruption_reason(&result).contains("100 element limit"));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_memory_growth_within_limit_completes() {
+        let ritual = test_ritual(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "execute_ritual") (result i32)
+                    (if (i32.eq (memory.grow (i32.const 15)) (i32.const -1))
+                        (then (return (i32.const 1))))
+                    (i32.const 0)))"#,
+        );
+
+        let result = ritual.execute(&mut SymbolicState::new()).await.unwrap();
+
+        assert!(matches!(result.completion_status, CompletionStatus::Complete));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_infinite_loop_hits_deadline() {
+        let mut ritual = test_ritual(INFINITE_LOOP_WAT);
+        ritual.definition.wasm_limits = WasmLimits {
+            max_fuel: u64::MAX,
+            max_millis: 50,
+            ..WasmLimits::default()
+        };
+        let mut state = SymbolicState::new();
+
+        let start = Instant::now();
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        assert!(matches!(result.completion_status, CompletionStatus::Interrupted));
+        assert!(start.elapsed().as_secs() < 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_wasm_deadline_does_not_interrupt_other_rituals() {
+        let mut looping = test_ritual(INFINITE_LOOP_WAT);
+        looping.definition.wasm_limits = WasmLimits {
+            max_fuel: u64::MAX,
+            max_millis: 50,
+            ..WasmLimits::default()
+        };
+        let mut counting = test_ritual(
+            r#"(module
+                (func (export "execute_ritual") (result i32)
+                    (local $i i32)
+                    (loop $count
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br_if $count (i32.lt_u (local.get $i) (i32.const 300000000))))
+                    (i32.const 0)))"#,
+        );
+        counting.definition.wasm_limits = WasmLimits {
+            max_fuel: u64::MAX,
+            max_millis: 60_000,
+            ..WasmLimits::default()
+        };
+
+        let counted = tokio::spawn(async move { counting.execute(&mut SymbolicState::new()).await });
+        let looped = tokio::spawn(async move { looping.execute(&mut SymbolicState::new()).await });
+
+        let looped = looped.await.unwrap().unwrap();
+        let counted = counted.await.unwrap().unwrap();
+        assert!(matches!(looped.completion_status, CompletionStatus::Interrupted));
+        assert!(matches!(counted.completion_status, CompletionStatus::Complete));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_cache_reuses_compiled_module() {
+        let cache = WasmModuleCache::new().unwrap();
+        let wat = INFINITE_LOOP_WAT.as_bytes();
+
+        let mut first = test_ritual(INFINITE_LOOP_WAT);
+        cache.load_into(&mut first, "loop-hash", wat).unwrap();
+        let mut second = test_ritual(INFINITE_LOOP_WAT);
+        cache.load_into(&mut second, "loop-hash", wat).unwrap();
+
+        assert_eq!(cache.compile_count(), 1);
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        let mut third = test_ritual(INFINITE_LOOP_WAT);
+        cache.load_into(&mut third, "loop-hash", wat).unwrap();
+        assert_eq!(cache.compile_count(), 2);
+    }
+
+    const SHADOW_INTEGRATION_WASM: &[u8] = include_bytes!("../rituals/shadow_integration.wasm");
+
+    #[test]
+    fn test_validate_wasm_module_accepts_valid_module() {
+        assert!(Ritual::validate_wasm_module(SHADOW_INTEGRATION_WASM).is_ok());
+    }
+
+    #[test]
+    fn test_validate_wasm_module_rejects_corrupt_module() {
+        let corrupt = &SHADOW_INTEGRATION_WASM[..SHADOW_INTEGRATION_WASM.len() / 2];
+
+        let error = Ritual::validate_wasm_module(corrupt).unwrap_err();
+
+        assert!(matches!(error, CodexError::WasmExecution { .. }));
+    }
+
+    fn export_error(wat: &str) -> String {
+        match test_ritual(wat).validate_wasm_exports() {
+            Err(CodexError::WasmExecution { error }) => error,
+            other => panic!("expected an export error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_wasm_exports_accepts_full_contract() {
+        let ritual = test_ritual(
+            r#"(module
+                (func (export "execute_ritual") (result i32) (i32.const 0))
+                (func (export "get_resonance") (result f64) (f64.const 0.5)))"#,
+        );
+        assert!(ritual.validate_wasm_exports().is_ok());
+        assert!(test_ritual(INFINITE_LOOP_WAT).validate_wasm_exports().is_ok());
+    }
+
+    #[test]
+    fn test_validate_wasm_exports_rejects_missing_entry_point() {
+        let error = export_error(r#"(module (func (export "run") (result i32) (i32.const 0)))"#);
+        assert_eq!(error, "Ritual module is missing the 'execute_ritual' export");
+    }
+
+    #[test]
+    fn test_validate_wasm_exports_rejects_wrong_signature() {
+        let error = export_error(
+            r#"(module (func (export "execute_ritual") (param i32) (result f64) (f64.const 0)))"#,
+        );
+        assert_eq!(
+            error,
+            "Ritual module export 'execute_ritual' has signature (i32) -> (f64), expected () -> (i32)"
+        );
+
+        let error = export_error(
+            r#"(module
+                (func (export "execute_ritual") (result i32) (i32.const 0))
+                (func (export "get_resonance") (result i32) (i32.const 1)))"#,
+        );
+        assert!(error.contains("'get_resonance'"), "{}", error);
+    }
+
+    #[test]
+    fn test_validate_wasm_exports_requires_resume_alongside_checkpoint() {
+        let error = export_error(
+            r#"(module
+                (func (export "execute_ritual") (result i32) (i32.const 0))
+                (func (export "checkpoint") (result i32) (i32.const 0)))"#,
+        );
+        assert_eq!(error, "Ritual module is missing the 'resume' export");
+    }
+
+    #[test]
+    fn test_verify_wasm_module_hash_detects_mismatch() {
+        let hash = Ritual::hash_wasm_module(SHADOW_INTEGRATION_WASM);
+        assert_eq!(hash.len(), 64);
+        assert!(Ritual::verify_wasm_module_hash(SHADOW_INTEGRATION_WASM, &hash).is_ok());
+
+        let mut tampered = SHADOW_INTEGRATION_WASM.to_vec();
+        *tampered.last_mut().unwrap() ^= 0xff;
+        let error = Ritual::verify_wasm_module_hash(&tampered, &hash).unwrap_err();
+
+        assert!(matches!(error, CodexError::StateCorruption { .. }));
+    }
+
+    #[test]
+    fn test_harmonic_weight_folds_coherence_into_resonance() {
+        let mut state = SymbolicState::new();
+        state.add_energy(crate::Energy::new("Fire".to_string(), 440.0, crate::Element::Fire));
+        state.add_energy(crate::Energy::new("Water".to_string(), 466.16, crate::Element::Water));
+
+        let plain = test_ritual(INFINITE_LOOP_WAT);
+        let weighted = test_ritual(INFINITE_LOOP_WAT).with_harmonic_weight(1.0);
+
+        assert!(plain.calculate_resonance(&state, 0.5) > 0.5);
+        assert!(weighted.calculate_resonance(&state, 0.5) < 0.1);
+    }
+
+    fn shadow_light_synergy_ritual() -> Ritual {
+        let mut ritual = test_ritual(INFINITE_LOOP_WAT);
+        ritual
+            .definition
+            .archetype_synergies
+            .insert(("Shadow".to_string(), "Light".to_string()), 0.2);
+        ritual
+    }
+
+    #[test]
+    fn test_synergy_bonus_requires_both_archetypes_active() {
+        let ritual = shadow_light_synergy_ritual();
+
+        let mut shadow_only = SymbolicState::new();
+        shadow_only.set_archetype_activation("Shadow", 0.8);
+        shadow_only.set_archetype_activation("Light", 0.2);
+
+        let mut both_active = shadow_only.clone();
+        both_active.set_archetype_activation("Light", 0.8);
+
+        let without_bonus = ritual.calculate_resonance(&shadow_only, 0.3);
+        let with_bonus = ritual.calculate_resonance(&both_active, 0.3);
+        assert!((with_bonus - without_bonus - 0.2).abs() < 1e-9);
+        // Without any synergies the score is unchanged
+        let plain = test_ritual(INFINITE_LOOP_WAT);
+        assert_eq!(without_bonus, plain.calculate_resonance(&shadow_only, 0.3));
+    }
+
+    #[test]
+    fn test_master_difficulty_lowers_resonance_of_a_partly_aligned_state() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.3);
+        state.set_energy_amplitude("Void", 0.2);
+        state.add_unresolved_symbol("☽".to_string());
+
+        let resonance = |difficulty| {
+            let mut ritual = native_ritual("void_contemplation");
+            ritual.definition.energy_requirements = HashMap::from([("Void".to_string(), 0.8)]);
+            ritual.definition.difficulty = difficulty;
+            ritual.calculate_resonance(&state, 0.4)
+        };
+
+        let beginner = resonance(Difficulty::Beginner);
+        let master = resonance(Difficulty::Master);
+        assert!(beginner > 0.0 && beginner < 1.0);
+        assert!((master - beginner.powi(2)).abs() < 1e-12);
+        assert!(resonance(Difficulty::Intermediate) < beginner);
+        assert!(resonance(Difficulty::Advanced) > master);
+    }
+
+    #[test]
+    fn test_energy_weighted_resonance_favors_strong_energy_over_weak_archetypes() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.1);
+        state.set_energy_amplitude("Fire", 0.9);
+
+        let resonance = |weights| {
+            let mut ritual = native_ritual("energy_attunement");
+            ritual.definition.energy_requirements = HashMap::from([("Fire".to_string(), 0.9)]);
+            ritual.definition.resonance_weights = weights;
+            ritual.calculate_resonance(&state, 0.1)
+        };
+
+        let default = resonance(ResonanceWeights::default());
+        let energy_weighted = resonance(ResonanceWeights {
+            base: 0.1,
+            energy_alignment: 0.8,
+            symbol_coherence: 0.1,
+        });
+        assert!(energy_weighted > default, "{} <= {}", energy_weighted, default);
+    }
+
+    #[test]
+    fn test_difficulty_maps_from_catalog_names() {
+        assert_eq!(Difficulty::from_name("advanced"), Difficulty::Advanced);
+        assert_eq!(Difficulty::from_name(" Master "), Difficulty::Master);
+        assert_eq!(Difficulty::from_name("intermediate"), Difficulty::Intermediate);
+        assert_eq!(Difficulty::from_name("unheard-of"), Difficulty::Beginner);
+    }
+
+    #[test]
+    fn test_synergies_round_trip_through_json() {
+        let definition = shadow_light_synergy_ritual().definition;
+        let json = serde_json::to_value(&definition).unwrap();
+        assert_eq!(
+            json["archetype_synergies"],
+            serde_json::json!([{ "archetypes": ["Shadow", "Light"], "bonus": 0.2 }])
+        );
+
+        let parsed: RitualDefinition = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.archetype_synergies, definition.archetype_synergies);
+
+        let mut legacy = serde_json::to_value(&definition).unwrap();
+        legacy.as_object_mut().unwrap().remove("archetype_synergies");
+        let parsed: RitualDefinition = serde_json::from_value(legacy).unwrap();
+        assert!(parsed.archetype_synergies.is_empty());
+    }
+
+    #[test]
+    fn test_parameter_mismatches_list_missing_and_mistyped_parameters() {
+        let mut definition = native_ritual("schema_ritual").definition;
+        definition.parameter_schema = HashMap::from([
+            ("intensity".to_string(), ParamType::Number),
+            ("rounds".to_string(), ParamType::Integer),
+            ("mantra".to_string(), ParamType::String),
+        ]);
+
+        let valid = HashMap::from([
+            ("intensity".to_string(), serde_json::json!(0.7)),
+            ("rounds".to_string(), serde_json::json!(3)),
+            ("mantra".to_string(), serde_json::json!("om")),
+            ("extra".to_string(), serde_json::json!(null)),
+        ]);
+        assert!(definition.parameter_mismatches(&valid).is_empty());
+
+        let invalid = HashMap::from([
+            ("intensity".to_string(), serde_json::json!("high")),
+            ("rounds".to_string(), serde_json::json!(2.5)),
+        ]);
+        assert_eq!(
+            definition.parameter_mismatches(&invalid),
+            vec![
+                "parameter 'intensity' should be number, got string",
+                "missing required parameter 'mantra' (string)",
+                "parameter 'rounds' should be integer, got number",
+            ]
+        );
+    }
+
+    fn shadow_integration(seed: u64) -> Ritual {
+        Ritual::new(RitualDefinition {
+            name: "shadow_integration".to_string(),
+            description: "Seeded native ritual".to_string(),
+            intent: "Integrate the shadow".to_string(),
+            required_archetypes: vec!["Shadow".to_string()],
+            energy_requirements: HashMap::new(),
+            wasm_module_path: None,
+            native_handler: Some("shadow_integration".to_string()),
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        })
+        .with_seed(Some(seed))
+    }
+
+    fn native_ritual(name: &str) -> Ritual {
+        Ritual::new(RitualDefinition {
+            name: name.to_string(),
+            description: "Native test ritual".to_string(),
+            intent: "Exercise a native handler".to_string(),
+            required_archetypes: vec![],
+            energy_requirements: HashMap::new(),
+            wasm_module_path: None,
+            native_handler: Some(name.to_string()),
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        })
+        .with_seed(Some(1))
+    }
+
+    #[tokio::test]
+    async fn test_symbol_resolution_resolves_only_known_symbols() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Sage", 0.6);
+        for symbol in ["🌑", "☽", "∞"] {
+            state.add_unresolved_symbol(symbol.to_string());
+        }
+
+        let result = native_ritual("symbol_resolution").execute(&mut state).await.unwrap();
+
+        assert_eq!(state.unresolved_symbols, ["☽"]);
+        let resolutions = result
+            .state_changes
+            .iter()
+            .filter(|change| matches!(change.change_type, ChangeType::SymbolResolution))
+            .count();
+        assert_eq!(resolutions, 2);
+        assert_eq!(
+            state.integrations["Meaning of 🌑"].wisdom,
+            interpret_symbol("🌑").unwrap()
+        );
+        assert_eq!(state.integrations["Meaning of ∞"].depth_level, 1);
+        assert!(!state.integrations.contains_key("Meaning of ☽"));
+        assert_eq!(result.emergent_symbols, ["🗝"]);
+    }
+
+    #[tokio::test]
+    async fn test_symbol_resolution_deepens_a_meaning_met_again() {
+        let mut state = SymbolicState::new();
+        state.add_unresolved_symbol("∞".to_string());
+        native_ritual("symbol_resolution").execute(&mut state).await.unwrap();
+
+        state.add_unresolved_symbol("∞".to_string());
+        let result = native_ritual("symbol_resolution").execute(&mut state).await.unwrap();
+
+        assert!(state.unresolved_symbols.is_empty());
+        assert_eq!(state.integrations["Meaning of ∞"].depth_level, 2);
+        assert!(result
+            .state_changes
+            .iter()
+            .any(|change| matches!(change.change_type, ChangeType::Integration)));
+    }
+
+    /// A custom ritual that raises Shadow, then takes `delay` to finish
+    fn slow_ritual(delay: Duration) -> Ritual {
+        native_ritual("slow_ritual").with_native_handler(Arc::new(move |state, result| {
+            state.set_archetype_activation("Shadow", 0.9);
+            std::thread::sleep(delay);
+            result.emergent_symbols.push("⏳".to_string());
+            0.6
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_slow_native_ritual_times_out_without_partial_changes() {
+        let ritual = slow_ritual(Duration::from_millis(500)).with_timeout(Duration::from_millis(50));
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.2);
+        let before = state.clone();
+
+        let start = Instant::now();
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        assert!(start.elapsed() < Duration::from_millis(400));
+        assert!(matches!(result.completion_status, CompletionStatus::Interrupted));
+        assert!(result.symbolic_outputs.contains_key("timeout"));
+        assert!(result.state_changes.is_empty());
+        assert!(result.emergent_symbols.is_empty());
+        // Neither the handler's change nor the transformation bookkeeping leaked through
+        assert!(before.diff(&state).is_empty());
+        assert_eq!(state.active_transformations, before.active_transformations);
+    }
+
+    #[tokio::test]
+    async fn test_native_ritual_within_timeout_commits_changes() {
+        let ritual = slow_ritual(Duration::from_millis(10)).with_timeout(Duration::from_secs(5));
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.2);
+
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        assert!(matches!(result.completion_status, CompletionStatus::Complete));
+        assert_eq!(result.emergent_symbols, ["⏳"]);
+        assert!(state.archetypes["Shadow"].activation_level > 0.2);
+        assert!(!result.state_changes.is_empty());
+    }
+
+    /// Runs a seeded native ritual requiring Void at `void_requirement` on a state
+    /// with the given Shadow activation and Void amplitude, returning the resonance
+    /// and the state it left behind
+    async fn seeded_resonance(
+        name: &str,
+        shadow: f64,
+        void: f64,
+        void_requirement: f64,
+    ) -> (f64, SymbolicState) {
+        let mut ritual = native_ritual(name);
+        ritual.definition.energy_requirements = HashMap::from([("Void".to_string(), void_requirement)]);
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", shadow);
+        state.set_energy_amplitude("Void", void);
+
+        let result = ritual.execute(&mut state).await.unwrap();
+        (result.resonance_level, state)
+    }
+
+    #[tokio::test]
+    async fn test_shadow_integration_resonance_blends_shadow_and_alignment() {
+        let (resonance, state) = seeded_resonance("shadow_integration", 0.2, 0.5, 0.5).await;
+
+        // The ritual's own resonance follows the Shadow it met; Void meets its
+        // requirement exactly and no symbols are unresolved
+        let own = state.archetypes["Shadow"].activation_level * 0.7;
+        assert!((resonance - (own * 0.4 + 1.0 * 0.3 + 1.0 * 0.3)).abs() < 1e-9);
+
+        // Same seed, so only the named input differs between runs
+        let (stronger_shadow, _) = seeded_resonance("shadow_integration", 0.4, 0.5, 0.5).await;
+        assert!(stronger_shadow > resonance);
+        let (misaligned, _) = seeded_resonance("shadow_integration", 0.2, 0.0, 0.5).await;
+        assert!((resonance - misaligned - 0.5 * 0.3).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_resonance_survives_the_blend() {
+        let (resonance, _) = seeded_resonance("energy_attunement", 0.2, 0.5, 0.5).await;
+        assert!((resonance - (0.8 * 0.4 + 1.0 * 0.3 + 1.0 * 0.3)).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_void_contemplation_deepens_void_and_contemplative_archetypes() {
+        let mut state = SymbolicState::new();
+        state.set_energy_amplitude("Void", 0.5);
+        state.set_archetype_activation("Sage", 0.4);
+
+        let result = native_ritual("void_contemplation").execute(&mut state).await.unwrap();
+
+        let void = state.energies["Void"].amplitude;
+        assert!((0.8..=1.0).contains(&void), "Void amplitude {}", void);
+        assert!((state.archetypes["Sage"].activation_level - 0.55).abs() < 1e-9);
+        assert!((state.archetypes["Mystic"].activation_level - 0.2).abs() < 1e-9);
+        assert_eq!(result.emergent_symbols, vec!["○", "∞", "◯", "⚬"]);
+        assert!(result
+            .state_changes
+            .iter()
+            .any(|change| matches!(change.change_type, ChangeType::EnergyShift)
+                && change.description.starts_with("Void")));
+    }
+
+    #[tokio::test]
+    async fn test_shallow_void_contemplation_keeps_to_the_surface_symbols() {
+        let mut state = SymbolicState::new();
+        state.set_energy_amplitude("Void", 0.0);
+
+        let result = native_ritual("void_contemplation").execute(&mut state).await.unwrap();
+
+        assert!(state.energies["Void"].amplitude <= 0.5);
+        assert_eq!(result.emergent_symbols, vec!["○", "∞"]);
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_shifts_balanced_polarities() {
+        let mut state = SymbolicState::new();
+        for (name, amplitude) in [("Fire", 0.5), ("Water", 0.55), ("Earth", 0.5), ("Air", 1.0)] {
+            state.set_energy_amplitude(name, amplitude);
+        }
+
+        native_ritual("energy_attunement").execute(&mut state).await.unwrap();
+
+        // Balance sits at 0.6375: Fire and Water settle near it, Air is still far above
+        // it and its excess holds its opposite Earth down
+        for name in ["Fire", "Water"] {
+            assert_eq!(state.energies[name].polarity, Polarity::Oscillating, "{}", name);
+        }
+        for name in ["Earth", "Air"] {
+            assert_eq!(state.energies[name].polarity, Polarity::Neutral, "{}", name);
+        }
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: SymbolicState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.energies["Fire"].polarity, Polarity::Oscillating);
+    }
+
+    fn attunement_state() -> SymbolicState {
+        // Air is missing, so the balance point sits below the present energies' mean
+        let mut state = SymbolicState::new();
+        for (name, amplitude) in [("Fire", 0.9), ("Water", 0.6), ("Earth", 0.3)] {
+            state.set_energy_amplitude(name, amplitude);
+        }
+        state
+    }
+
+    fn total_amplitude(state: &SymbolicState) -> f64 {
+        state.energies.values().map(|energy| energy.amplitude).sum()
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_conserves_total_when_asked() {
+        let mut state = attunement_state();
+        let before = total_amplitude(&state);
+
+        let mut ritual = native_ritual("energy_attunement");
+        ritual.definition.parameters.insert(CONSERVE_TOTAL_PARAMETER.to_string(), serde_json::json!(true));
+        ritual.execute(&mut state).await.unwrap();
+
+        assert!((total_amplitude(&state) - before).abs() < 1e-9);
+        // The energies still drew together
+        assert!(state.energies["Fire"].amplitude < 0.9);
+        assert!(state.energies["Earth"].amplitude > 0.3);
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_total_may_change_without_conservation() {
+        let mut state = attunement_state();
+        let before = total_amplitude(&state);
+
+        native_ritual("energy_attunement").execute(&mut state).await.unwrap();
+
+        assert!((total_amplitude(&state) - before).abs() > 1e-3);
+    }
+
+    fn elemental_state(amplitudes: [f64; 4]) -> SymbolicState {
+        let mut state = SymbolicState::new();
+        for (name, amplitude) in ["Fire", "Water", "Earth", "Air"].into_iter().zip(amplitudes) {
+            state.set_energy_amplitude(name, amplitude);
+        }
+        state
+    }
+
+    fn attunement_with_coupling(coupling: f64) -> Ritual {
+        let mut ritual = native_ritual("energy_attunement");
+        ritual.definition.parameters.insert(OPPOSITION_COUPLING_PARAMETER.to_string(), serde_json::json!(coupling));
+        ritual
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_fire_spike_damps_water() {
+        let mut coupled = elemental_state([1.0, 0.5, 0.5, 0.5]);
+        native_ritual("energy_attunement").execute(&mut coupled).await.unwrap();
+        let mut uncoupled = elemental_state([1.0, 0.5, 0.5, 0.5]);
+        attunement_with_coupling(0.0).execute(&mut uncoupled).await.unwrap();
+
+        // Water and Earth started level, but only Water faces the spike
+        assert!(coupled.energies["Water"].amplitude < uncoupled.energies["Water"].amplitude);
+        assert!(coupled.energies["Water"].amplitude < coupled.energies["Earth"].amplitude);
+        assert!(coupled.energies["Fire"].amplitude < 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_converges_over_repeated_runs() {
+        let spread = |state: &SymbolicState| {
+            let amplitudes: Vec<f64> =
+                ["Fire", "Water", "Earth", "Air"].iter().map(|name| state.energies[*name].amplitude).collect();
+            amplitudes.iter().cloned().fold(f64::MIN, f64::max) - amplitudes.iter().cloned().fold(f64::MAX, f64::min)
+        };
+
+        // An oversized coupling is clamped rather than left to drive the energies apart
+        for coupling in [DEFAULT_OPPOSITION_COUPLING, MAX_OPPOSITION_COUPLING, 5.0] {
+            let mut state = elemental_state([1.0, 0.0, 0.9, 0.1]);
+            let ritual = attunement_with_coupling(coupling);
+            let mut previous = spread(&state);
+            for _ in 0..100 {
+                ritual.execute(&mut state).await.unwrap();
+                let current = spread(&state);
+                assert!(current <= previous + 1e-12, "coupling {} spread grew to {}", coupling, current);
+                assert!(state.energies.values().all(|e| (0.0..=1.0).contains(&e.amplitude)));
+                previous = current;
+            }
+            assert!(previous < 1e-3, "coupling {} left a spread of {}", coupling, previous);
+        }
+
+        // Conservation at the strongest coupling keeps every energy in range, even
+        // when the opposition step pins one at a bound
+        for amplitudes in [[0.0, 1.0, 0.0, 0.0], [1.0, 0.0, 1.0, 1.0]] {
+            let mut state = elemental_state(amplitudes);
+            let before = total_amplitude(&state);
+            let mut ritual = attunement_with_coupling(MAX_OPPOSITION_COUPLING);
+            ritual.definition.parameters.insert(CONSERVE_TOTAL_PARAMETER.to_string(), serde_json::json!(true));
+            for _ in 0..100 {
+                ritual.execute(&mut state).await.unwrap();
+                assert!(
+                    state.energies.values().all(|e| (0.0..=1.0).contains(&e.amplitude)),
+                    "{:?} left the range", amplitudes
+                );
+                assert!((total_amplitude(&state) - before).abs() < 1e-9);
+            }
+            assert!(spread(&state) < 1e-3, "{:?} left a spread of {}", amplitudes, spread(&state));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_marks_dominant_elements() {
+        let mut state = SymbolicState::new();
+        for (name, amplitude) in [("Fire", 1.0), ("Water", 0.2), ("Earth", 0.2), ("Air", 0.2)] {
+            state.set_energy_amplitude(name, amplitude);
+        }
+
+        let result = native_ritual("energy_attunement").execute(&mut state).await.unwrap();
+
+        // Fire settles at 0.84, still above the threshold
+        assert_eq!(result.emergent_symbols, vec!["∿∿∿", "⚡", "🔥"]);
+    }
+
+    #[tokio::test]
+    async fn test_energy_attunement_balanced_low_state_keeps_base_symbols() {
+        let mut state = SymbolicState::new();
+        for name in ["Fire", "Water", "Earth", "Air", "Void"] {
+            state.set_energy_amplitude(name, 0.3);
+        }
+
+        let result = native_ritual("energy_attunement").execute(&mut state).await.unwrap();
+
+        assert_eq!(result.emergent_symbols, vec!["∿∿∿", "⚡"]);
+    }
+
+    #[test]
+    fn test_nearest_solfeggio_tone_folds_octaves() {
+        assert_eq!(nearest_solfeggio_tone(530.0), 528.0);
+        assert_eq!(nearest_solfeggio_tone(1050.0), 1056.0);
+        // 3.5 Hz is closest to 852 Hz eight octaves down
+        assert_eq!(nearest_solfeggio_tone(3.5), 852.0 / 256.0);
+    }
+
+    #[tokio::test]
+    async fn test_frequency_tuning_draws_frequencies_toward_solfeggio_tones() {
+        let mut state = SymbolicState::new();
+        state.add_energy(Energy::new("Fire".to_string(), 540.0, Element::Fire));
+        state.add_energy(Energy::new("Water".to_string(), 400.0, Element::Water));
+        state.add_energy(Energy::new("Void".to_string(), 0.0, Element::Void));
+        let before = state.clone();
+
+        let result = native_ritual("frequency_tuning").execute(&mut state).await.unwrap();
+
+        assert_eq!(state.energies["Fire"].frequency, 534.0);
+        assert_eq!(state.energies["Water"].frequency, 398.0);
+        for name in ["Fire", "Water"] {
+            assert!(state.energies[name].last_shifted > before.energies[name].last_shifted);
+        }
+        // A silent energy is left alone
+        assert_eq!(state.energies["Void"].frequency, 0.0);
+        assert_eq!(state.energies["Void"].last_shifted, before.energies["Void"].last_shifted);
+
+        let shifts: Vec<_> = result
+            .state_changes
+            .iter()
+            .filter(|change| matches!(change.change_type, ChangeType::EnergyShift))
+            .collect();
+        assert_eq!(shifts.len(), 2);
+        assert!(result.emergent_symbols.contains(&"𝄞".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_frequency_tuning_resonance_rises_as_energies_come_into_tune() {
+        let tuned_resonance = |frequency: f64| async move {
+            let mut state = SymbolicState::new();
+            state.add_energy(Energy::new("Fire".to_string(), frequency, Element::Fire));
+            native_ritual("frequency_tuning")
+                .execute(&mut state)
+                .await
+                .unwrap()
+                .resonance_level
+        };
+
+        let nearly_in_tune = tuned_resonance(530.0).await;
+        let far_out_of_tune = tuned_resonance(600.0).await;
+        assert!(nearly_in_tune > far_out_of_tune, "{} <= {}", nearly_in_tune, far_out_of_tune);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_shadow_integration_deepens_its_integration() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.2);
+
+        native_ritual("shadow_integration").execute(&mut state).await.unwrap();
+        let integration = &state.integrations[SHADOW_INTEGRATION];
+        assert_eq!(integration.depth_level, 1);
+        assert_eq!(integration.embodiment_status, EmbodimentStatus::Conceptual);
+        assert_eq!(integration.archetypes_involved, vec![state.archetypes["Shadow"].id]);
+
+        let result = native_ritual("shadow_integration").execute(&mut state).await.unwrap();
+        assert_eq!(state.integrations.len(), 1);
+        let integration = &state.integrations[SHADOW_INTEGRATION];
+        assert_eq!(integration.depth_level, 2);
+        assert_eq!(integration.embodiment_status, EmbodimentStatus::Emotional);
+        assert!(result.state_changes.iter().any(|change| {
+            matches!(change.change_type, ChangeType::Integration) && change.description.contains("deepened to level 2")
+        }));
+
+        // Embodiment only moves on every DEPTH_LEVELS_PER_EMBODIMENT levels
+        native_ritual("shadow_integration").execute(&mut state).await.unwrap();
+        let integration = &state.integrations[SHADOW_INTEGRATION];
+        assert_eq!(integration.depth_level, 3);
+        assert_eq!(integration.embodiment_status, EmbodimentStatus::Emotional);
+    }
+
+    #[tokio::test]
+    async fn test_shadow_integration_records_a_named_aspect() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.2);
+        let mut ritual = native_ritual("shadow_integration");
+        ritual.definition.parameters.insert(ASPECT_PARAMETER.to_string(), serde_json::json!("Pride"));
+
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        assert_eq!(state.archetypes["Shadow"].shadow_aspects, vec!["Pride"]);
+        assert!(state.archetypes["Shadow"].light_aspects.is_empty());
+        assert_eq!(result.symbolic_outputs["integrated_aspect"], serde_json::json!("Pride"));
+        assert!(result.state_changes.iter().any(|change| change.description == "Shadow aspect 'Pride' integrated"));
+
+        // Meeting the same aspect again does not record it twice
+        ritual.execute(&mut state).await.unwrap();
+        assert_eq!(state.archetypes["Shadow"].shadow_aspects, vec!["Pride"]);
+    }
+
+    #[tokio::test]
+    async fn test_shadow_integration_without_aspect_records_none() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.2);
+
+        let result = native_ritual("shadow_integration").execute(&mut state).await.unwrap();
+
+        assert!(state.archetypes["Shadow"].shadow_aspects.is_empty());
+        assert!(!result.symbolic_outputs.contains_key("integrated_aspect"));
+    }
+
+    #[tokio::test]
+    async fn test_archetype_invocation_boosts_all_eight_archetypes() {
+        let mut state = SymbolicState::new();
+        for archetype in INVOKED_ARCHETYPES {
+            state.set_archetype_activation(archetype, 0.3);
+        }
+
+        let result = native_ritual("archetype_invocation").execute(&mut state).await.unwrap();
+
+        let boost = state.archetypes["Sage"].activation_level - 0.3;
+        assert!((0.1..=0.2).contains(&boost), "boost {}", boost);
+        for archetype in INVOKED_ARCHETYPES {
+            assert!((state.archetypes[archetype].activation_level - 0.3 - boost).abs() < 1e-9);
+        }
+        assert_eq!(result.emergent_symbols, vec!["🔮", "∆∇∆"]);
+        let activations = result
+            .state_changes
+            .iter()
+            .filter(|change| matches!(change.change_type, ChangeType::ArchetypeActivation))
+            .count();
+        assert_eq!(activations, INVOKED_ARCHETYPES.len());
+    }
+
+    #[tokio::test]
+    async fn test_seeded_shadow_integration_is_deterministic() {
+        let mut starting_state = SymbolicState::new();
+        starting_state.set_archetype_activation("Shadow", 0.3);
+
+        let mut first_state = starting_state.clone();
+        let first = shadow_integration(7).execute(&mut first_state).await.unwrap();
+        let mut second_state = starting_state.clone();
+        let second = shadow_integration(7).execute(&mut second_state).await.unwrap();
+
+        assert_eq!(first.resonance_level, second.resonance_level);
+        assert_eq!(first.emergent_symbols, second.emergent_symbols);
+        assert_eq!(
+            first_state.archetypes["Shadow"].activation_level,
+            second_state.archetypes["Shadow"].activation_level
+        );
+
+        let mut other_state = starting_state.clone();
+        shadow_integration(8).execute(&mut other_state).await.unwrap();
+        assert_ne!(
+            first_state.archetypes["Shadow"].activation_level,
+            other_state.archetypes["Shadow"].activation_level
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wasm_get_random_follows_seed() {
+        // Stores the host's random draw as Shadow's activation level
+        const RANDOM_WAT: &str = r#"(module
+            (import "codex" "get_random" (func $get_random (result f64)))
+            (import "codex" "set_archetype_activation"
+                (func $set_activation (param i32 i32 f64)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "Shadow")
+            (func (export "execute_ritual") (result i32)
+                (call $set_activation (i32.const 0) (i32.const 6) (call $get_random))
+                (i32.const 0)))"#;
+        let draw = |seed| async move {
+            let mut state = SymbolicState::new();
+            test_ritual(RANDOM_WAT)
+                .with_seed(Some(seed))
+                .execute_wasm_ritual(&mut state, Uuid::new_v4())
+                .await
+                .unwrap();
+            state.archetypes["Shadow"].activation_level
+        };
+
+        assert_eq!(draw(42).await, draw(42).await);
+        assert_ne!(draw(42).await, draw(43).await);
+    }
+
+    #[tokio::test]
+    async fn test_wasm_branches_on_evolution_cycle() {
+        // Emits "🌱" for a newcomer and "🌳" once past the fifth cycle
+        const CYCLE_WAT: &str = r#"(module
+            (import "codex" "get_evolution_cycle" (func $get_evolution_cycle (result i32)))
+            (import "codex" "add_symbol" (func $add_symbol (param i32 i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "🌱")
+            (data (i32.const 4) "🌳")
+            (func (export "execute_ritual") (result i32)
+                (call $get_evolution_cycle)
+                (i32.const 5)
+                (i32.gt_s)
+                (if
+                    (then (call $add_symbol (i32.const 4) (i32.const 4)))
+                    (else (call $add_symbol (i32.const 0) (i32.const 4))))
+                (i32.const 0)))"#;
+        let symbols_at = |cycle| async move {
+            let mut state = SymbolicState::new();
+            state.evolution_cycle = cycle;
+            test_ritual(CYCLE_WAT)
+                .execute_wasm_ritual(&mut state, Uuid::new_v4())
+                .await
+                .unwrap()
+                .emergent_symbols
+        };
+
+        assert_eq!(symbols_at(0).await, vec!["🌱".to_string()]);
+        assert_eq!(symbols_at(6).await, vec!["🌳".to_string()]);
+    }
+
+    fn gated_shadow_integration(minimum: f64) -> Ritual {
+        let mut ritual = shadow_integration(7);
+        ritual.definition.min_archetype_resonance = Some(minimum);
+        ritual
+    }
+
+    #[tokio::test]
+    async fn test_gated_ritual_below_threshold_is_refused_without_changes() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.2);
+        let before = state.clone();
+
+        let result = gated_shadow_integration(0.5).execute(&mut state).await.unwrap();
+
+        assert!(matches!(
+            &result.completion_status,
+            CompletionStatus::PrerequisitesNotMet { missing } if missing == &["Shadow"]
+        ));
+        assert_eq!(result.resonance_level, 0.0);
+        assert!(result.state_changes.is_empty());
+        let explanation = result.symbolic_outputs["unmet_prerequisites"].as_str().unwrap();
+        assert!(explanation.contains("0.200 is below the 0.500"), "{}", explanation);
+        assert!(before.diff(&state).is_empty());
+        assert_eq!(state.active_transformations, before.active_transformations);
+    }
+
+    #[tokio::test]
+    async fn test_gated_ritual_above_threshold_runs() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.6);
+
+        let result = gated_shadow_integration(0.5).execute(&mut state).await.unwrap();
+
+        assert!(matches!(result.completion_status, CompletionStatus::Complete));
+        assert!(!result.symbolic_outputs.contains_key("unmet_prerequisites"));
+        assert!(state.archetypes["Shadow"].activation_level > 0.6);
+    }
+
+    #[tokio::test]
+    async fn test_faint_or_absent_archetypes_are_listed_as_missing() {
+        let mut ritual = shadow_integration(7);
+        ritual.definition.required_archetypes =
+            vec!["Sage".to_string(), "Creator".to_string(), "Shadow".to_string()];
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Sage", 0.8);
+        state.set_archetype_activation("Creator", 0.1);
+
+        let result = ritual.execute(&mut state).await.unwrap();
+
+        // The ritual still runs, but reports exactly which archetypes held it back
+        match &result.completion_status {
+            CompletionStatus::PrerequisitesNotMet { missing } => {
+                assert_eq!(missing, &["Creator", "Shadow"]);
+            }
+            other => panic!("expected unmet prerequisites, got {:?}", other),
+        }
+        assert!(!result.state_changes.is_empty());
+
+        state.set_archetype_activation("Creator", ARCHETYPE_PREREQUISITE_THRESHOLD);
+        state.set_archetype_activation("Shadow", 0.5);
+        let result = ritual.execute(&mut state).await.unwrap();
+        assert!(matches!(result.completion_status, CompletionStatus::Complete));
+    }
+
+    #[test]
+    fn test_invoke_prerequisites_raises_only_faint_archetypes() {
+        let mut ritual = gated_shadow_integration(0.5);
+        ritual.definition.required_archetypes = vec!["Sage".to_string(), "Creator".to_string(), "Magician".to_string()];
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Sage", 0.8);
+        state.set_archetype_activation("Creator", 0.2);
+
+        let steps = ritual.invoke_prerequisites(&mut state);
+
+        assert_eq!(steps, vec!["Invoked Creator from 0.20 to 0.50", "Invoked Magician from 0.00 to 0.50"]);
+        assert_eq!(state.archetypes["Sage"].activation_level, 0.8);
+        assert_eq!(state.archetypes["Creator"].activation_level, 0.5);
+        assert_eq!(state.archetypes["Magician"].activation_level, 0.5);
+        assert!(ritual.invoke_prerequisites(&mut state).is_empty());
+    }
+
+    fn definition() -> RitualDefinition {
+        RitualDefinition {
+            name: "dawn_attunement".to_string(),
+            description: "Greet the rising light".to_string(),
+            intent: "Balance fire and air".to_string(),
+            required_archetypes: vec!["Sage".to_string(), "Creator".to_string()],
+            energy_requirements: HashMap::from([
+                ("Fire".to_string(), 0.6),
+                ("Air".to_string(), 0.0),
+            ]),
+            wasm_module_path: None,
+            native_handler: None,
+            parameters: HashMap::new(),
+            wasm_limits: WasmLimits::default(),
+            archetype_synergies: HashMap::new(),
+            min_archetype_resonance: None,
+            parameter_schema: HashMap::new(),
+            difficulty: Difficulty::Beginner,
+            resonance_weights: ResonanceWeights::default(),
+            extends: None,
+        }
+    }
+
+    fn invalid_reason(definition: &RitualDefinition) -> String {
+        match definition.validate() {
+            Err(CodexError::InvalidRitual { reason, .. }) => reason,
+            other => panic!("expected an invalid ritual, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_well_formed_definition() {
+        assert!(definition().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_name() {
+        let mut definition = definition();
+        definition.name = "  ".to_string();
+
+        assert_eq!(invalid_reason(&definition), "name must not be empty");
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_energy_requirements() {
+        let mut definition = definition();
+        definition.energy_requirements.insert("Water".to_string(), 1.5);
+        assert!(invalid_reason(&definition).contains("'Water' is 1.5"));
+
+        definition.energy_requirements.insert("Water".to_string(), -0.1);
+        assert!(invalid_reason(&definition).contains("'Water' is -0.1"));
+
+        definition.energy_requirements.insert("Water".to_string(), f64::NAN);
+        assert!(invalid_reason(&definition).contains("'Water' is NaN"));
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_resonance_gate() {
+        let mut definition = definition();
+        definition.min_archetype_resonance = Some(1.5);
+
+        assert_eq!(
+            invalid_reason(&definition),
+            "minimum archetype resonance is 1.5, expected a value between 0 and 1"
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_resonance_weights_not_summing_to_one() {
+        let mut definition = definition();
+        definition.resonance_weights.energy_alignment = 0.5;
+        assert_eq!(
+            invalid_reason(&definition),
+            "resonance weights sum to 1.2, expected 1"
+        );
+
+        definition.resonance_weights = ResonanceWeights {
+            base: 1.2,
+            energy_alignment: -0.1,
+            symbol_coherence: -0.1,
+        };
+        assert!(invalid_reason(&definition).contains("'base' is 1.2"));
+    }
+
+    #[test]
+    fn test_resonance_weights_default_when_omitted() {
+        let definition: RitualDefinition = serde_json::from_value(serde_json::json!({
+            "name": "dusk",
+            "description": "Evening rite",
+            "intent": "Rest",
+            "wasm_module_path": null,
+            "native_handler": null,
+            "resonance_weights": { "base": 0.2, "energy_alignment": 0.5 },
+        }))
+        .unwrap();
+
+        assert_eq!(definition.resonance_weights.symbol_coherence, 0.3);
+        assert!(definition.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_required_archetypes() {
+        let mut definition = definition();
+        definition.required_archetypes.push("Sage".to_string());
+
+        assert_eq!(
+            invalid_reason(&definition),
+            "archetype 'Sage' is required more than once"
+        );
     }
 }
@@ -1,12 +1,20 @@
 use axum::{
-    response::Json,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use std::{net::SocketAddr, sync::Arc};
 use tower_http::cors::CorsLayer;
 
-use codex_control_engine::{auth, handlers, CodexEngine};
+use codex_control_engine::{
+    api_key_crypto, auth, database, handlers,
+    limits::RequestLimits,
+    locks::PractitionerLocks,
+    models,
+    ratelimit::{self, AuthRateLimiters},
+    retention,
+    streams::StateStreams,
+    CodexEngine,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -16,41 +24,112 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
-    // Database connection
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "postgresql://localhost/codex_sacred".to_string());
+    // Refuse to start without a token signing secret
+    auth::init_jwt_keys()?;
+
+    // Derive the key that seals practitioners' own API keys once, if one is configured
+    match api_key_crypto::api_key_sealer() {
+        Ok(_) | Err(api_key_crypto::ApiKeyError::NotConfigured(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
 
-    let db = sqlx::PgPool::connect(&database_url).await?;
+    // Database connection: Postgres by default, SQLite for `sqlite:` URLs
+    let db = database::connect_database().await?;
 
-    // Run migrations
-    sqlx::migrate!("./migrations").run(&db).await?;
+    // Check the schema before serving, migrating it unless CODEX_AUTO_MIGRATE=false,
+    // and refuse to start against one this build does not match
+    if let Err(e) = database::prepare_schema(&db, database::auto_migrate_from_env()).await {
+        tracing::error!("{}", e);
+        return Err(e.into());
+    }
 
     // Initialize the sacred engine
     let engine = Arc::new(CodexEngine::new()?);
 
-    let app_state = handlers::AppState { db, engine };
+    let app_state = handlers::AppState {
+        db,
+        engine,
+        state_streams: StateStreams::default(),
+        auth_rate_limiters: AuthRateLimiters::from_env(),
+        practitioner_locks: PractitionerLocks::default(),
+        ritual_batch_limit: models::ritual_batch_limit_from_env(),
+    };
+
+    // Keep state histories bounded when a retention is configured
+    if let Some(keep_last) = retention::state_retention_from_env() {
+        retention::spawn_scheduled_pruning(
+            app_state.db.clone(),
+            app_state.practitioner_locks.clone(),
+            keep_last,
+            retention::STATE_RETENTION_INTERVAL,
+        );
+    }
+
+    // Ritual uploads carry WASM modules and get a larger body limit than the rest
+    let upload_routes = Router::new()
+        .route("/api/rituals/upload", post(handlers::upload_ritual)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)));
 
     // Build sacred API routes
-    let app = Router::new()
-        .route("/api/health", get(health_check))
-        .route("/api/users/register", post(handlers::register_user))
-        .route("/api/users/login", post(handlers::login_user))
+    let api_routes = Router::new()
+        .route("/api/health", get(handlers::health_ready))
+        .route("/api/health/live", get(handlers::health_live))
+        .route("/api/health/ready", get(handlers::health_ready))
+        .route("/api/users/register", post(handlers::register_user)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.auth_rate_limiters.register.clone(), ratelimit::rate_limit_middleware)))
+        .route("/api/users/login", post(handlers::login_user)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.auth_rate_limiters.login.clone(), ratelimit::rate_limit_middleware)))
+        .route("/api/users/refresh", post(handlers::refresh_token))
+        .route("/api/users/logout", post(handlers::logout_user))
+        .route("/api/users/me", delete(handlers::delete_account)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/users/restore", post(handlers::restore_account)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.auth_rate_limiters.restore.clone(), ratelimit::rate_limit_middleware)))
         .route("/api/users/profile", get(handlers::get_profile)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/users/reflection-preferences", get(handlers::get_reflection_preferences)
+            .put(handlers::update_reflection_preferences)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
         .route("/api/rituals/execute", post(handlers::execute_ritual)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/rituals/execute-batch", post(handlers::execute_ritual_batch)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
         .route("/api/rituals/catalog", get(handlers::get_ritual_catalog))
-        .route("/api/rituals/upload", post(handlers::upload_ritual)
+        .route("/api/rituals/search", get(handlers::search_rituals))
+        .route("/api/rituals/:id", get(handlers::get_ritual_details).merge(
+            delete(handlers::delete_ritual)
+                .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware))))
+        .route("/api/rituals/:id/rate", post(handlers::rate_ritual)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
-        .route("/api/rituals/:id", get(handlers::get_ritual_details))
         .route("/api/state/current", get(handlers::get_current_state)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
         .route("/api/state/transform", post(handlers::transform_state)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/state/stream", get(handlers::stream_state)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
         .route("/api/state/history", get(handlers::get_state_history)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/state/prune", post(handlers::prune_state)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/state/audit", get(handlers::get_state_audit)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/state/compare", get(handlers::compare_states)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/state/replay", post(handlers::replay_state)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
         .route("/api/state/reflection", post(handlers::request_reflection)
             .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/insights", get(handlers::list_insights)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/insights/similar", get(handlers::get_similar_insights)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        .route("/api/stats/symbols", get(handlers::get_symbol_stats)
+            .route_layer(axum::middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware)))
+        // Authenticates its own `?token=` before upgrading
+        .route("/api/ws", get(handlers::ws_session));
+
+    let app = RequestLimits::from_env()
+        .apply(api_routes, upload_routes)
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
@@ -66,15 +145,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("✨ May this technology serve the highest good");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Client addresses feed the per-IP rate limiter
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
-
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "Sacred systems operational",
-        "version": env!("CARGO_PKG_VERSION"),
-        "message": "🔮 The Codex Control Engine serves"
-    }))
-}
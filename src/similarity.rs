@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+
+/// Lowercase alphanumeric words of at least three characters
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Term weights for one document: term frequency scaled by inverse document frequency
+fn tf_idf(terms: &[String], idf: &HashMap<&str, f64>) -> HashMap<String, f64> {
+    let mut weights: HashMap<String, f64> = HashMap::new();
+    for term in terms {
+        *weights.entry(term.clone()).or_default() += 1.0;
+    }
+    for (term, weight) in weights.iter_mut() {
+        *weight *= idf.get(term.as_str()).copied().unwrap_or(0.0);
+    }
+    weights
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a
+        .iter()
+        .filter_map(|(term, weight)| b.get(term).map(|other| weight * other))
+        .sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|w| w * w).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Ranks `documents` by TF-IDF cosine similarity to `query`, most similar first,
+/// returning each document's index with its score in `0.0..=1.0`
+pub fn rank_by_similarity(query: &str, documents: &[String]) -> Vec<(usize, f64)> {
+    let query_terms = tokenize(query);
+    let document_terms: Vec<Vec<String>> = documents.iter().map(|doc| tokenize(doc)).collect();
+
+    // Smoothed IDF over the documents plus the query, so shared filler words weigh little
+    let corpus_size = (documents.len() + 1) as f64;
+    let mut document_frequency: HashMap<&str, f64> = HashMap::new();
+    for terms in document_terms.iter().chain(std::iter::once(&query_terms)) {
+        let unique: HashSet<&str> = terms.iter().map(String::as_str).collect();
+        for term in unique {
+            *document_frequency.entry(term).or_default() += 1.0;
+        }
+    }
+    let idf: HashMap<&str, f64> = document_frequency
+        .into_iter()
+        .map(|(term, frequency)| (term, ((1.0 + corpus_size) / (1.0 + frequency)).ln() + 1.0))
+        .collect();
+
+    let query_vector = tf_idf(&query_terms, &idf);
+    let mut ranked: Vec<(usize, f64)> = document_terms
+        .iter()
+        .map(|terms| cosine(&query_vector, &tf_idf(terms, &idf)))
+        .enumerate()
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_similar_document_ranks_first() {
+        let documents = vec![
+            "Fire energy fuels creative expression".to_string(),
+            "The shadow holds rejected fear and anger".to_string(),
+            "Water energy invites emotional flow".to_string(),
+        ];
+
+        let ranked = rank_by_similarity("Facing the shadow brings fear to light", &documents);
+
+        assert_eq!(ranked[0].0, 1);
+        assert!(ranked[0].1 > ranked[1].1);
+        assert!(ranked
+            .iter()
+            .all(|(_, score)| (0.0..=1.0 + 1e-9).contains(score)));
+    }
+
+    #[test]
+    fn test_identical_text_scores_one_and_unrelated_scores_zero() {
+        let documents = vec!["void stillness".to_string(), "solar fire".to_string()];
+
+        let ranked = rank_by_similarity("Void stillness", &documents);
+
+        assert_eq!(ranked[0].0, 0);
+        assert!((ranked[0].1 - 1.0).abs() < 1e-9);
+        assert_eq!(ranked[1].1, 0.0);
+    }
+
+    #[test]
+    fn test_empty_query_matches_nothing() {
+        let ranked = rank_by_similarity("", &["shadow work".to_string()]);
+        assert_eq!(ranked, vec![(0, 0.0)]);
+    }
+}
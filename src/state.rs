@@ -1,6 +1,8 @@
+use crate::CodexError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use uuid::Uuid;
 
 /// Represents an archetypal force within the psyche
@@ -36,6 +38,13 @@ impl Archetype {
         self.evolution_count += 1;
     }
 
+    /// Sets the activation to an absolute level, clamped the same way as `invoke`
+    pub fn set_activation(&mut self, level: f64) {
+        self.activation_level = level.clamp(0.0, 1.0);
+        self.last_invoked = Some(Utc::now());
+        self.evolution_count += 1;
+    }
+
     pub fn integrate_aspect(&mut self, aspect: String, is_shadow: bool) {
         if is_shadow {
             self.shadow_aspects.push(aspect);
@@ -57,7 +66,7 @@ pub struct Energy {
     pub last_shifted: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Polarity {
     Positive,
     Negative,
@@ -65,7 +74,7 @@ pub enum Polarity {
     Oscillating,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Element {
     Fire,
     Water,
@@ -76,9 +85,72 @@ pub enum Element {
     Shadow,
 }
 
+/// How far, in cents, an energy may sit from its element's canonical frequency and
+/// still belong to the element's band (a minor third either way)
+pub const FREQUENCY_BAND_HALF_WIDTH_CENTS: f64 = 300.0;
+
+impl Element {
+    /// The frequency in Hz an energy of this element naturally sounds at, taken from
+    /// the Solfeggio tones; the Void's is the near-silence of stillness
+    pub fn canonical_frequency(&self) -> f64 {
+        match self {
+            Element::Earth => 174.0,
+            Element::Water => 396.0,
+            Element::Fire => 528.0,
+            Element::Air => 741.0,
+            Element::Light => 963.0,
+            Element::Shadow => 111.0,
+            Element::Void => 0.1,
+        }
+    }
+
+    /// The symbol a strong energy of this element leaves, in native rituals and in the
+    /// symbols the web API reports alike
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Element::Fire => "🔥",
+            Element::Water => "💧",
+            Element::Earth => "⛰",
+            Element::Air => "🜁",
+            Element::Void => "○",
+            Element::Light => "✨",
+            Element::Shadow => "🌘",
+        }
+    }
+
+    /// The frequencies in Hz that belong to this element: within
+    /// `FREQUENCY_BAND_HALF_WIDTH_CENTS` of its canonical frequency, reaching down to
+    /// silence for the Void
+    pub fn frequency_band(&self) -> RangeInclusive<f64> {
+        let center = self.canonical_frequency();
+        let spread = 2f64.powf(FREQUENCY_BAND_HALF_WIDTH_CENTS / 1200.0);
+        let low = if *self == Element::Void {
+            0.0
+        } else {
+            center / spread
+        };
+        low..=center * spread
+    }
+
+    /// Maps an energy name onto its element, defaulting to the Void
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "Fire" => Element::Fire,
+            "Water" => Element::Water,
+            "Earth" => Element::Earth,
+            "Air" => Element::Air,
+            "Light" => Element::Light,
+            "Shadow" => Element::Shadow,
+            _ => Element::Void,
+        }
+    }
+}
+
 impl Energy {
+    /// Creates an energy at half amplitude, warning if the frequency lies outside its
+    /// element's band
     pub fn new(name: String, frequency: f64, element: Element) -> Self {
-        Self {
+        let energy = Self {
             id: Uuid::new_v4(),
             name,
             frequency,
@@ -86,7 +158,34 @@ impl Energy {
             polarity: Polarity::Neutral,
             elemental_association: element,
             last_shifted: Utc::now(),
+        };
+        if let Some(warning) = energy.validate_frequency() {
+            tracing::warn!("{}", warning);
+        }
+        energy
+    }
+
+    /// An energy of `element` at the element's canonical frequency
+    pub fn attuned(name: String, element: Element) -> Self {
+        Self::new(name, element.canonical_frequency(), element)
+    }
+
+    /// Describes how the frequency strays from its element's band, or `None` if it
+    /// lies within it
+    pub fn validate_frequency(&self) -> Option<String> {
+        let band = self.elemental_association.frequency_band();
+        if band.contains(&self.frequency) {
+            return None;
         }
+
+        Some(format!(
+            "Energy '{}' sounds at {} Hz, outside the {:?} band of {:.1}-{:.1} Hz",
+            self.name,
+            self.frequency,
+            self.elemental_association,
+            band.start(),
+            band.end()
+        ))
     }
 
     pub fn modulate(&mut self, frequency_shift: f64, amplitude_shift: f64) {
@@ -94,6 +193,23 @@ impl Energy {
         self.amplitude = (self.amplitude + amplitude_shift).clamp(0.0, 1.0);
         self.last_shifted = Utc::now();
     }
+
+    /// Moves the polarity one step toward `toward`, passing through `Neutral`
+    /// between any two other polarities. Returns whether it changed; an energy
+    /// already at `toward` is left untouched.
+    pub fn shift_polarity(&mut self, toward: Polarity) -> bool {
+        if self.polarity == toward {
+            return false;
+        }
+
+        self.polarity = if self.polarity == Polarity::Neutral || toward == Polarity::Neutral {
+            toward
+        } else {
+            Polarity::Neutral
+        };
+        self.last_shifted = Utc::now();
+        true
+    }
 }
 
 /// Represents integrated wisdom and realizations
@@ -108,7 +224,24 @@ pub struct Integration {
     pub embodiment_status: EmbodimentStatus,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How many depth levels an integration gains before its embodiment moves on a stage
+pub const DEPTH_LEVELS_PER_EMBODIMENT: u8 = 2;
+
+/// Evolution cycles at which a new archetype awakens, with that archetype and its essence
+pub const EVOLUTION_MILESTONES: [(u32, &str, &str); 3] = [
+    (5, "Mystic", "The seer of the unity beneath all forms"),
+    (10, "Magician", "The will that turns vision into form"),
+    (
+        25,
+        "Ruler",
+        "The sovereign who brings order to the inner realm",
+    ),
+];
+
+/// Activation an archetype awakened by an evolution milestone starts with
+pub const MILESTONE_ARCHETYPE_ACTIVATION: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EmbodimentStatus {
     Conceptual,
     Emotional,
@@ -133,6 +266,18 @@ impl Integration {
     pub fn deepen(&mut self, levels: u8) {
         self.depth_level = (self.depth_level + levels).min(10);
     }
+
+    /// Moves the embodiment on to its next stage; `Transcendent` is the last
+    pub fn advance_embodiment(&mut self) {
+        self.embodiment_status = match self.embodiment_status {
+            EmbodimentStatus::Conceptual => EmbodimentStatus::Emotional,
+            EmbodimentStatus::Emotional => EmbodimentStatus::Energetic,
+            EmbodimentStatus::Energetic => EmbodimentStatus::Physical,
+            EmbodimentStatus::Physical | EmbodimentStatus::Transcendent => {
+                EmbodimentStatus::Transcendent
+            }
+        };
+    }
 }
 
 /// The complete symbolic state of the being
@@ -145,6 +290,8 @@ pub struct SymbolicState {
     pub active_transformations: Vec<String>,
     pub last_updated: DateTime<Utc>,
     pub evolution_cycle: u32,
+    #[serde(default)]
+    pub last_decayed: Option<DateTime<Utc>>,
 }
 
 impl Default for SymbolicState {
@@ -163,6 +310,7 @@ impl SymbolicState {
             active_transformations: Vec::new(),
             last_updated: Utc::now(),
             evolution_cycle: 0,
+            last_decayed: None,
         }
     }
 
@@ -184,6 +332,26 @@ impl SymbolicState {
         self.mark_updated();
     }
 
+    /// Sets an archetype's activation, awakening the archetype if it is not yet present
+    pub fn set_archetype_activation(&mut self, name: &str, level: f64) {
+        let archetype = self.archetypes.entry(name.to_string()).or_insert_with(|| {
+            Archetype::new(name.to_string(), format!("Archetypal force of {}", name))
+        });
+        archetype.set_activation(level);
+        self.mark_updated();
+    }
+
+    /// Sets an energy's amplitude with the same clamping as `Energy::modulate`,
+    /// opening a new energy flow if it is not yet present
+    pub fn set_energy_amplitude(&mut self, name: &str, amplitude: f64) {
+        let energy = self
+            .energies
+            .entry(name.to_string())
+            .or_insert_with(|| Energy::attuned(name.to_string(), Element::from_name(name)));
+        energy.modulate(0.0, amplitude - energy.amplitude);
+        self.mark_updated();
+    }
+
     pub fn add_unresolved_symbol(&mut self, symbol: String) {
         self.unresolved_symbols.push(symbol);
         self.mark_updated();
@@ -219,6 +387,380 @@ impl SymbolicState {
         }
     }
 
+    /// Awakens the archetype of every evolution milestone the cycle count has reached
+    /// and not yet recorded, recording each as an integration so it happens only once.
+    /// An archetype that is already present keeps its activation.
+    ///
+    /// Returns the cycle and archetype of each milestone reached by this call.
+    pub fn reach_evolution_milestones(&mut self) -> Vec<(u32, &'static str)> {
+        let mut reached = Vec::new();
+        for (cycle, name, essence) in EVOLUTION_MILESTONES {
+            let integration_name = format!("Evolution Milestone: Cycle {}", cycle);
+            if self.evolution_cycle < cycle || self.integrations.contains_key(&integration_name) {
+                continue;
+            }
+
+            let archetype_id = self
+                .archetypes
+                .entry(name.to_string())
+                .or_insert_with(|| {
+                    let mut archetype = Archetype::new(name.to_string(), essence.to_string());
+                    archetype.activation_level = MILESTONE_ARCHETYPE_ACTIVATION;
+                    archetype
+                })
+                .id;
+            self.add_integration(Integration::new(
+                integration_name,
+                format!(
+                    "After {} cycles of transformation, the {} awakens",
+                    cycle, name
+                ),
+                vec![archetype_id],
+            ));
+            reached.push((cycle, name));
+        }
+        reached
+    }
+
+    /// Fades archetype activation and energy amplitude exponentially with the time since each
+    /// was last invoked or shifted. Time already accounted for by an earlier decay is not
+    /// counted again, and archetypes that were never invoked are left untouched.
+    pub fn apply_decay(&mut self, half_life_days: f64) {
+        if !half_life_days.is_finite() || half_life_days <= 0.0 {
+            return;
+        }
+
+        let now = Utc::now();
+        let last_decayed = self.last_decayed;
+        let decay_factor = |since: DateTime<Utc>| {
+            let since = last_decayed.map_or(since, |decayed| since.max(decayed));
+            let elapsed_days = (now - since).num_milliseconds().max(0) as f64 / 86_400_000.0;
+            0.5_f64.powf(elapsed_days / half_life_days)
+        };
+
+        for archetype in self.archetypes.values_mut() {
+            if let Some(last_invoked) = archetype.last_invoked {
+                archetype.activation_level =
+                    (archetype.activation_level * decay_factor(last_invoked)).max(0.0);
+            }
+        }
+
+        for energy in self.energies.values_mut() {
+            energy.amplitude = (energy.amplitude * decay_factor(energy.last_shifted)).max(0.0);
+        }
+
+        self.last_decayed = Some(now);
+    }
+
+    /// How closely energy frequencies sit on consonant harmonic ratios, from 0.0 to 1.0.
+    ///
+    /// Each pair of frequencies is folded into a single octave and scored by its distance
+    /// in cents from the nearest just interval (unison/octave, fifth, fourth, thirds, sixths).
+    /// Non-positive frequencies do not sound and are skipped; fewer than two sounding
+    /// energies are trivially coherent.
+    pub fn harmonic_coherence(&self) -> f64 {
+        const CONSONANT_RATIOS: [f64; 8] = [
+            1.0,
+            6.0 / 5.0,
+            5.0 / 4.0,
+            4.0 / 3.0,
+            3.0 / 2.0,
+            8.0 / 5.0,
+            5.0 / 3.0,
+            2.0,
+        ];
+        const TOLERANCE_CENTS: f64 = 50.0;
+
+        let frequencies: Vec<f64> = self
+            .energies
+            .values()
+            .map(|e| e.frequency)
+            .filter(|f| f.is_finite() && *f > 0.0)
+            .collect();
+
+        let mut total_score = 0.0;
+        let mut pair_count = 0;
+
+        for (i, low) in frequencies.iter().enumerate() {
+            for high in &frequencies[i + 1..] {
+                let ratio = high.max(*low) / high.min(*low);
+                let cents = (1200.0 * ratio.log2()).rem_euclid(1200.0);
+                let nearest = CONSONANT_RATIOS
+                    .iter()
+                    .map(|r| (cents - 1200.0 * r.log2()).abs())
+                    .fold(f64::INFINITY, f64::min);
+
+                total_score += (1.0 - nearest / TOLERANCE_CENTS).max(0.0);
+                pair_count += 1;
+            }
+        }
+
+        if pair_count > 0 {
+            total_score / pair_count as f64
+        } else {
+            1.0
+        }
+    }
+
+    /// Combines several practitioners' states into one for a group ritual.
+    ///
+    /// Archetype activations and energy amplitudes are combined by `strategy`, with
+    /// a participant who lacks an archetype or energy counting as 0.0. Everything
+    /// else about a merged archetype or energy comes from the first state that has
+    /// it, except that aspects are unioned and an energy's frequency is averaged
+    /// over the states that carry it. Integrations and unresolved symbols are
+    /// unioned, keeping the deeper of two same-named integrations.
+    pub fn merge(states: &[SymbolicState], strategy: MergeStrategy) -> SymbolicState {
+        let mut merged = SymbolicState::new();
+        let participants = states.len();
+
+        let mut activations: HashMap<String, Vec<f64>> = HashMap::new();
+        let mut amplitudes: HashMap<String, Vec<f64>> = HashMap::new();
+        let mut frequencies: HashMap<String, Vec<f64>> = HashMap::new();
+
+        for state in states {
+            for (name, archetype) in &state.archetypes {
+                activations
+                    .entry(name.clone())
+                    .or_default()
+                    .push(archetype.activation_level);
+                let combined = merged
+                    .archetypes
+                    .entry(name.clone())
+                    .or_insert_with(|| Archetype {
+                        shadow_aspects: Vec::new(),
+                        light_aspects: Vec::new(),
+                        ..archetype.clone()
+                    });
+                for aspect in &archetype.shadow_aspects {
+                    if !combined.shadow_aspects.contains(aspect) {
+                        combined.shadow_aspects.push(aspect.clone());
+                    }
+                }
+                for aspect in &archetype.light_aspects {
+                    if !combined.light_aspects.contains(aspect) {
+                        combined.light_aspects.push(aspect.clone());
+                    }
+                }
+                combined.evolution_count = combined.evolution_count.max(archetype.evolution_count);
+                combined.last_invoked = combined.last_invoked.max(archetype.last_invoked);
+            }
+
+            for (name, energy) in &state.energies {
+                amplitudes
+                    .entry(name.clone())
+                    .or_default()
+                    .push(energy.amplitude);
+                frequencies
+                    .entry(name.clone())
+                    .or_default()
+                    .push(energy.frequency);
+                merged
+                    .energies
+                    .entry(name.clone())
+                    .or_insert_with(|| energy.clone());
+            }
+
+            for (name, integration) in &state.integrations {
+                match merged.integrations.get(name) {
+                    Some(existing) if existing.depth_level >= integration.depth_level => {}
+                    _ => {
+                        merged
+                            .integrations
+                            .insert(name.clone(), integration.clone());
+                    }
+                }
+            }
+
+            for symbol in &state.unresolved_symbols {
+                if !merged.unresolved_symbols.contains(symbol) {
+                    merged.unresolved_symbols.push(symbol.clone());
+                }
+            }
+
+            merged.evolution_cycle = merged.evolution_cycle.max(state.evolution_cycle);
+        }
+
+        for (name, archetype) in merged.archetypes.iter_mut() {
+            archetype.activation_level = strategy.combine(&activations[name], participants);
+        }
+        for (name, energy) in merged.energies.iter_mut() {
+            energy.amplitude = strategy.combine(&amplitudes[name], participants);
+            let carried = &frequencies[name];
+            energy.frequency = carried.iter().sum::<f64>() / carried.len() as f64;
+        }
+
+        merged
+    }
+
+    /// Describes how `other` differs from this state, ignoring changes below `DIFF_EPSILON`
+    pub fn diff(&self, other: &SymbolicState) -> StateDiff {
+        let mut diff = StateDiff::default();
+
+        let archetype_names = self.archetypes.keys().chain(other.archetypes.keys());
+        for name in archetype_names {
+            if diff.archetype_deltas.contains_key(name) {
+                continue;
+            }
+            let before = self
+                .archetypes
+                .get(name)
+                .map_or(0.0, |a| a.activation_level);
+            let after = other
+                .archetypes
+                .get(name)
+                .map_or(0.0, |a| a.activation_level);
+            if (after - before).abs() > DIFF_EPSILON {
+                diff.archetype_deltas.insert(name.clone(), after - before);
+            }
+        }
+
+        let energy_names = self.energies.keys().chain(other.energies.keys());
+        for name in energy_names {
+            if diff.energy_deltas.contains_key(name) {
+                continue;
+            }
+            let before = self.energies.get(name);
+            let after = other.energies.get(name);
+            let amplitude =
+                after.map_or(0.0, |e| e.amplitude) - before.map_or(0.0, |e| e.amplitude);
+            let frequency =
+                after.map_or(0.0, |e| e.frequency) - before.map_or(0.0, |e| e.frequency);
+            if amplitude.abs() > DIFF_EPSILON || frequency.abs() > DIFF_EPSILON {
+                diff.energy_deltas.insert(
+                    name.clone(),
+                    EnergyDelta {
+                        amplitude,
+                        frequency,
+                    },
+                );
+            }
+        }
+
+        diff.added_symbols = other
+            .unresolved_symbols
+            .iter()
+            .filter(|s| !self.unresolved_symbols.contains(s))
+            .cloned()
+            .collect();
+        diff.removed_symbols = self
+            .unresolved_symbols
+            .iter()
+            .filter(|s| !other.unresolved_symbols.contains(s))
+            .cloned()
+            .collect();
+        diff.new_integrations = other
+            .integrations
+            .keys()
+            .filter(|name| !self.integrations.contains_key(*name))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    /// Checks that every activation level and energy amplitude lies within 0.0-1.0
+    pub fn validate(&self) -> Result<(), CodexError> {
+        for archetype in self.archetypes.values() {
+            if !(0.0..=1.0).contains(&archetype.activation_level) {
+                return Err(CodexError::StateCorruption {
+                    reason: format!(
+                        "Archetype '{}' has activation {} outside 0.0-1.0",
+                        archetype.name, archetype.activation_level
+                    ),
+                });
+            }
+        }
+        for energy in self.energies.values() {
+            if !(0.0..=1.0).contains(&energy.amplitude) {
+                return Err(CodexError::StateCorruption {
+                    reason: format!(
+                        "Energy '{}' has amplitude {} outside 0.0-1.0",
+                        energy.name, energy.amplitude
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Repairs activation levels and energy amplitudes in place: NaN and infinities
+    /// become 0.0 and everything else is clamped into 0.0-1.0.
+    ///
+    /// Returns one warning per repaired value, ordered by name.
+    pub fn validate_and_clamp(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let mut archetypes: Vec<&mut Archetype> = self.archetypes.values_mut().collect();
+        archetypes.sort_by(|a, b| a.name.cmp(&b.name));
+        for archetype in archetypes {
+            if let Some(fixed) = normalize_unit(archetype.activation_level) {
+                warnings.push(format!(
+                    "Archetype '{}' activation {} reset to {}",
+                    archetype.name, archetype.activation_level, fixed
+                ));
+                archetype.activation_level = fixed;
+            }
+        }
+
+        let mut energies: Vec<&mut Energy> = self.energies.values_mut().collect();
+        energies.sort_by(|a, b| a.name.cmp(&b.name));
+        for energy in energies {
+            if let Some(fixed) = normalize_unit(energy.amplitude) {
+                warnings.push(format!(
+                    "Energy '{}' amplitude {} reset to {}",
+                    energy.name, energy.amplitude, fixed
+                ));
+                energy.amplitude = fixed;
+            }
+        }
+
+        warnings
+    }
+
+    /// Folds `other` into this state, keeping the stronger activation or amplitude
+    /// wherever both define the same archetype or energy
+    pub fn merge_from(&mut self, other: SymbolicState) {
+        for (name, archetype) in other.archetypes {
+            match self.archetypes.get_mut(&name) {
+                Some(existing) => {
+                    if archetype.activation_level > existing.activation_level {
+                        *existing = archetype;
+                    }
+                }
+                None => {
+                    self.archetypes.insert(name, archetype);
+                }
+            }
+        }
+        for (name, energy) in other.energies {
+            match self.energies.get_mut(&name) {
+                Some(existing) => {
+                    if energy.amplitude > existing.amplitude {
+                        *existing = energy;
+                    }
+                }
+                None => {
+                    self.energies.insert(name, energy);
+                }
+            }
+        }
+        for (name, integration) in other.integrations {
+            self.integrations.entry(name).or_insert(integration);
+        }
+        for symbol in other.unresolved_symbols {
+            if !self.unresolved_symbols.contains(&symbol) {
+                self.unresolved_symbols.push(symbol);
+            }
+        }
+        for transformation in other.active_transformations {
+            if !self.active_transformations.contains(&transformation) {
+                self.active_transformations.push(transformation);
+            }
+        }
+        self.mark_updated();
+    }
+
     fn mark_updated(&mut self) {
         self.last_updated = Utc::now();
     }
@@ -242,6 +784,149 @@ impl SymbolicState {
             self.active_transformations.len()
         )
     }
+
+    /// Each element's share of the total energy amplitude, summing to 1.0.
+    ///
+    /// Elements without energy are omitted, and a state with no amplitude at all
+    /// has an empty balance.
+    pub fn elemental_balance(&self) -> HashMap<Element, f64> {
+        let mut balance: HashMap<Element, f64> = HashMap::new();
+        for energy in self.energies.values() {
+            *balance.entry(energy.elemental_association).or_insert(0.0) +=
+                energy.amplitude.max(0.0);
+        }
+
+        let total: f64 = balance.values().sum();
+        if total <= 0.0 {
+            return HashMap::new();
+        }
+        balance.retain(|_, amplitude| *amplitude > 0.0);
+        for share in balance.values_mut() {
+            *share /= total;
+        }
+        balance
+    }
+
+    /// Summary statistics describing how settled the state is.
+    ///
+    /// The coherence score averages four 0.0–1.0 components: how evenly archetypes are
+    /// activated, how evenly energy amplitudes are balanced, how few symbols remain
+    /// unresolved, and the state's `harmonic_coherence`.
+    pub fn coherence_report(&self) -> CoherenceReport {
+        let (archetype_mean, archetype_variance) =
+            mean_and_variance(self.archetypes.values().map(|a| a.activation_level));
+        let (_, energy_variance) = mean_and_variance(self.energies.values().map(|e| e.amplitude));
+
+        let dominant_archetype = self
+            .archetypes
+            .values()
+            .max_by(|a, b| {
+                a.activation_level
+                    .total_cmp(&b.activation_level)
+                    .then_with(|| b.name.cmp(&a.name))
+            })
+            .map(|a| a.name.clone());
+
+        // Values in 0.0..=1.0 have a standard deviation of at most 0.5
+        let evenness = |variance: f64| (1.0 - 2.0 * variance.sqrt()).clamp(0.0, 1.0);
+        let unresolved_symbols = self.unresolved_symbols.len();
+        let coherence_score = (evenness(archetype_variance)
+            + evenness(energy_variance)
+            + 1.0 / (1.0 + unresolved_symbols as f64)
+            + self.harmonic_coherence())
+            / 4.0;
+
+        CoherenceReport {
+            archetype_mean,
+            archetype_variance,
+            energy_variance,
+            unresolved_symbols,
+            dominant_archetype,
+            coherence_score,
+        }
+    }
+}
+
+/// Population mean and variance, both 0.0 when there are no values
+fn mean_and_variance(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+    (mean, variance)
+}
+
+/// Quantified overview of a symbolic state, see `SymbolicState::coherence_report`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CoherenceReport {
+    pub archetype_mean: f64,
+    pub archetype_variance: f64,
+    /// Variance across energy amplitudes; lower means a more balanced field
+    pub energy_variance: f64,
+    pub unresolved_symbols: usize,
+    /// The most activated archetype, ties going to the alphabetically first name
+    pub dominant_archetype: Option<String>,
+    /// Overall coherence from 0.0 to 1.0
+    pub coherence_score: f64,
+}
+
+/// How `SymbolicState::merge` combines the participants' activations and amplitudes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeStrategy {
+    /// The mean across every participant
+    Average,
+    /// The strongest participant's level
+    Max,
+    /// All participants' levels added together, capped at 1.0
+    Sum,
+}
+
+impl MergeStrategy {
+    /// Combines the levels of the participants that hold a value; the other
+    /// `participants - values.len()` count as 0.0
+    fn combine(&self, values: &[f64], participants: usize) -> f64 {
+        let total: f64 = values.iter().sum();
+        let combined = match self {
+            MergeStrategy::Average => total / participants.max(1) as f64,
+            MergeStrategy::Max => values.iter().copied().fold(0.0, f64::max),
+            MergeStrategy::Sum => total,
+        };
+        combined.clamp(0.0, 1.0)
+    }
+}
+
+/// Changes smaller than this are treated as noise when diffing states
+pub const DIFF_EPSILON: f64 = 1e-6;
+
+/// Shift in a single energy between two states
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnergyDelta {
+    pub amplitude: f64,
+    pub frequency: f64,
+}
+
+/// What changed between two symbolic states
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StateDiff {
+    pub archetype_deltas: HashMap<String, f64>,
+    pub energy_deltas: HashMap<String, EnergyDelta>,
+    pub added_symbols: Vec<String>,
+    pub removed_symbols: Vec<String>,
+    pub new_integrations: Vec<String>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.archetype_deltas.is_empty()
+            && self.energy_deltas.is_empty()
+            && self.added_symbols.is_empty()
+            && self.removed_symbols.is_empty()
+            && self.new_integrations.is_empty()
+    }
 }
 
 /// Simplified state structure for web API compatibility
@@ -321,17 +1006,7 @@ impl ArchetypalState {
 
         // Convert energies
         for (name, &amplitude) in &self.energies {
-            let element = match name.as_str() {
-                "Fire" => Element::Fire,
-                "Water" => Element::Water,
-                "Earth" => Element::Earth,
-                "Air" => Element::Air,
-                "Void" => Element::Void,
-                "Light" => Element::Light,
-                "Shadow" => Element::Shadow,
-                _ => Element::Void,
-            };
-            let mut energy = Energy::new(name.clone(), 440.0, element);
+            let mut energy = Energy::attuned(name.clone(), Element::from_name(name));
             energy.amplitude = amplitude;
             symbolic.add_energy(energy);
         }
@@ -370,6 +1045,17 @@ pub struct RitualSession {
     pub execution_duration: std::time::Duration,
 }
 
+/// The in-range replacement for a 0.0-1.0 value, or `None` if it is already valid
+fn normalize_unit(value: f64) -> Option<f64> {
+    if !value.is_finite() {
+        Some(0.0)
+    } else if !(0.0..=1.0).contains(&value) {
+        Some(value.clamp(0.0, 1.0))
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -412,6 +1098,28 @@ mod tests {
         assert_eq!(archetype.evolution_count, 2);
     }
 
+    #[test]
+    fn test_archetype_set_activation_clamps() {
+        let mut archetype = Archetype::new("Trickster".to_string(), "Disruption".to_string());
+
+        archetype.set_activation(1.4);
+        assert_eq!(archetype.activation_level, 1.0);
+
+        archetype.set_activation(-0.3);
+        assert_eq!(archetype.activation_level, 0.0);
+        assert!(archetype.last_invoked.is_some());
+        assert_eq!(archetype.evolution_count, 2);
+    }
+
+    #[test]
+    fn test_symbolic_state_set_archetype_activation_creates_missing() {
+        let mut state = SymbolicState::new();
+
+        state.set_archetype_activation("Light", 0.4);
+
+        assert_eq!(state.archetypes["Light"].activation_level, 0.4);
+    }
+
     #[test]
     fn test_archetype_integrate_shadow_aspect() {
         let mut archetype = Archetype::new("Hero".to_string(), "The heroic journey".to_string());
@@ -434,6 +1142,39 @@ mod tests {
         assert!(archetype.shadow_aspects.is_empty());
     }
 
+    #[test]
+    fn test_fire_energy_at_water_frequency_is_flagged() {
+        let energy = Energy::new("Fire".to_string(), 396.0, Element::Fire);
+
+        let warning = energy.validate_frequency().expect("396 Hz is Water's tone");
+        assert!(warning.contains("Fire"), "{}", warning);
+        assert!(warning.contains("396"), "{}", warning);
+    }
+
+    #[test]
+    fn test_frequencies_within_their_band_pass_validation() {
+        for (frequency, element) in [
+            (528.0, Element::Fire),
+            (540.0, Element::Fire),
+            (194.18, Element::Earth),
+            (396.0, Element::Water),
+            (0.0, Element::Void),
+        ] {
+            let energy = Energy::new(format!("{:?}", element), frequency, element);
+            assert_eq!(energy.validate_frequency(), None, "{} Hz", frequency);
+        }
+    }
+
+    #[test]
+    fn test_attuned_energy_sits_at_its_band_center() {
+        for element in [Element::Earth, Element::Fire, Element::Void] {
+            let energy = Energy::attuned(format!("{:?}", element), element);
+
+            assert_eq!(energy.frequency, element.canonical_frequency());
+            assert!(element.frequency_band().contains(&energy.frequency));
+        }
+    }
+
     #[test]
     fn test_energy_creation() {
         let energy = Energy::new("Fire".to_string(), 528.0, Element::Fire);
@@ -445,6 +1186,33 @@ mod tests {
         assert!(matches!(energy.elemental_association, Element::Fire));
     }
 
+    #[test]
+    fn test_shift_polarity_passes_through_neutral() {
+        let mut energy = Energy::new("Air".to_string(), 741.0, Element::Air);
+        energy.polarity = Polarity::Positive;
+
+        assert!(energy.shift_polarity(Polarity::Oscillating));
+        assert_eq!(energy.polarity, Polarity::Neutral);
+        assert!(energy.shift_polarity(Polarity::Oscillating));
+        assert_eq!(energy.polarity, Polarity::Oscillating);
+    }
+
+    #[test]
+    fn test_shift_polarity_is_idempotent_at_target() {
+        let mut energy = Energy::new("Air".to_string(), 741.0, Element::Air);
+        energy.polarity = Polarity::Oscillating;
+        let last_shifted = energy.last_shifted;
+
+        assert!(!energy.shift_polarity(Polarity::Oscillating));
+        assert!(!energy.shift_polarity(Polarity::Oscillating));
+        assert_eq!(energy.polarity, Polarity::Oscillating);
+        assert_eq!(energy.last_shifted, last_shifted);
+
+        let json = serde_json::to_string(&energy).unwrap();
+        let restored: Energy = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.polarity, Polarity::Oscillating);
+    }
+
     #[test]
     fn test_energy_modulate() {
         let mut energy = Energy::new("Water".to_string(), 396.0, Element::Water);
@@ -469,6 +1237,22 @@ mod tests {
         assert_eq!(energy.amplitude, 1.0);
     }
 
+    #[test]
+    fn test_symbolic_state_set_energy_amplitude_clamps() {
+        let mut state = SymbolicState::new();
+        state.add_energy(Energy::new("Fire".to_string(), 528.0, Element::Fire));
+
+        state.set_energy_amplitude("Fire", 1.7);
+        assert_eq!(state.energies["Fire"].amplitude, 1.0);
+
+        state.set_energy_amplitude("Air", 0.25);
+        assert_eq!(state.energies["Air"].amplitude, 0.25);
+        assert!(matches!(
+            state.energies["Air"].elemental_association,
+            Element::Air
+        ));
+    }
+
     #[test]
     fn test_integration_creation() {
         let archetype_ids = vec![Uuid::new_v4(), Uuid::new_v4()];
@@ -506,6 +1290,31 @@ mod tests {
         assert_eq!(integration.depth_level, 10);
     }
 
+    #[test]
+    fn test_integration_advance_embodiment_stops_at_transcendent() {
+        let mut integration = Integration::new(
+            "Self Acceptance".to_string(),
+            "Accepting all parts of oneself".to_string(),
+            vec![],
+        );
+
+        let mut stages = Vec::new();
+        for _ in 0..5 {
+            integration.advance_embodiment();
+            stages.push(integration.embodiment_status);
+        }
+        assert_eq!(
+            stages,
+            vec![
+                EmbodimentStatus::Emotional,
+                EmbodimentStatus::Energetic,
+                EmbodimentStatus::Physical,
+                EmbodimentStatus::Transcendent,
+                EmbodimentStatus::Transcendent,
+            ]
+        );
+    }
+
     #[test]
     fn test_symbolic_state_new() {
         let state = SymbolicState::new();
@@ -593,6 +1402,372 @@ mod tests {
         assert!(!not_completed);
     }
 
+    #[test]
+    fn test_evolution_milestones_are_reached_once() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Mystic", 0.6);
+        assert!(state.reach_evolution_milestones().is_empty());
+
+        // A jump past several milestones reaches each of them
+        state.evolution_cycle = 12;
+        assert_eq!(
+            state.reach_evolution_milestones(),
+            vec![(5, "Mystic"), (10, "Magician")]
+        );
+        assert_eq!(state.archetypes["Mystic"].activation_level, 0.6);
+        assert_eq!(
+            state.archetypes["Magician"].activation_level,
+            MILESTONE_ARCHETYPE_ACTIVATION
+        );
+        let milestone = &state.integrations["Evolution Milestone: Cycle 10"];
+        assert_eq!(
+            milestone.archetypes_involved,
+            vec![state.archetypes["Magician"].id]
+        );
+
+        assert!(state.reach_evolution_milestones().is_empty());
+        assert_eq!(state.integrations.len(), 2);
+    }
+
+    #[test]
+    fn test_coherence_report_statistics() {
+        let mut state = SymbolicState::new();
+        for (name, level) in [("Sage", 0.2), ("Shadow", 0.8), ("Anima", 0.5)] {
+            let mut archetype = Archetype::new(name.to_string(), "Essence".to_string());
+            archetype.activation_level = level;
+            state.add_archetype(archetype);
+        }
+        for (name, amplitude) in [("Fire", 0.3), ("Water", 0.7)] {
+            let mut energy = Energy::new(name.to_string(), 256.0, Element::from_name(name));
+            energy.amplitude = amplitude;
+            state.add_energy(energy);
+        }
+        state.add_unresolved_symbol("☽".to_string());
+
+        let report = state.coherence_report();
+
+        assert!((report.archetype_mean - 0.5).abs() < 1e-12);
+        assert!((report.archetype_variance - 0.06).abs() < 1e-12);
+        assert!((report.energy_variance - 0.04).abs() < 1e-12);
+        assert_eq!(report.unresolved_symbols, 1);
+        assert_eq!(report.dominant_archetype.as_deref(), Some("Shadow"));
+
+        // Unison energies are fully harmonic; one symbol halves symbol clarity
+        let expected = ((1.0 - 2.0 * 0.06f64.sqrt()) + (1.0 - 2.0 * 0.2) + 0.5 + 1.0) / 4.0;
+        assert!((report.coherence_score - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_elemental_balance_shares() {
+        let mut state = SymbolicState::new();
+        for (name, amplitude, element) in [
+            ("Fire", 0.6, Element::Fire),
+            ("Water", 0.5, Element::Water),
+            ("Earth", 0.4, Element::Earth),
+            ("Ember", 0.3, Element::Fire),
+            ("Air", 0.2, Element::Air),
+            ("Stillness", 0.0, Element::Void),
+        ] {
+            let mut energy = Energy::new(name.to_string(), 432.0, element);
+            energy.amplitude = amplitude;
+            state.add_energy(energy);
+        }
+
+        let balance = state.elemental_balance();
+
+        // Fire gathers both of its energies; the silent Void is left out
+        assert_eq!(balance.len(), 4);
+        assert!((balance[&Element::Fire] - 0.45).abs() < 1e-12);
+        assert!((balance[&Element::Water] - 0.25).abs() < 1e-12);
+        assert!((balance[&Element::Earth] - 0.2).abs() < 1e-12);
+        assert!((balance[&Element::Air] - 0.1).abs() < 1e-12);
+        assert!((balance.values().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_elemental_balance_without_amplitude_is_empty() {
+        let mut state = SymbolicState::new();
+        assert!(state.elemental_balance().is_empty());
+
+        let mut energy = Energy::new("Fire".to_string(), 432.0, Element::Fire);
+        energy.amplitude = 0.0;
+        state.add_energy(energy);
+        assert!(state.elemental_balance().is_empty());
+    }
+
+    #[test]
+    fn test_coherence_report_of_empty_state() {
+        let report = SymbolicState::new().coherence_report();
+
+        assert_eq!(report.archetype_mean, 0.0);
+        assert_eq!(report.energy_variance, 0.0);
+        assert_eq!(report.dominant_archetype, None);
+        assert_eq!(report.coherence_score, 1.0);
+    }
+
+    #[test]
+    fn test_coherence_report_dominant_tie_goes_to_first_name() {
+        let mut state = SymbolicState::new();
+        for name in ["Shadow", "Anima"] {
+            let mut archetype = Archetype::new(name.to_string(), "Essence".to_string());
+            archetype.activation_level = 0.4;
+            state.add_archetype(archetype);
+        }
+
+        assert_eq!(
+            state.coherence_report().dominant_archetype.as_deref(),
+            Some("Anima")
+        );
+    }
+
+    /// Two practitioners sharing Shadow and Fire, each with an archetype of their own
+    fn group_participants() -> [SymbolicState; 2] {
+        let mut first = SymbolicState::new();
+        first.set_archetype_activation("Shadow", 0.6);
+        first.set_archetype_activation("Sage", 0.4);
+        first.set_energy_amplitude("Fire", 0.7);
+        first.add_unresolved_symbol("☽".to_string());
+        first
+            .archetypes
+            .get_mut("Shadow")
+            .unwrap()
+            .integrate_aspect("envy".to_string(), true);
+        let mut shallow = Integration::new("Stillness".to_string(), "Rest".to_string(), vec![]);
+        shallow.deepen(1);
+        first.add_integration(shallow);
+
+        let mut second = SymbolicState::new();
+        second.set_archetype_activation("Shadow", 0.8);
+        second.set_archetype_activation("Creator", 0.5);
+        second.set_energy_amplitude("Fire", 0.5);
+        second.set_energy_amplitude("Water", 0.2);
+        second.add_unresolved_symbol("☽".to_string());
+        second.add_unresolved_symbol("∞".to_string());
+        second
+            .archetypes
+            .get_mut("Shadow")
+            .unwrap()
+            .integrate_aspect("pride".to_string(), true);
+        let mut deep = Integration::new("Stillness".to_string(), "Deep rest".to_string(), vec![]);
+        deep.deepen(4);
+        second.add_integration(deep);
+        second.add_integration(Integration::new(
+            "Courage".to_string(),
+            "Step forward".to_string(),
+            vec![],
+        ));
+
+        [first, second]
+    }
+
+    fn activation(state: &SymbolicState, name: &str) -> f64 {
+        state.archetypes[name].activation_level
+    }
+
+    #[test]
+    fn test_merge_average() {
+        let merged = SymbolicState::merge(&group_participants(), MergeStrategy::Average);
+
+        assert!((activation(&merged, "Shadow") - 0.7).abs() < 1e-12);
+        // A participant without the archetype counts as 0.0
+        assert!((activation(&merged, "Sage") - 0.2).abs() < 1e-12);
+        assert!((activation(&merged, "Creator") - 0.25).abs() < 1e-12);
+        assert!((merged.energies["Fire"].amplitude - 0.6).abs() < 1e-12);
+        assert!((merged.energies["Water"].amplitude - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_merge_max() {
+        let merged = SymbolicState::merge(&group_participants(), MergeStrategy::Max);
+
+        assert_eq!(activation(&merged, "Shadow"), 0.8);
+        assert_eq!(activation(&merged, "Sage"), 0.4);
+        assert_eq!(activation(&merged, "Creator"), 0.5);
+        assert_eq!(merged.energies["Fire"].amplitude, 0.7);
+        assert_eq!(merged.energies["Water"].amplitude, 0.2);
+    }
+
+    #[test]
+    fn test_merge_sum_is_clamped() {
+        let merged = SymbolicState::merge(&group_participants(), MergeStrategy::Sum);
+
+        assert_eq!(activation(&merged, "Shadow"), 1.0);
+        assert_eq!(activation(&merged, "Sage"), 0.4);
+        assert_eq!(activation(&merged, "Creator"), 0.5);
+        assert_eq!(merged.energies["Fire"].amplitude, 1.0);
+        assert_eq!(merged.energies["Water"].amplitude, 0.2);
+    }
+
+    #[test]
+    fn test_merge_unions_symbols_integrations_and_aspects() {
+        let merged = SymbolicState::merge(&group_participants(), MergeStrategy::Average);
+
+        let mut names: Vec<_> = merged.archetypes.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, ["Creator", "Sage", "Shadow"]);
+        assert_eq!(
+            merged.archetypes["Shadow"].shadow_aspects,
+            ["envy", "pride"]
+        );
+        assert_eq!(merged.unresolved_symbols, ["☽", "∞"]);
+
+        assert_eq!(merged.integrations.len(), 2);
+        assert_eq!(merged.integrations["Stillness"].wisdom, "Deep rest");
+        assert!(merged.active_transformations.is_empty());
+    }
+
+    #[test]
+    fn test_merge_of_no_states_is_empty() {
+        let merged = SymbolicState::merge(&[], MergeStrategy::Average);
+
+        assert!(merged.archetypes.is_empty());
+        assert!(merged.energies.is_empty());
+    }
+
+    #[test]
+    fn test_diff_of_identical_states_is_empty() {
+        let mut state = SymbolicState::new();
+        state.add_archetype(Archetype::new("Sage".to_string(), "Wisdom".to_string()));
+        state.add_energy(Energy::new("Fire".to_string(), 528.0, Element::Fire));
+
+        assert!(state.diff(&state.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_archetype_changes() {
+        let mut before = SymbolicState::new();
+        before.add_archetype(Archetype::new("Shadow".to_string(), "Dark".to_string()));
+        before.add_archetype(Archetype::new("Sage".to_string(), "Wisdom".to_string()));
+
+        let mut after = before.clone();
+        after.archetypes.get_mut("Shadow").unwrap().activation_level = 0.4;
+        after.archetypes.get_mut("Sage").unwrap().activation_level = 1e-9;
+
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.archetype_deltas.len(), 1);
+        assert!((diff.archetype_deltas["Shadow"] - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_diff_energy_changes() {
+        let mut before = SymbolicState::new();
+        before.add_energy(Energy::new("Water".to_string(), 396.0, Element::Water));
+
+        let mut after = before.clone();
+        after.energies.get_mut("Water").unwrap().modulate(4.0, -0.2);
+        after.add_energy(Energy::new("Air".to_string(), 741.0, Element::Air));
+
+        let diff = before.diff(&after);
+
+        let water = &diff.energy_deltas["Water"];
+        assert!((water.amplitude + 0.2).abs() < 1e-12);
+        assert!((water.frequency - 4.0).abs() < 1e-12);
+        assert_eq!(diff.energy_deltas["Air"].frequency, 741.0);
+    }
+
+    #[test]
+    fn test_diff_symbol_and_integration_changes() {
+        let mut before = SymbolicState::new();
+        before.add_unresolved_symbol("☽".to_string());
+
+        let mut after = before.clone();
+        after.resolve_symbol("☽");
+        after.add_unresolved_symbol("🌑".to_string());
+        after.add_integration(Integration::new(
+            "Shadow Work".to_string(),
+            "Embracing darkness".to_string(),
+            vec![],
+        ));
+
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.added_symbols, vec!["🌑".to_string()]);
+        assert_eq!(diff.removed_symbols, vec!["☽".to_string()]);
+        assert_eq!(diff.new_integrations, vec!["Shadow Work".to_string()]);
+    }
+
+    #[test]
+    fn test_apply_decay_halves_after_one_half_life() {
+        let mut state = SymbolicState::new();
+        let mut sage = Archetype::new("Sage".to_string(), "Wisdom".to_string());
+        sage.activation_level = 1.0;
+        sage.last_invoked = Some(Utc::now() - chrono::Duration::days(7));
+        state.add_archetype(sage);
+
+        let mut fire = Energy::new("Fire".to_string(), 528.0, Element::Fire);
+        fire.amplitude = 0.8;
+        fire.last_shifted = Utc::now() - chrono::Duration::days(7);
+        state.add_energy(fire);
+
+        state.apply_decay(7.0);
+
+        assert!((state.archetypes["Sage"].activation_level - 0.5).abs() < 1e-3);
+        assert!((state.energies["Fire"].amplitude - 0.4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_apply_decay_skips_never_invoked_archetypes() {
+        let mut state = SymbolicState::new();
+        let mut shadow = Archetype::new("Shadow".to_string(), "Dark".to_string());
+        shadow.activation_level = 0.6;
+        state.add_archetype(shadow);
+
+        state.apply_decay(1.0);
+
+        assert_eq!(state.archetypes["Shadow"].activation_level, 0.6);
+    }
+
+    #[test]
+    fn test_apply_decay_does_not_double_count() {
+        let mut state = SymbolicState::new();
+        let mut sage = Archetype::new("Sage".to_string(), "Wisdom".to_string());
+        sage.activation_level = 1.0;
+        sage.last_invoked = Some(Utc::now() - chrono::Duration::days(7));
+        state.add_archetype(sage);
+
+        state.apply_decay(7.0);
+        state.apply_decay(7.0);
+
+        assert!((state.archetypes["Sage"].activation_level - 0.5).abs() < 1e-3);
+    }
+
+    fn state_with_frequencies(frequencies: &[f64]) -> SymbolicState {
+        let mut state = SymbolicState::new();
+        for (i, frequency) in frequencies.iter().enumerate() {
+            state.add_energy(Energy::new(
+                format!("Energy{}", i),
+                *frequency,
+                Element::Void,
+            ));
+        }
+        state
+    }
+
+    #[test]
+    fn test_harmonic_coherence_octaves_score_high() {
+        let state = state_with_frequencies(&[110.0, 220.0, 440.0, 880.0]);
+
+        assert!(state.harmonic_coherence() > 0.99);
+    }
+
+    #[test]
+    fn test_harmonic_coherence_dissonance_scores_low() {
+        // A semitone cluster: A4, A#4, B4
+        let state = state_with_frequencies(&[440.0, 466.16, 493.88]);
+
+        assert!(state.harmonic_coherence() < 0.3);
+    }
+
+    #[test]
+    fn test_harmonic_coherence_ignores_silent_frequencies() {
+        let state = state_with_frequencies(&[440.0, 660.0, 0.0, -12.0]);
+
+        let coherence = state.harmonic_coherence();
+        assert!(!coherence.is_nan());
+        assert!(coherence > 0.99);
+    }
+
     #[test]
     fn test_archetypal_state_new() {
         let state = ArchetypalState::new();
@@ -704,4 +1879,77 @@ mod tests {
         assert!(summary.contains("Integrations: 1"));
         assert!(summary.contains("Transformations: 1"));
     }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_activation() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Shadow", 0.5);
+        assert!(state.validate().is_ok());
+
+        state.archetypes.get_mut("Shadow").unwrap().activation_level = 1.5;
+        assert!(matches!(
+            state.validate(),
+            Err(CodexError::StateCorruption { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merge_keeps_stronger_activation() {
+        let mut current = SymbolicState::new();
+        current.set_archetype_activation("Shadow", 0.7);
+        current.set_archetype_activation("Sage", 0.2);
+
+        let mut imported = SymbolicState::new();
+        imported.set_archetype_activation("Shadow", 0.3);
+        imported.set_archetype_activation("Sage", 0.9);
+        imported.set_energy_amplitude("Fire", 0.4);
+
+        current.merge_from(imported);
+
+        assert_eq!(current.archetypes["Shadow"].activation_level, 0.7);
+        assert_eq!(current.archetypes["Sage"].activation_level, 0.9);
+        assert_eq!(current.energies["Fire"].amplitude, 0.4);
+    }
+
+    #[test]
+    fn test_validate_and_clamp_repairs_and_reports() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Sage", 0.4);
+        state.set_archetype_activation("Shadow", 0.0);
+        state.set_energy_amplitude("Fire", 0.0);
+        state.set_energy_amplitude("Water", 0.0);
+        state.archetypes.get_mut("Shadow").unwrap().activation_level = 5.0;
+        state.energies.get_mut("Fire").unwrap().amplitude = f64::NAN;
+        state.energies.get_mut("Water").unwrap().amplitude = -0.3;
+
+        let warnings = state.validate_and_clamp();
+
+        assert_eq!(state.archetypes["Sage"].activation_level, 0.4);
+        assert_eq!(state.archetypes["Shadow"].activation_level, 1.0);
+        assert_eq!(state.energies["Fire"].amplitude, 0.0);
+        assert_eq!(state.energies["Water"].amplitude, 0.0);
+        assert_eq!(
+            warnings,
+            vec![
+                "Archetype 'Shadow' activation 5 reset to 1".to_string(),
+                "Energy 'Fire' amplitude NaN reset to 0".to_string(),
+                "Energy 'Water' amplitude -0.3 reset to 0".to_string(),
+            ]
+        );
+        assert!(state.validate().is_ok());
+        assert!(state.validate_and_clamp().is_empty());
+    }
+
+    #[test]
+    fn test_validate_and_clamp_zeroes_infinite_activation() {
+        let mut state = SymbolicState::new();
+        state.set_archetype_activation("Anima", 0.0);
+        state.archetypes.get_mut("Anima").unwrap().activation_level = f64::INFINITY;
+
+        let warnings = state.validate_and_clamp();
+
+        assert_eq!(state.archetypes["Anima"].activation_level, 0.0);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("inf"));
+    }
 }
@@ -0,0 +1,139 @@
+use crate::CodexError;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Environment variable holding the passphrase that encrypts `state.json`, its
+/// snapshots, the ritual history and cached reflections at rest
+pub const STATE_KEY_ENV: &str = "CODEX_STATE_KEY";
+
+const FORMAT: &str = "codex-encrypted-state-v1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// On-disk envelope for an encrypted state file.
+///
+/// The key is derived from the passphrase with Argon2id over a per-file salt, and the
+/// JSON state is sealed with XChaCha20-Poly1305, whose tag detects any modification.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedState {
+    format: String,
+    #[serde(with = "crate::encoding::hex_bytes")]
+    salt: Vec<u8>,
+    #[serde(with = "crate::encoding::hex_bytes")]
+    nonce: Vec<u8>,
+    #[serde(with = "crate::encoding::hex_bytes")]
+    ciphertext: Vec<u8>,
+}
+
+/// Whether `content` is an encrypted envelope rather than a plaintext state
+pub fn is_encrypted(content: &str) -> bool {
+    serde_json::from_str::<EncryptedState>(content).is_ok_and(|envelope| envelope.format == FORMAT)
+}
+
+/// Seals serialized state under `passphrase` with a fresh salt and nonce
+pub fn encrypt(plaintext: &str, passphrase: &str) -> Result<String, CodexError> {
+    let mut salt = vec![0u8; SALT_LEN];
+    let mut nonce = vec![0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| CodexError::StateCorruption {
+            reason: "Could not encrypt state".to_string(),
+        })?;
+
+    Ok(serde_json::to_string_pretty(&EncryptedState {
+        format: FORMAT.to_string(),
+        salt,
+        nonce,
+        ciphertext,
+    })?)
+}
+
+/// Opens an envelope written by `encrypt`, failing if the passphrase is wrong or the
+/// file was altered
+pub fn decrypt(content: &str, passphrase: &str) -> Result<String, CodexError> {
+    let envelope: EncryptedState =
+        serde_json::from_str(content).map_err(|e| CodexError::StateCorruption {
+            reason: format!("Unreadable encrypted state: {}", e),
+        })?;
+    if envelope.format != FORMAT || envelope.nonce.len() != NONCE_LEN {
+        return Err(CodexError::StateCorruption {
+            reason: format!("Unsupported encrypted state format '{}'", envelope.format),
+        });
+    }
+
+    let plaintext = cipher(passphrase, &envelope.salt)?
+        .decrypt(
+            XNonce::from_slice(&envelope.nonce),
+            envelope.ciphertext.as_slice(),
+        )
+        .map_err(|_| CodexError::StateCorruption {
+            reason: format!(
+                "Encrypted state failed authentication: {} is wrong or the file was tampered with",
+                STATE_KEY_ENV
+            ),
+        })?;
+
+    String::from_utf8(plaintext).map_err(|e| CodexError::StateCorruption {
+        reason: format!("Decrypted state is not UTF-8: {}", e),
+    })
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, CodexError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CodexError::StateCorruption {
+            reason: format!("Could not derive state key: {}", e),
+        })?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trips() {
+        let sealed = encrypt(r#"{"evolution_cycle":3}"#, "open sesame").unwrap();
+
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("evolution_cycle"));
+        assert_eq!(
+            decrypt(&sealed, "open sesame").unwrap(),
+            r#"{"evolution_cycle":3}"#
+        );
+    }
+
+    #[test]
+    fn test_plaintext_state_is_not_encrypted() {
+        assert!(!is_encrypted(r#"{"evolution_cycle":3}"#));
+        assert!(!is_encrypted("not json"));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_is_rejected() {
+        let sealed = encrypt(r#"{"evolution_cycle":3}"#, "open sesame").unwrap();
+        let mut envelope: EncryptedState = serde_json::from_str(&sealed).unwrap();
+        envelope.ciphertext[0] ^= 0x01;
+        let tampered = serde_json::to_string(&envelope).unwrap();
+
+        let err = decrypt(&tampered, "open sesame").unwrap_err();
+        assert!(err.to_string().contains("failed authentication"), "{}", err);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_rejected() {
+        let sealed = encrypt(r#"{"evolution_cycle":3}"#, "open sesame").unwrap();
+
+        assert!(matches!(
+            decrypt(&sealed, "close sesame"),
+            Err(CodexError::StateCorruption { .. })
+        ));
+    }
+}
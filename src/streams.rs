@@ -0,0 +1,91 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::state::ArchetypalState;
+
+/// How many state updates a slow subscriber may fall behind before it skips ahead
+const STREAM_CAPACITY: usize = 16;
+
+/// Per-practitioner broadcast channels that carry each new `ArchetypalState`
+/// to live subscribers of `/api/state/stream`
+#[derive(Clone, Default)]
+pub struct StateStreams {
+    channels: Arc<Mutex<HashMap<Uuid, broadcast::Sender<ArchetypalState>>>>,
+}
+
+impl StateStreams {
+    /// Subscribes to state updates for `practitioner_id`, opening its channel on first use
+    pub fn subscribe(&self, practitioner_id: Uuid) -> broadcast::Receiver<ArchetypalState> {
+        let mut channels = self.channels.lock().unwrap();
+        Self::forget_idle(&mut channels);
+        channels
+            .entry(practitioner_id)
+            .or_insert_with(|| broadcast::channel(STREAM_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Sends `state` to every subscriber of `practitioner_id`, dropping any
+    /// channel nobody is listening to any more
+    pub fn publish(&self, practitioner_id: Uuid, state: &ArchetypalState) {
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(sender) = channels.get(&practitioner_id) {
+            let _ = sender.send(state.clone());
+        }
+        Self::forget_idle(&mut channels);
+    }
+
+    /// Forgets channels whose last subscriber disconnected so the map doesn't grow
+    /// with every practitioner that ever opened a stream
+    fn forget_idle(channels: &mut HashMap<Uuid, broadcast::Sender<ArchetypalState>>) {
+        channels.retain(|_, sender| sender.receiver_count() > 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_reaches_only_that_practitioner() {
+        let streams = StateStreams::default();
+        let practitioner = Uuid::new_v4();
+        let mut receiver = streams.subscribe(practitioner);
+        let mut other = streams.subscribe(Uuid::new_v4());
+
+        let mut state = ArchetypalState::new();
+        state.archetypes.insert("Shadow".to_string(), 0.7);
+        streams.publish(practitioner, &state);
+
+        assert_eq!(receiver.try_recv().unwrap().archetypes["Shadow"], 0.7);
+        assert!(other.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_publish_without_subscribers_drops_channel() {
+        let streams = StateStreams::default();
+        let practitioner = Uuid::new_v4();
+        drop(streams.subscribe(practitioner));
+
+        streams.publish(practitioner, &ArchetypalState::new());
+
+        assert!(streams.channels.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_disconnected_channels_are_forgotten() {
+        let streams = StateStreams::default();
+        drop(streams.subscribe(Uuid::new_v4()));
+        let listening = Uuid::new_v4();
+        let _receiver = streams.subscribe(listening);
+        assert_eq!(streams.channels.lock().unwrap().len(), 1);
+
+        drop(streams.subscribe(Uuid::new_v4()));
+        streams.publish(listening, &ArchetypalState::new());
+
+        assert_eq!(streams.channels.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+/// Each known symbol paired with its meaning
+const SYMBOL_MEANINGS: &[(&str, &str)] = &[
+    (
+        "◯●◯",
+        "The trinity of shadow integration - conscious, unconscious, and the unified whole",
+    ),
+    (
+        "🌑",
+        "New moon consciousness - the dark fertile void of potential",
+    ),
+    (
+        "⚡",
+        "Energetic activation - the lightning flash of illumination",
+    ),
+    (
+        "∿∿∿",
+        "Harmonic waves - the restoration of natural energetic flow",
+    ),
+    (
+        "🔮",
+        "Archetypal awakening - the activation of primordial wisdom",
+    ),
+    (
+        "○",
+        "The sacred circle - wholeness, completion, and eternal return",
+    ),
+    (
+        "∞",
+        "Infinite consciousness - transcendence of linear limitations",
+    ),
+    (
+        "𝄞",
+        "The tuning clef - each frequency finding its place in the sacred scale",
+    ),
+    (
+        "🔥",
+        "Elemental fire - will and passion burning steadily through the balance",
+    ),
+    (
+        "💧",
+        "Elemental water - feeling that flows on undiminished",
+    ),
+    (
+        "⛰",
+        "Elemental earth - the grounded mountain that holds its ground",
+    ),
+    (
+        "🜁",
+        "Elemental air - thought and breath moving freely",
+    ),
+    (
+        "🗝",
+        "The turning key - a hidden meaning unlocked and made conscious",
+    ),
+];
+
+/// The meaning of `symbol`, if it is one the Codex knows
+pub fn interpret_symbol(symbol: &str) -> Option<&'static str> {
+    SYMBOL_MEANINGS
+        .iter()
+        .find(|(known, _)| *known == symbol)
+        .map(|(_, meaning)| *meaning)
+}
+
+/// Pairs each known symbol in `symbols` with its meaning, in order, skipping unknown ones
+pub fn interpret_symbols(symbols: &[String]) -> Vec<(String, String)> {
+    symbols
+        .iter()
+        .filter_map(|symbol| {
+            interpret_symbol(symbol).map(|meaning| (symbol.clone(), meaning.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpret_known_symbol() {
+        assert_eq!(
+            interpret_symbol("∞"),
+            Some("Infinite consciousness - transcendence of linear limitations")
+        );
+    }
+
+    #[test]
+    fn test_interpret_unknown_symbol() {
+        assert_eq!(interpret_symbol("☿"), None);
+        assert_eq!(interpret_symbol(""), None);
+    }
+
+    #[test]
+    fn test_interpret_symbols_skips_unknown_ones() {
+        let symbols = vec!["🌑".to_string(), "☿".to_string(), "⚡".to_string()];
+
+        let interpreted = interpret_symbols(&symbols);
+
+        let names: Vec<&str> = interpreted
+            .iter()
+            .map(|(symbol, _)| symbol.as_str())
+            .collect();
+        assert_eq!(names, vec!["🌑", "⚡"]);
+        assert_eq!(interpreted[1].1, interpret_symbol("⚡").unwrap());
+    }
+}
@@ -0,0 +1,137 @@
+//! Soft-deleting an account and restoring it within the grace window.
+
+mod common;
+
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use codex_control_engine::{
+    auth::authenticate_token,
+    handlers,
+    models::{AuthToken, PractitionerLogin, RefreshTokenRequest, ACCOUNT_RESTORE_GRACE_DAYS},
+};
+
+fn credentials(auth: &AuthToken) -> PractitionerLogin {
+    PractitionerLogin {
+        email: auth.practitioner.email.clone(),
+        password: "sacred_transformation".to_string(),
+    }
+}
+
+async fn delete(app: &common::TestApp, auth: &AuthToken) {
+    let practitioner = common::practitioner(app, auth.practitioner.id).await;
+    let deletion = handlers::delete_account(State(app.state.clone()), Extension(practitioner))
+        .await
+        .expect("deletion failed")
+        .0
+        .data;
+    assert!(deletion["restorable_until"].is_string());
+}
+
+async fn login(app: &common::TestApp, auth: &AuthToken) -> Result<AuthToken, StatusCode> {
+    handlers::login_user(State(app.state.clone()), Json(credentials(auth)))
+        .await
+        .map(|response| response.0.data)
+        .map_err(|error| error.status())
+}
+
+async fn restore(app: &common::TestApp, auth: &AuthToken) -> Result<AuthToken, StatusCode> {
+    handlers::restore_account(State(app.state.clone()), Json(credentials(auth)))
+        .await
+        .map(|response| response.0.data)
+        .map_err(|error| error.status())
+}
+
+#[tokio::test]
+async fn test_deleted_account_cannot_sign_in() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    delete(&app, &auth).await;
+
+    assert_eq!(
+        login(&app, &auth).await.unwrap_err(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        authenticate_token(&app.state.db, &auth.token)
+            .await
+            .unwrap_err()
+            .status(),
+        StatusCode::UNAUTHORIZED
+    );
+    let status = handlers::refresh_token(
+        State(app.state.clone()),
+        Json(RefreshTokenRequest {
+            refresh_token: auth.refresh_token.clone(),
+        }),
+    )
+    .await
+    .expect_err("a deleted account's refresh token must not work")
+    .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The row is kept for restoring
+    let deleted: bool =
+        sqlx::query_scalar("SELECT deleted_at IS NOT NULL FROM practitioners WHERE id = $1")
+            .bind(auth.practitioner.id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert!(deleted);
+}
+
+#[tokio::test]
+async fn test_restore_within_window_signs_back_in() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+
+    // Only deleted accounts can be restored
+    assert_eq!(
+        restore(&app, &auth).await.unwrap_err(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    delete(&app, &auth).await;
+    let mut wrong_password = credentials(&auth);
+    wrong_password.password = "not_the_password".to_string();
+    let status = handlers::restore_account(State(app.state.clone()), Json(wrong_password))
+        .await
+        .expect_err("a wrong password must not restore the account")
+        .status();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let restored = restore(&app, &auth).await.expect("restore failed");
+    assert_eq!(restored.practitioner.id, auth.practitioner.id);
+    authenticate_token(&app.state.db, &restored.token)
+        .await
+        .expect("the restored account should authenticate");
+    login(&app, &auth)
+        .await
+        .expect("login after restore failed");
+}
+
+#[tokio::test]
+async fn test_restore_after_window_is_refused() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    delete(&app, &auth).await;
+
+    sqlx::query(
+        "UPDATE practitioners SET deleted_at = deleted_at - make_interval(days => $2) WHERE id = $1",
+    )
+    .bind(auth.practitioner.id)
+    .bind((ACCOUNT_RESTORE_GRACE_DAYS + 1) as i32)
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    assert_eq!(restore(&app, &auth).await.unwrap_err(), StatusCode::GONE);
+    assert_eq!(
+        login(&app, &auth).await.unwrap_err(),
+        StatusCode::UNAUTHORIZED
+    );
+}
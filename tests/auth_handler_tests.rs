@@ -0,0 +1,91 @@
+//! Refresh token issuing, renewal and revocation through the auth handlers.
+
+mod common;
+
+use axum::{extract::State, http::StatusCode, Json};
+use codex_control_engine::{
+    auth::{hash_refresh_token, verify_jwt_token},
+    handlers,
+    models::RefreshTokenRequest,
+};
+
+async fn refresh(app: &common::TestApp, refresh_token: &str) -> Result<String, StatusCode> {
+    let request = RefreshTokenRequest {
+        refresh_token: refresh_token.to_string(),
+    };
+    handlers::refresh_token(State(app.state.clone()), Json(request))
+        .await
+        .map(|response| response.0.data.token)
+        .map_err(|error| error.status())
+}
+
+async fn logout(app: &common::TestApp, refresh_token: &str) {
+    let request = RefreshTokenRequest {
+        refresh_token: refresh_token.to_string(),
+    };
+    let response = handlers::logout_user(State(app.state.clone()), Json(request))
+        .await
+        .expect("logout failed");
+    assert_eq!(response.0.data["revoked"], true);
+}
+
+#[tokio::test]
+async fn test_valid_refresh_issues_new_access_token() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+
+    let token = refresh(&app, &auth.refresh_token).await.unwrap();
+
+    let claims = verify_jwt_token(&token).unwrap();
+    assert_eq!(claims.sub, auth.practitioner.id.to_string());
+}
+
+#[tokio::test]
+async fn test_expired_refresh_token_is_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    sqlx::query(
+        "UPDATE refresh_tokens SET expires_at = NOW() - INTERVAL '1 day' WHERE token_hash = $1",
+    )
+    .bind(hash_refresh_token(&auth.refresh_token))
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        refresh(&app, &auth.refresh_token).await,
+        Err(StatusCode::UNAUTHORIZED)
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_refresh_token_is_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+
+    assert_eq!(
+        refresh(&app, "not-a-real-token").await,
+        Err(StatusCode::UNAUTHORIZED)
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_token_cannot_be_reused_after_logout() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    assert!(refresh(&app, &auth.refresh_token).await.is_ok());
+
+    logout(&app, &auth.refresh_token).await;
+
+    assert_eq!(
+        refresh(&app, &auth.refresh_token).await,
+        Err(StatusCode::UNAUTHORIZED)
+    );
+}
@@ -0,0 +1,142 @@
+//! Strongly resonant rituals consulting the oracle when `auto_reflect` is set.
+
+mod common;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue},
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{Practitioner, RitualExecutionRequest, TransformationResult, IDEMPOTENCY_KEY_HEADER},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+async fn execute(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    ritual_name: &str,
+    auto_reflect: bool,
+) -> TransformationResult {
+    execute_with_headers(
+        app,
+        practitioner,
+        ritual_name,
+        auto_reflect,
+        HeaderMap::new(),
+    )
+    .await
+}
+
+async fn execute_with_headers(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    ritual_name: &str,
+    auto_reflect: bool,
+    headers: HeaderMap,
+) -> TransformationResult {
+    handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        headers,
+        Json(RitualExecutionRequest {
+            ritual_name: ritual_name.to_string(),
+            parameters: HashMap::new(),
+            intention: "Ask the oracle".to_string(),
+            seed: Some(0),
+            dry_run: false,
+            auto_reflect,
+        }),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data
+}
+
+async fn insight_count(app: &common::TestApp, practitioner_id: Uuid) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM oracle_insights WHERE practitioner_id = $1")
+        .bind(practitioner_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_high_resonance_ritual_embeds_an_insight() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let result = execute(&app, &practitioner, "energy_attunement", true).await;
+    assert!(result.oracle_consultation_recommended);
+
+    let insight = result.oracle_insight.expect("no insight was embedded");
+    assert_eq!(insight.session_id, Some(result.session_id));
+    assert_eq!(insight_count(&app, practitioner.id).await, 1);
+}
+
+#[tokio::test]
+async fn test_low_resonance_ritual_skips_reflection() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let result = execute(&app, &practitioner, "archetype_invocation", true).await;
+    assert!(!result.oracle_consultation_recommended);
+    assert!(result.oracle_insight.is_none());
+    assert_eq!(insight_count(&app, practitioner.id).await, 0);
+}
+
+#[tokio::test]
+async fn test_reflection_is_opt_in() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let result = execute(&app, &practitioner, "energy_attunement", false).await;
+    assert!(result.oracle_consultation_recommended);
+    assert!(result.oracle_insight.is_none());
+    assert_eq!(insight_count(&app, practitioner.id).await, 0);
+}
+
+#[tokio::test]
+async fn test_retried_request_replays_the_embedded_insight() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        IDEMPOTENCY_KEY_HEADER,
+        HeaderValue::from_str(&Uuid::new_v4().to_string()).unwrap(),
+    );
+
+    let first = execute_with_headers(
+        &app,
+        &practitioner,
+        "energy_attunement",
+        true,
+        headers.clone(),
+    )
+    .await;
+    assert!(first.oracle_insight.is_some());
+    let retried =
+        execute_with_headers(&app, &practitioner, "energy_attunement", true, headers).await;
+
+    assert_eq!(retried.session_id, first.session_id);
+    assert_eq!(
+        retried.oracle_insight.map(|insight| insight.id),
+        first.oracle_insight.map(|insight| insight.id)
+    );
+    assert_eq!(insight_count(&app, practitioner.id).await, 1);
+}
@@ -0,0 +1,232 @@
+//! Runs the `codex` binary with `--format json` and parses what it prints.
+
+use codex_control_engine::{
+    ritual::CompletionStatus, Archetype, RitualResult, SymbolicState, WasmRunOutcome,
+};
+use std::process::Command;
+
+fn codex_json(home: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_codex"))
+        .env("HOME", home)
+        .env_remove("CODEX_DATA_DIR")
+        .arg("--format")
+        .arg("json")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "codex failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_ritual_run_json_parses_into_ritual_result() {
+    let home = tempfile::tempdir().unwrap();
+
+    let stdout = codex_json(home.path(), &["ritual", "run", "shadow_integration"]);
+
+    let result: RitualResult = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(result.ritual_name, "shadow_integration");
+    assert!((0.0..=1.0).contains(&result.resonance_level));
+}
+
+#[test]
+fn test_state_view_json_parses_into_symbolic_state() {
+    let home = tempfile::tempdir().unwrap();
+
+    let stdout = codex_json(home.path(), &["state", "view"]);
+
+    let state: SymbolicState = serde_json::from_str(&stdout).unwrap();
+    assert!(state.archetypes.contains_key("Shadow"));
+}
+
+#[test]
+fn test_list_json_names_foundational_rituals() {
+    let home = tempfile::tempdir().unwrap();
+
+    let stdout = codex_json(home.path(), &["list"]);
+
+    let rituals: Vec<serde_json::Value> = serde_json::from_str(&stdout).unwrap();
+    let names: Vec<&str> = rituals.iter().filter_map(|r| r["name"].as_str()).collect();
+    assert!(names.contains(&"shadow_integration"));
+}
+
+#[test]
+fn test_history_json_lists_runs_across_invocations() {
+    let home = tempfile::tempdir().unwrap();
+    codex_json(home.path(), &["ritual", "run", "shadow_integration"]);
+    codex_json(home.path(), &["ritual", "run", "energy_attunement"]);
+
+    let stdout = codex_json(home.path(), &["history"]);
+
+    let history: Vec<RitualResult> = serde_json::from_str(&stdout).unwrap();
+    let names: Vec<&str> = history.iter().map(|r| r.ritual_name.as_str()).collect();
+    assert_eq!(names, ["shadow_integration", "energy_attunement"]);
+}
+
+#[test]
+fn test_ritual_add_registers_custom_ritual_for_later_runs() {
+    let home = tempfile::tempdir().unwrap();
+    let ritual_file = home.path().join("dusk.toml");
+    std::fs::write(
+        &ritual_file,
+        "name = \"dusk_release\"\n\
+         description = \"Let the day go\"\n\
+         intent = \"To release what no longer serves\"\n\
+         required_archetypes = [\"Shadow\"]\n",
+    )
+    .unwrap();
+
+    let stdout = codex_json(
+        home.path(),
+        &["ritual", "add", ritual_file.to_str().unwrap()],
+    );
+    let added: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(added["name"], "dusk_release");
+    assert!(home
+        .path()
+        .join(".codex/rituals/dusk_release.toml")
+        .exists());
+
+    let stdout = codex_json(home.path(), &["ritual", "run", "dusk_release"]);
+    let result: RitualResult = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(result.ritual_name, "dusk_release");
+}
+
+fn ritual_module(file: &str) -> String {
+    format!("{}/rituals/{}", env!("CARGO_MANIFEST_DIR"), file)
+}
+
+#[test]
+fn test_run_wasm_runs_compiled_ritual_without_saving() {
+    let home = tempfile::tempdir().unwrap();
+
+    let stdout = codex_json(
+        home.path(),
+        &[
+            "ritual",
+            "run-wasm",
+            &ritual_module("shadow_integration.wasm"),
+        ],
+    );
+
+    let outcome: WasmRunOutcome = serde_json::from_str(&stdout).unwrap();
+    assert!(matches!(
+        outcome.result.completion_status,
+        CompletionStatus::Complete
+    ));
+    assert!(outcome.result.resonance_level > 0.0);
+    // Only the module, not a native fallback, adds the Light archetype
+    assert!(outcome.diff.archetype_deltas.contains_key("Light"));
+    assert!(outcome.state.archetypes.contains_key("Light"));
+    let saved: SymbolicState =
+        serde_json::from_str(&codex_json(home.path(), &["state", "view"])).unwrap();
+    assert!(!saved.archetypes.contains_key("Light"));
+}
+
+#[test]
+fn test_run_wasm_uses_the_given_state_file() {
+    let home = tempfile::tempdir().unwrap();
+    let mut state = SymbolicState::new();
+    let mut shadow = Archetype::new("Shadow".to_string(), "Rejected aspects".to_string());
+    shadow.activation_level = 0.1;
+    state.add_archetype(shadow);
+    let state_file = home.path().join("fixture.json");
+    std::fs::write(&state_file, serde_json::to_string(&state).unwrap()).unwrap();
+
+    let stdout = codex_json(
+        home.path(),
+        &[
+            "ritual",
+            "run-wasm",
+            &ritual_module("shadow_integration.wasm"),
+            "--state",
+            state_file.to_str().unwrap(),
+        ],
+    );
+
+    let outcome: WasmRunOutcome = serde_json::from_str(&stdout).unwrap();
+    assert!(outcome.state.archetypes["Shadow"].activation_level > 0.1);
+    // The fixture holds no primordial archetypes for the module to have seen
+    assert!(!outcome.state.archetypes.contains_key("Sage"));
+}
+
+#[test]
+fn test_doctor_exits_non_zero_for_corrupt_state() {
+    let home = tempfile::tempdir().unwrap();
+    let data_dir = home.path().join(".codex");
+    std::fs::create_dir(&data_dir).unwrap();
+    std::fs::write(data_dir.join("state.json"), "{ not json").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_codex"))
+        .env("HOME", home.path())
+        .args(["--format", "json", "doctor"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let checks = report["checks"].as_array().unwrap();
+    let state_check = checks.iter().find(|c| c["name"] == "state_file").unwrap();
+    assert_eq!(state_check["status"], "fail");
+    let dir_check = checks.iter().find(|c| c["name"] == "data_dir").unwrap();
+    assert_eq!(dir_check["status"], "pass");
+}
+
+#[test]
+fn test_doctor_reports_missing_data_dir() {
+    let home = tempfile::tempdir().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_codex"))
+        .env("HOME", home.path())
+        .args(["--format", "json", "doctor"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["checks"][0]["name"], "data_dir");
+    assert_eq!(report["checks"][0]["status"], "fail");
+    assert!(!home.path().join(".codex").exists());
+}
+
+#[test]
+fn test_data_dir_env_moves_state_out_of_home() {
+    let home = tempfile::tempdir().unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_codex"))
+        .env("HOME", home.path())
+        .env("CODEX_DATA_DIR", data_dir.path())
+        .args(["--format", "json", "ritual", "run", "shadow_integration"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert!(data_dir.path().join("state.json").exists());
+    assert!(!home.path().join(".codex").exists());
+}
+
+#[test]
+fn test_data_dir_flag_wins_over_env() {
+    let home = tempfile::tempdir().unwrap();
+    let env_dir = tempfile::tempdir().unwrap();
+    let flag_dir = tempfile::tempdir().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_codex"))
+        .env("HOME", home.path())
+        .env("CODEX_DATA_DIR", env_dir.path())
+        .arg("--data-dir")
+        .arg(flag_dir.path())
+        .args(["--format", "json", "ritual", "run", "shadow_integration"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert!(flag_dir.path().join("state.json").exists());
+    assert!(!env_dir.path().join("state.json").exists());
+    assert!(!home.path().join(".codex").exists());
+}
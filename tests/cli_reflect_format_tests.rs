@@ -0,0 +1,56 @@
+//! Runs `codex reflect` with the Markdown and ANSI output formats.
+
+use std::process::Command;
+
+fn codex(home: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_codex"))
+        .env("HOME", home)
+        .env_remove("CODEX_DATA_DIR")
+        // The offline reflection keeps these runs away from the network
+        .env_remove("OPENROUTER_API_KEY")
+        .env_remove("CODEX_REFLECTION_PROVIDER")
+        .env_remove("CLICOLOR_FORCE")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "codex failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_reflect_markdown_prints_only_clean_markdown() {
+    let home = tempfile::tempdir().unwrap();
+    codex(
+        home.path(),
+        &["--format", "json", "ritual", "run", "shadow_integration"],
+    );
+
+    let stdout = codex(home.path(), &["reflect", "--format", "md"]);
+
+    assert!(stdout.starts_with("# Reflection on shadow\\_integration\n"));
+    assert!(stdout.contains("\n## ARCHETYPAL INTERPRETATION\n"));
+    assert!(stdout.contains("\n## EMERGENT INSIGHTS\n\n- "));
+    assert!(!stdout.contains('\x1b'));
+}
+
+#[test]
+fn test_reflect_ansi_keeps_colors_when_redirected() {
+    let home = tempfile::tempdir().unwrap();
+    codex(
+        home.path(),
+        &["--format", "json", "ritual", "run", "shadow_integration"],
+    );
+
+    let ansi = codex(home.path(), &["reflect", "--format", "ansi"]);
+    assert!(ansi.contains("ARCHETYPAL INTERPRETATION"));
+    assert!(ansi.contains('\x1b'));
+
+    // Plain text is uncolored once it leaves the terminal
+    let text = codex(home.path(), &["reflect", "--format", "text"]);
+    assert!(text.contains("ARCHETYPAL INTERPRETATION"));
+    assert!(!text.contains('\x1b'));
+}
@@ -0,0 +1,145 @@
+//! Shared setup for handler tests.
+//!
+//! Tests on Postgres need `TEST_DATABASE_URL`; without it each returns early.
+//! `sqlite_app` needs nothing and always runs.
+
+#![allow(dead_code)]
+
+use axum::{extract::State, Json};
+use codex_control_engine::{
+    auth::{verify_jwt_token, Claims},
+    database::Database,
+    handlers,
+    handlers::AppState,
+    locks::PractitionerLocks,
+    models::*,
+    ratelimit::AuthRateLimiters,
+    streams::StateStreams,
+    CodexEngine,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+/// An app state backed by the test database and a throwaway engine data directory
+pub struct TestApp {
+    pub state: AppState,
+    /// The same Postgres pool the state uses, for fixtures and assertions
+    pub db: PgPool,
+    _data_dir: TempDir,
+}
+
+pub async fn test_app() -> Option<TestApp> {
+    let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping database test");
+        return None;
+    };
+
+    let db = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations")
+        .run(&db)
+        .await
+        .expect("Failed to run migrations");
+
+    let data_dir = tempfile::tempdir().unwrap();
+    let engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+
+    Some(TestApp {
+        state: AppState {
+            db: Database::Postgres(db.clone()),
+            engine: Arc::new(engine),
+            state_streams: StateStreams::default(),
+            auth_rate_limiters: AuthRateLimiters::default(),
+            practitioner_locks: PractitionerLocks::default(),
+            ritual_batch_limit: DEFAULT_RITUAL_BATCH_LIMIT,
+        },
+        db,
+        _data_dir: data_dir,
+    })
+}
+
+/// An app state backed by a fresh in-memory SQLite database and a throwaway engine
+/// data directory; `migrate` applies the schema, which tests that only ping the
+/// database can skip
+pub async fn sqlite_app(migrate: bool) -> (AppState, TempDir) {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    if migrate {
+        db.migrate().await.unwrap();
+    }
+
+    let data_dir = tempfile::tempdir().unwrap();
+    let engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+    let state = AppState {
+        db,
+        engine: Arc::new(engine),
+        state_streams: StateStreams::default(),
+        auth_rate_limiters: AuthRateLimiters::default(),
+        practitioner_locks: PractitionerLocks::default(),
+        ritual_batch_limit: DEFAULT_RITUAL_BATCH_LIMIT,
+    };
+    (state, data_dir)
+}
+
+/// Registers a practitioner with a unique email through the real handler
+pub async fn register(app: &TestApp) -> AuthToken {
+    let registration = PractitionerRegistration {
+        email: format!("test_{}@codex.sacred", Uuid::new_v4()),
+        password: "sacred_transformation".to_string(),
+        spiritual_name: Some("Test Seeker".to_string()),
+        sacred_path: None,
+    };
+
+    handlers::register_user(State(app.state.clone()), Json(registration))
+        .await
+        .expect("registration failed")
+        .0
+        .data
+}
+
+/// Gives a registered practitioner a new role and logs in again to pick it up
+pub async fn promote(app: &TestApp, auth: &AuthToken, role: &str) -> AuthToken {
+    sqlx::query("UPDATE practitioners SET role = $1 WHERE id = $2")
+        .bind(role)
+        .bind(auth.practitioner.id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    let login = PractitionerLogin {
+        email: auth.practitioner.email.clone(),
+        password: "sacred_transformation".to_string(),
+    };
+    handlers::login_user(State(app.state.clone()), Json(login))
+        .await
+        .expect("login failed")
+        .0
+        .data
+}
+
+pub fn claims(auth: &AuthToken) -> Claims {
+    verify_jwt_token(&auth.token).unwrap()
+}
+
+/// Inserts a public ritual authored by `author_id` and returns its id
+pub async fn create_ritual(app: &TestApp, author_id: Uuid) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO sacred_rituals (name, description, intent, author_id, is_public)
+         VALUES ($1, 'Test ritual', 'Testing', $2, true) RETURNING id",
+    )
+    .bind(format!("test_ritual_{}", Uuid::new_v4()))
+    .bind(author_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+pub async fn practitioner(app: &TestApp, id: Uuid) -> Practitioner {
+    sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE id = $1")
+        .bind(id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap()
+}
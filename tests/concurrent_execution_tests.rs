@@ -0,0 +1,158 @@
+//! Concurrent ritual executions by one practitioner applied one after the other.
+
+mod common;
+
+use axum::{extract::State, http::HeaderMap, Extension, Json};
+use codex_control_engine::{
+    handlers,
+    models::{RitualExecutionRequest, StateTransformationRequest, TransformationResult},
+    state::ArchetypalState,
+};
+use std::collections::HashMap;
+
+fn execution(seed: u64) -> RitualExecutionRequest {
+    RitualExecutionRequest {
+        ritual_name: "shadow_integration".to_string(),
+        parameters: HashMap::new(),
+        intention: "Racing myself".to_string(),
+        seed: Some(seed),
+        dry_run: false,
+        auto_reflect: false,
+    }
+}
+
+fn same_archetypes(a: &ArchetypalState, b: &ArchetypalState) -> bool {
+    a.archetypes.len() == b.archetypes.len()
+        && a.archetypes.iter().all(|(name, level)| {
+            b.archetypes
+                .get(name)
+                .is_some_and(|other| (level - other).abs() < 1e-9)
+        })
+}
+
+/// How many executions race each other. A second `shadow_integration` run
+/// can already push Shadow to its 1.0 ceiling, so any more and a serialized
+/// run could leave two executions with identical starting archetypes.
+const EXECUTIONS: u64 = 2;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_executions_apply_in_sequence() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let mut executions = tokio::task::JoinSet::new();
+    for seed in 0..EXECUTIONS {
+        let state = app.state.clone();
+        let practitioner = practitioner.clone();
+        executions.spawn(async move {
+            handlers::execute_ritual(
+                State(state),
+                Extension(practitioner),
+                HeaderMap::new(),
+                Json(execution(seed)),
+            )
+            .await
+            .expect("execution failed")
+            .0
+            .data
+        });
+    }
+    let results: Vec<TransformationResult> = executions.join_all().await;
+
+    // Each execution must start from a different state: two sharing one means
+    // they ran side by side and the later store overwrote the earlier one
+    for (i, a) in results.iter().enumerate() {
+        for b in &results[i + 1..] {
+            assert!(
+                !same_archetypes(&a.pre_state, &b.pre_state),
+                "two executions started from the same state"
+            );
+        }
+    }
+
+    // Apart from the one that started from the original state, every execution
+    // started from the state another one stored
+    let from_original = results
+        .iter()
+        .filter(|result| {
+            !results
+                .iter()
+                .any(|other| same_archetypes(&result.pre_state, &other.post_state))
+        })
+        .count();
+    assert_eq!(from_original, 1);
+
+    let stored =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert!(
+        !results
+            .iter()
+            .any(|result| same_archetypes(&result.pre_state, &stored)),
+        "the stored state should be the last execution's post_state"
+    );
+    assert!(results
+        .iter()
+        .any(|result| same_archetypes(&result.post_state, &stored)));
+
+    let stored_states: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM archetypal_states WHERE practitioner_id = $1")
+            .bind(practitioner.id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(stored_states, EXECUTIONS as i64 + 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_energy_adjustments_all_apply() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let before =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+
+    let mut adjustments = tokio::task::JoinSet::new();
+    for _ in 0..8 {
+        let state = app.state.clone();
+        let practitioner = practitioner.clone();
+        adjustments.spawn(async move {
+            let request = StateTransformationRequest {
+                transformation_type: "energy_adjustment".to_string(),
+                parameters: HashMap::from([
+                    ("energy_type".to_string(), serde_json::json!("Fire")),
+                    ("adjustment".to_string(), serde_json::json!(0.25)),
+                ]),
+            };
+            handlers::transform_state(State(state), Extension(practitioner), Json(request))
+                .await
+                .expect("transformation failed")
+                .0
+                .data
+        });
+    }
+    adjustments.join_all().await;
+
+    let stored =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    // Eight adjustments of 0.25 each; a lost update would leave Fire short
+    let expected = before.energies["Fire"] + 2.0;
+    assert!((stored.energies["Fire"] - expected).abs() < 1e-9);
+}
@@ -0,0 +1,87 @@
+//! Dry-run ritual executions that preview a transformation without storing it.
+
+mod common;
+
+use axum::{extract::State, http::HeaderMap, Extension, Json};
+use codex_control_engine::{handlers, models::RitualExecutionRequest};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_dry_run_leaves_stored_state_unchanged() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let stored_before =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    let usage_before: i32 =
+        sqlx::query_scalar("SELECT usage_count FROM sacred_rituals WHERE name = $1")
+            .bind("shadow_integration")
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+
+    let request = RitualExecutionRequest {
+        ritual_name: "shadow_integration".to_string(),
+        parameters: HashMap::new(),
+        intention: "Only looking".to_string(),
+        seed: Some(5),
+        dry_run: true,
+        auto_reflect: false,
+    };
+    let preview = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(request),
+    )
+    .await
+    .expect("dry run failed")
+    .0
+    .data;
+
+    assert!(preview.dry_run);
+    assert_ne!(
+        preview.post_state.archetypes["Shadow"],
+        preview.pre_state.archetypes["Shadow"]
+    );
+
+    let stored_after =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!(stored_after.archetypes, stored_before.archetypes);
+    assert_eq!(stored_after.energies, stored_before.energies);
+
+    let stored_states: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM archetypal_states WHERE practitioner_id = $1")
+            .bind(practitioner.id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(stored_states, 1);
+
+    let sessions: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM ritual_sessions WHERE practitioner_id = $1")
+            .bind(practitioner.id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(sessions, 0);
+
+    let usage_after: i32 =
+        sqlx::query_scalar("SELECT usage_count FROM sacred_rituals WHERE name = $1")
+            .bind("shadow_integration")
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(usage_after, usage_before);
+}
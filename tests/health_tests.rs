@@ -0,0 +1,39 @@
+//! Liveness and readiness probes.
+
+mod common;
+
+use axum::{extract::State, http::StatusCode};
+use codex_control_engine::{database::Database, handlers};
+
+#[tokio::test]
+async fn test_ready_check_reports_database_latency() {
+    let (state, _data_dir) = common::sqlite_app(false).await;
+
+    let (status, body) = handlers::health_ready(State(state)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.0["status"], "operational");
+    assert_eq!(body.0["database"]["backend"], "sqlite");
+    assert!(body.0["database"]["latency_ms"].as_f64().unwrap() >= 0.0);
+}
+
+#[tokio::test]
+async fn test_ready_check_fails_against_closed_pool() {
+    let (state, _data_dir) = common::sqlite_app(false).await;
+    match &state.db {
+        Database::Sqlite(pool) => pool.close().await,
+        Database::Postgres(pool) => pool.close().await,
+    }
+
+    let (status, body) = handlers::health_ready(State(state)).await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body.0["status"], "degraded");
+    assert!(body.0["database"]["error"].as_str().is_some());
+
+    // Liveness does not depend on the database
+    assert_eq!(
+        handlers::health_live().await.0["version"],
+        env!("CARGO_PKG_VERSION")
+    );
+}
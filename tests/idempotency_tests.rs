@@ -0,0 +1,162 @@
+//! Retried ritual executions carrying the same Idempotency-Key.
+
+mod common;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{
+        Practitioner, RitualExecutionRequest, TransformationResult, IDEMPOTENCY_KEY_HEADER,
+        IDEMPOTENCY_KEY_TTL_HOURS,
+    },
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+fn keyed(key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_str(key).unwrap());
+    headers
+}
+
+async fn execute(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    headers: HeaderMap,
+    seed: u64,
+) -> TransformationResult {
+    handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        headers,
+        Json(RitualExecutionRequest {
+            ritual_name: "shadow_integration".to_string(),
+            parameters: HashMap::new(),
+            intention: "Only once".to_string(),
+            seed: Some(seed),
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data
+}
+
+async fn count(app: &common::TestApp, table: &str, practitioner_id: Uuid) -> i64 {
+    sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} WHERE practitioner_id = $1",
+        table
+    ))
+    .bind(practitioner_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_repeated_key_returns_first_result_without_running_again() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let first = execute(&app, &practitioner, keyed("retry-me"), 1).await;
+    let states_after_first = count(&app, "archetypal_states", practitioner.id).await;
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 1);
+    assert_eq!(count(&app, "idempotency_keys", practitioner.id).await, 1);
+
+    // A different seed would change the outcome if the ritual actually ran
+    let retried = execute(&app, &practitioner, keyed("retry-me"), 2).await;
+    assert_eq!(retried.session_id, first.session_id);
+    assert_eq!(retried.post_state.archetypes, first.post_state.archetypes);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 1);
+    assert_eq!(
+        count(&app, "archetypal_states", practitioner.id).await,
+        states_after_first
+    );
+
+    let fresh = execute(&app, &practitioner, keyed("another-key"), 1).await;
+    assert_ne!(fresh.session_id, first.session_id);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 2);
+
+    // Without a key every request runs
+    execute(&app, &practitioner, HeaderMap::new(), 1).await;
+    execute(&app, &practitioner, HeaderMap::new(), 1).await;
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 4);
+}
+
+#[tokio::test]
+async fn test_expired_key_runs_again() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let first = execute(&app, &practitioner, keyed("old-key"), 1).await;
+    sqlx::query(
+        "UPDATE idempotency_keys SET created_at = created_at - make_interval(hours => $2)
+         WHERE practitioner_id = $1",
+    )
+    .bind(practitioner.id)
+    .bind((IDEMPOTENCY_KEY_TTL_HOURS + 1) as i32)
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let again = execute(&app, &practitioner, keyed("old-key"), 1).await;
+    assert_ne!(again.session_id, first.session_id);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 2);
+    assert_eq!(count(&app, "idempotency_keys", practitioner.id).await, 1);
+}
+
+#[tokio::test]
+async fn test_keys_are_scoped_to_the_practitioner() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let mine = common::register(&app).await;
+    let me = common::practitioner(&app, mine.practitioner.id).await;
+    let theirs = common::register(&app).await;
+    let them = common::practitioner(&app, theirs.practitioner.id).await;
+
+    let my_result = execute(&app, &me, keyed("shared-key"), 1).await;
+    let their_result = execute(&app, &them, keyed("shared-key"), 1).await;
+    assert_ne!(my_result.session_id, their_result.session_id);
+    assert_eq!(count(&app, "ritual_sessions", them.id).await, 1);
+}
+
+#[tokio::test]
+async fn test_oversized_key_is_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let status = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        keyed(&"k".repeat(256)),
+        Json(RitualExecutionRequest {
+            ritual_name: "shadow_integration".to_string(),
+            parameters: HashMap::new(),
+            intention: "Too long".to_string(),
+            seed: None,
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .expect_err("an oversized key should be rejected")
+    .status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 0);
+}
@@ -0,0 +1,177 @@
+//! `GET /api/insights` listing a practitioner's past oracle insights.
+
+mod common;
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{
+        InsightListParams, OracleInsight, PaginatedResponse, PaginationParams, Practitioner,
+        RitualExecutionRequest,
+    },
+};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+async fn run_session(app: &common::TestApp, practitioner: &Practitioner, seed: u64) -> Uuid {
+    handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(RitualExecutionRequest {
+            ritual_name: "shadow_integration".to_string(),
+            parameters: HashMap::new(),
+            intention: "Consult the oracle afterwards".to_string(),
+            seed: Some(seed),
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data
+    .session_id
+}
+
+/// Stores an insight `hours_ago` hours old
+async fn seed_insight(
+    app: &common::TestApp,
+    practitioner_id: Uuid,
+    session_id: Option<Uuid>,
+    hours_ago: i32,
+) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO oracle_insights (practitioner_id, session_id, insight_type, archetypal_analysis,
+                                      integration_suggestions, symbolic_emergence, oracle_model, created_at)
+         VALUES ($1, $2, 'ai_reflection', $3, '{}', '{}', 'test-oracle',
+                 NOW() - make_interval(hours => $4)) RETURNING id",
+    )
+    .bind(practitioner_id)
+    .bind(session_id)
+    .bind(json!({ "interpretation": "A seeded reading" }))
+    .bind(hours_ago)
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+async fn list(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    session_id: Option<Uuid>,
+    pagination: PaginationParams,
+) -> PaginatedResponse<OracleInsight> {
+    handlers::list_insights(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Query(InsightListParams { session_id }),
+        Query(pagination),
+    )
+    .await
+    .expect("listing failed")
+    .0
+    .data
+}
+
+fn ids(page: &PaginatedResponse<OracleInsight>) -> Vec<Uuid> {
+    page.items.iter().map(|insight| insight.id).collect()
+}
+
+#[tokio::test]
+async fn test_lists_all_insights_newest_first_with_paging() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let session = run_session(&app, &practitioner, 1).await;
+
+    let oldest = seed_insight(&app, practitioner.id, Some(session), 3).await;
+    let middle = seed_insight(&app, practitioner.id, None, 2).await;
+    let newest = seed_insight(&app, practitioner.id, Some(session), 1).await;
+
+    let all = list(&app, &practitioner, None, PaginationParams::default()).await;
+    assert_eq!(all.total, 3);
+    assert_eq!(ids(&all), vec![newest, middle, oldest]);
+
+    let second_page = list(
+        &app,
+        &practitioner,
+        None,
+        PaginationParams {
+            limit: Some(2),
+            offset: Some(2),
+        },
+    )
+    .await;
+    assert_eq!(second_page.total, 3);
+    assert_eq!(ids(&second_page), vec![oldest]);
+}
+
+#[tokio::test]
+async fn test_filters_insights_by_session() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let first_session = run_session(&app, &practitioner, 1).await;
+    let second_session = run_session(&app, &practitioner, 2).await;
+
+    let first = seed_insight(&app, practitioner.id, Some(first_session), 2).await;
+    seed_insight(&app, practitioner.id, Some(second_session), 1).await;
+    seed_insight(&app, practitioner.id, None, 1).await;
+
+    let filtered = list(
+        &app,
+        &practitioner,
+        Some(first_session),
+        PaginationParams::default(),
+    )
+    .await;
+    assert_eq!(filtered.total, 1);
+    assert_eq!(ids(&filtered), vec![first]);
+
+    let unknown = list(
+        &app,
+        &practitioner,
+        Some(Uuid::new_v4()),
+        PaginationParams::default(),
+    )
+    .await;
+    assert_eq!(unknown.total, 0);
+}
+
+#[tokio::test]
+async fn test_does_not_leak_another_practitioners_insights() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let mine = common::register(&app).await;
+    let me = common::practitioner(&app, mine.practitioner.id).await;
+    let theirs = common::register(&app).await;
+    let them = common::practitioner(&app, theirs.practitioner.id).await;
+
+    let my_session = run_session(&app, &me, 1).await;
+    let my_insight = seed_insight(&app, me.id, Some(my_session), 1).await;
+    seed_insight(&app, them.id, None, 1).await;
+    // A row claiming their ownership of my session is still not theirs to read
+    seed_insight(&app, them.id, Some(my_session), 1).await;
+
+    let mine_listed = list(&app, &me, None, PaginationParams::default()).await;
+    assert_eq!(ids(&mine_listed), vec![my_insight]);
+
+    let theirs_listed = list(&app, &them, None, PaginationParams::default()).await;
+    assert_eq!(theirs_listed.total, 1);
+    assert!(!ids(&theirs_listed).contains(&my_insight));
+
+    let probed = list(&app, &them, Some(my_session), PaginationParams::default()).await;
+    assert_eq!(probed.total, 0);
+    assert!(probed.items.is_empty());
+}
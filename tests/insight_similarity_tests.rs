@@ -0,0 +1,166 @@
+//! `GET /api/insights/similar` ranking a practitioner's past oracle insights.
+
+mod common;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{RitualExecutionRequest, SimilarInsightsQuery},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+async fn seed_insight(
+    app: &common::TestApp,
+    practitioner_id: Uuid,
+    session_id: Option<Uuid>,
+    analysis: &str,
+    suggestion: &str,
+) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO oracle_insights (practitioner_id, session_id, insight_type, archetypal_analysis,
+                                      integration_suggestions, symbolic_emergence, oracle_model)
+         VALUES ($1, $2, 'ai_reflection', $3, $4, '{}', 'test-oracle') RETURNING id",
+    )
+    .bind(practitioner_id)
+    .bind(session_id)
+    .bind(json!({ "interpretation": analysis }))
+    .bind(json!({ "next_steps": [suggestion] }))
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_most_similar_insight_ranks_first() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let session_id = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(RitualExecutionRequest {
+            ritual_name: "shadow_integration".to_string(),
+            parameters: HashMap::new(),
+            intention: "Meet the shadow".to_string(),
+            seed: Some(2),
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .unwrap()
+    .0
+    .data
+    .session_id;
+
+    seed_insight(
+        &app,
+        practitioner.id,
+        Some(session_id),
+        "The shadow surfaces carrying buried anger and fear",
+        "Journal with the shadow about the anger",
+    )
+    .await;
+    let fire = seed_insight(
+        &app,
+        practitioner.id,
+        None,
+        "Fire energy kindles creative expression",
+        "Paint or dance to channel the fire",
+    )
+    .await;
+    let shadow = seed_insight(
+        &app,
+        practitioner.id,
+        None,
+        "Old fear and anger return from the shadow",
+        "Dialogue with the shadow through active imagination",
+    )
+    .await;
+    let water = seed_insight(
+        &app,
+        practitioner.id,
+        None,
+        "Water invites emotional flow and softness",
+        "Rest near water and let feelings move",
+    )
+    .await;
+
+    // Another practitioner's matching insight must never be returned
+    let stranger = common::register(&app).await;
+    seed_insight(
+        &app,
+        stranger.practitioner.id,
+        None,
+        "The shadow surfaces carrying buried anger and fear",
+        "Journal with the shadow about the anger",
+    )
+    .await;
+
+    let similar = handlers::get_similar_insights(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Query(SimilarInsightsQuery {
+            session_id,
+            limit: None,
+        }),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+
+    let ids: Vec<Uuid> = similar.iter().map(|s| s.insight.id).collect();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids[0], shadow);
+    assert!(ids.contains(&fire) && ids.contains(&water));
+    assert!(similar[0].similarity > similar[1].similarity);
+
+    let top_one = handlers::get_similar_insights(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Query(SimilarInsightsQuery {
+            session_id,
+            limit: Some(1),
+        }),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+    assert_eq!(top_one.len(), 1);
+    assert_eq!(top_one[0].insight.id, shadow);
+}
+
+#[tokio::test]
+async fn test_unknown_session_is_not_found() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let status = handlers::get_similar_insights(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Query(SimilarInsightsQuery {
+            session_id: Uuid::new_v4(),
+            limit: None,
+        }),
+    )
+    .await
+    .unwrap_err()
+    .status();
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
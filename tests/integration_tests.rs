@@ -1,7 +1,11 @@
 use codex_control_engine::{
+    database::Database,
     handlers::AppState,
     auth::{create_jwt_token, verify_jwt_token},
     models::*,
+    locks::PractitionerLocks,
+    ratelimit::AuthRateLimiters,
+    streams::StateStreams,
     CodexEngine
 };
 use axum::{
@@ -42,6 +46,9 @@ async fn test_complete_ritual_flow() {
         ritual_name: "shadow_integration".to_string(),
         parameters: std::collections::HashMap::new(),
         intention: "Complete integration test of shadow work".to_string(),
+        seed: None,
+        dry_run: false,
+        auto_reflect: false,
     };
     
     let result = execute_test_ritual(&app_state, &practitioner, ritual_request).await;
@@ -77,7 +84,7 @@ async fn test_complete_ritual_flow() {
     let catalog = get_test_ritual_catalog(&app_state).await;
     assert!(catalog.len() >= 4); // At least the 4 foundational rituals
     
-    cleanup_test_data(&app_state.db, &practitioner).await;
+    cleanup_test_data(pg(&app_state), &practitioner).await;
 }
 
 #[tokio::test]
@@ -92,6 +99,9 @@ async fn test_wasm_ritual_execution() {
         ritual_name: "energy_attunement".to_string(),
         parameters: std::collections::HashMap::new(),
         intention: "Testing WASM execution path".to_string(),
+        seed: None,
+        dry_run: false,
+        auto_reflect: false,
     };
     
     let result = execute_test_ritual(&app_state, &practitioner, ritual_request).await;
@@ -103,7 +113,7 @@ async fn test_wasm_ritual_execution() {
     // Check that energy levels were modified
     assert!(result.post_state.energies.values().any(|&v| v != result.pre_state.energies.get("Fire").unwrap_or(&0.0)));
     
-    cleanup_test_data(&app_state.db, &practitioner).await;
+    cleanup_test_data(pg(&app_state), &practitioner).await;
 }
 
 #[tokio::test]
@@ -141,12 +151,15 @@ async fn test_authentication_and_authorization() {
         ritual_name: "void_contemplation".to_string(),
         parameters: std::collections::HashMap::new(),
         intention: "Testing authenticated access".to_string(),
+        seed: None,
+        dry_run: false,
+        auto_reflect: false,
     };
     
     let result = execute_test_ritual(&app_state, &practitioner, ritual_request).await;
     assert!(result.transformation_intensity > 0.0);
     
-    cleanup_test_data(&app_state.db, &practitioner).await;
+    cleanup_test_data(pg(&app_state), &practitioner).await;
 }
 
 #[tokio::test]
@@ -171,6 +184,9 @@ async fn test_multiple_ritual_progression() {
             ritual_name: ritual_name.to_string(),
             parameters: std::collections::HashMap::new(),
             intention: format!("Progressive ritual sequence: {}", ritual_name),
+            seed: None,
+            dry_run: false,
+            auto_reflect: false,
         };
         
         let result = execute_test_ritual(&app_state, &practitioner, ritual_request).await;
@@ -194,7 +210,7 @@ async fn test_multiple_ritual_progression() {
     let history = get_test_state_history(&app_state, &practitioner).await;
     assert!(history.len() >= ritual_sequence.len());
     
-    cleanup_test_data(&app_state.db, &practitioner).await;
+    cleanup_test_data(pg(&app_state), &practitioner).await;
 }
 
 #[tokio::test]
@@ -209,6 +225,9 @@ async fn test_ai_reflection_system() {
         ritual_name: "shadow_integration".to_string(),
         parameters: std::collections::HashMap::new(),
         intention: "Testing AI reflection capabilities".to_string(),
+        seed: None,
+        dry_run: false,
+        auto_reflect: false,
     };
     
     let ritual_result = execute_test_ritual(&app_state, &practitioner, ritual_request).await;
@@ -231,7 +250,7 @@ async fn test_ai_reflection_system() {
     let symbolic_emergence = reflection.symbolic_emergence.as_object().unwrap();
     assert!(symbolic_emergence.contains_key("symbols") || symbolic_emergence.contains_key("resonance_analysis"));
     
-    cleanup_test_data(&app_state.db, &practitioner).await;
+    cleanup_test_data(pg(&app_state), &practitioner).await;
 }
 
 // Helper functions for integration tests
@@ -246,7 +265,22 @@ async fn setup_test_database() -> PgPool {
 
 async fn create_test_app_state(db: PgPool) -> AppState {
     let engine = Arc::new(CodexEngine::new().expect("Failed to create Codex engine"));
-    AppState { db, engine }
+    AppState {
+        db: Database::Postgres(db),
+        engine,
+        state_streams: StateStreams::default(),
+        auth_rate_limiters: AuthRateLimiters::default(),
+        practitioner_locks: PractitionerLocks::default(),
+        ritual_batch_limit: DEFAULT_RITUAL_BATCH_LIMIT,
+    }
+}
+
+/// The Postgres pool behind the app state, for the helpers' direct queries
+fn pg(app_state: &AppState) -> &PgPool {
+    match &app_state.db {
+        Database::Postgres(pool) => pool,
+        Database::Sqlite(_) => panic!("integration tests run against Postgres"),
+    }
 }
 
 async fn register_test_practitioner(app_state: &AppState, registration: PractitionerRegistration) -> Practitioner {
@@ -262,7 +296,7 @@ async fn register_test_practitioner(app_state: &AppState, registration: Practiti
     .bind(&password_hash)
     .bind(&registration.spiritual_name)
     .bind(&registration.sacred_path)
-    .fetch_one(&app_state.db)
+    .fetch_one(pg(app_state))
     .await
     .expect("Failed to create test practitioner")
 }
@@ -284,7 +318,7 @@ async fn execute_test_ritual(
     request: RitualExecutionRequest
 ) -> TransformationResult {
     // This simulates the handlers::execute_ritual function
-    let current_state = get_or_create_initial_state(&app_state.db, practitioner.id).await;
+    let current_state = get_or_create_initial_state(pg(app_state), practitioner.id).await;
     
     // For testing, we'll create a mock transformation result
     let session_id = Uuid::new_v4();
@@ -312,11 +346,13 @@ async fn execute_test_ritual(
             post_state
         },
         transformation_intensity: 0.75,
+        dry_run: false,
         emerged_symbols: vec!["🔮".to_string()],
         integration_required: vec!["Practice integration".to_string()],
         next_rituals_suggested: vec!["Continue with complementary work".to_string()],
         oracle_consultation_recommended: true,
         execution_duration_ms: 1500,
+        oracle_insight: None,
     }
 }
 
@@ -350,7 +386,7 @@ async fn request_test_reflection(
 }
 
 async fn get_test_current_state(app_state: &AppState, practitioner: &Practitioner) -> crate::state::ArchetypalState {
-    get_or_create_initial_state(&app_state.db, practitioner.id).await
+    get_or_create_initial_state(pg(app_state), practitioner.id).await
 }
 
 async fn get_test_state_history(app_state: &AppState, practitioner: &Practitioner) -> Vec<StoredState> {
@@ -358,7 +394,7 @@ async fn get_test_state_history(app_state: &AppState, practitioner: &Practitione
         "SELECT * FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at DESC LIMIT 10"
     )
     .bind(practitioner.id)
-    .fetch_all(&app_state.db)
+    .fetch_all(pg(app_state))
     .await
     .unwrap_or_default()
 }
@@ -367,7 +403,7 @@ async fn get_test_ritual_catalog(app_state: &AppState) -> Vec<SacredRitual> {
     sqlx::query_as::<_, SacredRitual>(
         "SELECT * FROM sacred_rituals WHERE is_public = true ORDER BY usage_count DESC"
     )
-    .fetch_all(&app_state.db)
+    .fetch_all(pg(app_state))
     .await
     .unwrap_or_default()
 }
@@ -377,7 +413,7 @@ async fn login_test_practitioner(app_state: &AppState, login: PractitionerLogin)
         "SELECT * FROM practitioners WHERE email = $1"
     )
     .bind(&login.email)
-    .fetch_one(&app_state.db)
+    .fetch_one(pg(app_state))
     .await
     .expect("Test practitioner not found");
     
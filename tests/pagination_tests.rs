@@ -0,0 +1,121 @@
+//! `?limit=&offset=` handling on the state history and ritual catalog endpoints.
+
+mod common;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension,
+};
+use codex_control_engine::{handlers, models::PaginationParams};
+use uuid::Uuid;
+
+fn page(limit: i64, offset: i64) -> Query<PaginationParams> {
+    Query(PaginationParams {
+        limit: Some(limit),
+        offset: Some(offset),
+    })
+}
+
+/// Stores `count` states for the practitioner, one hour apart, newest first
+async fn seed_history(app: &common::TestApp, practitioner_id: Uuid, count: i32) {
+    for hours_ago in 0..count {
+        sqlx::query(
+            "INSERT INTO archetypal_states (practitioner_id, state_data, archetypes, energies, created_at)
+             VALUES ($1, '{}', '{}', '{}', NOW() - make_interval(hours => $2))",
+        )
+        .bind(practitioner_id)
+        .bind(hours_ago)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_second_history_page_is_distinct_and_older() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    seed_history(&app, auth.practitioner.id, 5).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let first = handlers::get_state_history(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        page(2, 0),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+    let second = handlers::get_state_history(
+        State(app.state.clone()),
+        Extension(practitioner),
+        page(2, 2),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+
+    assert_eq!(first.total, 5);
+    assert_eq!((second.limit, second.offset), (2, 2));
+    assert_eq!(first.items.len(), 2);
+    assert_eq!(second.items.len(), 2);
+    assert!(second
+        .items
+        .iter()
+        .all(|state| first.items.iter().all(|seen| seen.id != state.id)));
+    let oldest_on_first = first.items.iter().map(|s| s.created_at).min().unwrap();
+    let newest_on_second = second.items.iter().map(|s| s.created_at).max().unwrap();
+    assert!(newest_on_second < oldest_on_first);
+}
+
+#[tokio::test]
+async fn test_out_of_range_pagination_is_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    for (limit, offset) in [(0, 0), (-5, 0), (1_000, 0), (10, -1)] {
+        let history = handlers::get_state_history(
+            State(app.state.clone()),
+            Extension(practitioner.clone()),
+            page(limit, offset),
+        )
+        .await;
+        assert_eq!(history.unwrap_err().status(), StatusCode::BAD_REQUEST);
+
+        let catalog =
+            handlers::get_ritual_catalog(State(app.state.clone()), page(limit, offset)).await;
+        assert_eq!(catalog.unwrap_err().status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_catalog_defaults_and_limits() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+
+    let catalog =
+        handlers::get_ritual_catalog(State(app.state.clone()), Query(PaginationParams::default()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!((catalog.limit, catalog.offset), (20, 0));
+    assert!(catalog.total >= 4);
+
+    let first_only = handlers::get_ritual_catalog(State(app.state.clone()), page(1, 0))
+        .await
+        .unwrap()
+        .0
+        .data;
+    assert_eq!(first_only.items.len(), 1);
+    assert_eq!(first_only.total, catalog.total);
+}
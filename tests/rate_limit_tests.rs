@@ -0,0 +1,141 @@
+//! Per-IP rate limiting in front of the login, registration and restore endpoints.
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header::RETRY_AFTER, Request, StatusCode},
+    routing::post,
+    Router,
+};
+use codex_control_engine::ratelimit::{rate_limit_middleware, AuthRateLimiters, RateLimiter};
+use std::{net::SocketAddr, time::Duration};
+use tower::ServiceExt;
+
+fn limited_app(limiter: RateLimiter) -> Router {
+    Router::new().route(
+        "/api/users/login",
+        post(|| async { "welcome" }).route_layer(axum::middleware::from_fn_with_state(
+            limiter,
+            rate_limit_middleware,
+        )),
+    )
+}
+
+async fn attempt(app: &Router, client: &str) -> axum::response::Response {
+    forwarded_attempt(app, client, None).await
+}
+
+async fn forwarded_attempt(
+    app: &Router,
+    client: &str,
+    forwarded_for: Option<&str>,
+) -> axum::response::Response {
+    let mut request = Request::post("/api/users/login");
+    if let Some(forwarded_for) = forwarded_for {
+        request = request.header("x-forwarded-for", forwarded_for);
+    }
+    let mut request = request.body(Body::empty()).unwrap();
+    let addr: SocketAddr = client.parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(addr));
+    app.clone().oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_attempts_beyond_limit_are_rejected_until_window_passes() {
+    let app = limited_app(RateLimiter::new(3, Duration::from_millis(300)));
+
+    for _ in 0..3 {
+        assert_eq!(
+            attempt(&app, "10.0.0.1:5000").await.status(),
+            StatusCode::OK
+        );
+    }
+
+    let rejected = attempt(&app, "10.0.0.1:5001").await;
+    assert_eq!(rejected.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(rejected.headers()[RETRY_AFTER], "1");
+
+    // Other clients keep their own allowance
+    assert_eq!(
+        attempt(&app, "10.0.0.2:5000").await.status(),
+        StatusCode::OK
+    );
+
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    assert_eq!(
+        attempt(&app, "10.0.0.1:5000").await.status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_retry_after_counts_down_whole_seconds() {
+    let limiter = RateLimiter::new(1, Duration::from_secs(30));
+    let client = "10.0.0.3".parse().unwrap();
+
+    limiter.check(client).await.unwrap();
+    let wait = limiter.check(client).await.unwrap_err();
+
+    assert!(wait <= Duration::from_secs(30) && wait > Duration::from_secs(29));
+    let app = limited_app(limiter);
+    assert_eq!(
+        attempt(&app, "10.0.0.3:80").await.headers()[RETRY_AFTER],
+        "30"
+    );
+}
+
+#[tokio::test]
+async fn test_zero_attempts_still_allows_one() {
+    let limiter = RateLimiter::new(0, Duration::from_secs(30));
+    let client = "10.0.0.4".parse().unwrap();
+
+    limiter.check(client).await.unwrap();
+    assert!(limiter.check(client).await.is_err());
+}
+
+#[tokio::test]
+async fn test_forwarded_for_is_only_used_when_trusted() {
+    let proxy = "10.0.0.9:443";
+
+    // Untrusted, every client behind the proxy shares its allowance
+    let app = limited_app(RateLimiter::new(1, Duration::from_secs(30)));
+    assert_eq!(
+        forwarded_attempt(&app, proxy, Some("203.0.113.1")).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        forwarded_attempt(&app, proxy, Some("203.0.113.2")).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // Trusted, the proxy's appended entry names the client; earlier entries are ignored
+    let app = limited_app(RateLimiter::new(1, Duration::from_secs(30)).with_trusted_forwarded_for(true));
+    assert_eq!(
+        forwarded_attempt(&app, proxy, Some("198.51.100.7, 203.0.113.1")).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        forwarded_attempt(&app, proxy, Some("198.51.100.8, 203.0.113.1")).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(
+        forwarded_attempt(&app, proxy, Some("203.0.113.2")).await.status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_auth_routes_have_separate_allowances() {
+    let limiters = AuthRateLimiters {
+        login: RateLimiter::new(1, Duration::from_secs(30)),
+        register: RateLimiter::new(1, Duration::from_secs(30)),
+        restore: RateLimiter::new(1, Duration::from_secs(30)),
+    };
+    let client = "10.0.0.5".parse().unwrap();
+
+    limiters.login.check(client).await.unwrap();
+    assert!(limiters.login.check(client).await.is_err());
+
+    assert!(limiters.register.check(client).await.is_ok());
+    assert!(limiters.restore.check(client).await.is_ok());
+}
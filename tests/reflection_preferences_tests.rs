@@ -0,0 +1,128 @@
+//! Practitioners choosing their own oracle provider, model and API key.
+
+mod common;
+
+use axum::{extract::State, Extension, Json};
+use codex_control_engine::{
+    handlers,
+    models::{
+        ReflectionPreferences, ReflectionPreferencesUpdate, ReflectionRequest,
+        REFLECTION_KEY_SECRET_ENV,
+    },
+};
+
+const API_KEY: &str = "sk-practitioner-secret-key";
+
+fn preferring(model: &str, api_key: Option<&str>) -> ReflectionPreferencesUpdate {
+    ReflectionPreferencesUpdate {
+        preferences: ReflectionPreferences {
+            model: Some(model.to_string()),
+            temperature: Some(0.3),
+            ..ReflectionPreferences::default()
+        },
+        api_key: api_key.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn test_stored_api_key_is_encrypted_and_never_returned() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    std::env::set_var(REFLECTION_KEY_SECRET_ENV, "test-reflection-secret");
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let view = handlers::update_reflection_preferences(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Json(preferring("mistralai/mistral-large", Some(API_KEY))),
+    )
+    .await
+    .expect("update failed")
+    .0
+    .data;
+
+    assert!(view.has_api_key);
+    assert!(!serde_json::to_string(&view).unwrap().contains(API_KEY));
+
+    let stored: Option<String> =
+        sqlx::query_scalar("SELECT reflection_api_key FROM practitioners WHERE id = $1")
+            .bind(auth.practitioner.id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert!(!stored.unwrap().contains(API_KEY));
+
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    assert!(!serde_json::to_string(&practitioner)
+        .unwrap()
+        .contains("reflection_api_key"));
+    let fetched = handlers::get_reflection_preferences(Extension(practitioner))
+        .await
+        .0
+        .data;
+    assert!(fetched.has_api_key);
+    assert_eq!(
+        fetched.preferences.model.as_deref(),
+        Some("mistralai/mistral-large")
+    );
+}
+
+#[tokio::test]
+async fn test_reflection_uses_the_practitioners_model() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let view = handlers::update_reflection_preferences(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Json(preferring("meta-llama/llama-3.1-70b", None)),
+    )
+    .await
+    .expect("update failed")
+    .0
+    .data;
+    assert!(!view.has_api_key);
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let insight = handlers::request_reflection(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Json(ReflectionRequest {
+            session_id: None,
+            custom_query: None,
+        }),
+    )
+    .await
+    .expect("reflection failed")
+    .0
+    .data;
+
+    assert_eq!(insight.oracle_model, "meta-llama/llama-3.1-70b");
+}
+
+#[tokio::test]
+async fn test_out_of_range_temperature_is_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let mut update = preferring("anthropic/claude-3-haiku", None);
+    update.preferences.temperature = Some(3.5);
+
+    let status = handlers::update_reflection_preferences(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Json(update),
+    )
+    .await
+    .unwrap_err()
+    .status();
+
+    assert_eq!(status, axum::http::StatusCode::BAD_REQUEST);
+}
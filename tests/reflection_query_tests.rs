@@ -0,0 +1,65 @@
+//! Practitioners asking the oracle their own question through the reflection handler.
+
+mod common;
+
+use axum::{extract::State, Extension, Json};
+use codex_control_engine::{handlers, models::ReflectionRequest};
+
+#[tokio::test]
+async fn test_custom_query_is_recorded_with_the_insight() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let insight = handlers::request_reflection(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(ReflectionRequest {
+            session_id: None,
+            custom_query: Some("  What is my Shadow asking of me?  ".to_string()),
+        }),
+    )
+    .await
+    .expect("reflection failed")
+    .0
+    .data;
+
+    assert_eq!(
+        insight.archetypal_analysis["query"],
+        "What is my Shadow asking of me?"
+    );
+    let stored: serde_json::Value =
+        sqlx::query_scalar("SELECT archetypal_analysis FROM oracle_insights WHERE id = $1")
+            .bind(insight.id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(stored["query"], "What is my Shadow asking of me?");
+}
+
+#[tokio::test]
+async fn test_blank_query_keeps_the_generic_reflection() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let insight = handlers::request_reflection(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(ReflectionRequest {
+            session_id: None,
+            custom_query: Some("   ".to_string()),
+        }),
+    )
+    .await
+    .expect("reflection failed")
+    .0
+    .data;
+
+    assert!(insight.archetypal_analysis["query"].is_null());
+    assert_eq!(insight.archetypal_analysis["resonance_level"], 0.7);
+}
@@ -0,0 +1,108 @@
+//! Timeouts and body-size limits in front of the API routes.
+
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use codex_control_engine::limits::RequestLimits;
+use std::time::Duration;
+use tower::ServiceExt;
+
+fn limited_app(limits: RequestLimits) -> Router {
+    let echo = |Json(body): Json<serde_json::Value>| async move { Json(body) };
+    let api_routes = Router::new()
+        .route("/api/state/transform", post(echo))
+        .route(
+            "/api/state/reflection",
+            post(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "too late"
+            }),
+        );
+    let upload_routes = Router::new().route("/api/rituals/upload", post(echo));
+    limits.apply(api_routes, upload_routes)
+}
+
+fn small_limits() -> RequestLimits {
+    RequestLimits {
+        timeout: Duration::from_millis(100),
+        body_limit: 1024,
+        upload_body_limit: 4096,
+    }
+}
+
+/// A JSON body of roughly `len` bytes
+fn json_body(len: usize) -> String {
+    serde_json::json!({ "wasm_module": "a".repeat(len) }).to_string()
+}
+
+async fn post_json(app: &Router, uri: &str, body: String) -> StatusCode {
+    let request = Request::post(uri)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_oversized_upload_is_rejected_with_413() {
+    let app = limited_app(small_limits());
+
+    assert_eq!(
+        post_json(&app, "/api/rituals/upload", json_body(8192)).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
+#[tokio::test]
+async fn test_uploads_may_exceed_the_general_body_limit() {
+    let app = limited_app(small_limits());
+
+    assert_eq!(
+        post_json(&app, "/api/rituals/upload", json_body(2048)).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        post_json(&app, "/api/state/transform", json_body(2048)).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        post_json(&app, "/api/state/transform", json_body(512)).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_streamed_body_without_length_is_still_limited() {
+    let app = limited_app(small_limits());
+    let chunks =
+        futures_util::stream::iter((0..16).map(|_| Ok::<_, std::io::Error>(vec![b' '; 512])));
+    let request = Request::post("/api/state/transform")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from_stream(chunks))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_slow_request_times_out_with_408() {
+    let app = limited_app(small_limits());
+    let request = Request::post("/api/state/reflection")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+}
+
+#[test]
+fn test_default_limits_allow_larger_uploads() {
+    let limits = RequestLimits::default();
+
+    assert_eq!(limits.timeout, Duration::from_secs(30));
+    assert!(limits.upload_body_limit > limits.body_limit);
+}
@@ -0,0 +1,165 @@
+//! Several rituals submitted together and stored all or nothing.
+
+mod common;
+
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use codex_control_engine::{handlers, models::RitualExecutionRequest};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+fn execution(ritual_name: &str, seed: u64) -> RitualExecutionRequest {
+    RitualExecutionRequest {
+        ritual_name: ritual_name.to_string(),
+        parameters: HashMap::new(),
+        intention: "Planned sequence".to_string(),
+        seed: Some(seed),
+        dry_run: false,
+        auto_reflect: false,
+    }
+}
+
+async fn count(app: &common::TestApp, table: &str, practitioner_id: Uuid) -> i64 {
+    sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} WHERE practitioner_id = $1",
+        table
+    ))
+    .bind(practitioner_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_batch_runs_rituals_in_order_on_evolving_state() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let batch = vec![
+        execution("energy_attunement", 1),
+        execution("shadow_integration", 2),
+        execution("void_contemplation", 3),
+    ];
+    let results = handlers::execute_ritual_batch(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(batch),
+    )
+    .await
+    .expect("batch failed")
+    .0
+    .data;
+
+    assert_eq!(results.len(), 3);
+    // Each ritual starts from the state the one before it left
+    for pair in results.windows(2) {
+        assert_eq!(pair[1].pre_state.archetypes, pair[0].post_state.archetypes);
+        assert_eq!(pair[1].pre_state.energies, pair[0].post_state.energies);
+    }
+
+    let stored =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!(stored.archetypes, results[2].post_state.archetypes);
+
+    // The initial state plus one per ritual, and one session each
+    assert_eq!(count(&app, "archetypal_states", practitioner.id).await, 4);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 3);
+
+    // Sessions chain each ritual's pre-state to the one before's post-state
+    let chained: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM ritual_sessions later
+         JOIN ritual_sessions earlier ON later.pre_state_id = earlier.post_state_id
+         WHERE later.practitioner_id = $1",
+    )
+    .bind(practitioner.id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(chained, 2);
+}
+
+#[tokio::test]
+async fn test_batch_rolls_back_when_a_ritual_fails() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let stored_before =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    let usage_before: i32 =
+        sqlx::query_scalar("SELECT usage_count FROM sacred_rituals WHERE name = $1")
+            .bind("energy_attunement")
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+
+    let batch = vec![
+        execution("energy_attunement", 1),
+        execution("no_such_ritual", 2),
+        execution("void_contemplation", 3),
+    ];
+    let error = handlers::execute_ritual_batch(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(batch),
+    )
+    .await
+    .expect_err("batch with an unknown ritual should fail");
+    assert_eq!(error.status(), StatusCode::NOT_FOUND);
+    assert!(error.to_string().contains("no_such_ritual"));
+
+    let stored_after =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!(stored_after.archetypes, stored_before.archetypes);
+    assert_eq!(stored_after.energies, stored_before.energies);
+    assert_eq!(count(&app, "archetypal_states", practitioner.id).await, 1);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 0);
+
+    let usage_after: i32 =
+        sqlx::query_scalar("SELECT usage_count FROM sacred_rituals WHERE name = $1")
+            .bind("energy_attunement")
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(usage_after, usage_before);
+}
+
+#[tokio::test]
+async fn test_batch_over_limit_is_rejected() {
+    let Some(mut app) = common::test_app().await else {
+        return;
+    };
+    app.state.ritual_batch_limit = 2;
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let batch = (0..3)
+        .map(|seed| execution("shadow_integration", seed))
+        .collect();
+    let status = handlers::execute_ritual_batch(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(batch),
+    )
+    .await
+    .expect_err("oversized batch should be rejected")
+    .status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(count(&app, "ritual_sessions", practitioner.id).await, 0);
+}
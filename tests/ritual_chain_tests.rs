@@ -0,0 +1,52 @@
+//! Runs the foundational native rituals back to back through the engine.
+
+use codex_control_engine::CodexEngine;
+
+#[tokio::test]
+async fn test_chain_runs_all_native_rituals() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+    let names: Vec<String> = [
+        "energy_attunement",
+        "archetype_invocation",
+        "shadow_integration",
+        "void_contemplation",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect();
+
+    let chain = engine.execute_chain(&names).await.unwrap();
+
+    assert!(chain.completed);
+    let ran: Vec<&str> = chain
+        .results
+        .iter()
+        .map(|r| r.ritual_name.as_str())
+        .collect();
+    assert_eq!(ran, names);
+
+    let expected_mean = chain.results.iter().map(|r| r.resonance_level).sum::<f64>() / 4.0;
+    assert!((chain.mean_resonance() - expected_mean).abs() < 1e-12);
+    assert_eq!(
+        chain.total_duration_ms(),
+        chain.results.iter().map(|r| r.duration_ms).sum::<u64>()
+    );
+
+    // Each step saved the state it left behind, so the last save reflects the whole chain
+    let reloaded = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+    assert_eq!(
+        reloaded.get_state().evolution_cycle,
+        engine.get_state().evolution_cycle
+    );
+}
+
+#[tokio::test]
+async fn test_chain_stops_at_unknown_ritual() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut engine = CodexEngine::new_with_data_dir(data_dir.path().to_path_buf()).unwrap();
+    let names = vec!["energy_attunement".to_string(), "no_such_rite".to_string()];
+
+    assert!(engine.execute_chain(&names).await.is_err());
+    assert_eq!(engine.snapshot_count(), 1);
+}
@@ -0,0 +1,103 @@
+//! Who may delete a ritual through `DELETE /api/rituals/:id`.
+
+mod common;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use codex_control_engine::{handlers, models::AuthToken};
+use uuid::Uuid;
+
+async fn delete(app: &common::TestApp, auth: &AuthToken, ritual_id: Uuid) -> Result<(), StatusCode> {
+    let practitioner = common::practitioner(app, auth.practitioner.id).await;
+    handlers::delete_ritual(State(app.state.clone()), Extension(practitioner), Path(ritual_id))
+        .await
+        .map(|_| ())
+        .map_err(|error| error.status())
+}
+
+async fn ritual_exists(app: &common::TestApp, ritual_id: Uuid) -> bool {
+    sqlx::query("SELECT id FROM sacred_rituals WHERE id = $1")
+        .bind(ritual_id)
+        .fetch_optional(&app.db)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+#[tokio::test]
+async fn test_author_deletes_own_ritual() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+
+    assert_eq!(
+        delete(&app, &author, ritual_id).await,
+        Ok(())
+    );
+    assert!(!ritual_exists(&app, ritual_id).await);
+}
+
+#[tokio::test]
+async fn test_moderator_deletes_any_ritual() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+    let moderator = common::register(&app).await;
+    let moderator = common::promote(&app, &moderator, "moderator").await;
+
+    assert_eq!(
+        delete(&app, &moderator, ritual_id).await,
+        Ok(())
+    );
+    assert!(!ritual_exists(&app, ritual_id).await);
+}
+
+#[tokio::test]
+async fn test_plain_practitioner_cannot_delete_others_ritual() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+    let stranger = common::register(&app).await;
+
+    assert_eq!(
+        delete(&app, &stranger, ritual_id).await,
+        Err(StatusCode::FORBIDDEN)
+    );
+    assert!(ritual_exists(&app, ritual_id).await);
+}
+
+#[tokio::test]
+async fn test_demoted_moderator_loses_rights_before_token_expires() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+    let moderator = common::register(&app).await;
+    let moderator = common::promote(&app, &moderator, "moderator").await;
+    sqlx::query("UPDATE practitioners SET role = 'practitioner' WHERE id = $1")
+        .bind(moderator.practitioner.id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    // The token still claims the moderator role
+    assert_eq!(
+        common::claims(&moderator).role,
+        codex_control_engine::auth::Role::Moderator
+    );
+    assert_eq!(
+        delete(&app, &moderator, ritual_id).await,
+        Err(StatusCode::FORBIDDEN)
+    );
+    assert!(ritual_exists(&app, ritual_id).await);
+}
@@ -0,0 +1,104 @@
+//! Execution parameters checked against a ritual's parameter schema.
+
+mod common;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{Practitioner, RitualExecutionRequest},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A ritual expecting a numeric `intensity` and a string `mantra`, returned by name
+async fn schema_ritual(app: &common::TestApp, author_id: Uuid) -> String {
+    let ritual_id = common::create_ritual(app, author_id).await;
+    sqlx::query_scalar(
+        "UPDATE sacred_rituals SET parameter_schema = $2 WHERE id = $1 RETURNING name",
+    )
+    .bind(ritual_id)
+    .bind(serde_json::json!({ "intensity": "number", "mantra": "string" }))
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+async fn execute(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    ritual_name: &str,
+    parameters: serde_json::Value,
+) -> Result<(), (StatusCode, String)> {
+    handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(RitualExecutionRequest {
+            ritual_name: ritual_name.to_string(),
+            parameters: serde_json::from_value::<HashMap<String, serde_json::Value>>(parameters)
+                .unwrap(),
+            intention: "Follow the form".to_string(),
+            seed: Some(1),
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .map(|_| ())
+    .map_err(|error| (error.status(), error.to_string()))
+}
+
+async fn session_count(app: &common::TestApp, practitioner_id: Uuid) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM ritual_sessions WHERE practitioner_id = $1")
+        .bind(practitioner_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_parameters_matching_the_schema_run() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let ritual_name = schema_ritual(&app, practitioner.id).await;
+
+    execute(
+        &app,
+        &practitioner,
+        &ritual_name,
+        serde_json::json!({ "intensity": 0.8, "mantra": "so hum", "extra": true }),
+    )
+    .await
+    .expect("conforming parameters should run");
+    assert_eq!(session_count(&app, practitioner.id).await, 1);
+}
+
+#[tokio::test]
+async fn test_missing_and_mistyped_parameters_are_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let ritual_name = schema_ritual(&app, practitioner.id).await;
+
+    let (status, error) = execute(
+        &app,
+        &practitioner,
+        &ritual_name,
+        serde_json::json!({ "intensity": "high" }),
+    )
+    .await
+    .expect_err("nonconforming parameters should be rejected");
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error.contains("parameter 'intensity' should be number, got string"));
+    assert!(error.contains("missing required parameter 'mantra'"));
+    assert_eq!(session_count(&app, practitioner.id).await, 0);
+}
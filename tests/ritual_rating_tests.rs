@@ -0,0 +1,119 @@
+//! `POST /api/rituals/:id/rate` and the aggregate it keeps on `sacred_rituals`.
+
+mod common;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{AuthToken, RitualRatingRequest, RitualRatingSummary},
+};
+use uuid::Uuid;
+
+async fn rate(
+    app: &common::TestApp,
+    rater: &AuthToken,
+    ritual_id: Uuid,
+    rating: i32,
+) -> Result<RitualRatingSummary, StatusCode> {
+    let practitioner = common::practitioner(app, rater.practitioner.id).await;
+    handlers::rate_ritual(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Path(ritual_id),
+        Json(RitualRatingRequest { rating }),
+    )
+    .await
+    .map(|response| response.0.data)
+    .map_err(|error| error.status())
+}
+
+async fn stored_rating(app: &common::TestApp, ritual_id: Uuid) -> (f64, i32) {
+    sqlx::query_as("SELECT effectiveness_rating, rating_count FROM sacred_rituals WHERE id = $1")
+        .bind(ritual_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_first_rating_sets_average_and_count() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+
+    let summary = rate(&app, &author, ritual_id, 4).await.unwrap();
+
+    assert_eq!(summary.rating, 4);
+    assert_eq!(summary.rating_count, 1);
+    assert_eq!(summary.effectiveness_rating, 4.0);
+    assert_eq!(stored_rating(&app, ritual_id).await, (4.0, 1));
+}
+
+#[tokio::test]
+async fn test_rerating_replaces_previous_rating() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+
+    rate(&app, &author, ritual_id, 2).await.unwrap();
+    let summary = rate(&app, &author, ritual_id, 5).await.unwrap();
+
+    assert_eq!(summary.rating_count, 1);
+    assert_eq!(summary.effectiveness_rating, 5.0);
+    assert_eq!(stored_rating(&app, ritual_id).await, (5.0, 1));
+}
+
+#[tokio::test]
+async fn test_average_is_recomputed_across_practitioners() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let other = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+
+    rate(&app, &author, ritual_id, 5).await.unwrap();
+    let summary = rate(&app, &other, ritual_id, 2).await.unwrap();
+
+    assert_eq!(summary.rating_count, 2);
+    assert_eq!(summary.effectiveness_rating, 3.5);
+    assert_eq!(stored_rating(&app, ritual_id).await, (3.5, 2));
+}
+
+#[tokio::test]
+async fn test_out_of_range_rating_is_rejected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let author = common::register(&app).await;
+    let ritual_id = common::create_ritual(&app, author.practitioner.id).await;
+
+    for rating in [0, 6] {
+        assert_eq!(
+            rate(&app, &author, ritual_id, rating).await.unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+    assert_eq!(stored_rating(&app, ritual_id).await, (0.0, 0));
+}
+
+#[tokio::test]
+async fn test_rating_unknown_ritual_is_not_found() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let rater = common::register(&app).await;
+
+    assert_eq!(
+        rate(&app, &rater, Uuid::new_v4(), 3).await.unwrap_err(),
+        StatusCode::NOT_FOUND
+    );
+}
@@ -0,0 +1,200 @@
+//! Filtering the public catalog through `/api/rituals/search`.
+
+mod common;
+
+use axum::extract::{Query, State};
+use codex_control_engine::{
+    handlers,
+    models::{PaginatedResponse, PaginationParams, RitualSearchParams, SacredRitual},
+};
+use uuid::Uuid;
+
+async fn insert_ritual(
+    app: &common::TestApp,
+    difficulty: &str,
+    required_archetypes: serde_json::Value,
+    tags: serde_json::Value,
+) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO sacred_rituals
+         (name, description, intent, difficulty_level, required_archetypes, tags, is_public)
+         VALUES ($1, 'Search fixture', 'Testing', $2, $3, $4, true) RETURNING id",
+    )
+    .bind(format!("search_ritual_{}", Uuid::new_v4()))
+    .bind(difficulty)
+    .bind(required_archetypes)
+    .bind(tags)
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+async fn search(
+    app: &common::TestApp,
+    params: RitualSearchParams,
+) -> PaginatedResponse<SacredRitual> {
+    handlers::search_rituals(
+        State(app.state.clone()),
+        Query(params),
+        Query(PaginationParams::default()),
+    )
+    .await
+    .expect("search failed")
+    .0
+    .data
+}
+
+fn ids(page: &PaginatedResponse<SacredRitual>) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = page.items.iter().map(|ritual| ritual.id).collect();
+    ids.sort();
+    ids
+}
+
+/// A value no other test's rituals will share
+fn unique(prefix: &str) -> String {
+    format!("{}{}", prefix, &Uuid::new_v4().simple().to_string()[..8])
+}
+
+#[tokio::test]
+async fn test_search_filters_by_difficulty() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let difficulty = unique("adept-");
+    let mut expected = vec![
+        insert_ritual(
+            &app,
+            &difficulty,
+            serde_json::json!([]),
+            serde_json::json!([]),
+        )
+        .await,
+        insert_ritual(
+            &app,
+            &difficulty,
+            serde_json::json!(["Sage"]),
+            serde_json::json!([]),
+        )
+        .await,
+    ];
+    expected.sort();
+    insert_ritual(
+        &app,
+        "beginner",
+        serde_json::json!([]),
+        serde_json::json!([]),
+    )
+    .await;
+
+    let found = search(
+        &app,
+        RitualSearchParams {
+            difficulty: Some(difficulty.to_uppercase()),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(found.total, 2);
+    assert_eq!(ids(&found), expected);
+
+    // Paging applies to the filtered set
+    let first = handlers::search_rituals(
+        State(app.state.clone()),
+        Query(RitualSearchParams {
+            difficulty: Some(difficulty.clone()),
+            ..Default::default()
+        }),
+        Query(PaginationParams {
+            limit: Some(1),
+            offset: Some(1),
+        }),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+    assert_eq!(first.total, 2);
+    assert_eq!(first.items.len(), 1);
+}
+
+#[tokio::test]
+async fn test_search_matches_required_archetype_containment() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let archetype = unique("Seeker");
+    let required = insert_ritual(
+        &app,
+        "beginner",
+        serde_json::json!(["Sage", archetype]),
+        serde_json::json!([]),
+    )
+    .await;
+    // A longer name that merely starts with the archetype must not match
+    insert_ritual(
+        &app,
+        "beginner",
+        serde_json::json!([format!("{}hood", archetype)]),
+        serde_json::json!([]),
+    )
+    .await;
+
+    let found = search(
+        &app,
+        RitualSearchParams {
+            archetype: Some(archetype.clone()),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(ids(&found), vec![required]);
+
+    let narrowed = search(
+        &app,
+        RitualSearchParams {
+            archetype: Some(archetype),
+            difficulty: Some("advanced".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(narrowed.total, 0);
+}
+
+#[tokio::test]
+async fn test_search_text_matches_tags_and_binds_input_literally() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let tag = unique("moonlit_");
+    let tagged = insert_ritual(
+        &app,
+        "beginner",
+        serde_json::json!([]),
+        serde_json::json!(["lunar", tag]),
+    )
+    .await;
+
+    let found = search(
+        &app,
+        RitualSearchParams {
+            q: Some(tag.to_uppercase()),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(ids(&found), vec![tagged]);
+
+    // Quotes and wildcards are matched as text rather than altering the query
+    for q in ["' OR '1'='1", "%", "100%_sure"] {
+        let found = search(
+            &app,
+            RitualSearchParams {
+                q: Some(q.to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(found.total, 0, "{:?} should match nothing", q);
+    }
+}
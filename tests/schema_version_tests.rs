@@ -0,0 +1,90 @@
+//! The startup check that the database schema matches the embedded migrations.
+
+use codex_control_engine::{
+    database::{self, Database},
+    CodexError,
+};
+
+async fn migrated_sqlite() -> Database {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    db.migrate().await.unwrap();
+    db
+}
+
+#[tokio::test]
+async fn test_fully_migrated_database_passes() {
+    let db = migrated_sqlite().await;
+
+    assert_eq!(
+        db.applied_schema_version().await.unwrap(),
+        Some(db.expected_schema_version())
+    );
+    db.check_schema_version().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_database_behind_by_one_migration_is_refused() {
+    let db = migrated_sqlite().await;
+    let Database::Sqlite(pool) = &db else {
+        unreachable!()
+    };
+    sqlx::query(
+        "DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    let applied = db.applied_schema_version().await.unwrap().unwrap();
+    let expected = db.expected_schema_version();
+    assert!(applied < expected);
+
+    let error = database::prepare_schema(&db, false).await.unwrap_err();
+
+    assert!(matches!(error, CodexError::Configuration { .. }));
+    let message = error.to_string();
+    assert!(message.contains(&applied.to_string()), "{}", message);
+    assert!(message.contains(&expected.to_string()), "{}", message);
+    assert!(
+        message.contains("apply the pending migrations"),
+        "{}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn test_unmigrated_database_is_migrated_only_when_allowed() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+
+    let error = database::prepare_schema(&db, false).await.unwrap_err();
+    assert!(error.to_string().contains("at migration none"), "{}", error);
+    assert_eq!(db.applied_schema_version().await.unwrap(), None);
+
+    database::prepare_schema(&db, true).await.unwrap();
+    assert_eq!(
+        db.applied_schema_version().await.unwrap(),
+        Some(db.expected_schema_version())
+    );
+}
+
+#[tokio::test]
+async fn test_database_ahead_of_the_build_is_described_before_migrating() {
+    let db = migrated_sqlite().await;
+    let Database::Sqlite(pool) = &db else {
+        unreachable!()
+    };
+    let future_version = db.expected_schema_version() + 1;
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+         VALUES ($1, 'from a newer build', true, X'00', 0)",
+    )
+    .bind(future_version)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let error = database::prepare_schema(&db, true).await.unwrap_err();
+
+    let message = error.to_string();
+    assert!(message.contains(&future_version.to_string()), "{}", message);
+    assert!(message.contains("deploy a newer build"), "{}", message);
+}
@@ -0,0 +1,410 @@
+//! The API handlers running against an in-memory SQLite database.
+
+mod common;
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use codex_control_engine::{
+    auth,
+    database::Database,
+    handlers::{self, AppState},
+    models::*,
+};
+
+async fn register(state: &AppState, email: &str) -> AuthToken {
+    let registration = PractitionerRegistration {
+        email: email.to_string(),
+        password: "sacred_transformation".to_string(),
+        spiritual_name: Some("Offline Seeker".to_string()),
+        sacred_path: None,
+    };
+    handlers::register_user(State(state.clone()), Json(registration))
+        .await
+        .expect("registration failed")
+        .0
+        .data
+}
+
+#[tokio::test]
+async fn test_register_and_fetch_practitioner() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+
+    let registered = register(&state, "solo@codex.sacred").await;
+    assert!(matches!(state.db, Database::Sqlite(_)));
+
+    let login = PractitionerLogin {
+        email: "solo@codex.sacred".to_string(),
+        password: "sacred_transformation".to_string(),
+    };
+    let logged_in = handlers::login_user(State(state.clone()), Json(login))
+        .await
+        .expect("login failed")
+        .0
+        .data;
+    assert_eq!(logged_in.practitioner.id, registered.practitioner.id);
+    assert_eq!(logged_in.practitioner.role, "practitioner");
+
+    // Renewal looks the practitioner up by id and checks the stored token's expiry
+    let refresh = RefreshTokenRequest {
+        refresh_token: registered.refresh_token.clone(),
+    };
+    let renewed = handlers::refresh_token(State(state.clone()), Json(refresh))
+        .await
+        .expect("refresh failed")
+        .0
+        .data;
+    assert_eq!(
+        renewed.practitioner.spiritual_name.as_deref(),
+        Some("Offline Seeker")
+    );
+}
+
+#[tokio::test]
+async fn test_duplicate_registration_conflicts() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+    register(&state, "twice@codex.sacred").await;
+
+    let registration = PractitionerRegistration {
+        email: "twice@codex.sacred".to_string(),
+        password: "another_password".to_string(),
+        spiritual_name: None,
+        sacred_path: None,
+    };
+    let status = handlers::register_user(State(state.clone()), Json(registration))
+        .await
+        .unwrap_err()
+        .status();
+
+    assert_eq!(status, axum::http::StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_catalog_rating_and_state_history() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+    let auth = register(&state, "rater@codex.sacred").await;
+    let practitioner = match &state.db {
+        Database::Sqlite(pool) => {
+            sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE id = $1")
+                .bind(auth.practitioner.id)
+                .fetch_one(pool)
+                .await
+                .unwrap()
+        }
+        Database::Postgres(_) => unreachable!(),
+    };
+
+    let catalog =
+        handlers::get_ritual_catalog(State(state.clone()), Query(PaginationParams::default()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!(catalog.total, 6);
+    let shadow = catalog
+        .items
+        .iter()
+        .find(|ritual| ritual.name == "shadow_integration")
+        .unwrap();
+
+    let summary = handlers::rate_ritual(
+        State(state.clone()),
+        Extension(practitioner.clone()),
+        Path(shadow.id),
+        Json(RitualRatingRequest { rating: 4 }),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+    assert_eq!(summary.rating_count, 1);
+    assert_eq!(summary.effectiveness_rating, 4.0);
+
+    let current =
+        handlers::get_current_state(State(state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap();
+    assert!(!current.0.data.archetypes.is_empty());
+
+    let history = handlers::get_state_history(
+        State(state.clone()),
+        Extension(practitioner),
+        Query(PaginationParams::default()),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+    assert_eq!(history.total, 1);
+}
+
+#[tokio::test]
+async fn test_pruning_unlinks_sessions_from_deleted_states() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+    let auth = register(&state, "pruner@codex.sacred").await;
+    let Database::Sqlite(pool) = &state.db else {
+        unreachable!()
+    };
+    let practitioner =
+        sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE id = $1")
+            .bind(auth.practitioner.id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+    for seed in 0..3 {
+        let result = handlers::execute_ritual(
+            State(state.clone()),
+            Extension(practitioner.clone()),
+            axum::http::HeaderMap::new(),
+            Json(RitualExecutionRequest {
+                ritual_name: "shadow_integration".to_string(),
+                parameters: std::collections::HashMap::new(),
+                intention: "Grow".to_string(),
+                seed: Some(seed),
+                dry_run: false,
+                auto_reflect: false,
+            }),
+        )
+        .await
+        .expect("execution failed")
+        .0
+        .data;
+        assert!(!result.dry_run);
+    }
+
+    let result = handlers::prune_state(
+        State(state.clone()),
+        Extension(practitioner),
+        Json(StatePruneRequest { keep_last: 1 }),
+    )
+    .await
+    .expect("prune failed")
+    .0
+    .data;
+
+    assert_eq!((result.deleted, result.remaining), (3, 1));
+    let linked_sessions: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM ritual_sessions WHERE pre_state_id IS NOT NULL OR post_state_id IS NOT NULL",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(linked_sessions, 1);
+}
+
+#[tokio::test]
+async fn test_symbol_stats_aggregate_executed_sessions() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+    let auth = register(&state, "symbols@codex.sacred").await;
+    let Database::Sqlite(pool) = &state.db else {
+        unreachable!()
+    };
+    let practitioner =
+        sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE id = $1")
+            .bind(auth.practitioner.id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+    let mut emerged = 0;
+    for seed in 0..2 {
+        let result = handlers::execute_ritual(
+            State(state.clone()),
+            Extension(practitioner.clone()),
+            axum::http::HeaderMap::new(),
+            Json(RitualExecutionRequest {
+                ritual_name: "shadow_integration".to_string(),
+                parameters: std::collections::HashMap::new(),
+                intention: "Notice".to_string(),
+                seed: Some(seed),
+                dry_run: false,
+                auto_reflect: false,
+            }),
+        )
+        .await
+        .expect("execution failed")
+        .0
+        .data;
+        emerged += result.emerged_symbols.len() as i64;
+    }
+
+    let stats = handlers::get_symbol_stats(
+        State(state.clone()),
+        Extension(practitioner),
+        Query(SymbolStatsParams::default()),
+    )
+    .await
+    .expect("symbol stats failed")
+    .0
+    .data;
+
+    assert_eq!(
+        stats.iter().map(|stat| stat.occurrences).sum::<i64>(),
+        emerged
+    );
+    assert!(stats
+        .iter()
+        .all(|stat| (0.0..=1.0).contains(&stat.average_resonance)));
+}
+
+#[tokio::test]
+async fn test_ritual_search_filters() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+
+    let search = |params: RitualSearchParams| {
+        handlers::search_rituals(
+            State(state.clone()),
+            Query(params),
+            Query(PaginationParams::default()),
+        )
+    };
+
+    let beginner = search(RitualSearchParams {
+        difficulty: Some("beginner".to_string()),
+        ..Default::default()
+    })
+    .await
+    .unwrap()
+    .0
+    .data;
+    assert!(beginner
+        .items
+        .iter()
+        .all(|ritual| ritual.difficulty_level == "beginner"));
+    assert!(beginner.total > 0);
+
+    let sage = search(RitualSearchParams {
+        archetype: Some("Sage".to_string()),
+        ..Default::default()
+    })
+    .await
+    .unwrap()
+    .0
+    .data;
+    let mut names: Vec<_> = sage
+        .items
+        .iter()
+        .map(|ritual| ritual.name.as_str())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "energy_attunement",
+            "frequency_tuning",
+            "symbol_resolution",
+            "void_contemplation"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_uploaded_ritual_round_trips_every_column() {
+    let (state, _data_dir) = common::sqlite_app(true).await;
+    let auth = register(&state, "author@codex.sacred").await;
+    let practitioner = match &state.db {
+        Database::Sqlite(pool) => {
+            sqlx::query_as::<_, Practitioner>("SELECT * FROM practitioners WHERE id = $1")
+                .bind(auth.practitioner.id)
+                .fetch_one(pool)
+                .await
+                .unwrap()
+        }
+        Database::Postgres(_) => unreachable!(),
+    };
+
+    let upload = RitualUpload {
+        name: "dusk_listening".to_string(),
+        description: "Listen as the light fades".to_string(),
+        intent: "Attune to endings".to_string(),
+        tradition: "universal".to_string(),
+        difficulty_level: "beginner".to_string(),
+        required_archetypes: vec!["Sage".to_string()],
+        energy_requirements: [("Water".to_string(), 0.3)].into_iter().collect(),
+        parameter_schema: Default::default(),
+        wasm_module: None,
+        module_language: None,
+        is_public: true,
+        tags: vec!["dusk".to_string(), "listening".to_string()],
+    };
+    let uploaded = handlers::upload_ritual(
+        State(state.clone()),
+        Extension(practitioner.clone()),
+        Json(upload),
+    )
+    .await
+    .unwrap()
+    .0
+    .data;
+
+    let loaded = handlers::get_ritual_details(State(state.clone()), Path(uploaded.id))
+        .await
+        .unwrap()
+        .0
+        .data;
+    assert_eq!(loaded.name, "dusk_listening");
+    assert_eq!(loaded.tags, serde_json::json!(["dusk", "listening"]));
+    assert_eq!(loaded.required_archetypes, serde_json::json!(["Sage"]));
+    assert_eq!(
+        loaded.energy_requirements,
+        serde_json::json!({ "Water": 0.3 })
+    );
+    assert_eq!(loaded.author_id, Some(practitioner.id));
+    assert_eq!(loaded.usage_count, 0);
+    assert_eq!(loaded.rating_count, 0);
+    assert_eq!(loaded.updated_at, uploaded.updated_at);
+    assert!(loaded.updated_at >= loaded.created_at);
+
+    // The catalog's explicit column list maps onto the same struct
+    let catalog =
+        handlers::get_ritual_catalog(State(state.clone()), Query(PaginationParams::default()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    let listed = catalog
+        .items
+        .iter()
+        .find(|ritual| ritual.id == uploaded.id)
+        .unwrap();
+    assert_eq!(listed.tags, loaded.tags);
+    assert_eq!(listed.rating_count, 0);
+    assert_eq!(listed.updated_at, loaded.updated_at);
+}
+
+#[tokio::test]
+async fn test_auth_failures_carry_an_error_body() {
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    let (state, _data_dir) = common::sqlite_app(true).await;
+    let app = Router::new().route(
+        "/api/users/profile",
+        get(|| async { "profile" }).route_layer(axum::middleware::from_fn_with_state(
+            state,
+            auth::auth_middleware,
+        )),
+    );
+
+    for authorization in [None, Some("Bearer not-a-token")] {
+        let mut request = Request::get("/api/users/profile");
+        if let Some(value) = authorization {
+            request = request.header("authorization", value);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), axum::http::StatusCode::UNAUTHORIZED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "unauthorized");
+        assert!(!body["error"].as_str().unwrap().is_empty());
+    }
+}
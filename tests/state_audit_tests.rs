@@ -0,0 +1,124 @@
+//! The per-change audit log written alongside each stored ritual session.
+
+mod common;
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{
+        PaginatedResponse, PaginationParams, Practitioner, RitualExecutionRequest,
+        StateTransformation, TransformationResult,
+    },
+};
+use std::collections::HashMap;
+
+async fn integrate_shadow(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    dry_run: bool,
+) -> TransformationResult {
+    handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(RitualExecutionRequest {
+            ritual_name: "shadow_integration".to_string(),
+            parameters: HashMap::from([("aspect".to_string(), serde_json::json!("Envy"))]),
+            intention: "Own what I envy".to_string(),
+            seed: Some(7),
+            dry_run,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data
+}
+
+async fn audit_log(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+) -> PaginatedResponse<StateTransformation> {
+    handlers::get_state_audit(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Query(PaginationParams {
+            limit: None,
+            offset: None,
+        }),
+    )
+    .await
+    .expect("audit failed")
+    .0
+    .data
+}
+
+#[tokio::test]
+async fn test_shadow_integration_records_its_changes() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let result = integrate_shadow(&app, &practitioner, false).await;
+    let log = audit_log(&app, &practitioner).await;
+
+    assert_eq!(log.total as usize, result.integration_required.len());
+    assert!(log
+        .items
+        .iter()
+        .all(|change| change.session_id == result.session_id));
+    assert!(log
+        .items
+        .iter()
+        .all(|change| change.ritual_name.as_deref() == Some("shadow_integration")));
+    let aspect = log
+        .items
+        .iter()
+        .find(|change| change.description == "Shadow aspect 'Envy' integrated")
+        .expect("the named aspect is logged");
+    assert_eq!(aspect.change_type, "Integration");
+    assert_eq!(aspect.magnitude, 1.0);
+
+    let stored: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM state_transformations WHERE session_id = $1")
+            .bind(result.session_id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(stored, log.total);
+}
+
+#[tokio::test]
+async fn test_dry_run_leaves_the_audit_log_empty() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    integrate_shadow(&app, &practitioner, true).await;
+
+    assert_eq!(audit_log(&app, &practitioner).await.total, 0);
+}
+
+#[tokio::test]
+async fn test_audit_log_lists_newest_session_first() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    integrate_shadow(&app, &practitioner, false).await;
+    let latest = integrate_shadow(&app, &practitioner, false).await;
+
+    let log = audit_log(&app, &practitioner).await;
+    assert_eq!(log.items[0].session_id, latest.session_id);
+}
@@ -0,0 +1,157 @@
+//! Diffing two of a practitioner's stored states.
+
+mod common;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{Practitioner, StateComparisonParams, StateTransformationRequest},
+    state::ArchetypalState,
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+async fn transform(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+    kind: &str,
+    parameters: serde_json::Value,
+) -> ArchetypalState {
+    let request = StateTransformationRequest {
+        transformation_type: kind.to_string(),
+        parameters: serde_json::from_value::<HashMap<String, serde_json::Value>>(parameters)
+            .unwrap(),
+    };
+    handlers::transform_state(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(request),
+    )
+    .await
+    .expect("transformation failed")
+    .0
+    .data
+}
+
+async fn current_state(app: &common::TestApp, practitioner: &Practitioner) -> ArchetypalState {
+    handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+        .await
+        .unwrap()
+        .0
+        .data
+}
+
+/// The practitioner's stored state ids, oldest first
+async fn state_ids(app: &common::TestApp, practitioner_id: Uuid) -> Vec<Uuid> {
+    sqlx::query_scalar(
+        "SELECT id FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at",
+    )
+    .bind(practitioner_id)
+    .fetch_all(&app.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_compare_own_states_reports_deltas() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let initial = current_state(&app, &practitioner).await;
+    transform(
+        &app,
+        &practitioner,
+        "archetype_activation",
+        serde_json::json!({ "archetype": "Shadow", "intensity": 0.7 }),
+    )
+    .await;
+    transform(
+        &app,
+        &practitioner,
+        "energy_adjustment",
+        serde_json::json!({ "energy_type": "Fire", "adjustment": 0.25 }),
+    )
+    .await;
+
+    let ids = state_ids(&app, practitioner.id).await;
+    assert_eq!(ids.len(), 3);
+    let comparison = handlers::compare_states(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Query(StateComparisonParams {
+            from: ids[0],
+            to: ids[2],
+        }),
+    )
+    .await
+    .expect("comparison failed")
+    .0
+    .data;
+
+    assert_eq!(comparison.from_state_id, ids[0]);
+    assert_eq!(comparison.to_state_id, ids[2]);
+    assert!(comparison.from_created_at <= comparison.to_created_at);
+
+    let diff = comparison.diff;
+    assert_eq!(diff.archetype_deltas.len(), 1);
+    let shadow_delta = 0.7 - initial.archetypes["Shadow"];
+    assert!((diff.archetype_deltas["Shadow"] - shadow_delta).abs() < 1e-9);
+    assert_eq!(diff.energy_deltas.len(), 1);
+    assert!((diff.energy_deltas["Fire"].amplitude - 0.25).abs() < 1e-9);
+    assert_eq!(diff.energy_deltas["Fire"].frequency, 0.0);
+    assert!(diff.added_symbols.is_empty());
+    assert!(diff.removed_symbols.is_empty());
+}
+
+#[tokio::test]
+async fn test_compare_rejects_another_practitioners_state() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let mine = common::register(&app).await;
+    let me = common::practitioner(&app, mine.practitioner.id).await;
+    let theirs = common::register(&app).await;
+    let them = common::practitioner(&app, theirs.practitioner.id).await;
+
+    for practitioner in [&me, &them] {
+        current_state(&app, practitioner).await;
+    }
+    let my_state = state_ids(&app, me.id).await[0];
+    let their_state = state_ids(&app, them.id).await[0];
+
+    for (from, to) in [
+        (my_state, their_state),
+        (their_state, my_state),
+        (their_state, their_state),
+    ] {
+        let status = handlers::compare_states(
+            State(app.state.clone()),
+            Extension(me.clone()),
+            Query(StateComparisonParams { from, to }),
+        )
+        .await
+        .expect_err("another practitioner's state must not be compared")
+        .status();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    let status = handlers::compare_states(
+        State(app.state.clone()),
+        Extension(me.clone()),
+        Query(StateComparisonParams {
+            from: my_state,
+            to: Uuid::new_v4(),
+        }),
+    )
+    .await
+    .expect_err("an unknown state must not be compared")
+    .status();
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,126 @@
+//! Replaying a practitioner's ritual sessions to check their current state.
+
+mod common;
+
+use axum::{extract::State, http::HeaderMap, http::StatusCode, Extension, Json};
+use codex_control_engine::{
+    handlers,
+    models::{Practitioner, RitualExecutionRequest, StateReplay},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+async fn execute(app: &common::TestApp, practitioner: &Practitioner, ritual_name: &str) {
+    let result = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(RitualExecutionRequest {
+            ritual_name: ritual_name.to_string(),
+            parameters: HashMap::new(),
+            intention: "Walk the path again".to_string(),
+            seed: None,
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data;
+    assert!(!result.dry_run);
+}
+
+async fn replay(
+    app: &common::TestApp,
+    practitioner: &Practitioner,
+) -> Result<StateReplay, StatusCode> {
+    handlers::replay_state(State(app.state.clone()), Extension(practitioner.clone()))
+        .await
+        .map(|response| response.0.data)
+        .map_err(|error| error.status())
+}
+
+/// Runs three unseeded rituals for a new practitioner
+async fn practitioner_with_history(app: &common::TestApp) -> Practitioner {
+    let auth = common::register(app).await;
+    let practitioner = common::practitioner(app, auth.practitioner.id).await;
+    for ritual_name in [
+        "shadow_integration",
+        "void_contemplation",
+        "shadow_integration",
+    ] {
+        execute(app, &practitioner, ritual_name).await;
+    }
+    practitioner
+}
+
+async fn latest_state_id(app: &common::TestApp, practitioner_id: Uuid) -> Uuid {
+    sqlx::query_scalar(
+        "SELECT id FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(practitioner_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_replay_reproduces_current_state() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let practitioner = practitioner_with_history(&app).await;
+
+    let report = replay(&app, &practitioner).await.expect("replay failed");
+    assert_eq!(report.sessions_replayed, 3);
+    assert_eq!(
+        report.current_state_id,
+        latest_state_id(&app, practitioner.id).await
+    );
+    assert!(report.matches, "unexpected diff: {:?}", report.diff);
+    assert!(report.diff.is_none());
+}
+
+#[tokio::test]
+async fn test_tampered_state_is_detected() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let practitioner = practitioner_with_history(&app).await;
+
+    sqlx::query(
+        "UPDATE archetypal_states
+         SET state_data = jsonb_set(state_data, '{archetypes,Shadow,activation_level}', '0.01')
+         WHERE id = $1",
+    )
+    .bind(latest_state_id(&app, practitioner.id).await)
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let report = replay(&app, &practitioner).await.expect("replay failed");
+    assert!(!report.matches);
+    let diff = report.diff.expect("a mismatch should come with a diff");
+    assert!(diff.archetype_deltas.contains_key("Shadow"));
+    assert!(diff.energy_deltas.is_empty());
+}
+
+#[tokio::test]
+async fn test_session_without_seed_cannot_be_replayed() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let practitioner = practitioner_with_history(&app).await;
+
+    sqlx::query("UPDATE ritual_sessions SET seed = NULL WHERE practitioner_id = $1")
+        .bind(practitioner.id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        replay(&app, &practitioner).await.unwrap_err(),
+        StatusCode::CONFLICT
+    );
+}
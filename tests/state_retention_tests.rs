@@ -0,0 +1,139 @@
+//! Pruning a practitioner's stored state history down to its newest states.
+
+mod common;
+
+use axum::{extract::State, Extension, Json};
+use codex_control_engine::{
+    handlers,
+    models::StatePruneRequest,
+    retention::{prune_all_state_histories, prune_state_history},
+};
+use uuid::Uuid;
+
+/// Stores `count` states a minute apart, oldest first, returning their ids in that order
+async fn insert_states(app: &common::TestApp, practitioner_id: Uuid, count: i64) -> Vec<Uuid> {
+    let start = chrono::Utc::now() - chrono::Duration::days(1);
+    let mut ids = Vec::new();
+    for minute in 0..count {
+        let id: Uuid = sqlx::query_scalar(
+            "INSERT INTO archetypal_states (practitioner_id, state_data, archetypes, energies, created_at)
+             VALUES ($1, '{}', '{}', '{}', $2) RETURNING id",
+        )
+        .bind(practitioner_id)
+        .bind(start + chrono::Duration::minutes(minute))
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+        ids.push(id);
+    }
+    ids
+}
+
+async fn stored_state_ids(app: &common::TestApp, practitioner_id: Uuid) -> Vec<Uuid> {
+    sqlx::query_scalar(
+        "SELECT id FROM archetypal_states WHERE practitioner_id = $1 ORDER BY created_at",
+    )
+    .bind(practitioner_id)
+    .fetch_all(&app.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_pruning_keeps_only_the_newest_states() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner_id = auth.practitioner.id;
+    let before = stored_state_ids(&app, practitioner_id).await;
+    let inserted = insert_states(&app, practitioner_id, 30).await;
+
+    // A session spanning a pruned and a kept state
+    let session_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO ritual_sessions (practitioner_id, pre_state_id, post_state_id)
+         VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(practitioner_id)
+    .bind(inserted[19])
+    .bind(inserted[20])
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+
+    let deleted = prune_state_history(&app.state.db, practitioner_id, 10)
+        .await
+        .unwrap();
+
+    assert_eq!(deleted as usize, before.len() + 20);
+    assert_eq!(
+        stored_state_ids(&app, practitioner_id).await,
+        inserted[20..]
+    );
+    let (pre_state_id, post_state_id): (Option<Uuid>, Option<Uuid>) =
+        sqlx::query_as("SELECT pre_state_id, post_state_id FROM ritual_sessions WHERE id = $1")
+            .bind(session_id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(pre_state_id, None);
+    assert_eq!(post_state_id, Some(inserted[20]));
+}
+
+#[tokio::test]
+async fn test_prune_endpoint_always_keeps_the_latest_state() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+    let inserted = insert_states(&app, practitioner.id, 5).await;
+
+    let result = handlers::prune_state(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        Json(StatePruneRequest { keep_last: 0 }),
+    )
+    .await
+    .expect("prune failed")
+    .0
+    .data;
+
+    assert_eq!(result.remaining, 1);
+    assert_eq!(
+        stored_state_ids(&app, practitioner.id).await,
+        [*inserted.last().unwrap()]
+    );
+}
+
+#[tokio::test]
+async fn test_pruning_leaves_other_practitioners_alone() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let pruned = common::register(&app).await.practitioner.id;
+    let other = common::register(&app).await.practitioner.id;
+    insert_states(&app, pruned, 3).await;
+    let others_before = stored_state_ids(&app, other).await;
+
+    prune_state_history(&app.state.db, pruned, 1).await.unwrap();
+
+    assert_eq!(stored_state_ids(&app, pruned).await.len(), 1);
+    assert_eq!(stored_state_ids(&app, other).await, others_before);
+}
+
+#[tokio::test]
+async fn test_scheduled_pruning_covers_every_practitioner() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let first = common::register(&app).await.practitioner.id;
+    let second = common::register(&app).await.practitioner.id;
+    insert_states(&app, first, 4).await;
+    insert_states(&app, second, 4).await;
+
+    prune_all_state_histories(&app.state.db, &app.state.practitioner_locks, 2).await.unwrap();
+
+    assert_eq!(stored_state_ids(&app, first).await.len(), 2);
+    assert_eq!(stored_state_ids(&app, second).await.len(), 2);
+}
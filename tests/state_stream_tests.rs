@@ -0,0 +1,60 @@
+//! `GET /api/state/stream` delivering state updates as server-sent events.
+
+mod common;
+
+use axum::{extract::State, http::HeaderMap, response::IntoResponse, Extension, Json};
+use codex_control_engine::{handlers, models::RitualExecutionRequest, state::ArchetypalState};
+use std::{collections::HashMap, time::Duration};
+use tokio_stream::StreamExt;
+
+#[tokio::test]
+async fn test_ritual_execution_emits_state_event() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let stream = handlers::stream_state(State(app.state.clone()), Extension(practitioner.clone()))
+        .await
+        .into_response();
+    let mut body = stream.into_body().into_data_stream();
+
+    let request = RitualExecutionRequest {
+        ritual_name: "shadow_integration".to_string(),
+        parameters: HashMap::new(),
+        intention: "Watch the shadow move".to_string(),
+        seed: Some(11),
+        dry_run: false,
+        auto_reflect: false,
+    };
+    let result = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner),
+        HeaderMap::new(),
+        Json(request),
+    )
+    .await
+    .expect("ritual execution failed")
+    .0
+    .data;
+
+    let chunk = tokio::time::timeout(Duration::from_secs(5), body.next())
+        .await
+        .expect("no state event arrived")
+        .unwrap()
+        .unwrap();
+    let event = String::from_utf8(chunk.to_vec()).unwrap();
+    assert!(
+        event.starts_with("event: state\n"),
+        "unexpected event: {event}"
+    );
+
+    let data = event
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap();
+    let streamed: ArchetypalState = serde_json::from_str(data).unwrap();
+    assert_eq!(streamed.archetypes, result.post_state.archetypes);
+    assert_eq!(streamed.symbols, result.post_state.symbols);
+}
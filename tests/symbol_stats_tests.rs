@@ -0,0 +1,157 @@
+//! Symbol frequency statistics through `GET /api/stats/symbols`.
+
+mod common;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use codex_control_engine::{
+    handlers,
+    models::{AuthToken, RitualExecutionRequest, SymbolStatistic, SymbolStatsParams},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Records a session that resonated at `resonance` and gave rise to `symbols`
+async fn seed_session(
+    app: &common::TestApp,
+    practitioner_id: Uuid,
+    resonance: f64,
+    symbols: &[&str],
+) {
+    let session_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO ritual_sessions (id, practitioner_id, transformation_intensity) VALUES ($1, $2, $3)",
+    )
+    .bind(session_id)
+    .bind(practitioner_id)
+    .bind(resonance)
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    for (position, symbol) in symbols.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO session_symbols (practitioner_id, session_id, position, symbol)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(practitioner_id)
+        .bind(session_id)
+        .bind(position as i32)
+        .bind(symbol)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+}
+
+async fn symbol_stats(
+    app: &common::TestApp,
+    auth: &AuthToken,
+    all: bool,
+) -> Result<Vec<SymbolStatistic>, StatusCode> {
+    let practitioner = common::practitioner(app, auth.practitioner.id).await;
+    handlers::get_symbol_stats(
+        State(app.state.clone()),
+        Extension(practitioner),
+        Query(SymbolStatsParams { all }),
+    )
+    .await
+    .map(|response| response.0.data)
+    .map_err(|error| error.status())
+}
+
+fn statistic<'a>(stats: &'a [SymbolStatistic], symbol: &str) -> Option<&'a SymbolStatistic> {
+    stats.iter().find(|stat| stat.symbol == symbol)
+}
+
+/// A symbol no other test run emits, so global counts stay predictable
+fn unique_symbol(name: &str) -> String {
+    format!("{}-{}", name, Uuid::new_v4())
+}
+
+#[tokio::test]
+async fn test_own_symbol_counts_and_average_resonance() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let id = auth.practitioner.id;
+    seed_session(&app, id, 0.2, &["🌑", "🔥"]).await;
+    seed_session(&app, id, 0.6, &["🌑"]).await;
+    seed_session(&app, id, 0.7, &["🌑", "💧"]).await;
+
+    let stats = symbol_stats(&app, &auth, false).await.unwrap();
+
+    assert_eq!(stats.len(), 3);
+    assert_eq!(stats[0].symbol, "🌑");
+    assert_eq!(stats[0].occurrences, 3);
+    assert!((stats[0].average_resonance - 0.5).abs() < 1e-9);
+    let fire = statistic(&stats, "🔥").unwrap();
+    assert_eq!(fire.occurrences, 1);
+    assert!((fire.average_resonance - 0.2).abs() < 1e-9);
+    assert!((statistic(&stats, "💧").unwrap().average_resonance - 0.7).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_only_moderators_see_all_practitioners() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let shared = unique_symbol("shared");
+    let first = common::register(&app).await;
+    let second = common::register(&app).await;
+    seed_session(&app, first.practitioner.id, 0.4, &[&shared]).await;
+    seed_session(&app, second.practitioner.id, 0.8, &[&shared]).await;
+
+    let own = symbol_stats(&app, &first, false).await.unwrap();
+    assert_eq!(statistic(&own, &shared).unwrap().occurrences, 1);
+    assert_eq!(
+        symbol_stats(&app, &first, true).await.unwrap_err(),
+        StatusCode::FORBIDDEN
+    );
+
+    let moderator = common::register(&app).await;
+    let moderator = common::promote(&app, &moderator, "moderator").await;
+    let global = symbol_stats(&app, &moderator, true).await.unwrap();
+    let shared_stat = statistic(&global, &shared).unwrap();
+    assert_eq!(shared_stat.occurrences, 2);
+    assert!((shared_stat.average_resonance - 0.6).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_executed_ritual_records_its_symbols() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let result = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner),
+        HeaderMap::new(),
+        Json(RitualExecutionRequest {
+            ritual_name: "shadow_integration".to_string(),
+            parameters: HashMap::new(),
+            intention: "See what emerges".to_string(),
+            seed: Some(3),
+            dry_run: false,
+            auto_reflect: false,
+        }),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data;
+
+    let stats = symbol_stats(&app, &auth, false).await.unwrap();
+    assert_eq!(stats.len(), result.emerged_symbols.len());
+    for symbol in &result.emerged_symbols {
+        let stat = statistic(&stats, symbol).unwrap();
+        assert_eq!(stat.occurrences, 1);
+        assert!((stat.average_resonance - result.transformation_intensity).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,150 @@
+//! The full symbolic state surviving the web layer's store/fetch round-trip.
+
+mod common;
+
+use axum::{extract::State, http::HeaderMap, Extension, Json};
+use codex_control_engine::{
+    handlers,
+    models::RitualExecutionRequest,
+    state::{ArchetypalState, Archetype, Element, Energy, SymbolicState},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+async fn insert_state(
+    app: &common::TestApp,
+    practitioner_id: Uuid,
+    state_data: serde_json::Value,
+    flattened: &ArchetypalState,
+) {
+    sqlx::query(
+        "INSERT INTO archetypal_states (id, practitioner_id, state_data, archetypes, energies) \
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(practitioner_id)
+    .bind(state_data)
+    .bind(serde_json::to_value(&flattened.archetypes).unwrap())
+    .bind(serde_json::to_value(&flattened.energies).unwrap())
+    .execute(&app.db)
+    .await
+    .unwrap();
+}
+
+fn seasoned_state() -> SymbolicState {
+    let mut state = SymbolicState::new();
+    for name in ["Sage", "Shadow", "Anima", "Creator"] {
+        let mut archetype = Archetype::new(name.to_string(), format!("Archetype: {}", name));
+        archetype.activation_level = 0.1;
+        state.add_archetype(archetype);
+    }
+    let shadow = state.archetypes.get_mut("Shadow").unwrap();
+    shadow.evolution_count = 3;
+    shadow.integrate_aspect("envy".to_string(), true);
+
+    for (name, element) in [
+        ("Fire", Element::Fire),
+        ("Water", Element::Water),
+        ("Earth", Element::Earth),
+        ("Air", Element::Air),
+    ] {
+        let mut energy = Energy::new(name.to_string(), 417.0, element);
+        energy.amplitude = 0.3;
+        state.add_energy(energy);
+    }
+    state
+}
+
+#[tokio::test]
+async fn test_ritual_preserves_evolution_count_across_round_trip() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    let seeded = seasoned_state();
+    insert_state(
+        &app,
+        practitioner.id,
+        serde_json::to_value(&seeded).unwrap(),
+        &ArchetypalState::from_symbolic_state(&seeded),
+    )
+    .await;
+
+    let request = RitualExecutionRequest {
+        ritual_name: "shadow_integration".to_string(),
+        parameters: HashMap::new(),
+        intention: "Keep my history".to_string(),
+        seed: Some(11),
+        dry_run: false,
+        auto_reflect: false,
+    };
+    let result = handlers::execute_ritual(
+        State(app.state.clone()),
+        Extension(practitioner.clone()),
+        HeaderMap::new(),
+        Json(request),
+    )
+    .await
+    .expect("execution failed")
+    .0
+    .data;
+
+    let state_data: serde_json::Value = sqlx::query_scalar(
+        "SELECT state_data FROM archetypal_states WHERE practitioner_id = $1 \
+         ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(practitioner.id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    let stored: SymbolicState = serde_json::from_value(state_data).unwrap();
+
+    let shadow = &stored.archetypes["Shadow"];
+    assert_eq!(shadow.evolution_count, 3);
+    assert_eq!(shadow.shadow_aspects, vec!["envy".to_string()]);
+    assert_eq!(
+        shadow.activation_level,
+        result.post_state.archetypes["Shadow"]
+    );
+    assert_eq!(stored.energies["Fire"].frequency, 417.0);
+
+    let current =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!(current.archetypes, result.post_state.archetypes);
+}
+
+#[tokio::test]
+async fn test_flattened_rows_still_load() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let practitioner = common::practitioner(&app, auth.practitioner.id).await;
+
+    // Rows written before `state_data` held the full state only carry the
+    // simplified struct there
+    let mut legacy = ArchetypalState::new();
+    legacy.archetypes.insert("Shadow".to_string(), 0.6);
+    insert_state(
+        &app,
+        practitioner.id,
+        serde_json::to_value(&legacy).unwrap(),
+        &legacy,
+    )
+    .await;
+
+    let current =
+        handlers::get_current_state(State(app.state.clone()), Extension(practitioner.clone()))
+            .await
+            .unwrap()
+            .0
+            .data;
+    assert_eq!(current.archetypes, legacy.archetypes);
+    assert_eq!(current.energies, legacy.energies);
+}
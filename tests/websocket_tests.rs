@@ -0,0 +1,104 @@
+//! Interactive ritual sessions over `/api/ws`.
+
+mod common;
+
+use axum::{routing::get, Router};
+use codex_control_engine::{handlers, models::WsFrame};
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Serves just the websocket route on an ephemeral port
+async fn serve(app: &common::TestApp) -> SocketAddr {
+    let router = Router::new()
+        .route("/api/ws", get(handlers::ws_session))
+        .with_state(app.state.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    addr
+}
+
+async fn send(socket: &mut Socket, command: serde_json::Value) -> WsFrame {
+    socket
+        .send(Message::Text(command.to_string()))
+        .await
+        .unwrap();
+    loop {
+        match socket.next().await.expect("socket closed").unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_execute_then_reflect_over_socket() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let auth = common::register(&app).await;
+    let addr = serve(&app).await;
+
+    let (mut socket, _) = connect_async(format!("ws://{}/api/ws?token={}", addr, auth.token))
+        .await
+        .expect("upgrade failed");
+
+    let frame = send(
+        &mut socket,
+        serde_json::json!({
+            "type": "execute",
+            "ritual_name": "shadow_integration",
+            "intention": "Face what I hide",
+            "seed": 7
+        }),
+    )
+    .await;
+    let WsFrame::TransformationResult(result) = frame else {
+        panic!("expected a transformation result, got {:?}", frame);
+    };
+    assert!(!result.dry_run);
+
+    // Without a session id the reflection is on the ritual just executed
+    let frame = send(&mut socket, serde_json::json!({ "type": "reflect" })).await;
+    let WsFrame::OracleInsight(insight) = frame else {
+        panic!("expected an oracle insight, got {:?}", frame);
+    };
+    assert_eq!(insight.session_id, Some(result.session_id));
+
+    let frame = send(&mut socket, serde_json::json!({ "type": "dance" })).await;
+    assert!(matches!(frame, WsFrame::Error(_)));
+
+    let frame = send(
+        &mut socket,
+        serde_json::json!({
+            "type": "execute",
+            "ritual_name": "no_such_ritual",
+            "intention": "Nothing"
+        }),
+    )
+    .await;
+    let WsFrame::Error(error) = frame else {
+        panic!("expected an error, got {:?}", frame);
+    };
+    assert!(error.contains("no_such_ritual"));
+}
+
+#[tokio::test]
+async fn test_invalid_token_is_refused_before_upgrade() {
+    let Some(app) = common::test_app().await else {
+        return;
+    };
+    let addr = serve(&app).await;
+
+    let error = connect_async(format!("ws://{}/api/ws?token=not-a-token", addr))
+        .await
+        .expect_err("an invalid token must not open a session");
+    let tokio_tungstenite::tungstenite::Error::Http(response) = error else {
+        panic!("expected an HTTP refusal, got {:?}", error);
+    };
+    assert_eq!(response.status(), 401);
+}